@@ -58,7 +58,9 @@
 //! - **MI**: derived in `mehen_metrics::state::apply_state_to` from
 //!   loc/cyclomatic/halstead — no C-specific logic.
 
-use mehen_core::{LineIndex, MetricSpace, SpaceKind};
+use std::collections::BTreeMap;
+
+use mehen_core::{HalsteadClass, LineIndex, MetricSpace, SpaceKind};
 use mehen_metrics::{HalsteadOperand, HalsteadOperator, State};
 use mehen_tree_sitter::{OpenSpaceRequest, WalkerCtx, WalkerHooks, node_span, run, text_of};
 use smol_str::SmolStr;
@@ -69,9 +71,22 @@ use crate::grammar::C;
 /// Drive the walker over the parsed C tree and return the populated
 /// `MetricSpace`. Plugs C classification into the shared
 /// [`mehen_tree_sitter::run`] scaffold.
-pub(crate) fn walk_program(root: Node<'_>, source: &[u8], line_index: &LineIndex) -> MetricSpace {
+pub(crate) fn walk_program(
+    root: Node<'_>,
+    source: &[u8],
+    line_index: &LineIndex,
+    halstead_overrides: &BTreeMap<String, HalsteadClass>,
+    halstead_tokens: bool,
+) -> MetricSpace {
     let mut hooks = CHooks;
-    run(&mut hooks, root, source, line_index)
+    run(
+        &mut hooks,
+        root,
+        source,
+        line_index,
+        halstead_overrides,
+        halstead_tokens,
+    )
 }
 
 struct CHooks;
@@ -167,8 +182,9 @@ fn classify_cognitive(ctx: &mut WalkerCtx<'_>, node: &Node<'_>, kind: C) {
         // clause` arm and only the boolean-seq reset stays here
         // (defense-in-depth duplicate of the ElseClause reset).
         C::IfStatement if !is_else_if(node) => {
-            let effective = ctx.cognitive.nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
-            ctx.current().cognitive.increase_nesting(effective);
+            let nesting = ctx.cognitive.nesting;
+            let effective = nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
+            ctx.current().cognitive.increase_nesting(effective, nesting);
             ctx.cognitive.nesting = ctx.cognitive.nesting.saturating_add(1);
         }
         C::IfStatement => {
@@ -179,8 +195,9 @@ fn classify_cognitive(ctx: &mut WalkerCtx<'_>, node: &Node<'_>, kind: C) {
         | C::DoStatement
         | C::SwitchStatement
         | C::ConditionalExpression => {
-            let effective = ctx.cognitive.nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
-            ctx.current().cognitive.increase_nesting(effective);
+            let nesting = ctx.cognitive.nesting;
+            let effective = nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
+            ctx.current().cognitive.increase_nesting(effective, nesting);
             ctx.cognitive.nesting = ctx.cognitive.nesting.saturating_add(1);
         }
         C::ElseClause => {
@@ -312,22 +329,22 @@ fn classify_loc(ctx: &mut WalkerCtx<'_>, node: &Node<'_>, kind: C) {
 }
 
 fn classify_halstead(ctx: &mut WalkerCtx<'_>, node: &Node<'_>, kind: C) {
-    match halstead_op_type(kind) {
-        HalsteadType::Operator => {
+    match ctx.halstead_class(node, halstead_op_type(kind)) {
+        HalsteadClass::Operator => {
             let kind_label: &'static str = kind.into();
             ctx.current().halstead.observe_operator(HalsteadOperator {
                 kind: SmolStr::new(kind_label),
                 text: None,
             });
         }
-        HalsteadType::Operand => {
+        HalsteadClass::Operand => {
             let text = text_of(node, ctx.source);
             ctx.current().halstead.observe_operand(HalsteadOperand {
                 kind: SmolStr::new("Operand"),
                 text: Some(SmolStr::new(text)),
             });
         }
-        HalsteadType::Unknown => {}
+        HalsteadClass::Unknown => {}
     }
 }
 
@@ -335,13 +352,7 @@ fn classify_halstead(ctx: &mut WalkerCtx<'_>, node: &Node<'_>, kind: C) {
 // Halstead classification (legacy `Getter::get_op_type for CCode`).
 // --------------------------------------------------------------------
 
-enum HalsteadType {
-    Operator,
-    Operand,
-    Unknown,
-}
-
-fn halstead_op_type(kind: C) -> HalsteadType {
+fn halstead_op_type(kind: C) -> HalsteadClass {
     match kind {
         // Keywords and control flow.
         C::If
@@ -451,7 +462,7 @@ fn halstead_op_type(kind: C) -> HalsteadType {
         | C::HASHelif
         | C::HASHelifdef
         | C::HASHelifndef
-        | C::HASHendif => HalsteadType::Operator,
+        | C::HASHendif => HalsteadClass::Operator,
 
         // Operands: identifiers, type identifiers, and literals.
         C::Identifier
@@ -467,9 +478,9 @@ fn halstead_op_type(kind: C) -> HalsteadType {
         | C::False
         | C::NULL
         | C::Nullptr
-        | C::SystemLibString => HalsteadType::Operand,
+        | C::SystemLibString => HalsteadClass::Operand,
 
-        _ => HalsteadType::Unknown,
+        _ => HalsteadClass::Unknown,
     }
 }
 
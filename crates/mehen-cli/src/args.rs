@@ -3,7 +3,11 @@
 
 use std::path::PathBuf;
 
-use clap::{Args, Parser, Subcommand};
+use clap::builder::PossibleValuesParser;
+use clap::{Args, ColorChoice, Parser, Subcommand};
+
+use mehen_core::{DEFAULT_BUGS_CONSTANT, DEFAULT_STROUD_NUMBER, DebtWeights};
+use mehen_report::metrics_json::METRIC_FAMILIES;
 
 /// `mehen` — code metrics CLI.
 ///
@@ -26,12 +30,22 @@ pub(crate) struct Cli {
     #[arg(long, short = 'V', global = true)]
     pub(crate) version: bool,
 
+    /// Print the version plus the version and ABI of every bundled
+    /// tree-sitter grammar, and exit.
+    #[arg(long, global = true, conflicts_with = "version")]
+    pub(crate) versions: bool,
+
     /// Emit output as JSON. Currently only meaningful with
     /// `--version`; clap rejects the flag unless `--version` is also
     /// passed.
     #[arg(long, global = true, requires = "version")]
     pub(crate) json: bool,
 
+    /// When to color help, errors and log lines on stderr. Reports on
+    /// stdout are never colored.
+    #[arg(long, global = true, value_enum, value_name = "WHEN")]
+    pub(crate) color: Option<ColorChoice>,
+
     #[command(subcommand)]
     pub(crate) command: Option<Command>,
 }
@@ -49,17 +63,52 @@ pub(crate) enum Command {
     Diff(mehen_engine::DiffOpts),
     /// Rank files by one or more metrics (worst offenders first).
     TopOffenders(mehen_engine::TopOffendersOpts),
+    /// Count files and SLOC per detected language, without running the
+    /// metric suite.
+    Languages(mehen_engine::LanguagesOpts),
+    /// Describe a metric: what it measures, its formula and whether
+    /// higher or lower is better.
+    ExplainMetric(mehen_engine::ExplainOpts),
+    /// Report what mehen detects about the environment: git repository,
+    /// CI context, available languages and config files.
+    Doctor(mehen_engine::DoctorOpts),
 }
 
 #[derive(Debug, Args)]
 pub(crate) struct MetricsArgs {
     /// Path to the file to analyze. `mehen metrics` never walks directories.
-    pub(crate) path: PathBuf,
+    #[arg(required_unless_present = "stdin", conflicts_with = "stdin")]
+    pub(crate) path: Option<PathBuf>,
+
+    /// Read the source from stdin instead of a file. Pass `--language`
+    /// or `--stdin-name` so the language can be determined.
+    #[arg(long)]
+    pub(crate) stdin: bool,
+
+    /// File name to use for stdin input: the language is detected from
+    /// it and the report's `path` shows it.
+    #[arg(long, requires = "stdin", value_name = "NAME")]
+    pub(crate) stdin_name: Option<PathBuf>,
+
+    /// Analyze the staged (git index) version of the file instead of
+    /// the working tree, e.g. from a pre-commit hook.
+    #[arg(long, conflicts_with = "stdin")]
+    pub(crate) staged: bool,
 
     /// Override language detection.
     #[arg(long)]
     pub(crate) language: Option<String>,
 
+    /// Expand tabs in leading indentation before parsing, so spans and
+    /// byte offsets do not depend on indentation style.
+    #[arg(long)]
+    pub(crate) normalize_whitespace: bool,
+
+    /// Tab stop width for `--normalize-whitespace`. Defaults to the
+    /// language's own: 8 for Python, 4 otherwise.
+    #[arg(long, value_name = "N", requires = "normalize_whitespace")]
+    pub(crate) tab_width: Option<usize>,
+
     /// Output format.
     #[arg(long, default_value = "json")]
     pub(crate) format: OutputFormat,
@@ -71,6 +120,210 @@ pub(crate) struct MetricsArgs {
     /// Built-in profile preset.
     #[arg(long, default_value = "default")]
     pub(crate) profile: Profile,
+
+    /// Fail with exit code 2 when any function has more than N exit
+    /// points (the per-function `nexit` value).
+    #[arg(long, value_name = "N")]
+    pub(crate) max_returns: Option<u64>,
+
+    /// Fail with exit code 2 when the file imports more than N modules
+    /// (the unit's `import_count`). Python, Rust and Go only.
+    #[arg(long, value_name = "N")]
+    pub(crate) max_imports: Option<u64>,
+
+    /// Fail with exit code 2 when the file defines more than N functions
+    /// and closures (the unit's `nom`).
+    #[arg(long, value_name = "N")]
+    pub(crate) max_functions: Option<u64>,
+
+    /// Print analysis time, syntax-tree node count and space count to
+    /// stderr. Metric output on stdout is unchanged.
+    #[arg(long)]
+    pub(crate) stats: bool,
+
+    /// Record this run's file-level metrics in `.mehen/last-run.json`
+    /// and print to stderr what changed since the previous run of the
+    /// same file. A local ratchet for iterating without commits.
+    #[arg(long)]
+    pub(crate) vs_last: bool,
+
+    /// Instead of the metrics report, print the N most frequent Halstead
+    /// operators and operands across the whole file.
+    #[arg(long, value_name = "N")]
+    pub(crate) ops_summary: Option<usize>,
+
+    /// Instead of the metrics report, run a tree-sitter S-expression
+    /// query (e.g. `(function_declaration (block) @b)`) and print how
+    /// many nodes each capture matched, with their locations. C, Go,
+    /// Kotlin and PowerShell only.
+    #[arg(
+        long,
+        value_name = "QUERY",
+        conflicts_with_all = ["ops_summary", "smells", "file_level", "byte_start", "byte_end"]
+    )]
+    pub(crate) query: Option<String>,
+
+    /// Instead of the metrics report, print a flat JSON array with the
+    /// kind, name, depth and byte/line range of every space (unit,
+    /// classes, functions, closures), for editor navigation. The file is
+    /// still analyzed in full, so this is no faster than the report.
+    #[arg(
+        long,
+        conflicts_with_all = ["ops_summary", "smells", "query", "file_level", "flat_json"]
+    )]
+    pub(crate) emit_spans: bool,
+
+    /// Instead of the metrics report, print per-function smell flags:
+    /// `long_function`, `too_many_params`, `high_cognitive`,
+    /// `too_many_exits` and `deeply_nested`.
+    #[arg(long, conflicts_with = "ops_summary")]
+    pub(crate) smells: bool,
+
+    /// Override a `--smells` threshold as `NAME=VALUE`, e.g.
+    /// `long_function=80`. A flag fires when the value is above it.
+    /// Repeatable.
+    #[arg(long, requires = "smells", value_name = "NAME=VALUE")]
+    pub(crate) smell_threshold: Vec<String>,
+
+    /// Also append the markdown report (or the `--smells` table) to the
+    /// GitHub Actions job summary (`$GITHUB_STEP_SUMMARY`), whatever
+    /// `--format` is.
+    #[arg(long)]
+    pub(crate) github_summary: bool,
+
+    /// Replace the contents of string literals with `***` in output that
+    /// echoes source tokens (`--ops-summary`). Quotes are kept.
+    #[arg(long)]
+    pub(crate) redact_strings: bool,
+
+    /// Only report the space (function, class, …) that lies fully at or
+    /// after this byte offset. Use with `--byte-end` for editor
+    /// selections; the whole file is still analyzed.
+    #[arg(long, value_name = "OFFSET")]
+    pub(crate) byte_start: Option<u32>,
+
+    /// Only report the space that ends at or before this byte offset
+    /// (exclusive).
+    #[arg(long, value_name = "OFFSET")]
+    pub(crate) byte_end: Option<u32>,
+
+    /// Report only the unit (file-level) space: the nested function and
+    /// class tree is dropped from the output.
+    #[arg(long)]
+    pub(crate) file_level: bool,
+
+    /// Wrap each value of the JSON `metrics` object as
+    /// `{ value, unit, description }`.
+    #[arg(long)]
+    pub(crate) with_metadata: bool,
+
+    /// Emit JSON as columns: a `functions` array with one entry per
+    /// nested space, and each metric as an array parallel to it.
+    #[arg(long, conflicts_with_all = ["file_level", "with_metadata"])]
+    pub(crate) flat_json: bool,
+
+    /// Emit only raw per-space values (`loc.sloc`, `cyclomatic`, Halstead
+    /// counts, …): the derived `*_average`, `*_min` and `*_max` fields
+    /// are left out of the JSON.
+    #[arg(long)]
+    pub(crate) raw: bool,
+
+    /// Count only named functions in NOM: `nom.total` and `nom.average`
+    /// leave closures and lambdas out.
+    #[arg(long)]
+    pub(crate) exclude_closures: bool,
+
+    /// Recompute every min/max from the per-space values of the finished
+    /// tree, so the bounds cannot depend on the order spaces were merged.
+    #[arg(long)]
+    pub(crate) recompute_minmax: bool,
+
+    /// Leave a metric family out of the report, e.g. `halstead`.
+    /// Repeat the flag to drop several.
+    #[arg(
+        long,
+        value_name = "FAMILY",
+        value_parser = PossibleValuesParser::new(METRIC_FAMILIES.iter().map(|(name, _)| *name)),
+    )]
+    pub(crate) exclude_metric: Vec<String>,
+
+    /// Skip the deep metrics of a file whose syntax tree has more than
+    /// N nodes (tokens for TypeScript/JavaScript and Python): it gets
+    /// only the LOC family and a warning. Guards against generated or
+    /// minified files.
+    #[arg(long, value_name = "N")]
+    pub(crate) max_nodes: Option<u64>,
+
+    /// Fold closures into the enclosing function instead of reporting
+    /// each as a child space, so callback-heavy code is measured as one
+    /// unit. Rust only.
+    #[arg(long)]
+    pub(crate) inline_closures: bool,
+
+    /// Count each `match` arm guard as a cyclomatic decision, as clippy
+    /// does. Rust only.
+    #[arg(long)]
+    pub(crate) count_match_guards: bool,
+
+    /// Count a grammar node kind as a Halstead operator, operand or
+    /// neither, as `KIND=operator|operand|unknown`, e.g. `.=operand`.
+    /// Wins over `.mehen.toml`'s `[halstead-overrides]` for the same
+    /// kind. Go, C and Kotlin only; other languages are rejected.
+    /// Repeatable.
+    #[arg(long, value_name = "KIND=CLASS")]
+    pub(crate) halstead_override: Vec<String>,
+
+    /// Add a metric computed by a shell command, as `NAME=CMD`. The
+    /// command reads the JSON report on stdin (and the file path from
+    /// `MEHEN_PATH`) and prints a number, published as
+    /// `external.NAME`. A command still running after 60 seconds is
+    /// killed. Repeatable.
+    #[arg(long, value_name = "NAME=CMD")]
+    pub(crate) external_metric: Vec<String>,
+
+    /// Halstead's Stroud number, the mental discriminations per second
+    /// behind `halstead.time = effort / N`.
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_STROUD_NUMBER)]
+    pub(crate) halstead_stroud_number: f64,
+
+    /// Divisor of `halstead.bugs = effort^(2/3) / N`.
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_BUGS_CONSTANT)]
+    pub(crate) halstead_bugs_constant: f64,
+
+    /// Remediation minutes `debt_ratio` charges per decision point.
+    #[arg(long, value_name = "MINUTES", default_value_t = DebtWeights::default().cyclomatic_minutes)]
+    pub(crate) debt_cyclomatic_minutes: f64,
+
+    /// Remediation minutes `debt_ratio` charges per cognitive-complexity
+    /// point.
+    #[arg(long, value_name = "MINUTES", default_value_t = DebtWeights::default().cognitive_minutes)]
+    pub(crate) debt_cognitive_minutes: f64,
+
+    /// Count `import` statements as logical lines (`loc.lloc`).
+    /// `--measure-imports-as-lloc=false` leaves them out, to match tools
+    /// that do not count them. Honored by the Python analyzer.
+    #[arg(
+        long,
+        default_value_t = true,
+        action = clap::ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    pub(crate) measure_imports_as_lloc: bool,
+
+    /// Write the report into this directory instead of stdout, named
+    /// after the input file plus the format's extension. `-` means
+    /// stdout.
+    #[arg(long, short = 'o', value_name = "DIR")]
+    pub(crate) output: Option<PathBuf>,
+
+    /// How `--output` lays out report files: `flat` keeps only the
+    /// file name (`out/b.rs.json`), `mirror` recreates the input's
+    /// directories (`out/src/a/b.rs.json`) so same-named files in
+    /// different directories do not overwrite each other.
+    #[arg(long, value_enum, default_value = "flat", requires = "output")]
+    pub(crate) output_dir_structure: OutputDirStructure,
 }
 
 #[derive(Debug, Clone, Copy, clap::ValueEnum)]
@@ -81,6 +334,12 @@ pub(crate) enum OutputFormat {
     Toml,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum OutputDirStructure {
+    Flat,
+    Mirror,
+}
+
 #[derive(Debug, Clone, Copy, clap::ValueEnum)]
 pub(crate) enum Profile {
     Default,
@@ -3,74 +3,493 @@
 
 //! Command implementations for the 1.0 CLI.
 
+use std::collections::BTreeMap;
 use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use camino::Utf8PathBuf;
 
-use mehen_core::{AnalysisConfig, DiagnosticSeverity, Language, MetricsReport, SourceFile};
-use mehen_engine::{AnalyzeMetricsInput, analyze_metrics, detect_language};
-use mehen_report::render_metrics_json;
+use mehen_core::{
+    AnalysisConfig, DebtWeights, DiagnosticSeverity, HalsteadClass, Language, MetricKey,
+    MetricSpace, MetricsReport, SourceFile, SpaceKind, keys, normalize_whitespace,
+};
+use mehen_engine::{
+    AnalyzeMetricsInput, EXTERNAL_METRIC_TIMEOUT, ExternalMetric, ExternalMetricError,
+    LAST_RUN_FILE, LastRunDelta, ProjectConfig, SmellThresholds, SmellsReport, analyze_metrics,
+    detect_language, language_from_modeline, query_source, record_last_run,
+};
+use mehen_report::metrics_json::METRIC_FAMILIES;
+use mehen_report::{
+    MetricsJsonOptions, render_metrics_json_with, render_ops_summary_json,
+    render_ops_summary_markdown, render_spans_json,
+};
 
-use crate::args::{MetricsArgs, OutputFormat, Profile};
+use crate::args::{MetricsArgs, OutputDirStructure, OutputFormat, Profile};
 use crate::exit::ExitCode;
 
 pub(crate) fn metrics(args: MetricsArgs) -> ExitCode {
-    let path = match Utf8PathBuf::try_from(args.path.clone()) {
+    // clap requires either a path or `--stdin`.
+    let input = match (&args.path, &args.stdin_name) {
+        (Some(path), _) | (None, Some(path)) => path.clone(),
+        (None, None) => PathBuf::from("<stdin>"),
+    };
+    let path = match Utf8PathBuf::try_from(input.clone()) {
         Ok(p) => p,
         Err(_) => {
-            log::error!("path is not valid UTF-8: {}", args.path.display());
+            log::error!("path is not valid UTF-8: {}", input.display());
             return ExitCode::SetupError;
         }
     };
 
-    let language = if let Some(lang_str) = args.language.as_deref() {
-        match lang_str.parse::<Language>() {
-            Ok(l) => l,
+    let explicit = match args.language.as_deref() {
+        Some(lang_str) => match lang_str.parse::<Language>() {
+            Ok(l) => Some(l),
             Err(_) => {
                 log::error!("unknown --language value: {lang_str}");
                 return ExitCode::SetupError;
             }
+        },
+        None => None,
+    };
+
+    let read = if args.stdin {
+        io::read_to_string(io::stdin())
+    } else if args.staged {
+        match read_staged(&path) {
+            Some(text) => Ok(text),
+            None => return ExitCode::SetupError,
         }
     } else {
-        match detect_language(path.as_path()) {
-            Some(l) => l,
-            None => {
-                log::error!(
-                    "could not detect language from path `{path}`; pass --language explicitly"
-                );
-                return ExitCode::SetupError;
+        std::fs::read_to_string(&path)
+    };
+    let text = match read {
+        Ok(t) => t,
+        Err(e) => {
+            if args.stdin {
+                log::error!("failed to read stdin: {e}");
+            } else {
+                log::error!("failed to read `{path}`: {e}");
             }
+            return ExitCode::SetupError;
         }
     };
 
-    let text = match std::fs::read_to_string(&path) {
-        Ok(t) => t,
-        Err(e) => {
-            log::error!("failed to read `{path}`: {e}");
+    // `--language` wins over a `mehen-lang:` modeline, which wins over
+    // the extension.
+    let detected = explicit
+        .or_else(|| language_from_modeline(&text))
+        .or_else(|| detect_language(path.as_path()));
+    let language = match detected {
+        Some(l) => l,
+        None if args.stdin && args.stdin_name.is_none() => {
+            log::error!("reading stdin needs --language or --stdin-name");
+            return ExitCode::SetupError;
+        }
+        None => {
+            log::error!("could not detect language from path `{path}`; pass --language explicitly");
             return ExitCode::SetupError;
         }
     };
+    let text = if args.normalize_whitespace {
+        normalize_whitespace(&text, args.tab_width.unwrap_or(language.tab_width()))
+    } else {
+        text
+    };
+
+    let mut thresholds = SmellThresholds::default();
+    for spec in &args.smell_threshold {
+        if let Err(e) = thresholds.set(spec) {
+            log::error!("{e}");
+            return ExitCode::SetupError;
+        }
+    }
+
+    let target = match args.output.as_deref().filter(|dir| *dir != Path::new("-")) {
+        Some(dir) => match output_file(dir, &input, args.output_dir_structure, args.format) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                log::error!("{e}");
+                return ExitCode::SetupError;
+            }
+        },
+        None => None,
+    };
+    let target = target.as_deref();
 
     let source = SourceFile::new(path, language, text);
-    let input = AnalyzeMetricsInput {
-        source,
-        config: config_for_profile(args.profile),
+    if let Some(query) = args.query.as_deref() {
+        return run_query(&source, query, args.format, args.pretty, target);
+    }
+    let mut config = config_for_profile(args.profile);
+    config.exclude_closures = args.exclude_closures;
+    config.inline_closures = args.inline_closures;
+    config.count_match_guards = args.count_match_guards;
+    config.recompute_minmax = args.recompute_minmax;
+    config.excluded_metrics = args
+        .exclude_metric
+        .iter()
+        .filter_map(|name| METRIC_FAMILIES.iter().find(|(family, _)| family == name))
+        .map(|(_, prefix)| prefix.to_string())
+        .collect();
+    config.max_nodes = args.max_nodes;
+    config.halstead_tokens = args.ops_summary.is_some();
+    config.exclude_imports_from_lloc = !args.measure_imports_as_lloc;
+    let project = match std::env::current_dir().map(|cwd| ProjectConfig::discover(&cwd)) {
+        Ok(Ok(project)) => project,
+        Ok(Err(e)) => {
+            log::error!("{e}");
+            return ExitCode::SetupError;
+        }
+        Err(_) => ProjectConfig::default(),
     };
+    config.halstead_overrides = project.halstead_overrides;
+    for spec in &args.halstead_override {
+        let class = spec.split_once('=').and_then(|(kind, class)| {
+            let class = match class.trim() {
+                "operator" => HalsteadClass::Operator,
+                "operand" => HalsteadClass::Operand,
+                "unknown" => HalsteadClass::Unknown,
+                _ => return None,
+            };
+            Some((kind.trim().to_string(), class))
+        });
+        let Some((kind, class)) = class else {
+            log::error!(
+                "invalid --halstead-override '{spec}'; expected KIND=operator|operand|unknown"
+            );
+            return ExitCode::SetupError;
+        };
+        config.halstead_overrides.insert(kind, class);
+    }
+    // The `.mehen.toml` section covers a whole repository and is simply
+    // not read by other analyzers; the flag names this one file.
+    if !args.halstead_override.is_empty()
+        && !AnalysisConfig::honors_halstead_overrides(source.language)
+    {
+        log::error!(
+            "--halstead-override is not supported for {}; only C, Go and Kotlin read it",
+            source.language
+        );
+        return ExitCode::SetupError;
+    }
+    for (flag, value) in [
+        ("--halstead-stroud-number", args.halstead_stroud_number),
+        ("--halstead-bugs-constant", args.halstead_bugs_constant),
+    ] {
+        if !(value.is_finite() && value > 0.0) {
+            log::error!("invalid {flag} '{value}'; expected a positive number");
+            return ExitCode::SetupError;
+        }
+    }
+    config.halstead_stroud_number = args.halstead_stroud_number;
+    config.halstead_bugs_constant = args.halstead_bugs_constant;
+    for (flag, value) in [
+        ("--debt-cyclomatic-minutes", args.debt_cyclomatic_minutes),
+        ("--debt-cognitive-minutes", args.debt_cognitive_minutes),
+    ] {
+        if !(value.is_finite() && value >= 0.0) {
+            log::error!("invalid {flag} '{value}'; expected a non-negative number");
+            return ExitCode::SetupError;
+        }
+    }
+    config.debt_weights = DebtWeights {
+        cyclomatic_minutes: args.debt_cyclomatic_minutes,
+        cognitive_minutes: args.debt_cognitive_minutes,
+    };
+    let mut external_metrics = Vec::with_capacity(args.external_metric.len());
+    for spec in &args.external_metric {
+        match spec.parse::<ExternalMetric>() {
+            Ok(metric) => external_metrics.push(metric),
+            Err(e) => {
+                log::error!("{e}");
+                return ExitCode::SetupError;
+            }
+        }
+    }
+    let input = AnalyzeMetricsInput { source, config };
 
-    let report = match analyze_metrics(input) {
+    let started = Instant::now();
+    let mut report = match analyze_metrics(input) {
         Ok(r) => r,
         Err(e) => {
             log::error!("analysis failed: {e}");
             return ExitCode::SetupError;
         }
     };
+    // Before the external metrics run, so `--stats` times the analysis
+    // alone.
+    let analysis_time = started.elapsed();
+    for diagnostic in &report.diagnostics {
+        if diagnostic.severity == DiagnosticSeverity::Warning {
+            log::warn!("{}: {}", report.path, diagnostic.message);
+        }
+    }
+    if !external_metrics.is_empty()
+        && let Err(e) = add_external_metrics(&mut report, &external_metrics)
+    {
+        log::error!("{e}");
+        return ExitCode::SetupError;
+    }
+    if args.stats {
+        print_stats(&report, analysis_time);
+    }
+    if args.vs_last {
+        match record_last_run(
+            Path::new(LAST_RUN_FILE),
+            report.path.as_std_path(),
+            &report.root.metrics,
+        ) {
+            Ok(deltas) => eprint!("{}", vs_last_summary(report.path.as_str(), deltas)),
+            Err(e) => {
+                log::error!("--vs-last: {e}");
+                return ExitCode::SetupError;
+            }
+        }
+    }
+    if args.byte_start.is_some() || args.byte_end.is_some() {
+        let start = args.byte_start.unwrap_or(0);
+        let end = args.byte_end.unwrap_or(u32::MAX);
+        match select_byte_range(&mut report, start, end) {
+            1 => {}
+            0 => {
+                log::error!(
+                    "{}: no space lies fully within bytes {start}..{end}",
+                    report.path
+                );
+                return ExitCode::SetupError;
+            }
+            n => {
+                log::error!(
+                    "{}: {n} spaces lie within bytes {start}..{end}; narrow the range to one",
+                    report.path
+                );
+                return ExitCode::SetupError;
+            }
+        }
+    }
+    if args.redact_strings
+        && let Some(tokens) = report.root.halstead_tokens.as_mut()
+    {
+        tokens.redact_string_literals();
+    }
+    // Checked before `--file-level` prunes the function tree they read.
+    let over_max_returns = args
+        .max_returns
+        .is_some_and(|max| exceeds_max_returns(&report, max));
+    let over_max_imports = args.max_imports.is_some_and(|max| {
+        exceeds_unit_max(&report, keys::IMPORT_COUNT, max, "imports", "--max-imports")
+    });
+    // `nom` counts named functions plus closures, or functions only
+    // under `--exclude-closures`.
+    let over_max_functions = args.max_functions.is_some_and(|max| {
+        exceeds_unit_max(&report, keys::NOM, max, "functions", "--max-functions")
+    });
+    let smells = args
+        .smells
+        .then(|| SmellsReport::from_space(&report.root, &thresholds));
+    if args.file_level {
+        report.root.spaces.clear();
+    }
+
+    if args.github_summary {
+        let markdown = match &smells {
+            Some(smells) => smells.to_markdown(),
+            None => mehen_report::render_metrics_markdown_with(
+                &report,
+                &project.metric_aliases,
+                &args.exclude_metric,
+            ),
+        };
+        mehen_engine::ci::write_step_summary(&markdown);
+    }
 
-    if let Some(exit) = render_report(&report, args.format, args.pretty)
+    let rendered = match (args.ops_summary, smells) {
+        _ if args.emit_spans => render_spans(&report, args.format, args.pretty, target),
+        (_, Some(smells)) => render_smells(&smells, args.format, args.pretty, target),
+        (Some(top), None) => render_ops_summary(&report, top, args.format, args.pretty, target),
+        (None, None) => render_report(
+            &report,
+            args.format,
+            MetricsJsonOptions {
+                pretty: args.pretty,
+                file_level: args.file_level,
+                with_metadata: args.with_metadata,
+                flat: args.flat_json,
+                raw: args.raw,
+                excluded: &args.exclude_metric,
+            },
+            &project.metric_aliases,
+            target,
+        ),
+    };
+    if let Some(exit) = rendered
         && !matches!(exit, ExitCode::Success)
     {
         return exit;
     }
-    exit_code_from_report(&report)
+    let exit = exit_code_from_report(&report);
+    if matches!(exit, ExitCode::Success)
+        && (over_max_returns || over_max_imports || over_max_functions)
+    {
+        return ExitCode::ThresholdFailure;
+    }
+    exit
+}
+
+/// Run each `--external-metric` command on the report's JSON and
+/// publish its result in the unit space. Every command sees the report
+/// as analyzed, without the other external results.
+fn add_external_metrics(
+    report: &mut MetricsReport,
+    metrics: &[ExternalMetric],
+) -> Result<(), ExternalMetricError> {
+    let input =
+        mehen_report::render_metrics_json(report, false).map_err(ExternalMetricError::Input)?;
+    for metric in metrics {
+        let value = metric.run(report.path.as_str(), &input, EXTERNAL_METRIC_TIMEOUT)?;
+        report
+            .root
+            .metrics
+            .insert(MetricKey::new(metric.key()), value);
+    }
+    Ok(())
+}
+
+/// `--staged`: the index content of `path`. Logs why and returns
+/// `None` when it cannot be read.
+fn read_staged(path: &Utf8PathBuf) -> Option<String> {
+    match mehen_engine::read_staged_file(path.as_std_path()) {
+        Ok(Some(bytes)) => match String::from_utf8(bytes) {
+            Ok(text) => Some(text),
+            Err(_) => {
+                log::error!("staged `{path}` is not valid UTF-8");
+                None
+            }
+        },
+        Ok(None) => {
+            log::error!("`{path}` is not in the git index");
+            None
+        }
+        Err(e) => {
+            log::error!("failed to read staged `{path}`: {e}");
+            None
+        }
+    }
+}
+
+/// `--stats` line on stderr. Analyzers parse and walk in one pass, so
+/// the duration covers both; the count is every space in the tree,
+/// the unit included.
+fn print_stats(report: &MetricsReport, elapsed: Duration) {
+    let mut spaces = 0usize;
+    report.root.visit(&mut |_, _| spaces += 1);
+    // Analyzers that do not count syntax nodes publish no `node_count`.
+    let nodes = report
+        .root
+        .metrics
+        .get(&MetricKey::new(keys::NODE_COUNT))
+        .map_or_else(|| "n/a".to_string(), |v| v.as_f64().to_string());
+    eprintln!(
+        "{}: language={} time={:.3}ms nodes={nodes} spaces={spaces}",
+        report.path,
+        report.language,
+        elapsed.as_secs_f64() * 1000.0,
+    );
+}
+
+/// `--vs-last` stderr summary: one `key: before → after (±delta)` line
+/// per file-level metric that moved since the previous run.
+fn vs_last_summary(path: &str, deltas: Option<Vec<LastRunDelta>>) -> String {
+    let Some(deltas) = deltas else {
+        return format!("{path}: no previous run recorded\n");
+    };
+    if deltas.is_empty() {
+        return format!("{path}: unchanged since last run\n");
+    }
+    // Whole numbers as is, fractional ones to two decimals.
+    let num = |v: f64, sign: bool| match (v.fract() == 0.0, sign) {
+        (true, false) => format!("{v}"),
+        (true, true) => format!("{v:+}"),
+        (false, false) => format!("{v:.2}"),
+        (false, true) => format!("{v:+.2}"),
+    };
+    let mut out = format!("{path} vs last run:\n");
+    for d in deltas {
+        out.push_str(&format!(
+            "  {}: {} → {} ({})\n",
+            d.key,
+            num(d.previous, false),
+            num(d.current, false),
+            num(d.current - d.previous, true)
+        ));
+    }
+    out
+}
+
+/// Narrow the report to the one outermost space that lies fully inside
+/// `start..end`, which becomes the report root, so the top-level
+/// metrics are that space's own. This only selects from the finished
+/// report: the whole file is still analyzed. Returns how many outermost
+/// spaces lie inside the range; the report is left alone unless that is
+/// exactly one.
+fn select_byte_range(report: &mut MetricsReport, start: u32, end: u32) -> usize {
+    fn collect<'a>(space: &'a MetricSpace, start: u32, end: u32, out: &mut Vec<&'a MetricSpace>) {
+        for child in &space.spaces {
+            if child.span.start_byte >= start && child.span.end_byte <= end {
+                out.push(child);
+            } else {
+                collect(child, start, end, out);
+            }
+        }
+    }
+
+    let mut selected = Vec::new();
+    collect(&report.root, start, end, &mut selected);
+    let count = selected.len();
+    if let [only] = selected[..] {
+        report.root = only.clone();
+    }
+    count
+}
+
+/// Apply the `--max-returns` gate: log every function whose own
+/// `nexit` count is above `max` and report whether any was found.
+fn exceeds_max_returns(report: &MetricsReport, max: u64) -> bool {
+    let key = MetricKey::new(keys::NEXIT);
+    let mut failed = false;
+    report.root.visit(&mut |space, _| {
+        if !matches!(space.kind, SpaceKind::Function) {
+            return;
+        }
+        let exits = space.metrics.get(&key).map_or(0.0, |v| v.as_f64());
+        if exits > max as f64 {
+            log::error!(
+                "{}: function `{}` (line {}) has {exits} exit points, above --max-returns {max}",
+                report.path,
+                space.name.as_deref().unwrap_or("<anonymous>"),
+                space.span.start_line,
+            );
+            failed = true;
+        }
+    });
+    failed
+}
+
+/// Apply a file-level gate: log and report whether the unit's `key`
+/// value is above `max`. `what` names the counted items in the message
+/// and `flag` the option that set the limit.
+fn exceeds_unit_max(report: &MetricsReport, key: &str, max: u64, what: &str, flag: &str) -> bool {
+    let value = report
+        .root
+        .metrics
+        .get(&MetricKey::new(key))
+        .map_or(0.0, |v| v.as_f64());
+    if value > max as f64 {
+        log::error!("{}: {value} {what}, above {flag} {max}", report.path);
+        return true;
+    }
+    false
 }
 
 /// Map the `--profile` flag to an [`AnalysisConfig`]. Until plan §3.6
@@ -92,8 +511,8 @@ fn config_for_profile(profile: Profile) -> AnalysisConfig {
 
 /// Map a `MetricsReport`'s diagnostic severities to a CLI exit code per
 /// the diagnostic contract (rewrite plan §9.3): `Warning` is exit 0,
-/// `Error`/`Fatal` are exit 1. Threshold violations (exit 2) are not
-/// emitted by `mehen metrics`.
+/// `Error`/`Fatal` are exit 1. Threshold violations (exit 2) come only
+/// from the `--max-returns` / `--max-imports` gates, checked separately.
 fn exit_code_from_report(report: &MetricsReport) -> ExitCode {
     let has_error_or_fatal = report.diagnostics.iter().any(|d| {
         matches!(
@@ -108,34 +527,205 @@ fn exit_code_from_report(report: &MetricsReport) -> ExitCode {
     }
 }
 
-fn render_report(report: &MetricsReport, format: OutputFormat, pretty: bool) -> Option<ExitCode> {
+fn render_report(
+    report: &MetricsReport,
+    format: OutputFormat,
+    json_options: MetricsJsonOptions<'_>,
+    labels: &BTreeMap<String, String>,
+    target: Option<&Path>,
+) -> Option<ExitCode> {
     match format {
-        OutputFormat::Json => match render_metrics_json(report, pretty) {
-            Ok(rendered) => {
-                let mut stdout = io::stdout().lock();
-                if writeln!(stdout, "{rendered}").is_err() {
-                    return Some(ExitCode::SerializationError);
+        OutputFormat::Json => match render_metrics_json_with(report, json_options) {
+            Ok(rendered) => emit(&rendered, target),
+            Err(e) => {
+                log::error!("failed to render JSON: {e}");
+                Some(ExitCode::SerializationError)
+            }
+        },
+        OutputFormat::Markdown => emit(
+            &mehen_report::render_metrics_markdown_with(report, labels, json_options.excluded),
+            target,
+        ),
+        OutputFormat::Yaml | OutputFormat::Toml => {
+            log::error!(
+                "the {format:?} format is reserved for a future phase; use --format json or markdown."
+            );
+            Some(ExitCode::SetupError)
+        }
+    }
+}
+
+/// Write `rendered` plus a trailing newline to `target`, creating its
+/// parent directories, or to stdout when there is no `--output`.
+fn emit(rendered: &str, target: Option<&Path>) -> Option<ExitCode> {
+    let Some(target) = target else {
+        let mut stdout = io::stdout().lock();
+        return writeln!(stdout, "{rendered}")
+            .is_err()
+            .then_some(ExitCode::SerializationError);
+    };
+    let written = target
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| std::fs::write(target, format!("{rendered}\n")));
+    match written {
+        Ok(()) => None,
+        Err(e) => {
+            log::error!("failed to write `{}`: {e}", target.display());
+            Some(ExitCode::SetupError)
+        }
+    }
+}
+
+/// The report file for `input` under the `--output` directory `dir`.
+/// `mirror` keeps the input's directories; absolute inputs are taken
+/// relative to the working directory when they lie under it, and `.`
+/// and root components are dropped so the file never lands outside
+/// `dir`. A `..` cancels the directory before it; one that climbs above
+/// the start of the path is an error, since dropping it would let
+/// `../a/x.rs` and `a/x.rs` share a report file.
+fn output_file(
+    dir: &Path,
+    input: &Path,
+    structure: OutputDirStructure,
+    format: OutputFormat,
+) -> Result<PathBuf, String> {
+    let extension = match format {
+        OutputFormat::Json => "json",
+        OutputFormat::Markdown => "md",
+        OutputFormat::Yaml => "yaml",
+        OutputFormat::Toml => "toml",
+    };
+    let mut relative = PathBuf::new();
+    match structure {
+        OutputDirStructure::Flat => relative.extend(input.file_name()),
+        OutputDirStructure::Mirror => {
+            let cwd = std::env::current_dir().ok();
+            let input = cwd
+                .as_deref()
+                .and_then(|cwd| input.strip_prefix(cwd).ok())
+                .unwrap_or(input);
+            for component in input.components() {
+                match component {
+                    Component::Normal(part) => relative.push(part),
+                    Component::ParentDir if relative.pop() => {}
+                    Component::ParentDir => {
+                        return Err(format!(
+                            "--output-dir-structure mirror: `{}` leads outside the working \
+                             directory; pass a path below it or use `flat`",
+                            input.display()
+                        ));
+                    }
+                    _ => {}
                 }
-                None
             }
+        }
+    }
+    let mut name = relative.into_os_string();
+    name.push(".");
+    name.push(extension);
+    Ok(dir.join(name))
+}
+
+fn run_query(
+    source: &SourceFile,
+    query: &str,
+    format: OutputFormat,
+    pretty: bool,
+    target: Option<&Path>,
+) -> ExitCode {
+    let report = match query_source(source, query) {
+        Ok(report) => report,
+        Err(e) => {
+            log::error!("{}: {e}", source.path);
+            return ExitCode::SetupError;
+        }
+    };
+    let rendered = match format {
+        OutputFormat::Json => match report.to_json(pretty) {
+            Ok(rendered) => rendered,
             Err(e) => {
                 log::error!("failed to render JSON: {e}");
-                Some(ExitCode::SerializationError)
+                return ExitCode::SerializationError;
             }
         },
-        OutputFormat::Markdown => {
-            let rendered = mehen_report::render_metrics_markdown(report);
-            let mut stdout = io::stdout().lock();
-            if writeln!(stdout, "{rendered}").is_err() {
+        OutputFormat::Markdown => report.to_markdown(),
+        OutputFormat::Yaml | OutputFormat::Toml => {
+            log::error!(
+                "the {format:?} format is reserved for a future phase; use --format json or markdown."
+            );
+            return ExitCode::SetupError;
+        }
+    };
+    emit(&rendered, target).unwrap_or(ExitCode::Success)
+}
+
+fn render_smells(
+    smells: &SmellsReport,
+    format: OutputFormat,
+    pretty: bool,
+    target: Option<&Path>,
+) -> Option<ExitCode> {
+    let rendered = match format {
+        OutputFormat::Json => match smells.to_json(pretty) {
+            Ok(rendered) => rendered,
+            Err(e) => {
+                log::error!("failed to render JSON: {e}");
                 return Some(ExitCode::SerializationError);
             }
-            None
-        }
+        },
+        OutputFormat::Markdown => smells.to_markdown(),
         OutputFormat::Yaml | OutputFormat::Toml => {
             log::error!(
                 "the {format:?} format is reserved for a future phase; use --format json or markdown."
             );
-            Some(ExitCode::SetupError)
+            return Some(ExitCode::SetupError);
+        }
+    };
+    emit(&rendered, target)
+}
+
+fn render_spans(
+    report: &MetricsReport,
+    format: OutputFormat,
+    pretty: bool,
+    target: Option<&Path>,
+) -> Option<ExitCode> {
+    if !matches!(format, OutputFormat::Json) {
+        log::error!("--emit-spans supports --format json only.");
+        return Some(ExitCode::SetupError);
+    }
+    match render_spans_json(report, pretty) {
+        Ok(rendered) => emit(&rendered, target),
+        Err(e) => {
+            log::error!("failed to render JSON: {e}");
+            Some(ExitCode::SerializationError)
         }
     }
 }
+
+fn render_ops_summary(
+    report: &MetricsReport,
+    top: usize,
+    format: OutputFormat,
+    pretty: bool,
+    target: Option<&Path>,
+) -> Option<ExitCode> {
+    let rendered = match format {
+        OutputFormat::Json => match render_ops_summary_json(report, top, pretty) {
+            Ok(rendered) => rendered,
+            Err(e) => {
+                log::error!("failed to render JSON: {e}");
+                return Some(ExitCode::SerializationError);
+            }
+        },
+        OutputFormat::Markdown => render_ops_summary_markdown(report, top),
+        OutputFormat::Yaml | OutputFormat::Toml => {
+            log::error!(
+                "the {format:?} format is reserved for a future phase; use --format json or markdown."
+            );
+            return Some(ExitCode::SetupError);
+        }
+    };
+    emit(&rendered, target)
+}
@@ -9,8 +9,9 @@ pub enum ExitCode {
     /// Setup, IO, git, parser fatal, unsupported-language, or invalid-state
     /// error. Also covers "analysis errors" diagnostics on `mehen metrics`.
     SetupError = 1,
-    /// Threshold or policy failure. Reserved for `mehen diff` and
-    /// `mehen top-offenders`.
+    /// Threshold or policy failure. Emitted by `mehen metrics
+    /// --max-returns` / `--max-imports` / `--max-functions` and `mehen
+    /// top-offenders --fail-above`; reserved for `mehen diff`.
     ThresholdFailure = 2,
     /// Invalid machine-output serialization state.
     SerializationError = 3,
@@ -12,25 +12,44 @@ mod args;
 mod commands;
 mod exit;
 
+use std::ffi::OsString;
 use std::io::{self, Write};
+use std::path::Path;
 
-use clap::Parser;
+use clap::{ColorChoice, CommandFactory, FromArgMatches, ValueEnum};
+use env_logger::WriteStyle;
 
 use args::{Cli, Command};
 use exit::ExitCode;
 
 fn main() {
-    env_logger::init();
     // Register the legacy embedded-code dispatch so the moved
     // `mehen-markdown` analyzer can fold fenced-code metrics into its
     // output. Idempotent — safe to call multiple times.
     mehen_engine::init_markdown();
-    let cli = Cli::parse();
+    let args = match expand_arg_files(std::env::args_os()) {
+        Ok(args) => args,
+        Err(e) => {
+            init_logger(color_arg(std::env::args_os()));
+            log::error!("{e}");
+            std::process::exit(ExitCode::SetupError.into());
+        }
+    };
+    // clap renders its own help and errors before `Cli` exists, so the
+    // flag is read ahead of the parse as well.
+    let color = color_arg(args.iter().cloned()).unwrap_or_default();
+    let matches = Cli::command().color(color).get_matches_from(args);
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    init_logger(cli.color);
 
     if cli.version {
         print_version(cli.json);
         return;
     }
+    if cli.versions {
+        print_versions();
+        return;
+    }
 
     let Some(command) = cli.command else {
         // Match clap's default "no subcommand and no global action"
@@ -43,6 +62,67 @@ fn main() {
     std::process::exit(code.into());
 }
 
+/// Replace every `@FILE` argument naming an existing file with the
+/// whitespace-separated tokens read from it, so long invocations can
+/// dodge command-line length limits. Any other `@` argument is kept, so
+/// a path such as `@scope/pkg` still works, and `@@x` passes a literal
+/// `@x`. Expansion is not recursive: an `@` token inside the file is
+/// passed through as is.
+fn expand_arg_files(args: impl IntoIterator<Item = OsString>) -> io::Result<Vec<OsString>> {
+    let mut out = Vec::new();
+    for arg in args {
+        match arg.to_str().and_then(|a| a.strip_prefix('@')) {
+            Some(escaped) if escaped.starts_with('@') => out.push(OsString::from(escaped)),
+            Some(path) if Path::new(path).is_file() => {
+                let text = std::fs::read_to_string(path).map_err(|e| {
+                    io::Error::new(e.kind(), format!("cannot read argument file {path}: {e}"))
+                })?;
+                out.extend(text.split_whitespace().map(OsString::from));
+            }
+            _ => out.push(arg),
+        }
+    }
+    Ok(out)
+}
+
+/// The last valid `--color WHEN` / `--color=WHEN` before a `--`, or
+/// `None` when there is none and the defaults apply.
+fn color_arg(args: impl IntoIterator<Item = OsString>) -> Option<ColorChoice> {
+    let mut color = None;
+    let mut args = args.into_iter().skip(1);
+    while let Some(arg) = args.next() {
+        let value = match arg.to_str() {
+            Some("--") => break,
+            Some("--color") => args.next(),
+            Some(arg) => arg.strip_prefix("--color=").map(OsString::from),
+            None => None,
+        };
+        if let Some(choice) = value
+            .as_deref()
+            .and_then(|v| v.to_str())
+            .and_then(|v| ColorChoice::from_str(v, true).ok())
+        {
+            color = Some(choice);
+        }
+    }
+    color
+}
+
+/// Log to stderr at the `RUST_LOG` level. `--color` overrides the
+/// `RUST_LOG_STYLE` environment variable, which otherwise decides
+/// whether log lines are colored.
+fn init_logger(color: Option<ColorChoice>) {
+    let mut builder = env_logger::Builder::from_default_env();
+    if let Some(color) = color {
+        builder.write_style(match color {
+            ColorChoice::Auto => WriteStyle::Auto,
+            ColorChoice::Always => WriteStyle::Always,
+            ColorChoice::Never => WriteStyle::Never,
+        });
+    }
+    builder.init();
+}
+
 fn run(command: Command) -> ExitCode {
     match command {
         Command::Metrics(args) => commands::metrics(args),
@@ -54,6 +134,18 @@ fn run(command: Command) -> ExitCode {
             mehen_engine::run_top_offenders(opts);
             ExitCode::Success
         }
+        Command::Languages(opts) => {
+            mehen_engine::run_languages(opts);
+            ExitCode::Success
+        }
+        Command::ExplainMetric(opts) => {
+            mehen_engine::run_explain(opts);
+            ExitCode::Success
+        }
+        Command::Doctor(opts) => {
+            mehen_engine::run_doctor(opts);
+            ExitCode::Success
+        }
     }
 }
 
@@ -85,3 +177,29 @@ fn print_version(as_json: bool) {
         .expect("failed to write version");
     }
 }
+
+/// Print `mehen X.Y.Z`, then one `<language> <grammar version> (ABI
+/// <n>)` line per bundled tree-sitter grammar and one `<language>
+/// <version> (<parser>)` line per other parser. Grammars generated
+/// before ABI 15 carry no version metadata and print `unknown`.
+fn print_versions() {
+    let mut out = format!("{} {}\n", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    for grammar in mehen_engine::grammar_versions() {
+        out.push_str(&format!(
+            "{} {} (ABI {})\n",
+            grammar.language,
+            grammar.version.as_deref().unwrap_or("unknown"),
+            grammar.abi
+        ));
+    }
+    for parser in mehen_engine::parser_versions() {
+        out.push_str(&format!(
+            "{} {} ({})\n",
+            parser.language, parser.version, parser.parser
+        ));
+    }
+    io::stdout()
+        .lock()
+        .write_all(out.as_bytes())
+        .expect("failed to write versions");
+}
@@ -0,0 +1,159 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! `top-offenders --baseline-write` → `diff --baseline` round trip.
+
+mod common;
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+use common::git;
+
+fn mehen(repo: &Path, args: &[&str]) -> Output {
+    let out = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(args)
+        .current_dir(repo)
+        .env_remove("GITHUB_ACTIONS")
+        .env_remove("GITHUB_EVENT_NAME")
+        .env_remove("GITHUB_BASE_REF")
+        .env_remove("GITHUB_SHA")
+        .output()
+        .expect("failed to run mehen");
+    assert!(
+        out.status.success(),
+        "mehen {args:?} failed: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    out
+}
+
+#[test]
+fn diff_reads_back_a_written_baseline() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let repo = dir.path();
+    git(repo, &["init", "-q", "-b", "main"]);
+    std::fs::create_dir_all(repo.join("src")).unwrap();
+    std::fs::write(repo.join("src/a.rs"), "fn a(x: u32) -> u32 {\n    x\n}\n").unwrap();
+    git(repo, &["add", "-A"]);
+    git(repo, &["commit", "-q", "-m", "base"]);
+
+    mehen(
+        repo,
+        &[
+            "top-offenders",
+            "-M",
+            "cyclomatic",
+            "--baseline-write",
+            "baseline.json",
+            "./src",
+        ],
+    );
+    let written: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(repo.join("baseline.json")).unwrap())
+            .expect("baseline must be valid JSON");
+    assert_eq!(written["schema_version"], "1.0");
+    assert!(written["files"]["src/a.rs"].is_object(), "{written}");
+
+    std::fs::write(
+        repo.join("src/a.rs"),
+        "fn a(x: u32) -> u32 {\n    if x > 1 { x } else { 0 }\n}\n",
+    )
+    .unwrap();
+    git(repo, &["commit", "-q", "-am", "head"]);
+
+    let out = mehen(
+        repo,
+        &[
+            "diff",
+            "--from",
+            "HEAD~1",
+            "--to",
+            "HEAD",
+            "--baseline",
+            "baseline.json",
+            "--output-format",
+            "json",
+        ],
+    );
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&out.stdout).expect("diff output must be valid JSON");
+    let stdout = parsed.to_string();
+    assert!(stdout.contains("src/a.rs"), "{stdout}");
+    assert!(!stdout.contains("\"is_new\":true"), "{stdout}");
+    // The same snapshot through `--baseline-url`, as a CI artifact path.
+    let url = format!("file://{}", repo.join("baseline.json").display());
+    if url.starts_with("file:///") {
+        let out = mehen(
+            repo,
+            &[
+                "diff",
+                "--from",
+                "HEAD~1",
+                "--to",
+                "HEAD",
+                "--baseline-url",
+                &url,
+                "--output-format",
+                "json",
+            ],
+        );
+        let via_url: serde_json::Value =
+            serde_json::from_slice(&out.stdout).expect("diff output must be valid JSON");
+        assert_eq!(via_url, parsed);
+    }
+}
+
+#[test]
+fn ignore_new_drops_files_the_baseline_does_not_record() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let repo = dir.path();
+    git(repo, &["init", "-q", "-b", "main"]);
+    std::fs::create_dir_all(repo.join("src")).unwrap();
+    std::fs::write(repo.join("src/a.rs"), "fn a(x: u32) -> u32 {\n    x\n}\n").unwrap();
+    std::fs::write(repo.join("src/b.rs"), "fn b(x: u32) -> u32 {\n    x\n}\n").unwrap();
+    git(repo, &["add", "-A"]);
+    git(repo, &["commit", "-q", "-m", "base"]);
+    // Only `a.rs` is recorded, so a modified `b.rs` is new to the baseline.
+    mehen(
+        repo,
+        &[
+            "top-offenders",
+            "-M",
+            "cyclomatic",
+            "--baseline-write",
+            "baseline.json",
+            "./src/a.rs",
+        ],
+    );
+
+    for file in ["src/a.rs", "src/b.rs"] {
+        std::fs::write(
+            repo.join(file),
+            "fn a(x: u32) -> u32 {\n    if x > 1 { x } else { 0 }\n}\n",
+        )
+        .unwrap();
+    }
+    git(repo, &["commit", "-q", "-am", "head"]);
+
+    let diff = |extra: &[&str]| {
+        let mut args = vec![
+            "diff",
+            "--from",
+            "HEAD~1",
+            "--to",
+            "HEAD",
+            "--baseline",
+            "baseline.json",
+            "--output-format",
+            "json",
+        ];
+        args.extend_from_slice(extra);
+        String::from_utf8(mehen(repo, &args).stdout).unwrap()
+    };
+    let all = diff(&[]);
+    assert!(all.contains("src/b.rs"), "{all}");
+    let existing = diff(&["--ignore-new"]);
+    assert!(existing.contains("src/a.rs"), "{existing}");
+    assert!(!existing.contains("src/b.rs"), "{existing}");
+}
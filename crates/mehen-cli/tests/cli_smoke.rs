@@ -8,7 +8,7 @@
 //! dropped per the rewrite plan §2.1; the new surface is `metrics`,
 //! `diff`, and `top-offenders`.
 use std::io::Write;
-use std::process::Command;
+use std::process::{Command, Stdio};
 
 fn write_python(dir: &std::path::Path, name: &str, body: &str) -> std::path::PathBuf {
     let path = dir.join(name);
@@ -28,6 +28,66 @@ fn version_prints_name_and_version() {
     assert!(stdout.contains("mehen"));
 }
 
+#[test]
+fn versions_lists_each_grammar_and_parser() {
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .arg("--versions")
+        .output()
+        .expect("failed to run mehen --versions");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout utf8");
+    let mut lines = stdout.lines();
+    assert!(lines.next().unwrap().starts_with("mehen "));
+    let grammars: Vec<&str> = lines.collect();
+    for language in ["c", "go", "powershell"] {
+        let line = grammars
+            .iter()
+            .find(|l| l.starts_with(&format!("{language} ")))
+            .unwrap_or_else(|| panic!("no {language} line in {stdout}"));
+        let version = line.split(' ').nth(1).unwrap();
+        assert_eq!(version.split('.').count(), 3, "{line}");
+        assert!(line.ends_with(')') && line.contains("(ABI "), "{line}");
+    }
+    assert!(
+        grammars.iter().any(|l| l.starts_with("kotlin ")),
+        "{stdout}"
+    );
+    for (language, parser) in [
+        ("typescript", "oxc"),
+        ("rust", "ra_ap_syntax"),
+        ("markdown", "pulldown-cmark"),
+    ] {
+        let line = grammars
+            .iter()
+            .find(|l| l.starts_with(&format!("{language} ")))
+            .unwrap_or_else(|| panic!("no {language} line in {stdout}"));
+        assert!(line.ends_with(&format!(" ({parser})")), "{line}");
+        let version = line.split(' ').nth(1).unwrap();
+        assert_eq!(version.split('.').count(), 3, "{line}");
+    }
+}
+
+#[test]
+fn color_flag_controls_stderr_escapes() {
+    let run = |color: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+            .args(["metrics", "missing.rs", "--color", color])
+            .env_remove("RUST_LOG_STYLE")
+            .output()
+            .expect("failed to run mehen metrics");
+        assert_eq!(output.status.code(), Some(1));
+        String::from_utf8(output.stderr).expect("stderr utf8")
+    };
+    assert!(run("always").contains('\u{1b}'));
+    assert!(!run("never").contains('\u{1b}'));
+
+    let invalid = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["--color", "sometimes", "languages"])
+        .output()
+        .expect("failed to run mehen");
+    assert_eq!(invalid.status.code(), Some(2));
+}
+
 #[test]
 fn help_succeeds() {
     let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
@@ -105,3 +165,1515 @@ fn top_offenders_requires_paths() {
         "top-offenders without paths must fail"
     );
 }
+
+#[test]
+fn metrics_max_returns_gate() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("sample.rs");
+    std::fs::write(
+        &path,
+        "fn pick(x: u32) -> u32 {\n    if x == 0 {\n        return 0;\n    }\n    if x == 1 {\n        return 1;\n    }\n    if x == 2 {\n        return 2;\n    }\n    return 3;\n}\n",
+    )
+    .expect("write rs file");
+
+    let run = |max: &str| {
+        Command::new(env!("CARGO_BIN_EXE_mehen"))
+            .args(["metrics", path.to_str().unwrap(), "--max-returns", max])
+            .output()
+            .expect("failed to run mehen metrics")
+    };
+
+    let failing = run("3");
+    assert_eq!(failing.status.code(), Some(2));
+    assert!(
+        String::from_utf8_lossy(&failing.stderr).contains("`pick`"),
+        "stderr must name the offending function: {}",
+        String::from_utf8_lossy(&failing.stderr)
+    );
+    assert!(!failing.stdout.is_empty(), "report is still printed");
+
+    assert_eq!(run("4").status.code(), Some(0));
+}
+
+#[test]
+fn explain_metric_cyclomatic_prints_description() {
+    let out = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["explain-metric", "cyclomatic"])
+        .output()
+        .expect("failed to run mehen explain-metric");
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.starts_with("cyclomatic\n\n"), "{stdout}");
+    assert!(
+        stdout.contains("decision"),
+        "description is missing: {stdout}"
+    );
+    assert!(stdout.contains("Polarity: lower is better"), "{stdout}");
+
+    let unknown = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["explain-metric", "nonsense"])
+        .output()
+        .expect("failed to run mehen explain-metric");
+    assert_eq!(unknown.status.code(), Some(1));
+}
+
+#[test]
+fn metrics_stdin_detects_language_from_stdin_name() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["metrics", "--stdin", "--stdin-name", "foo.rs"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to run mehen metrics");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"fn main() {\n    if true {\n        println!(\"hi\");\n    }\n}\n")
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+    assert!(out.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout).expect("valid JSON");
+    assert_eq!(json["path"], "foo.rs");
+    assert_eq!(json["language"], "rust");
+
+    let nameless = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["metrics", "--stdin"])
+        .stdin(Stdio::null())
+        .output()
+        .expect("failed to run mehen metrics");
+    assert_eq!(nameless.status.code(), Some(1));
+}
+
+#[test]
+fn metrics_modeline_overrides_extension() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("view.ts");
+    std::fs::write(
+        &path,
+        "// mehen-lang: tsx\nexport const View = () => <div>{1}</div>;\n",
+    )
+    .expect("write ts file");
+
+    let out = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["metrics", path.to_str().unwrap()])
+        .output()
+        .expect("failed to run mehen metrics");
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout).expect("valid JSON");
+    assert_eq!(json["language"], "tsx");
+    assert_eq!(json["diagnostics"], serde_json::json!([]));
+}
+
+#[test]
+fn metrics_flat_json_columns_are_parallel_to_functions() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("sample.rs");
+    std::fs::write(
+        &path,
+        "fn a(x: u32) -> u32 {\n    if x > 1 { 1 } else { 2 }\n}\n\nstruct S;\n\nimpl S {\n    fn b(&self) {}\n}\n",
+    )
+    .expect("write rs file");
+
+    let out = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["metrics", path.to_str().unwrap(), "--flat-json"])
+        .output()
+        .expect("failed to run mehen metrics");
+    assert!(out.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout).expect("valid JSON");
+    assert!(json.get("root").is_none());
+
+    let functions = json["functions"].as_array().expect("functions array");
+    let names: Vec<&str> = functions
+        .iter()
+        .filter_map(|f| f["name"].as_str())
+        .collect();
+    assert_eq!(names, ["a", "S", "b"]);
+    let metrics = json["metrics"].as_object().expect("metrics object");
+    assert!(metrics.contains_key("cyclomatic"));
+    for (key, column) in metrics {
+        assert_eq!(
+            column.as_array().map(Vec::len),
+            Some(functions.len()),
+            "{key}"
+        );
+    }
+    assert_eq!(metrics["cyclomatic"][0], 2.0);
+}
+
+#[test]
+fn metrics_output_dash_streams_to_stdout() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("sample.rs");
+    std::fs::write(&path, "fn main() {}\n").expect("write rs file");
+
+    let out = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .current_dir(dir.path())
+        .args(["metrics", "sample.rs", "--format", "json", "--output", "-"])
+        .output()
+        .expect("failed to run mehen metrics");
+    assert!(out.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout).expect("valid JSON");
+    assert_eq!(json["path"], "sample.rs");
+    assert!(
+        !dir.path().join("-").exists(),
+        "no `-` directory is created"
+    );
+}
+
+#[test]
+fn metrics_max_imports_gate() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("sample.rs");
+    std::fs::write(
+        &path,
+        "use std::fmt;\nuse std::io;\nuse std::path::{Path, PathBuf};\n\nfn f() {}\n",
+    )
+    .expect("write rs file");
+
+    let run = |max: &str| {
+        Command::new(env!("CARGO_BIN_EXE_mehen"))
+            .args(["metrics", path.to_str().unwrap(), "--max-imports", max])
+            .output()
+            .expect("failed to run mehen metrics")
+    };
+
+    let failing = run("2");
+    assert_eq!(failing.status.code(), Some(2));
+    assert!(
+        String::from_utf8_lossy(&failing.stderr).contains("3 imports"),
+        "stderr must report the import count: {}",
+        String::from_utf8_lossy(&failing.stderr)
+    );
+
+    assert_eq!(run("3").status.code(), Some(0));
+}
+
+#[test]
+fn metrics_max_functions_gate() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("sample.rs");
+    std::fs::write(
+        &path,
+        "fn a() {}\nfn b() {}\nfn c() {}\nfn d() {}\nfn e() {}\n",
+    )
+    .expect("write rs file");
+
+    let run = |max: &str| {
+        Command::new(env!("CARGO_BIN_EXE_mehen"))
+            .args(["metrics", path.to_str().unwrap(), "--max-functions", max])
+            .output()
+            .expect("failed to run mehen metrics")
+    };
+
+    let failing = run("3");
+    assert_eq!(failing.status.code(), Some(2));
+    assert!(
+        String::from_utf8_lossy(&failing.stderr).contains("5 functions"),
+        "stderr must report the function count: {}",
+        String::from_utf8_lossy(&failing.stderr)
+    );
+
+    assert_eq!(run("5").status.code(), Some(0));
+}
+
+#[test]
+fn metrics_normalize_whitespace_makes_tabs_and_spaces_equal() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let tabs = dir.path().join("tabs.rs");
+    let spaces = dir.path().join("spaces.rs");
+    std::fs::write(&tabs, "fn f(x: u32) {\n\tif x > 1 {\n  \t\tg();\n\t}\n}\n").unwrap();
+    std::fs::write(
+        &spaces,
+        "fn f(x: u32) {\n    if x > 1 {\n        g();\n    }\n}\n",
+    )
+    .unwrap();
+
+    let root = |path: &std::path::Path, extra: &[&str]| {
+        let out = Command::new(env!("CARGO_BIN_EXE_mehen"))
+            .arg("metrics")
+            .arg(path)
+            .args(extra)
+            .output()
+            .expect("failed to run mehen metrics");
+        assert!(out.status.success());
+        let mut json: serde_json::Value = serde_json::from_slice(&out.stdout).expect("valid JSON");
+        // `function_id` embeds the file path.
+        json["root"]["spaces"][0]["function_id"].take();
+        json["root"].take()
+    };
+    assert_ne!(root(&tabs, &[]), root(&spaces, &[]));
+    let normalized = ["--normalize-whitespace"];
+    assert_eq!(root(&tabs, &normalized), root(&spaces, &normalized));
+}
+
+#[test]
+fn metrics_stats_goes_to_stderr_only() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("sample.rs");
+    std::fs::write(&path, "fn a() {}\nfn b(x: u32) -> u32 { x + 1 }\n").expect("write rs file");
+
+    let run = |extra: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_mehen"))
+            .args(["metrics", path.to_str().unwrap()])
+            .args(extra)
+            .output()
+            .expect("failed to run mehen metrics")
+    };
+
+    let plain = run(&[]);
+    let with_stats = run(&["--stats"]);
+    assert!(with_stats.status.success());
+    assert_eq!(plain.stdout, with_stats.stdout);
+    let stderr = String::from_utf8_lossy(&with_stats.stderr);
+    assert!(stderr.contains("language=rust"), "stderr: {stderr}");
+    assert!(stderr.contains("spaces=3"), "stderr: {stderr}");
+    let nodes: u64 = stderr
+        .split_once("nodes=")
+        .and_then(|(_, rest)| rest.split_whitespace().next())
+        .and_then(|n| n.parse().ok())
+        .unwrap_or_else(|| panic!("no node count in stderr: {stderr}"));
+    assert!(nodes > 20, "stderr: {stderr}");
+}
+
+#[test]
+fn metrics_ops_summary_reports_top_tokens() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("sample.rs");
+    std::fs::write(
+        &path,
+        "fn f(x: u32) -> u32 {\n    let y = x + x;\n    x * y + x\n}\n",
+    )
+    .expect("write rs file");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["metrics", path.to_str().unwrap(), "--ops-summary", "2"])
+        .output()
+        .expect("failed to run mehen metrics");
+    assert!(output.status.success());
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("ops summary must be valid JSON");
+    let operands = parsed["operands"].as_array().expect("operands array");
+    assert_eq!(operands.len(), 2);
+    assert_eq!(operands[0]["token"].as_str(), Some("x"));
+    assert_eq!(operands[0]["count"].as_u64(), Some(5));
+    assert_eq!(parsed["operators"].as_array().map(Vec::len), Some(2));
+}
+
+#[test]
+fn metrics_ops_summary_is_byte_identical_across_runs() {
+    // Hash seeds differ per process, so two separate runs catch any output
+    // that still follows hash-map iteration order.
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("sample.rs");
+    std::fs::write(
+        &path,
+        "fn f(a: u32, b: u32, c: u32) -> u32 {\n    let d = a + b - c;\n    a * b / c % d\n}\n",
+    )
+    .expect("write rs file");
+
+    let run = || {
+        let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+            .args(["metrics", path.to_str().unwrap(), "--ops-summary", "100"])
+            .output()
+            .expect("failed to run mehen metrics");
+        assert!(output.status.success());
+        output.stdout
+    };
+
+    let first = run();
+    for _ in 0..4 {
+        assert_eq!(first, run());
+    }
+}
+
+#[test]
+fn metrics_emit_spans_lists_nested_functions() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("sample.rs");
+    std::fs::write(
+        &path,
+        "fn outer() {\n    fn inner() {}\n    inner();\n}\n\nfn other() {}\n",
+    )
+    .expect("write rs file");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["metrics", path.to_str().unwrap(), "--emit-spans"])
+        .output()
+        .expect("failed to run mehen metrics");
+    assert!(output.status.success());
+    let spans: Vec<serde_json::Value> =
+        serde_json::from_slice(&output.stdout).expect("valid JSON array");
+    let summary: Vec<(String, Option<String>, u64, u64, u64)> = spans
+        .iter()
+        .map(|s| {
+            (
+                s["kind"].as_str().unwrap().to_string(),
+                s["name"].as_str().map(str::to_string),
+                s["depth"].as_u64().unwrap(),
+                s["start_line"].as_u64().unwrap(),
+                s["end_line"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("unit".to_string(), None, 0, 1, 7),
+            ("function".to_string(), Some("outer".to_string()), 1, 1, 4),
+            ("function".to_string(), Some("inner".to_string()), 2, 2, 2),
+            ("function".to_string(), Some("other".to_string()), 1, 6, 6),
+        ]
+    );
+    assert!(spans.iter().all(|s| s.get("metrics").is_none()));
+}
+
+#[test]
+fn metrics_redact_strings_hides_literal_contents() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("sample.rs");
+    std::fs::write(&path, "fn f() -> &'static str {\n    \"hunter2\"\n}\n").expect("write rs file");
+
+    let run = |extra: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+            .args(["metrics", path.to_str().unwrap(), "--ops-summary", "10"])
+            .args(extra)
+            .output()
+            .expect("failed to run mehen metrics");
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).expect("stdout utf8")
+    };
+
+    assert!(run(&[]).contains("hunter2"));
+    let redacted = run(&["--redact-strings"]);
+    assert!(!redacted.contains("hunter2"), "stdout: {redacted}");
+    assert!(redacted.contains(r#""\"***\"""#), "stdout: {redacted}");
+}
+
+#[test]
+fn top_offenders_lang_filter_keeps_only_selected_languages() {
+    // The walker skips hidden directories such as `/tmp/.tmpXXXX`, so
+    // analyze a relative subdirectory from inside the tempdir.
+    let dir = tempfile::tempdir().expect("tempdir");
+    let src = dir.path().join("src");
+    std::fs::create_dir(&src).expect("create src");
+    std::fs::write(src.join("main.go"), "package main\n\nfunc main() {}\n").expect("write go file");
+    std::fs::write(src.join("lib.rs"), "fn f() {}\n").expect("write rs file");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["top-offenders", "-M", "cyclomatic", "-O", "json"])
+        .args(["--lang-filter", "go", "src"])
+        .current_dir(dir.path())
+        .output()
+        .expect("failed to run mehen top-offenders");
+    assert!(
+        output.status.success(),
+        "stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("top-offenders output must be JSON");
+    let paths: Vec<&str> = parsed
+        .as_array()
+        .expect("offender array")
+        .iter()
+        .filter_map(|o| o["path"].as_str())
+        .collect();
+    assert_eq!(paths.len(), 1, "{paths:?}");
+    assert!(paths[0].ends_with("main.go"), "{paths:?}");
+}
+
+#[test]
+fn arg_file_expands_like_inline_arguments() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let src = dir.path().join("src");
+    std::fs::create_dir(&src).expect("create src");
+    std::fs::write(
+        src.join("main.rs"),
+        "fn main() {\n    if std::env::args().count() > 1 {}\n}\n",
+    )
+    .expect("write rs file");
+    std::fs::write(src.join("lib.rs"), "fn f() {}\n").expect("write rs file");
+    std::fs::write(
+        dir.path().join("args.txt"),
+        "-M cyclomatic\n-O json\n  src/main.rs\n",
+    )
+    .expect("write argument file");
+
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+            .arg("top-offenders")
+            .args(args)
+            .current_dir(dir.path())
+            .output()
+            .expect("failed to run mehen top-offenders");
+        assert!(
+            output.status.success(),
+            "stderr={}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        output.stdout
+    };
+
+    let inline = run(&["-M", "cyclomatic", "-O", "json", "src/main.rs"]);
+    assert!(String::from_utf8_lossy(&inline).contains("main.rs"));
+    assert_eq!(run(&["@args.txt"]), inline);
+}
+
+#[test]
+fn at_paths_that_are_not_argument_files_pass_through() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let scope = dir.path().join("@scope");
+    std::fs::create_dir(&scope).expect("create @scope");
+    std::fs::write(scope.join("index.rs"), "fn f() {}\n").expect("write rs file");
+
+    let run = |path: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+            .args(["top-offenders", "-M", "cyclomatic", "-O", "json", path])
+            .current_dir(dir.path())
+            .output()
+            .expect("failed to run mehen top-offenders");
+        assert!(
+            output.status.success(),
+            "stderr={}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).expect("utf-8 stdout")
+    };
+
+    // A directory is not an argument file, so `@scope` is a path.
+    assert!(run("@scope").contains("index.rs"));
+    // `@@` escapes a leading `@`, even where a file would match.
+    std::fs::write(dir.path().join("scope"), "-M abc\n").expect("write argument file");
+    assert!(run("@@scope").contains("index.rs"));
+}
+
+#[test]
+fn top_offenders_report_skipped_lists_unanalyzed_files() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let src = dir.path().join("src");
+    std::fs::create_dir(&src).expect("create src");
+    std::fs::write(src.join("lib.rs"), "fn f() {}\n").expect("write rs file");
+    std::fs::write(src.join("empty.rs"), "").expect("write empty file");
+    std::fs::write(src.join("notes.xyz"), "hello\n").expect("write xyz file");
+    std::fs::write(src.join("blob.rs"), b"\x7fELF\x00\xff\x00").expect("write binary file");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["top-offenders", "-M", "cyclomatic", "-O", "json"])
+        .args(["--report-skipped", "src"])
+        .current_dir(dir.path())
+        .output()
+        .expect("failed to run mehen top-offenders");
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("skipped 2 file(s)"), "{stderr}");
+    assert!(
+        stderr.contains("notes.xyz: unsupported language"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("blob.rs: binary file"), "{stderr}");
+    assert!(
+        !stderr.contains("lib.rs") && !stderr.contains("empty.rs"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn top_offenders_exclude_decls_only_skips_signature_files() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let src = dir.path().join("src");
+    std::fs::create_dir(&src).expect("create src");
+    std::fs::write(
+        src.join("decls.rs"),
+        "pub trait Shape {\n    fn area(&self) -> f64;\n}\npub struct Circle {\n    pub r: f64,\n}\n",
+    )
+    .expect("write decls file");
+    std::fs::write(
+        src.join("impls.rs"),
+        "pub fn area(r: f64) -> f64 {\n    r * r\n}\n",
+    )
+    .expect("write impls file");
+    std::fs::write(src.join("script.js"), "const r = 2;\nconsole.log(r * r);\n")
+        .expect("write script file");
+    std::fs::write(
+        src.join("shapes.d.ts"),
+        "export interface Shape {\n    area(): number;\n}\nexport declare const unit: Shape;\n",
+    )
+    .expect("write declaration file");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["top-offenders", "-M", "cyclomatic", "-O", "json"])
+        .args(["--exclude-decls-only", "--report-skipped", "src"])
+        .current_dir(dir.path())
+        .output()
+        .expect("failed to run mehen top-offenders");
+    assert!(output.status.success());
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("top-offenders output must be JSON");
+    let paths: Vec<&str> = parsed
+        .as_array()
+        .expect("offender array")
+        .iter()
+        .filter_map(|o| o["path"].as_str())
+        .collect();
+    assert_eq!(paths.len(), 2, "{paths:?}");
+    assert!(paths.iter().any(|p| p.ends_with("impls.rs")), "{paths:?}");
+    assert!(paths.iter().any(|p| p.ends_with("script.js")), "{paths:?}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("decls.rs: declarations only"), "{stderr}");
+    assert!(
+        stderr.contains("shapes.d.ts: declarations only"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn top_offenders_lang_filter_rejects_unknown_language() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args([
+            "top-offenders",
+            "-M",
+            "cyclomatic",
+            "--lang-filter",
+            "cobol",
+        ])
+        .arg(dir.path())
+        .output()
+        .expect("failed to run mehen top-offenders");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn top_offenders_fail_above_exits_two() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let src = dir.path().join("src");
+    std::fs::create_dir(&src).expect("create src");
+    std::fs::write(
+        src.join("bad.rs"),
+        "fn f(a: bool, b: bool) -> u8 {\n    if a { 1 } else if b { 2 } else { 3 }\n}\n",
+    )
+    .expect("write bad file");
+    std::fs::write(src.join("good.rs"), "fn g() {}\n").expect("write good file");
+
+    let run = |limit: &str| {
+        Command::new(env!("CARGO_BIN_EXE_mehen"))
+            .args(["top-offenders", "-M", "cyclomatic", "-O", "json"])
+            .args(["--fail-above", limit, "--fail-fast", "src"])
+            .current_dir(dir.path())
+            .output()
+            .expect("failed to run mehen top-offenders")
+    };
+    assert_eq!(run("2").status.code(), Some(2));
+    assert_eq!(run("10").status.code(), Some(0));
+}
+
+#[test]
+fn top_offenders_top_lists_worst_functions() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let src = dir.path().join("src");
+    std::fs::create_dir(&src).expect("create src");
+    std::fs::write(
+        src.join("a.rs"),
+        "fn flat() {}\n\nfn one(a: bool) {\n    if a {}\n}\n",
+    )
+    .expect("write a.rs");
+    std::fs::write(
+        src.join("b.rs"),
+        "fn nested(a: bool, b: bool) {\n    if a {\n        if b {}\n    }\n}\n",
+    )
+    .expect("write b.rs");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["top-offenders", "-M", "loc.lloc", "-O", "json"])
+        .args(["--top", "2", "--top-by", "cognitive", "src"])
+        .current_dir(dir.path())
+        .output()
+        .expect("failed to run mehen top-offenders");
+    assert!(
+        output.status.success(),
+        "stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("top-offenders output must be JSON");
+    let ranked: Vec<(&str, u64, f64)> = parsed
+        .as_array()
+        .expect("function array")
+        .iter()
+        .map(|f| {
+            (
+                f["name"].as_str().expect("name"),
+                f["line"].as_u64().expect("line"),
+                f["value"].as_f64().expect("value"),
+            )
+        })
+        .collect();
+    assert_eq!(ranked, vec![("nested", 1, 3.0), ("one", 3, 1.0)]);
+}
+
+#[test]
+fn top_offenders_honors_mehenignore_without_exclude_flag() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    std::fs::create_dir(dir.path().join(".git")).expect("create .git");
+    std::fs::write(dir.path().join(".mehenignore"), "# skip me\nsrc/skip.rs\n")
+        .expect("write .mehenignore");
+    let src = dir.path().join("src");
+    std::fs::create_dir(&src).expect("create src");
+    std::fs::write(src.join("keep.rs"), "fn keep() {}\n").expect("write keep.rs");
+    std::fs::write(src.join("skip.rs"), "fn skip() {}\n").expect("write skip.rs");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["top-offenders", "-M", "cyclomatic", "-O", "json", "src"])
+        .current_dir(dir.path())
+        .output()
+        .expect("failed to run mehen top-offenders");
+    assert!(
+        output.status.success(),
+        "stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("top-offenders output must be JSON");
+    let paths: Vec<&str> = parsed
+        .as_array()
+        .expect("offender array")
+        .iter()
+        .filter_map(|o| o["path"].as_str())
+        .collect();
+    assert_eq!(paths, vec!["src/keep.rs"]);
+}
+
+#[test]
+fn metrics_byte_range_reports_only_the_selected_function() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let first = "fn first(a: bool) -> u8 {\n    if a { 1 } else { 0 }\n}\n";
+    let second = "fn second() -> u8 {\n    7\n}\n";
+    let file = dir.path().join("two.rs");
+    std::fs::write(&file, format!("{first}{second}")).expect("write fixture");
+
+    let start = first.len().to_string();
+    let end = (first.len() + second.len()).to_string();
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["metrics", "--byte-start", &start, "--byte-end", &end])
+        .arg(&file)
+        .output()
+        .expect("failed to run mehen metrics");
+    assert!(
+        output.status.success(),
+        "stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("metrics output must be JSON");
+    assert_eq!(parsed["root"]["name"], "second");
+    assert_eq!(parsed["root"]["kind"], "function");
+    assert_eq!(parsed["metrics"]["cyclomatic"]["sum"], 1.0);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["metrics", "--byte-start", "1", "--byte-end", "5"])
+        .arg(&file)
+        .output()
+        .expect("failed to run mehen metrics");
+    assert_eq!(output.status.code(), Some(1));
+
+    // Both functions lie inside: the range is rejected, not reported
+    // under a unit without metrics of its own.
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["metrics", "--byte-start", "0", "--byte-end", &end])
+        .arg(&file)
+        .output()
+        .expect("failed to run mehen metrics");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("2 spaces lie within bytes"),
+        "stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn metrics_file_level_drops_nested_spaces() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let file = dir.path().join("two.rs");
+    std::fs::write(
+        &file,
+        "fn a() -> u8 {\n    1\n}\nfn b() -> u8 {\n    2\n}\n",
+    )
+    .expect("write fixture");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["metrics", "--file-level"])
+        .arg(&file)
+        .output()
+        .expect("failed to run mehen metrics");
+    assert!(
+        output.status.success(),
+        "stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("metrics output must be JSON");
+    assert_eq!(parsed["root"]["kind"], "unit");
+    assert!(parsed["root"].get("spaces").is_none());
+    assert_eq!(parsed["metrics"]["nom"]["functions"], 2.0);
+}
+
+#[test]
+fn metrics_with_metadata_wraps_values_with_units() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let file = dir.path().join("add.rs");
+    std::fs::write(&file, "fn add(a: u8, b: u8) -> u8 {\n    a + b\n}\n").expect("write fixture");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["metrics", "--with-metadata"])
+        .arg(&file)
+        .output()
+        .expect("failed to run mehen metrics");
+    assert!(
+        output.status.success(),
+        "stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("metrics output must be JSON");
+    let time = &parsed["metrics"]["halstead"]["time"];
+    assert_eq!(time["unit"], "seconds");
+    assert!(time["value"].as_f64().is_some_and(|t| t > 0.0));
+    assert!(time["description"].is_string());
+    assert_eq!(parsed["metrics"]["cyclomatic"]["sum"]["value"], 2.0);
+}
+
+#[test]
+fn metrics_raw_drops_averages_and_bounds() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let file = dir.path().join("add.rs");
+    std::fs::write(&file, "fn add(a: u8, b: u8) -> u8 {\n    a + b\n}\n").expect("write fixture");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["metrics", "--raw"])
+        .arg(&file)
+        .output()
+        .expect("failed to run mehen metrics");
+    assert!(output.status.success());
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("metrics output must be JSON");
+    let loc = parsed["metrics"]["loc"].as_object().expect("loc family");
+    assert_eq!(loc["sloc"], 3.0);
+    assert!(!loc.contains_key("sloc_average"), "{loc:?}");
+    assert!(!loc.contains_key("sloc_min"), "{loc:?}");
+    let function = parsed["root"]["spaces"][0]["metrics"]
+        .as_object()
+        .expect("function metrics");
+    assert!(function.contains_key("cyclomatic"));
+    assert!(!function.contains_key("cyclomatic.min"), "{function:?}");
+    assert!(parsed["metrics"]["halstead"]["N1"].is_number());
+    // No space keeps a rolled-up bound, average or percentile, however
+    // deeply its key is nested.
+    let mut spaces = vec![&parsed["root"]];
+    while let Some(space) = spaces.pop() {
+        let metrics = space["metrics"].as_object().expect("space metrics");
+        for key in metrics.keys() {
+            assert!(
+                ![".avg", ".min", ".max"].iter().any(|s| key.ends_with(s)),
+                "{key}"
+            );
+            assert!(!key.contains("_p50") && !key.contains("_p9"), "{key}");
+        }
+        if let Some(children) = space["spaces"].as_array() {
+            spaces.extend(children);
+        }
+    }
+}
+
+#[test]
+fn metrics_exclude_metric_drops_the_family() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let file = dir.path().join("add.rs");
+    std::fs::write(&file, "fn add(a: u8, b: u8) -> u8 {\n    a + b\n}\n").expect("write fixture");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["metrics", "--exclude-metric", "halstead"])
+        .arg(&file)
+        .output()
+        .expect("failed to run mehen metrics");
+    assert!(output.status.success());
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("metrics output must be JSON");
+    let families = parsed["metrics"].as_object().expect("metrics families");
+    assert!(!families.contains_key("halstead"), "{families:?}");
+    assert!(families.contains_key("cyclomatic"));
+    let unit = parsed["root"]["metrics"].as_object().expect("unit metrics");
+    assert!(!unit.keys().any(|k| k.starts_with("halstead")), "{unit:?}");
+    // Derived from Halstead volume before the family was dropped.
+    assert!(unit.contains_key("mi.visual_studio"), "{unit:?}");
+
+    let unknown = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["metrics", "--exclude-metric", "halsted"])
+        .arg(&file)
+        .output()
+        .expect("failed to run mehen metrics");
+    assert!(!unknown.status.success());
+
+    let markdown = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args([
+            "metrics",
+            "--format",
+            "markdown",
+            "--exclude-metric",
+            "halstead",
+        ])
+        .arg(&file)
+        .output()
+        .expect("failed to run mehen metrics");
+    assert!(markdown.status.success());
+    let markdown = String::from_utf8(markdown.stdout).expect("utf-8 stdout");
+    assert!(!markdown.contains("### Halstead"), "{markdown}");
+    assert!(markdown.contains("### Cyclomatic"), "{markdown}");
+
+    let cognitive = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["metrics", "--exclude-metric", "cognitive"])
+        .arg(&file)
+        .output()
+        .expect("failed to run mehen metrics");
+    assert!(cognitive.status.success());
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&cognitive.stdout).expect("metrics output must be JSON");
+    let unit = parsed["root"]["metrics"].as_object().expect("unit metrics");
+    assert!(!unit.keys().any(|k| k.starts_with("cognitive")), "{unit:?}");
+}
+
+#[test]
+fn metrics_exclude_closures_drops_lambdas_from_nom() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let file = dir.path().join("lambdas.rs");
+    std::fs::write(
+        &file,
+        "fn run(xs: &[u8]) -> usize {\n    let f = |x: &u8| *x > 1;\n    let g = |x: &u8| *x < 9;\n    xs.iter().filter(|x| f(x) && g(x)).count()\n}\n",
+    )
+    .expect("write fixture");
+
+    let nom = |extra: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+            .arg("metrics")
+            .args(extra)
+            .arg(&file)
+            .output()
+            .expect("failed to run mehen metrics");
+        assert!(
+            output.status.success(),
+            "stderr={}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let parsed: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("metrics output must be JSON");
+        parsed["metrics"]["nom"].clone()
+    };
+
+    let with = nom(&[]);
+    assert_eq!(with["functions"], 1.0);
+    assert_eq!(with["closures"], 3.0);
+    assert_eq!(with["total"], 4.0);
+
+    let without = nom(&["--exclude-closures"]);
+    assert_eq!(without["functions"], 1.0);
+    assert_eq!(without["closures"], 0.0);
+    assert_eq!(without["total"], 1.0);
+    assert_eq!(without["average"], without["functions_average"]);
+}
+
+#[test]
+fn metrics_stroud_number_scales_halstead_time() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let file = dir.path().join("sum.rs");
+    std::fs::write(&file, "fn sum(a: u32, b: u32) -> u32 {\n    a + b * a\n}\n")
+        .expect("write fixture");
+
+    let halstead = |extra: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+            .arg("metrics")
+            .args(extra)
+            .arg(&file)
+            .output()
+            .expect("failed to run mehen metrics");
+        assert!(
+            output.status.success(),
+            "stderr={}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let parsed: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("metrics output must be JSON");
+        parsed["metrics"]["halstead"].clone()
+    };
+
+    let classic = halstead(&[]);
+    let tuned = halstead(&[
+        "--halstead-stroud-number",
+        "9",
+        "--halstead-bugs-constant",
+        "1500",
+    ]);
+    let time = classic["time"].as_f64().unwrap();
+    assert!(time > 0.0);
+    assert_eq!(tuned["effort"], classic["effort"]);
+    assert!((tuned["time"].as_f64().unwrap() - 2.0 * time).abs() < 1e-9);
+    let bugs = classic["bugs"].as_f64().unwrap();
+    assert!((tuned["bugs"].as_f64().unwrap() - 2.0 * bugs).abs() < 1e-12);
+
+    let rejected = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["metrics", "--halstead-stroud-number", "0"])
+        .arg(&file)
+        .output()
+        .expect("failed to run mehen metrics");
+    assert!(!rejected.status.success());
+}
+
+#[test]
+fn metrics_reads_halstead_overrides_from_project_config() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    std::fs::create_dir(dir.path().join(".git")).expect("create .git");
+    std::fs::write(
+        dir.path().join("main.go"),
+        "package main\nfunc f() { fmt.Println(os.Args) }\n",
+    )
+    .expect("write main.go");
+    std::fs::write(dir.path().join("lib.rs"), "fn f() {}\n").expect("write lib.rs");
+
+    let counts = || {
+        let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+            .args(["metrics", "main.go"])
+            .current_dir(dir.path())
+            .output()
+            .expect("failed to run mehen metrics");
+        assert!(
+            output.status.success(),
+            "stderr={}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let parsed: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("metrics output must be JSON");
+        let halstead = &parsed["metrics"]["halstead"];
+        (
+            halstead["n1"].as_f64().unwrap(),
+            halstead["n2"].as_f64().unwrap(),
+        )
+    };
+    let (n1, n2) = counts();
+    std::fs::write(
+        dir.path().join(".mehen.toml"),
+        "[halstead-overrides]\n\".\" = \"operand\"\n",
+    )
+    .expect("write .mehen.toml");
+    assert_eq!(counts(), (n1 - 1.0, n2 + 1.0));
+
+    // The flag names one file, so a language that ignores it is an error.
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["metrics", "--halstead-override", ".=operand", "lib.rs"])
+        .current_dir(dir.path())
+        .output()
+        .expect("failed to run mehen metrics");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("not supported for rust"),
+        "stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn metrics_debt_ratio_is_higher_for_branchy_code() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let simple = dir.path().join("simple.rs");
+    std::fs::write(
+        &simple,
+        "fn area(w: u32, h: u32) -> u32 {\n    let a = w * h;\n    a + w + h\n}\n",
+    )
+    .expect("write fixture");
+    let branchy = dir.path().join("branchy.rs");
+    std::fs::write(
+        &branchy,
+        "fn grade(s: u32, bonus: bool) -> u32 {\n    if s > 90 {\n        if bonus { 5 } else { 4 }\n    } else if s > 70 {\n        for _ in 0..s {\n            if bonus && s > 80 {\n                return 3;\n            }\n        }\n        2\n    } else {\n        1\n    }\n}\n",
+    )
+    .expect("write fixture");
+
+    let debt_ratio = |file: &std::path::Path| {
+        let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+            .arg("metrics")
+            .arg(file)
+            .output()
+            .expect("failed to run mehen metrics");
+        assert!(output.status.success());
+        let parsed: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("metrics output must be JSON");
+        parsed["metrics"]["debt_ratio"]
+            .as_f64()
+            .expect("unit-level debt_ratio")
+    };
+
+    let simple = debt_ratio(&simple);
+    let branchy = debt_ratio(&branchy);
+    assert_eq!(simple, 0.0);
+    assert!(branchy > simple, "branchy={branchy} simple={simple}");
+}
+
+#[test]
+fn metrics_vs_last_reports_delta_from_previous_run() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    std::fs::write(
+        dir.path().join("lib.rs"),
+        "fn f(x: bool) {\n    if x {}\n}\n",
+    )
+    .expect("write fixture");
+
+    let run = || {
+        let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+            .args(["metrics", "lib.rs", "--vs-last"])
+            .current_dir(dir.path())
+            .output()
+            .expect("failed to run mehen metrics");
+        assert!(output.status.success());
+        String::from_utf8(output.stderr).expect("stderr utf8")
+    };
+
+    assert_eq!(run(), "lib.rs: no previous run recorded\n");
+    assert!(dir.path().join(".mehen/last-run.json").is_file());
+    assert_eq!(run(), "lib.rs: unchanged since last run\n");
+
+    std::fs::write(
+        dir.path().join("lib.rs"),
+        "fn f(x: bool, y: bool) {\n    if x {}\n    if y {}\n}\n",
+    )
+    .expect("rewrite fixture");
+    let changed = run();
+    assert!(changed.starts_with("lib.rs vs last run:\n"), "{changed}");
+    assert!(
+        changed.contains("  cyclomatic.sum: 3 → 4 (+1)\n"),
+        "{changed}"
+    );
+    assert!(changed.contains("  nargs: 1 → 2 (+1)\n"), "{changed}");
+    assert!(
+        changed.contains("  cyclomatic.avg: 1.50 → 2 (+0.50)\n"),
+        "{changed}"
+    );
+}
+
+#[test]
+fn metrics_output_mirror_keeps_same_named_files_apart() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    for (sub, body) in [("a", "fn a() {}\n"), ("b", "fn b() {\n    if true {}\n}\n")] {
+        std::fs::create_dir_all(dir.path().join("src").join(sub)).expect("mkdir");
+        std::fs::write(dir.path().join("src").join(sub).join("mod.rs"), body)
+            .expect("write fixture");
+    }
+
+    for sub in ["a", "b"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+            .current_dir(dir.path())
+            .args([
+                "metrics",
+                "--output",
+                "out",
+                "--output-dir-structure",
+                "mirror",
+            ])
+            .arg(format!("src/{sub}/mod.rs"))
+            .output()
+            .expect("failed to run mehen metrics");
+        assert!(
+            output.status.success(),
+            "stderr={}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(output.stdout.is_empty(), "report must go to the file only");
+    }
+
+    let read = |sub: &str| -> serde_json::Value {
+        let path = dir.path().join("out/src").join(sub).join("mod.rs.json");
+        let text = std::fs::read_to_string(&path).expect("mirrored report exists");
+        serde_json::from_str(&text).expect("report must be JSON")
+    };
+    assert_eq!(read("a")["metrics"]["cyclomatic"]["sum"], 2.0);
+    assert_eq!(read("b")["metrics"]["cyclomatic"]["sum"], 3.0);
+    assert!(!dir.path().join("out/mod.rs.json").exists());
+
+    // `..` cannot be dropped without `../src/a/mod.rs` and `src/a/mod.rs`
+    // sharing a report, so a path that climbs out is rejected.
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .current_dir(dir.path().join("src"))
+        .args([
+            "metrics",
+            "--output",
+            "out",
+            "--output-dir-structure",
+            "mirror",
+            "../src/a/mod.rs",
+        ])
+        .output()
+        .expect("failed to run mehen metrics");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("leads outside the working directory"),
+        "stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn metrics_smells_flags_functions_over_thresholds() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let file = dir.path().join("smelly.rs");
+    std::fs::write(
+        &file,
+        "fn wide(a: u8, b: u8, c: u8) -> u8 {\n    a + b + c\n}\n\n\
+         fn deep(x: u8) -> u8 {\n    if x > 0 {\n        if x > 1 {\n            return 1;\n        }\n    }\n    0\n}\n",
+    )
+    .expect("write fixture");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args([
+            "metrics",
+            "--smells",
+            "--smell-threshold",
+            "too_many_params=2",
+            "--smell-threshold",
+            "deeply_nested=1",
+        ])
+        .arg(&file)
+        .output()
+        .expect("failed to run mehen metrics");
+    assert!(
+        output.status.success(),
+        "stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("smells output must be JSON");
+    let functions = parsed["functions"].as_array().expect("functions array");
+    assert_eq!(functions.len(), 2);
+    assert_eq!(functions[0]["name"], "wide");
+    assert_eq!(functions[0]["too_many_params"], true);
+    assert_eq!(functions[0]["deeply_nested"], false);
+    assert_eq!(functions[1]["name"], "deep");
+    assert_eq!(functions[1]["too_many_params"], false);
+    assert_eq!(functions[1]["deeply_nested"], true);
+    assert_eq!(functions[1]["long_function"], false);
+}
+
+#[test]
+fn metrics_github_summary_appends_markdown_to_step_summary() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let file = dir.path().join("lib.rs");
+    std::fs::write(&file, "fn add(a: u8, b: u8) -> u8 {\n    a + b\n}\n").expect("write fixture");
+    let summary = dir.path().join("summary.md");
+    std::fs::write(&summary, "## Earlier step\n").expect("write summary");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["metrics", "--github-summary"])
+        .arg(&file)
+        .env("GITHUB_STEP_SUMMARY", &summary)
+        .output()
+        .expect("failed to run mehen metrics");
+    assert!(
+        output.status.success(),
+        "stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    // stdout keeps the requested format; the summary gets markdown.
+    serde_json::from_slice::<serde_json::Value>(&output.stdout).expect("stdout must be JSON");
+    let written = std::fs::read_to_string(&summary).expect("read summary");
+    assert!(written.starts_with("## Earlier step\n# "), "{written}");
+    assert!(written.contains("- language: `rust`"), "{written}");
+}
+
+#[test]
+fn metrics_json_gives_same_named_methods_distinct_function_ids() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let file = dir.path().join("lib.rs");
+    std::fs::write(
+        &file,
+        "struct A;\nstruct B;\n\nimpl A {\n    fn new() -> Self {\n        A\n    }\n}\n\n\
+         impl B {\n    fn new() -> Self {\n        B\n    }\n}\n",
+    )
+    .expect("write fixture");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .arg("metrics")
+        .arg(&file)
+        .output()
+        .expect("failed to run mehen metrics");
+    assert!(
+        output.status.success(),
+        "stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("metrics output must be JSON");
+    let ids: Vec<&str> = parsed["root"]["spaces"]
+        .as_array()
+        .expect("impl spaces")
+        .iter()
+        .map(|imp| {
+            imp["spaces"][0]["function_id"]
+                .as_str()
+                .expect("function_id")
+        })
+        .collect();
+    let path = file.to_str().unwrap();
+    assert_eq!(
+        ids,
+        vec![format!("{path}::A::new"), format!("{path}::B::new")]
+    );
+}
+
+#[test]
+fn metrics_accepts_multibyte_char_across_the_64_byte_boundary() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let file = dir.path().join("lib.rs");
+    // `€` is three bytes; put it at bytes 62..65 so it straddles the
+    // 64-byte head an eager check would sniff.
+    let comment = format!("//{}€\n", "x".repeat(60));
+    assert_eq!(comment.find('€'), Some(62));
+    std::fs::write(&file, format!("{comment}fn f() {{}}\n")).expect("write fixture");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .arg("metrics")
+        .arg(&file)
+        .output()
+        .expect("failed to run mehen metrics");
+    assert!(
+        output.status.success(),
+        "stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("metrics output must be JSON");
+    assert_eq!(parsed["metrics"]["nom"]["functions"], 1.0, "{parsed}");
+}
+
+#[test]
+fn metrics_query_counts_tree_sitter_captures() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let file = dir.path().join("main.go");
+    std::fs::write(
+        &file,
+        "package main\n\nfunc a() {}\n\nfunc b() {\n\tfor {\n\t}\n}\n",
+    )
+    .expect("write fixture");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args([
+            "metrics",
+            "--query",
+            "(function_declaration (block) @b) (for_statement) @loop",
+        ])
+        .arg(&file)
+        .output()
+        .expect("failed to run mehen metrics");
+    assert!(
+        output.status.success(),
+        "stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("query output must be JSON");
+    assert_eq!(parsed["counts"]["b"], 2);
+    assert_eq!(parsed["counts"]["loop"], 1);
+    assert_eq!(parsed["captures"][0]["kind"], "block");
+    assert_eq!(parsed["captures"][0]["span"]["start_line"], 3);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["metrics", "--query", "(function_declaration"])
+        .arg(&file)
+        .output()
+        .expect("failed to run mehen metrics");
+    assert_eq!(output.status.code(), Some(1));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["metrics", "--query", "(block) @b", "--file-level"])
+        .arg(&file)
+        .output()
+        .expect("failed to run mehen metrics");
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("cannot be used with"),
+        "stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn top_offenders_sorted_output_is_path_ordered_and_reproducible() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let src = dir.path().join("src");
+    std::fs::create_dir(&src).expect("create src");
+    std::fs::write(src.join("a.rs"), "fn a() {}\n").expect("write a.rs");
+    std::fs::write(
+        src.join("b.rs"),
+        "fn b(x: bool) {\n    if x {}\n    if !x {}\n}\n",
+    )
+    .expect("write b.rs");
+    std::fs::write(src.join("c.rs"), "fn c(x: bool) {\n    if x {}\n}\n").expect("write c.rs");
+
+    let run = || {
+        let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+            .args(["top-offenders", "-M", "cyclomatic", "-O", "json"])
+            .args(["--sorted-output", "-j", "4", "src"])
+            .current_dir(dir.path())
+            .output()
+            .expect("failed to run mehen top-offenders");
+        assert!(
+            output.status.success(),
+            "stderr={}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        output.stdout
+    };
+    let first = run();
+    assert_eq!(first, run());
+
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&first).expect("top-offenders output must be JSON");
+    let paths: Vec<&str> = parsed
+        .as_array()
+        .expect("offender array")
+        .iter()
+        .filter_map(|o| o["path"].as_str())
+        .collect();
+    assert_eq!(paths, vec!["src/a.rs", "src/b.rs", "src/c.rs"]);
+}
+
+#[test]
+fn top_offenders_output_template_prints_one_line_per_function() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let src = dir.path().join("src");
+    std::fs::create_dir(&src).expect("create src");
+    std::fs::write(
+        src.join("lib.rs"),
+        "fn calm() {}\nfn busy(x: bool, y: bool) {\n    if x && y {}\n}\n",
+    )
+    .expect("write lib.rs");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["top-offenders", "-M", "cyclomatic", "--top", "1"])
+        .args([
+            "--output-template",
+            "{path}:{line} {name} cyc={cyclomatic} cog={cognitive}",
+        ])
+        .arg("src")
+        .current_dir(dir.path())
+        .output()
+        .expect("failed to run mehen top-offenders");
+    assert!(
+        output.status.success(),
+        "stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "src/lib.rs:2 busy cyc=3 cog=2\n"
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["top-offenders", "-M", "cyclomatic", "--top", "1"])
+        .args(["--output-template", "{path} {cyclo}", "src"])
+        .current_dir(dir.path())
+        .output()
+        .expect("failed to run mehen top-offenders");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown placeholder '{cyclo}'"));
+
+    // Duplicate groups have no per-function line to fill in.
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["top-offenders", "--duplicates"])
+        .args(["--output-template", "{path}", "src"])
+        .current_dir(dir.path())
+        .output()
+        .expect("failed to run mehen top-offenders");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with"));
+}
+
+#[test]
+fn top_offenders_verify_passes_over_workspace_sources() {
+    let crates = concat!(env!("CARGO_MANIFEST_DIR"), "/..");
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args([
+            "top-offenders",
+            "-M",
+            "cyclomatic",
+            "-O",
+            "json",
+            "--verify",
+        ])
+        .arg(crates)
+        .output()
+        .expect("failed to run mehen top-offenders");
+    assert!(
+        output.status.success(),
+        "stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn doctor_reports_ci_detection() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let run = |github_actions: Option<&str>| {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_mehen"));
+        cmd.arg("doctor")
+            .arg(dir.path())
+            .env_remove("GITHUB_ACTIONS")
+            .env_remove("GITHUB_EVENT_PATH")
+            .env_remove("GITHUB_BASE_REF")
+            .env("GITHUB_EVENT_NAME", "push");
+        if let Some(value) = github_actions {
+            cmd.env("GITHUB_ACTIONS", value);
+        }
+        let output = cmd.output().expect("failed to run mehen doctor");
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).expect("stdout utf8")
+    };
+
+    let local = run(None);
+    assert!(local.contains("\nCI: not detected"), "{local}");
+    assert!(local.contains("\nVCS: "), "{local}");
+    let ci = run(Some("true"));
+    assert!(ci.contains("\nCI: GitHub Actions, event `push`\n"), "{ci}");
+}
+
+#[cfg(unix)]
+#[test]
+fn metrics_external_metric_merges_command_output() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let file = dir.path().join("add.rs");
+    std::fs::write(&file, "fn add(a: u8, b: u8) -> u8 {\n    a + b\n}\n").expect("write fixture");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args([
+            "metrics",
+            "--external-metric",
+            "answer=cat > /dev/null; echo 42",
+        ])
+        .arg(&file)
+        .output()
+        .expect("failed to run mehen metrics");
+    assert!(
+        output.status.success(),
+        "stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("metrics output must be JSON");
+    assert_eq!(parsed["root"]["metrics"]["external.answer"], 42.0);
+
+    let failing = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["metrics", "--external-metric", "answer=echo none"])
+        .arg(&file)
+        .output()
+        .expect("failed to run mehen metrics");
+    assert_eq!(failing.status.code(), Some(1));
+}
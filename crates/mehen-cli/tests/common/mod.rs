@@ -0,0 +1,27 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Helpers shared by the integration tests that build git fixtures.
+
+use std::path::Path;
+use std::process::Command;
+
+/// Run `git` in `repo` with a fixed identity and no global config,
+/// failing the test on a non-zero exit.
+pub(crate) fn git(repo: &Path, args: &[&str]) {
+    let out = Command::new("git")
+        .args(args)
+        .current_dir(repo)
+        .env("GIT_AUTHOR_NAME", "Mehen Test")
+        .env("GIT_AUTHOR_EMAIL", "test@mehen.invalid")
+        .env("GIT_COMMITTER_NAME", "Mehen Test")
+        .env("GIT_COMMITTER_EMAIL", "test@mehen.invalid")
+        .env("GIT_CONFIG_GLOBAL", "/dev/null")
+        .output()
+        .expect("failed to spawn git");
+    assert!(
+        out.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+}
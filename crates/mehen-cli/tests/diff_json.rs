@@ -0,0 +1,269 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! `mehen diff -O json` document shape.
+
+mod common;
+
+use std::path::Path;
+use std::process::Command;
+
+use common::git;
+
+fn diff_json(repo: &Path, extra: &[&str]) -> serde_json::Value {
+    let out = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["diff", "--from", "HEAD~1", "--to", "HEAD", "-O", "json"])
+        .args(extra)
+        .current_dir(repo)
+        .env_remove("GITHUB_ACTIONS")
+        .env_remove("GITHUB_EVENT_NAME")
+        .env_remove("GITHUB_BASE_REF")
+        .env_remove("GITHUB_SHA")
+        .output()
+        .expect("failed to run mehen diff");
+    assert!(
+        out.status.success(),
+        "mehen diff failed: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    serde_json::from_slice(&out.stdout).expect("diff output must be valid JSON")
+}
+
+fn two_commit_repo(dir: &Path) {
+    git(dir, &["init", "-q", "-b", "main"]);
+    std::fs::write(dir.join("a.rs"), "fn a() {}\n").unwrap();
+    git(dir, &["add", "-A"]);
+    git(dir, &["commit", "-q", "-m", "base"]);
+    std::fs::write(dir.join("a.rs"), "fn a(x: bool) {\n    if x {}\n}\n").unwrap();
+    git(dir, &["commit", "-q", "-am", "head"]);
+}
+
+#[test]
+fn unknown_metric_is_reported_as_a_warning() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    two_commit_repo(dir.path());
+
+    let parsed = diff_json(dir.path(), &["-M", "cyclomatic", "-M", "bogus"]);
+    let warnings = parsed["warnings"].as_array().expect("warnings array");
+    assert_eq!(warnings.len(), 1, "{parsed}");
+    assert_eq!(warnings[0]["code"], "unknown-metric");
+    assert!(
+        warnings[0]["message"].as_str().unwrap().contains("bogus"),
+        "{parsed}"
+    );
+}
+
+#[test]
+fn warnings_key_is_absent_when_nothing_was_skipped() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    two_commit_repo(dir.path());
+
+    let parsed = diff_json(dir.path(), &["-M", "cyclomatic"]);
+    assert!(parsed.get("warnings").is_none(), "{parsed}");
+}
+
+#[test]
+fn parallel_blob_reads_do_not_change_the_report() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let repo = dir.path();
+    git(repo, &["init", "-q", "-b", "main"]);
+    for i in 0..6 {
+        std::fs::write(repo.join(format!("m{i}.rs")), "fn f() {}\n").unwrap();
+    }
+    std::fs::write(repo.join("gone.rs"), "fn gone() {}\n").unwrap();
+    git(repo, &["add", "-A"]);
+    git(repo, &["commit", "-q", "-m", "base"]);
+    for i in 0..6 {
+        let body = "    if x {}\n".repeat(i + 1);
+        std::fs::write(
+            repo.join(format!("m{i}.rs")),
+            format!("fn f(x: bool) {{\n{body}}}\n"),
+        )
+        .unwrap();
+    }
+    std::fs::remove_file(repo.join("gone.rs")).unwrap();
+    std::fs::write(repo.join("new.rs"), "fn new() {}\n").unwrap();
+    git(repo, &["add", "-A"]);
+    git(repo, &["commit", "-q", "-m", "head"]);
+
+    let serial = diff_json(repo, &["-j", "1"]);
+    let parallel = diff_json(repo, &["-j", "4"]);
+    assert_eq!(serial, parallel);
+    assert_eq!(
+        serial["source_code"].as_array().map(Vec::len),
+        Some(8),
+        "{serial}"
+    );
+}
+
+/// More changed files than one read window holds (16 per `-j` reader):
+/// every file is still read and reported, in the same order.
+#[test]
+fn blob_reads_in_windows_cover_every_changed_file() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let repo = dir.path();
+    git(repo, &["init", "-q", "-b", "main"]);
+    for i in 0..40 {
+        std::fs::write(repo.join(format!("m{i:02}.rs")), "fn f() {}\n").unwrap();
+    }
+    git(repo, &["add", "-A"]);
+    git(repo, &["commit", "-q", "-m", "base"]);
+    for i in 0..40 {
+        let body = "    if x {}\n".repeat(i % 5 + 1);
+        std::fs::write(
+            repo.join(format!("m{i:02}.rs")),
+            format!("fn f(x: bool) {{\n{body}}}\n"),
+        )
+        .unwrap();
+    }
+    git(repo, &["commit", "-q", "-am", "head"]);
+
+    let serial = diff_json(repo, &["-j", "1"]);
+    let parallel = diff_json(repo, &["-j", "3"]);
+    assert_eq!(serial, parallel);
+    assert_eq!(
+        serial["source_code"].as_array().map(Vec::len),
+        Some(40),
+        "{serial}"
+    );
+}
+
+#[test]
+fn exclude_tests_drops_changed_test_files() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let repo = dir.path();
+    git(repo, &["init", "-q", "-b", "main"]);
+    std::fs::write(repo.join("server.go"), "package main\n\nfunc f() {}\n").unwrap();
+    std::fs::write(repo.join("server_test.go"), "package main\n\nfunc g() {}\n").unwrap();
+    git(repo, &["add", "-A"]);
+    git(repo, &["commit", "-q", "-m", "base"]);
+    let body = "package main\n\nfunc f(x bool) {\n\tif x {\n\t}\n}\n";
+    std::fs::write(repo.join("server.go"), body).unwrap();
+    std::fs::write(repo.join("server_test.go"), body.replace("f(", "g(")).unwrap();
+    git(repo, &["commit", "-q", "-am", "head"]);
+
+    let paths = |parsed: &serde_json::Value| -> Vec<String> {
+        parsed["source_code"]
+            .as_array()
+            .expect("source_code array")
+            .iter()
+            .map(|f| f["path"].as_str().unwrap().to_string())
+            .collect()
+    };
+    let all = diff_json(repo, &[]);
+    assert_eq!(paths(&all), vec!["server.go", "server_test.go"], "{all}");
+    let production = diff_json(repo, &["--exclude-tests"]);
+    assert_eq!(paths(&production), vec!["server.go"], "{production}");
+}
+
+#[test]
+fn ignore_new_drops_added_files() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let repo = dir.path();
+    git(repo, &["init", "-q", "-b", "main"]);
+    std::fs::write(repo.join("old.rs"), "fn old() {}\n").unwrap();
+    git(repo, &["add", "-A"]);
+    git(repo, &["commit", "-q", "-m", "base"]);
+    std::fs::write(repo.join("old.rs"), "fn old(x: bool) {\n    if x {}\n}\n").unwrap();
+    std::fs::write(repo.join("added.rs"), "fn added() {}\n").unwrap();
+    git(repo, &["add", "-A"]);
+    git(repo, &["commit", "-q", "-m", "head"]);
+
+    let paths = |v: &serde_json::Value| -> Vec<String> {
+        v["source_code"]
+            .as_array()
+            .expect("source_code array")
+            .iter()
+            .map(|f| f["path"].as_str().unwrap().to_string())
+            .collect()
+    };
+    let all = diff_json(repo, &[]);
+    assert_eq!(paths(&all), vec!["added.rs", "old.rs"], "{all}");
+    let existing = diff_json(repo, &["--ignore-new"]);
+    assert_eq!(paths(&existing), vec!["old.rs"], "{existing}");
+}
+
+#[test]
+fn resolved_commit_shas_are_reported() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    two_commit_repo(dir.path());
+    let rev_parse = |rev: &str| {
+        let out = Command::new("git")
+            .args(["rev-parse", rev])
+            .current_dir(dir.path())
+            .output()
+            .expect("failed to spawn git");
+        String::from_utf8(out.stdout).unwrap().trim().to_string()
+    };
+
+    let parsed = diff_json(dir.path(), &["-M", "cyclomatic"]);
+    assert_eq!(parsed["from_sha"], rev_parse("HEAD~1"), "{parsed}");
+    assert_eq!(parsed["to_sha"], rev_parse("HEAD"), "{parsed}");
+}
+
+#[test]
+fn modeline_overrides_extension_on_both_sides() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let repo = dir.path();
+    git(repo, &["init", "-q", "-b", "main"]);
+    std::fs::write(
+        repo.join("view.ts"),
+        "// mehen-lang: tsx\nexport const View = () => <div />;\n",
+    )
+    .unwrap();
+    git(repo, &["add", "-A"]);
+    git(repo, &["commit", "-q", "-m", "base"]);
+    std::fs::write(
+        repo.join("view.ts"),
+        "// mehen-lang: tsx\nexport const View = (x: boolean) => (x ? <div /> : <span />);\n",
+    )
+    .unwrap();
+    git(repo, &["commit", "-q", "-am", "head"]);
+
+    // Parsed as plain TypeScript the JSX is a syntax error and the diff
+    // fails.
+    let parsed = diff_json(repo, &["-M", "cyclomatic"]);
+    let files = parsed["source_code"].as_array().expect("source_code array");
+    assert_eq!(files.len(), 1, "{parsed}");
+    assert_eq!(files[0]["path"], "view.ts");
+}
+
+#[test]
+fn config_thresholds_use_the_most_specific_prefix() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let repo = dir.path();
+    git(repo, &["init", "-q", "-b", "main"]);
+    for dir in ["core", "lib"] {
+        std::fs::create_dir(repo.join(dir)).unwrap();
+        std::fs::write(repo.join(dir).join("a.rs"), "fn a() {}\n").unwrap();
+    }
+    git(repo, &["add", "-A"]);
+    git(repo, &["commit", "-q", "-m", "base"]);
+    for dir in ["core", "lib"] {
+        std::fs::write(
+            repo.join(dir).join("a.rs"),
+            "fn a(x: bool) {\n    if x {}\n}\n",
+        )
+        .unwrap();
+    }
+    git(repo, &["commit", "-q", "-am", "head"]);
+    std::fs::write(
+        repo.join(".mehen.toml"),
+        "[thresholds]\ncyclomatic = 50\n\n[path-thresholds.core]\ncyclomatic = 1\n",
+    )
+    .unwrap();
+
+    let out = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["diff", "--from", "HEAD~1", "--to", "HEAD", "-O", "json"])
+        .current_dir(repo)
+        .env_remove("GITHUB_ACTIONS")
+        .output()
+        .expect("failed to run mehen diff");
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert_eq!(out.status.code(), Some(2), "{stderr}");
+    assert!(stderr.contains("core/a.rs: cyclomatic.sum is"), "{stderr}");
+    assert!(!stderr.contains("lib/a.rs"), "{stderr}");
+
+    std::fs::write(repo.join(".mehen.toml"), "[thresholds]\ncyclomatic = 50\n").unwrap();
+    diff_json(repo, &[]);
+}
@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! `mehen diff` Markdown table, buffered and streamed.
+
+mod common;
+
+use std::path::Path;
+use std::process::Command;
+
+use common::git;
+
+fn diff_markdown(repo: &Path, extra: &[&str]) -> String {
+    let out = Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(["diff", "--from", "HEAD~1", "--to", "HEAD"])
+        .args(["-M", "cyclomatic,nom.functions"])
+        .args(extra)
+        .current_dir(repo)
+        .env_remove("GITHUB_ACTIONS")
+        .env_remove("GITHUB_EVENT_NAME")
+        .env_remove("GITHUB_BASE_REF")
+        .env_remove("GITHUB_SHA")
+        .output()
+        .expect("failed to run mehen diff");
+    assert!(
+        out.status.success(),
+        "mehen diff failed: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    String::from_utf8(out.stdout).expect("diff output must be UTF-8")
+}
+
+/// `| path |` rows of the table, in output order.
+fn row_paths(markdown: &str) -> Vec<&str> {
+    markdown
+        .lines()
+        .filter_map(|line| line.strip_prefix("| "))
+        .filter_map(|line| line.split(" |").next())
+        .filter(|cell| cell.ends_with(".rs"))
+        .collect()
+}
+
+#[test]
+fn parallel_output_streams_the_same_rows_in_changed_file_order() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let repo = dir.path();
+    git(repo, &["init", "-q", "-b", "main"]);
+    std::fs::write(repo.join("a.rs"), "fn a() {}\n").unwrap();
+    std::fs::write(repo.join("b.rs"), "fn b() {}\n").unwrap();
+    git(repo, &["add", "-A"]);
+    git(repo, &["commit", "-q", "-m", "base"]);
+    std::fs::write(repo.join("a.rs"), "fn a(x: bool) {\n    if x {}\n}\n").unwrap();
+    std::fs::write(
+        repo.join("b.rs"),
+        "fn b(x: bool) {\n    if x {}\n}\nfn c() {}\nfn d() {}\n",
+    )
+    .unwrap();
+    git(repo, &["commit", "-q", "-am", "head"]);
+
+    // Buffered rows are sorted by function count, most first.
+    let buffered = diff_markdown(repo, &[]);
+    assert_eq!(row_paths(&buffered), vec!["b.rs", "a.rs"], "{buffered}");
+
+    let streamed = diff_markdown(repo, &["--parallel-diff-output"]);
+    assert_eq!(row_paths(&streamed), vec!["a.rs", "b.rs"], "{streamed}");
+    let mut buffered_lines: Vec<&str> = buffered.lines().collect();
+    let mut streamed_lines: Vec<&str> = streamed.lines().collect();
+    buffered_lines.sort_unstable();
+    streamed_lines.sort_unstable();
+    assert_eq!(streamed_lines, buffered_lines);
+}
+
+#[test]
+fn parallel_output_of_an_unchanged_diff_says_so() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let repo = dir.path();
+    git(repo, &["init", "-q", "-b", "main"]);
+    std::fs::write(repo.join("a.rs"), "fn a() {}\n").unwrap();
+    git(repo, &["add", "-A"]);
+    git(repo, &["commit", "-q", "-m", "base"]);
+    std::fs::write(repo.join("a.rs"), "fn a() {\n}\n").unwrap();
+    git(repo, &["commit", "-q", "-am", "head"]);
+
+    let buffered = diff_markdown(repo, &[]);
+    let streamed = diff_markdown(repo, &["--parallel-diff-output"]);
+    assert!(
+        streamed.contains("No metric changes detected."),
+        "{streamed}"
+    );
+    assert_eq!(streamed, buffered);
+}
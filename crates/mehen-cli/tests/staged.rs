@@ -0,0 +1,53 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! `metrics --staged` reads the git index, not the working tree.
+
+mod common;
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+use common::git;
+
+fn mehen(repo: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_mehen"))
+        .args(args)
+        .current_dir(repo)
+        .output()
+        .expect("failed to run mehen")
+}
+
+#[test]
+fn metrics_staged_analyzes_index_content() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let repo = dir.path();
+    git(repo, &["init", "-q", "-b", "main"]);
+    std::fs::write(repo.join("a.rs"), "fn a() {}\n").unwrap();
+    git(repo, &["add", "-A"]);
+    git(repo, &["commit", "-q", "-m", "base"]);
+
+    // Staged: two functions. Working tree: three.
+    std::fs::write(repo.join("a.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+    git(repo, &["add", "a.rs"]);
+    std::fs::write(repo.join("a.rs"), "fn a() {}\nfn b() {}\nfn c() {}\n").unwrap();
+
+    let functions = |out: &Output| {
+        assert!(
+            out.status.success(),
+            "{}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+        let json: serde_json::Value = serde_json::from_slice(&out.stdout).expect("valid JSON");
+        json["root"]["spaces"]
+            .as_array()
+            .expect("spaces array")
+            .len()
+    };
+    assert_eq!(functions(&mehen(repo, &["metrics", "--staged", "a.rs"])), 2);
+    assert_eq!(functions(&mehen(repo, &["metrics", "a.rs"])), 3);
+
+    std::fs::write(repo.join("new.rs"), "fn n() {}\n").unwrap();
+    let untracked = mehen(repo, &["metrics", "--staged", "new.rs"]);
+    assert_eq!(untracked.status.code(), Some(1));
+}
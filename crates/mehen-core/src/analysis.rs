@@ -91,6 +91,11 @@ impl MetricSet {
         self.0.iter()
     }
 
+    /// Keep only the metrics for which `keep` returns true.
+    pub fn retain(&mut self, mut keep: impl FnMut(&MetricKey) -> bool) {
+        self.0.retain(|key, _| keep(key));
+    }
+
     pub fn len(&self) -> usize {
         self.0.len()
     }
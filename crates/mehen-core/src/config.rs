@@ -1,8 +1,12 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 // Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::Language;
+
 /// Configuration handed to a [`crate::LanguageAnalyzer::analyze`] call.
 ///
 /// Kept intentionally small in 1.0 — analyzer-specific options should live
@@ -29,6 +33,131 @@ pub struct AnalysisConfig {
     /// recursive call. Analyzers do not need to read this; the dispatcher
     /// uses it to enforce `max_dispatch_depth`.
     pub dispatch_depth: u8,
+
+    /// Count only named functions in the NOM totals: `nom` and
+    /// `nom.average` drop closures and the `nom.closures*` keys read 0.
+    /// Closure spaces stay in the tree. Applied by the engine once the
+    /// analyzer returns, so analyzers do not need to read it.
+    #[serde(default)]
+    pub exclude_closures: bool,
+
+    /// Halstead classification overrides, keyed by grammar node kind
+    /// (e.g. `"."`). A listed kind is counted as the given class instead
+    /// of the analyzer's built-in choice. Read by the tree-sitter walkers
+    /// (see [`Self::honors_halstead_overrides`]); other analyzers ignore
+    /// it.
+    #[serde(default)]
+    pub halstead_overrides: BTreeMap<String, HalsteadClass>,
+
+    /// Leave `import` / `from … import` statements out of `loc.lloc`,
+    /// for parity with tools that do not count imports as logical
+    /// lines. Read by the Python analyzer; others ignore it.
+    #[serde(default)]
+    pub exclude_imports_from_lloc: bool,
+
+    /// Fold closures into the enclosing space instead of giving each
+    /// one a child space: their decisions, LOC and Halstead tokens count
+    /// toward the parent, and no closure shows up in NOM. Read by the
+    /// Rust analyzer; others ignore it.
+    #[serde(default)]
+    pub inline_closures: bool,
+
+    /// Count each `match` arm guard (`x if x > 0 =>`) as a cyclomatic
+    /// decision on top of the arm itself, the way clippy does. Off by
+    /// default, matching the pre-1.0 counts. Read by the Rust analyzer;
+    /// others ignore it.
+    #[serde(default)]
+    pub count_match_guards: bool,
+
+    /// Halstead's Stroud number, the mental discriminations per second
+    /// assumed by `halstead.time = E / S`. Like `exclude_closures`, the
+    /// engine applies it once the analyzer returns.
+    #[serde(default = "default_stroud_number")]
+    pub halstead_stroud_number: f64,
+
+    /// Divisor of `halstead.bugs = E^(2/3) / K`. Applied by the engine
+    /// alongside `halstead_stroud_number`.
+    #[serde(default = "default_bugs_constant")]
+    pub halstead_bugs_constant: f64,
+
+    /// Remediation minutes charged per unit of complexity when the engine
+    /// computes the unit-level `debt_ratio`.
+    #[serde(default)]
+    pub debt_weights: DebtWeights,
+
+    /// Recompute every published min/max from the per-space values of
+    /// the finished tree instead of keeping the bounds the analyzer
+    /// folded while closing spaces, so they cannot depend on merge
+    /// order. Applied by the engine once the analyzer returns.
+    #[serde(default)]
+    pub recompute_minmax: bool,
+
+    /// Metric families to leave out of the report, by key prefix: an
+    /// entry drops its bare key and every `entry.*` key from every
+    /// space (`halstead` drops `halstead.volume`, …). The engine applies
+    /// it after every other pass, so `mi` and `debt_ratio` are still
+    /// derived from the full set.
+    #[serde(default)]
+    pub excluded_metrics: Vec<String>,
+
+    /// Syntax-tree size above which a file is not walked: analyzers
+    /// publish only the `loc.*` family for it, with a warning. The size
+    /// is the tree's node count, or its token count for the parsers
+    /// that keep no cheap node count (Oxc, Ruff). `None` walks every
+    /// file.
+    #[serde(default)]
+    pub max_nodes: Option<u64>,
+
+    /// Keep the file-wide Halstead operator/operand frequencies in the
+    /// unit's [`crate::MetricSpace::halstead_tokens`], for
+    /// `--ops-summary`. Off by default: the maps copy every distinct
+    /// token of the file. Read by every analyzer that emits Halstead
+    /// events.
+    #[serde(default)]
+    pub halstead_tokens: bool,
+}
+
+/// Weights of the SQALE-style `debt_ratio`: estimated remediation
+/// minutes per decision point (cyclomatic complexity above 1 in each
+/// space) and per point of cognitive complexity.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DebtWeights {
+    pub cyclomatic_minutes: f64,
+    pub cognitive_minutes: f64,
+}
+
+impl Default for DebtWeights {
+    fn default() -> Self {
+        Self {
+            cyclomatic_minutes: 1.0,
+            cognitive_minutes: 2.0,
+        }
+    }
+}
+
+/// Halstead's original Stroud number (`halstead.time = E / 18`).
+pub const DEFAULT_STROUD_NUMBER: f64 = 18.0;
+
+/// Halstead's original bugs divisor (`halstead.bugs = E^(2/3) / 3000`).
+pub const DEFAULT_BUGS_CONSTANT: f64 = 3000.0;
+
+fn default_stroud_number() -> f64 {
+    DEFAULT_STROUD_NUMBER
+}
+
+fn default_bugs_constant() -> f64 {
+    DEFAULT_BUGS_CONSTANT
+}
+
+/// How a token counts towards the Halstead metrics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HalsteadClass {
+    Operator,
+    Operand,
+    /// Not counted at all.
+    Unknown,
 }
 
 /// Default `max_dispatch_depth` for `production()` / `benchmark()` /
@@ -48,6 +177,18 @@ impl Default for AnalysisConfig {
             emit_contributions: false,
             max_dispatch_depth: DEFAULT_MAX_DISPATCH_DEPTH,
             dispatch_depth: 0,
+            exclude_closures: false,
+            halstead_overrides: BTreeMap::new(),
+            exclude_imports_from_lloc: false,
+            inline_closures: false,
+            count_match_guards: false,
+            halstead_stroud_number: DEFAULT_STROUD_NUMBER,
+            halstead_bugs_constant: DEFAULT_BUGS_CONSTANT,
+            debt_weights: DebtWeights::default(),
+            recompute_minmax: false,
+            excluded_metrics: Vec::new(),
+            max_nodes: None,
+            halstead_tokens: false,
         }
     }
 }
@@ -59,6 +200,18 @@ impl AnalysisConfig {
             emit_contributions: true,
             max_dispatch_depth: DEFAULT_MAX_DISPATCH_DEPTH,
             dispatch_depth: 0,
+            exclude_closures: false,
+            halstead_overrides: BTreeMap::new(),
+            exclude_imports_from_lloc: false,
+            inline_closures: false,
+            count_match_guards: false,
+            halstead_stroud_number: DEFAULT_STROUD_NUMBER,
+            halstead_bugs_constant: DEFAULT_BUGS_CONSTANT,
+            debt_weights: DebtWeights::default(),
+            recompute_minmax: false,
+            excluded_metrics: Vec::new(),
+            max_nodes: None,
+            halstead_tokens: false,
         }
     }
 
@@ -69,8 +222,26 @@ impl AnalysisConfig {
             emit_contributions: false,
             max_dispatch_depth: DEFAULT_MAX_DISPATCH_DEPTH,
             dispatch_depth: 0,
+            exclude_closures: false,
+            halstead_overrides: BTreeMap::new(),
+            exclude_imports_from_lloc: false,
+            inline_closures: false,
+            count_match_guards: false,
+            halstead_stroud_number: DEFAULT_STROUD_NUMBER,
+            halstead_bugs_constant: DEFAULT_BUGS_CONSTANT,
+            debt_weights: DebtWeights::default(),
+            recompute_minmax: false,
+            excluded_metrics: Vec::new(),
+            max_nodes: None,
+            halstead_tokens: false,
         }
     }
+
+    /// Whether the `language` analyzer reads `halstead_overrides`: the
+    /// ones built on the shared tree-sitter walker.
+    pub fn honors_halstead_overrides(language: Language) -> bool {
+        matches!(language, Language::C | Language::Go | Language::Kotlin)
+    }
 }
 
 #[cfg(test)]
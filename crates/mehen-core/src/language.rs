@@ -43,6 +43,23 @@ impl fmt::Display for LanguageParseError {
 impl core::error::Error for LanguageParseError {}
 
 impl Language {
+    /// Every language, in declaration order.
+    pub const ALL: [Language; 13] = [
+        Language::Python,
+        Language::TypeScript,
+        Language::Tsx,
+        Language::JavaScript,
+        Language::Jsx,
+        Language::Php,
+        Language::Ruby,
+        Language::Rust,
+        Language::Go,
+        Language::Kotlin,
+        Language::PowerShell,
+        Language::C,
+        Language::Markdown,
+    ];
+
     /// The canonical lowercase identifier used in JSON and CLI output.
     pub fn canonical(&self) -> &'static str {
         match self {
@@ -61,6 +78,16 @@ impl Language {
             Language::Markdown => "markdown",
         }
     }
+
+    /// Columns between tab stops when tabs in indentation are expanded.
+    /// Python's tokenizer uses 8; the other languages have no fixed
+    /// rule, so 4 matches the common editor default.
+    pub fn tab_width(&self) -> usize {
+        match self {
+            Language::Python => 8,
+            _ => 4,
+        }
+    }
 }
 
 impl fmt::Display for Language {
@@ -121,21 +148,7 @@ mod tests {
 
     #[test]
     fn parses_canonical_identifiers() {
-        for lang in [
-            Language::Python,
-            Language::TypeScript,
-            Language::Tsx,
-            Language::JavaScript,
-            Language::Jsx,
-            Language::Php,
-            Language::Ruby,
-            Language::Rust,
-            Language::Go,
-            Language::Kotlin,
-            Language::PowerShell,
-            Language::C,
-            Language::Markdown,
-        ] {
+        for lang in Language::ALL {
             assert_eq!(lang.canonical().parse::<Language>().unwrap(), lang);
         }
     }
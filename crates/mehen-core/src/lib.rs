@@ -42,18 +42,20 @@ pub use analysis::{
 };
 pub use analyzer::{LanguageAnalyzer, LanguageDispatcher};
 pub use backend::AnalysisBackend;
-pub use config::AnalysisConfig;
+pub use config::{
+    AnalysisConfig, DEFAULT_BUGS_CONSTANT, DEFAULT_STROUD_NUMBER, DebtWeights, HalsteadClass,
+};
 pub use diagnostic::{DiagnosticSeverity, ParseDiagnostic};
 pub use language::{Language, LanguageParseError, language_aliases};
 pub use line_index::LineIndex;
 pub use metric_key::{MetricKey, keys};
 pub use report::{
     AnalysisErrorRecord, AnalyzeMetricsInput, DiffFile, DiffInput, DiffReport, DiffSide,
-    MetricsReport, TopOffenderEntry, TopOffendersInput, TopOffendersReport,
+    MetricsReport, PathThresholds, TopOffenderEntry, TopOffendersInput, TopOffendersReport,
 };
 pub use selector::{MetricSelector, SelectorAggregator, SelectorParseError};
-pub use source::SourceFile;
-pub use space::{MetricSpace, SpaceId, SpaceKind};
+pub use source::{SourceFile, normalize_whitespace};
+pub use space::{HalsteadTokens, MetricSpace, SpaceId, SpaceKind, TokenCount};
 pub use span::{SourceSpan, byte_offset_checked, byte_offset_clamped};
 pub use threshold::{Polarity, Threshold, ThresholdEvaluation, ThresholdViolation};
 
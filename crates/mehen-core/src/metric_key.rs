@@ -61,6 +61,30 @@ pub mod keys {
     pub const LOC_PLOC: &str = "loc.ploc";
     pub const LOC_CLOC: &str = "loc.cloc";
     pub const LOC_BLANK: &str = "loc.blank";
+    pub const LOC_BLANK_BLOCKS: &str = "loc.blank_blocks";
+    pub const LOC_SHARE: &str = "loc.share";
+    pub const LOC_MAX_BLOCK_LINES: &str = "loc.max_block_lines";
+    pub const LOC_AVG_FUNCTION_SLOC: &str = "loc.avg_function_sloc";
+    pub const LOC_FUNCTION_SLOC_P50: &str = "loc.function_sloc_p50";
+    pub const LOC_FUNCTION_SLOC_P90: &str = "loc.function_sloc_p90";
+    pub const LOC_FUNCTION_SLOC_P99: &str = "loc.function_sloc_p99";
+    pub const LOC_SLOC_MIN_AT: &str = "loc.sloc_min_at";
+    pub const LOC_SLOC_MAX_AT: &str = "loc.sloc_max_at";
+    pub const MAX_CALL_CHAIN: &str = "max_call_chain";
+    pub const IMPORT_COUNT: &str = "import_count";
+    pub const MAGIC_NUMBERS: &str = "magic_numbers";
+    pub const DEFAULTED_ARGS: &str = "defaulted_args";
+    pub const MAX_PARAM_NAME_LENGTH: &str = "max_param_name_length";
+    pub const MAX_CONDITION_TERMS: &str = "max_condition_terms";
+    pub const UNUSED_PRIVATE: &str = "unused_private";
+    pub const DEBT_RATIO: &str = "debt_ratio";
+    pub const MAX_BRANCHES: &str = "max_branches";
+    pub const MAX_EXPR_DEPTH: &str = "max_expr_depth";
+    pub const MAX_NESTING: &str = "max_nesting";
+    pub const COGNITIVE_NESTING: &str = "cognitive_nesting";
+    pub const COGNITIVE_STRUCTURAL: &str = "cognitive_structural";
+    pub const NODE_COUNT: &str = "node_count";
+    pub const LEAF_COUNT: &str = "leaf_count";
     pub const HALSTEAD: &str = "halstead";
     pub const HALSTEAD_VOLUME: &str = "halstead.volume";
     pub const HALSTEAD_DIFFICULTY: &str = "halstead.difficulty";
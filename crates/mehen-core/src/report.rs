@@ -68,9 +68,25 @@ pub struct DiffInput {
     pub to: String,
     pub paths: Vec<Utf8PathBuf>,
     pub thresholds: Vec<Threshold>,
+    /// Per-directory overrides of `thresholds`. A file is checked
+    /// against the list of the most specific matching prefix, or
+    /// `thresholds` when none matches.
+    pub path_thresholds: Vec<PathThresholds>,
     pub config: AnalysisConfig,
 }
 
+/// Thresholds that replace [`DiffInput::thresholds`] for the files under
+/// `prefix`, e.g. a stricter budget for `core/` than for
+/// `experimental/`. The list replaces the default one entirely, so
+/// repeat any default rule that should still apply.
+#[derive(Clone, Debug)]
+pub struct PathThresholds {
+    /// Repository-relative directory or file, matched by whole path
+    /// components: `core` covers `core/a.rs`, not `core2/a.rs`.
+    pub prefix: Utf8PathBuf,
+    pub thresholds: Vec<Threshold>,
+}
+
 /// `mehen diff --format json` output shape (rewrite plan §9.2).
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct DiffReport {
@@ -41,6 +41,33 @@ impl SourceFile {
     }
 }
 
+/// Expand tabs in the leading indentation of every line to
+/// `tab_width`-column tab stops (see [`Language::tab_width`]). Run
+/// before parsing so byte offsets and columns do not depend on the
+/// author's indentation style. Everything after the indentation is
+/// kept as written: tabs inside string literals and trailing spaces,
+/// which are a hard line break in Markdown, are content.
+pub fn normalize_whitespace(text: &str, tab_width: usize) -> String {
+    let tab_width = tab_width.max(1);
+    let mut out = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let body = line.trim_start_matches([' ', '\t']);
+        let indent = &line[..line.len() - body.len()];
+        let mut column = 0;
+        for c in indent.chars() {
+            let width = if c == '\t' {
+                tab_width - column % tab_width
+            } else {
+                1
+            };
+            out.extend(std::iter::repeat_n(' ', width));
+            column += width;
+        }
+        out.push_str(body);
+    }
+    out
+}
+
 #[derive(Deserialize)]
 struct SourceFileWire {
     path: Utf8PathBuf,
@@ -54,3 +81,28 @@ impl<'de> Deserialize<'de> for SourceFile {
         Ok(SourceFile::new(wire.path, wire.language, wire.text))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_whitespace_expands_indentation_tabs_to_stops() {
+        assert_eq!(
+            normalize_whitespace("\tif x {\r\n  \ty = 1;\n}", 4),
+            "    if x {\r\n    y = 1;\n}"
+        );
+        assert_eq!(normalize_whitespace("\t\tx\n", 8), "                x\n");
+    }
+
+    #[test]
+    fn normalize_whitespace_keeps_content_after_the_indentation() {
+        assert_eq!(
+            normalize_whitespace("\ts = \"a\tb\"\t\n", 4),
+            "    s = \"a\tb\"\t\n"
+        );
+        // A Markdown hard line break.
+        assert_eq!(normalize_whitespace("line  \nnext\n", 4), "line  \nnext\n");
+        assert_eq!(normalize_whitespace("\t\n", 4), "    \n");
+    }
+}
@@ -60,20 +60,215 @@ pub struct MetricSpace {
     pub id: SpaceId,
     pub kind: SpaceKind,
     pub name: Option<String>,
+    /// Stable key for function and closure spaces: the file path plus
+    /// the qualified scope chain, e.g. `src/lib.rs::Parser::new`. Set by
+    /// [`MetricSpace::assign_function_ids`]; only serialized when set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function_id: Option<String>,
     pub span: SourceSpan,
     pub metrics: MetricSet,
+    /// `true` when a function space calls itself directly. Only
+    /// serialized when set, so non-recursive spaces keep their shape.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_recursive: bool,
+    /// File-wide Halstead token frequencies. Set on the unit space by
+    /// analyzers that emit Halstead events when
+    /// `AnalysisConfig::halstead_tokens` asks for it; never serialized —
+    /// the `--ops-summary` view reads it in-process.
+    #[serde(skip)]
+    pub halstead_tokens: Option<HalsteadTokens>,
     pub spaces: Vec<MetricSpace>,
 }
 
+/// How often each distinct Halstead operator and operand occurs.
+///
+/// Both lists are sorted by descending count, ties broken by token
+/// text, so the first `n` entries are the top-`n`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct HalsteadTokens {
+    pub operators: Vec<TokenCount>,
+    pub operands: Vec<TokenCount>,
+}
+
+impl HalsteadTokens {
+    /// Replace the interior of every string / character literal token
+    /// with `***`, keeping prefixes and delimiters. Literals that
+    /// collapse to the same redacted text are merged and the lists are
+    /// re-ranked.
+    ///
+    /// ```
+    /// use mehen_core::{HalsteadTokens, TokenCount};
+    ///
+    /// let count = |token: &str, count| TokenCount { token: token.into(), count };
+    /// let mut tokens = HalsteadTokens {
+    ///     operators: Vec::new(),
+    ///     operands: vec![count("x", 3), count("\"hunter2\"", 1), count("r#\"key\"#", 1)],
+    /// };
+    /// tokens.redact_string_literals();
+    /// assert_eq!(tokens.operands[0], count("x", 3));
+    /// assert_eq!(tokens.operands[1], count("\"***\"", 1));
+    /// assert_eq!(tokens.operands[2], count("r#\"***\"#", 1));
+    /// ```
+    pub fn redact_string_literals(&mut self) {
+        redact_tokens(&mut self.operators);
+        redact_tokens(&mut self.operands);
+    }
+}
+
+fn redact_tokens(tokens: &mut Vec<TokenCount>) {
+    let mut merged: Vec<TokenCount> = Vec::with_capacity(tokens.len());
+    for t in tokens.drain(..) {
+        let token = redact_literal(&t.token).unwrap_or(t.token);
+        match merged.iter_mut().find(|m| m.token == token) {
+            Some(m) => m.count += t.count,
+            None => merged.push(TokenCount {
+                token,
+                count: t.count,
+            }),
+        }
+    }
+    merged.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.token.cmp(&b.token)));
+    *tokens = merged;
+}
+
+/// `Some(redacted)` when `token` looks like a quoted literal: an
+/// optional alphanumeric / `#` prefix (`r#`, `b`, `f`, `rb`, …), an
+/// opening quote run (one quote, or three for Python-style triple
+/// quotes), and the matching closing run plus any raw-string hashes.
+fn redact_literal(token: &str) -> Option<String> {
+    let open = token.find(['"', '\'', '`'])?;
+    let prefix = &token[..open];
+    if !prefix
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '#')
+    {
+        return None;
+    }
+    let quote = token[open..].chars().next()?;
+    let run = token[open..].chars().take_while(|&c| c == quote).count();
+    let delimiter_len = if run >= 3 { 3 } else { 1 };
+    let delimiter = &token[open..open + delimiter_len];
+    let hashes = "#".repeat(prefix.chars().filter(|&c| c == '#').count());
+    let closing = format!("{delimiter}{hashes}");
+    if token.len() < open + delimiter_len + closing.len() || !token.ends_with(&closing) {
+        return None;
+    }
+    Some(format!("{prefix}{delimiter}***{closing}"))
+}
+
+/// One token and the number of times it occurs.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenCount {
+    pub token: String,
+    pub count: u32,
+}
+
 impl MetricSpace {
     pub fn new(id: SpaceId, kind: SpaceKind, span: SourceSpan) -> Self {
         Self {
             id,
             kind,
             name: None,
+            function_id: None,
             span,
             metrics: MetricSet::default(),
+            is_recursive: false,
+            halstead_tokens: None,
             spaces: Vec::new(),
         }
     }
+
+    /// Walk this space and every nested space in pre-order (parent
+    /// before children, children in source order), calling `f` with
+    /// each space and its depth. `self` is visited at depth `0`. The
+    /// callback receives references tied to `self`, so it may collect
+    /// them for later use.
+    ///
+    /// Report renderers that need the whole tree — per-function diff
+    /// rows, nested Markdown sections, custom exports — can build on
+    /// this instead of hand-rolling the recursion.
+    ///
+    /// ```
+    /// use mehen_core::{MetricSpace, SourceSpan, SpaceId, SpaceKind};
+    ///
+    /// let mut unit = MetricSpace::new(SpaceId(0), SpaceKind::Unit, SourceSpan::empty());
+    /// let mut class = MetricSpace::new(SpaceId(1), SpaceKind::Class, SourceSpan::empty());
+    /// class
+    ///     .spaces
+    ///     .push(MetricSpace::new(SpaceId(2), SpaceKind::Function, SourceSpan::empty()));
+    /// unit.spaces.push(class);
+    /// unit.spaces
+    ///     .push(MetricSpace::new(SpaceId(3), SpaceKind::Function, SourceSpan::empty()));
+    ///
+    /// let mut functions = 0;
+    /// let mut deepest = 0;
+    /// unit.visit(&mut |space, depth| {
+    ///     if space.kind == SpaceKind::Function {
+    ///         functions += 1;
+    ///     }
+    ///     deepest = deepest.max(depth);
+    /// });
+    /// assert_eq!(functions, 2);
+    /// assert_eq!(deepest, 2);
+    /// ```
+    pub fn visit<'a>(&'a self, f: &mut impl FnMut(&'a MetricSpace, usize)) {
+        self.visit_at(0, f);
+    }
+
+    /// Set [`MetricSpace::function_id`] on every function and closure
+    /// below `self` to `{path}::{scope}::{name}`. Each enclosing
+    /// non-unit space contributes its name (`<anonymous>` when it has
+    /// none); a name repeated within one scope gets a `#N` suffix from
+    /// its second occurrence on, so overloads stay distinct.
+    ///
+    /// ```
+    /// use mehen_core::{MetricSpace, SourceSpan, SpaceId, SpaceKind};
+    ///
+    /// let named = |id, kind, name: &str| {
+    ///     let mut space = MetricSpace::new(SpaceId(id), kind, SourceSpan::empty());
+    ///     space.name = Some(name.to_string());
+    ///     space
+    /// };
+    /// let mut unit = MetricSpace::new(SpaceId(0), SpaceKind::Unit, SourceSpan::empty());
+    /// let mut class = named(1, SpaceKind::Class, "Parser");
+    /// class.spaces.push(named(2, SpaceKind::Function, "parse"));
+    /// class.spaces.push(named(3, SpaceKind::Function, "parse"));
+    /// unit.spaces.push(class);
+    ///
+    /// unit.assign_function_ids("src/parser.py");
+    /// let ids: Vec<_> = unit.spaces[0]
+    ///     .spaces
+    ///     .iter()
+    ///     .map(|f| f.function_id.as_deref().unwrap())
+    ///     .collect();
+    /// assert_eq!(ids, ["src/parser.py::Parser::parse", "src/parser.py::Parser::parse#2"]);
+    /// ```
+    pub fn assign_function_ids(&mut self, path: &str) {
+        fn assign(space: &mut MetricSpace, scope: &str) {
+            let mut seen: std::collections::HashMap<String, usize> =
+                std::collections::HashMap::new();
+            for child in &mut space.spaces {
+                let name = child.name.as_deref().unwrap_or("<anonymous>");
+                let n = seen.entry(name.to_string()).or_insert(0);
+                *n += 1;
+                let qualified = if *n > 1 {
+                    format!("{scope}::{name}#{n}")
+                } else {
+                    format!("{scope}::{name}")
+                };
+                if matches!(child.kind, SpaceKind::Function | SpaceKind::Closure) {
+                    child.function_id = Some(qualified.clone());
+                }
+                assign(child, &qualified);
+            }
+        }
+        assign(self, path);
+    }
+
+    fn visit_at<'a>(&'a self, depth: usize, f: &mut impl FnMut(&'a MetricSpace, usize)) {
+        f(self, depth);
+        for child in &self.spaces {
+            child.visit_at(depth + 1, f);
+        }
+    }
 }
@@ -9,11 +9,14 @@ use crate::selector::MetricSelector;
 /// (`HigherIsBetter`). Per the rewrite plan §5.1 this lives with the metric
 /// contract because the same number means different things across metrics:
 /// `cognitive` going up is bad, `mi.visual_studio` going up is good.
+/// `Neutral` metrics describe a shape rather than a quality, such as
+/// `halstead.operator_operand_ratio`; neither direction is better.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Polarity {
     HigherIsWorse,
     HigherIsBetter,
+    Neutral,
 }
 
 /// A user-supplied threshold rule.
@@ -38,7 +41,8 @@ impl Threshold {
     /// True when `actual` violates this threshold.
     pub fn violated_by(&self, actual: f64) -> bool {
         match self.polarity {
-            Polarity::HigherIsWorse => actual > self.value,
+            // A limit on a neutral metric is read as an upper bound.
+            Polarity::HigherIsWorse | Polarity::Neutral => actual > self.value,
             Polarity::HigherIsBetter => actual < self.value,
         }
     }
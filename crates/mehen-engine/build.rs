@@ -0,0 +1,66 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Records the resolved versions of the parsers that are not tree-sitter
+//! grammars, for `mehen --versions`. They are read from the workspace
+//! `Cargo.lock`; a version that cannot be found is `unknown`.
+
+use std::path::{Path, PathBuf};
+
+/// Lock-file package name and the environment variable it is exposed as.
+const PARSERS: &[(&str, &str)] = &[
+    ("ruff_python_parser", "MEHEN_RUFF_VERSION"),
+    ("oxc_parser", "MEHEN_OXC_VERSION"),
+    ("mago-syntax", "MEHEN_MAGO_SYNTAX_VERSION"),
+    ("ruby-prism", "MEHEN_RUBY_PRISM_VERSION"),
+    ("ra_ap_syntax", "MEHEN_RA_AP_SYNTAX_VERSION"),
+    ("pulldown-cmark", "MEHEN_PULLDOWN_CMARK_VERSION"),
+];
+
+fn main() {
+    let manifest_dir = PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap());
+    let lock = find_lock(&manifest_dir);
+    let text = match &lock {
+        Some(path) => {
+            println!("cargo:rerun-if-changed={}", path.display());
+            std::fs::read_to_string(path).unwrap_or_default()
+        }
+        None => {
+            println!("cargo:rerun-if-changed=build.rs");
+            String::new()
+        }
+    };
+    for (package, var) in PARSERS {
+        let version = locked_version(&text, package).unwrap_or("unknown");
+        println!("cargo:rustc-env={var}={version}");
+    }
+}
+
+fn find_lock(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|path| path.is_file())
+}
+
+/// Version of `package` in `lock`. Git dependencies such as ruff are all
+/// versioned `0.0.0` upstream, so the tag they are pinned to is used
+/// instead.
+fn locked_version<'a>(lock: &'a str, package: &str) -> Option<&'a str> {
+    lock.split("[[package]]").find_map(|block| {
+        let field = |key: &str| {
+            block.lines().find_map(|line| {
+                line.strip_prefix(key)?
+                    .strip_prefix(" = \"")?
+                    .strip_suffix('"')
+            })
+        };
+        if field("name")? != package {
+            return None;
+        }
+        let tag = field("source")
+            .and_then(|source| source.split_once("tag="))
+            .and_then(|(_, rest)| rest.split(['#', '&']).next());
+        tag.or_else(|| field("version"))
+    })
+}
@@ -0,0 +1,284 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Metric baseline files.
+//!
+//! A baseline is a JSON snapshot of every analyzed file's unit-level
+//! `MetricSet`, keyed by the file's path relative to where the snapshot
+//! was taken. `mehen top-offenders --baseline-write` produces one;
+//! `mehen diff --baseline` compares the head revision against it instead
+//! of analyzing the `--from` revision, so teams can ratchet metrics
+//! against a committed or cached snapshot.
+//!
+//! ```json
+//! {
+//!   "schema_version": "1.0",
+//!   "files": {
+//!     "src/lib.rs": { "cyclomatic.sum": 4, "loc.sloc": 120, "…": 0 }
+//!   }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+use mehen_core::{MetricSet, MetricSpace, SourceSpan, SpaceId, SpaceKind};
+use serde::{Deserialize, Serialize};
+
+/// Schema version written into new baselines. Readers reject any other
+/// major version.
+pub(crate) const BASELINE_SCHEMA_VERSION: &str = "1.0";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct Baseline {
+    pub(crate) schema_version: String,
+    pub(crate) files: BTreeMap<String, MetricSet>,
+}
+
+impl Default for Baseline {
+    fn default() -> Self {
+        Self {
+            schema_version: BASELINE_SCHEMA_VERSION.to_string(),
+            files: BTreeMap::new(),
+        }
+    }
+}
+
+impl Baseline {
+    pub(crate) fn insert(&mut self, path: &Path, metrics: MetricSet) {
+        self.files.insert(baseline_key(path), metrics);
+    }
+
+    /// The recorded unit metrics for `path`, wrapped in a childless
+    /// `Unit` space so selector lookups work unchanged.
+    pub(crate) fn space_for(&self, path: &Path) -> Option<MetricSpace> {
+        let metrics = self.files.get(&baseline_key(path))?;
+        let mut space = MetricSpace::new(SpaceId(0), SpaceKind::Unit, SourceSpan::empty());
+        space.metrics = metrics.clone();
+        Some(space)
+    }
+
+    /// Whether the baseline has an entry for `path`.
+    pub(crate) fn records(&self, path: &Path) -> bool {
+        self.files.contains_key(&baseline_key(path))
+    }
+
+    pub(crate) fn read(path: &Path) -> Result<Self, BaselineError> {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| BaselineError::Io(path.to_path_buf(), e))?;
+        let baseline: Self =
+            serde_json::from_str(&text).map_err(|e| BaselineError::Parse(path.to_path_buf(), e))?;
+        let major = baseline.schema_version.split('.').next();
+        if major != BASELINE_SCHEMA_VERSION.split('.').next() {
+            return Err(BaselineError::UnsupportedSchema(baseline.schema_version));
+        }
+        Ok(baseline)
+    }
+
+    /// Read a baseline from a `--baseline-url` location: a local path or
+    /// a `file://` URL. Remote schemes are recognized but not fetched
+    /// yet; a download would go in the `Remote` arm of
+    /// [`BaselineLocation`] and parse the body like [`Self::read`].
+    pub(crate) fn fetch(location: &str) -> Result<Self, BaselineError> {
+        match BaselineLocation::parse(location) {
+            BaselineLocation::Local(path) => Self::read(&path),
+            BaselineLocation::Remote(scheme) => Err(BaselineError::UnsupportedScheme(scheme)),
+        }
+    }
+
+    pub(crate) fn write(&self, path: &Path) -> Result<(), BaselineError> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| BaselineError::Serialize(path.to_path_buf(), e))?;
+        std::fs::write(path, format!("{json}\n"))
+            .map_err(|e| BaselineError::Io(path.to_path_buf(), e))
+    }
+}
+
+/// Where `--baseline-url` points.
+#[derive(Debug, PartialEq, Eq)]
+enum BaselineLocation {
+    Local(PathBuf),
+    /// A `scheme://` URL other than `file://`.
+    Remote(String),
+}
+
+impl BaselineLocation {
+    fn parse(location: &str) -> Self {
+        if let Some(path) = location.strip_prefix("file://") {
+            // `file://localhost/x` names the same file as `file:///x`.
+            let path = path.strip_prefix("localhost").unwrap_or(path);
+            return Self::Local(PathBuf::from(percent_decode(path)));
+        }
+        match location.split_once("://") {
+            Some((scheme, _))
+                if !scheme.is_empty()
+                    && scheme
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')) =>
+            {
+                Self::Remote(scheme.to_ascii_lowercase())
+            }
+            _ => Self::Local(PathBuf::from(location)),
+        }
+    }
+}
+
+/// Decode the `%XX` escapes of a `file://` URL path, e.g. `%20` for a
+/// space. An escape that is not two hex digits is kept as written, and
+/// so is the whole path when the decoded bytes are not UTF-8.
+fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| path.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).unwrap_or_else(|_| path.to_string())
+}
+
+/// Normalize a path into a baseline key: drop `.` components and join
+/// with `/` so `./src/a.rs`, `src/a.rs` and `src\a.rs` (on Windows) all
+/// match the repository-relative paths `mehen diff` sees.
+fn baseline_key(path: &Path) -> String {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(s) => Some(s.to_string_lossy()),
+            Component::CurDir => None,
+            // Joining with a leading empty segment keeps a single `/`.
+            Component::RootDir => Some("".into()),
+            other => Some(other.as_os_str().to_string_lossy()),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Why a baseline (or `--vs-last` state) file could not be read or
+/// written.
+#[derive(Debug)]
+pub enum BaselineError {
+    Io(PathBuf, std::io::Error),
+    Parse(PathBuf, serde_json::Error),
+    Serialize(PathBuf, serde_json::Error),
+    UnsupportedSchema(String),
+    UnsupportedScheme(String),
+}
+
+impl fmt::Display for BaselineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, e) => write!(f, "baseline `{}`: {e}", path.display()),
+            Self::Parse(path, e) => write!(f, "baseline `{}` is not valid: {e}", path.display()),
+            Self::Serialize(path, e) => {
+                write!(
+                    f,
+                    "baseline `{}` could not be serialized: {e}",
+                    path.display()
+                )
+            }
+            Self::UnsupportedSchema(v) => write!(
+                f,
+                "baseline schema version `{v}` is not supported (expected {BASELINE_SCHEMA_VERSION})"
+            ),
+            Self::UnsupportedScheme(scheme) => write!(
+                f,
+                "baseline URL scheme `{scheme}` is not supported; pass a local path or a file:// URL"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BaselineError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_ignore_leading_curdir() {
+        assert_eq!(baseline_key(Path::new("./src/a.rs")), "src/a.rs");
+        assert_eq!(baseline_key(Path::new("src/a.rs")), "src/a.rs");
+    }
+
+    #[test]
+    fn round_trips_through_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("baseline.json");
+        let mut metrics = MetricSet::new();
+        metrics.insert("cyclomatic.sum", 4_i64);
+        let mut baseline = Baseline::default();
+        baseline.insert(Path::new("./src/a.rs"), metrics);
+        baseline.write(&file).unwrap();
+
+        let read = Baseline::read(&file).unwrap();
+        let space = read.space_for(Path::new("src/a.rs")).unwrap();
+        assert_eq!(
+            space
+                .metrics
+                .get(&mehen_core::MetricKey::new("cyclomatic.sum"))
+                .map(|v| v.as_f64()),
+            Some(4.0)
+        );
+        assert!(read.space_for(Path::new("src/b.rs")).is_none());
+    }
+
+    #[test]
+    fn fetch_reads_local_paths_and_file_urls() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("baseline.json");
+        let mut baseline = Baseline::default();
+        baseline.insert(Path::new("src/a.rs"), MetricSet::new());
+        baseline.write(&file).unwrap();
+
+        let path = file.to_str().unwrap();
+        assert_eq!(Baseline::fetch(path).unwrap().files.len(), 1);
+        if path.starts_with('/') {
+            let url = format!("file://{path}");
+            assert_eq!(Baseline::fetch(&url).unwrap().files.len(), 1);
+        }
+        assert!(matches!(
+            Baseline::fetch("https://example.com/baseline.json"),
+            Err(BaselineError::UnsupportedScheme(s)) if s == "https"
+        ));
+        assert_eq!(
+            BaselineLocation::parse("file://localhost/tmp/b.json"),
+            BaselineLocation::Local(PathBuf::from("/tmp/b.json"))
+        );
+        assert_eq!(
+            BaselineLocation::parse("file:///tmp/my%20dir/b%C3%A9.json"),
+            BaselineLocation::Local(PathBuf::from("/tmp/my dir/bé.json"))
+        );
+        assert_eq!(
+            BaselineLocation::parse("file:///tmp/100%/b%2.json"),
+            BaselineLocation::Local(PathBuf::from("/tmp/100%/b%2.json"))
+        );
+        assert_eq!(
+            BaselineLocation::parse("C:\\b.json"),
+            BaselineLocation::Local(PathBuf::from("C:\\b.json"))
+        );
+    }
+
+    #[test]
+    fn rejects_other_major_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("baseline.json");
+        std::fs::write(&file, r#"{"schema_version":"2.0","files":{}}"#).unwrap();
+        assert!(matches!(
+            Baseline::read(&file),
+            Err(BaselineError::UnsupportedSchema(_))
+        ));
+    }
+}
@@ -1,7 +1,8 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 // Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
 
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 use mehen_git::{ChangeStatus, ChangedFile};
 
@@ -93,6 +94,39 @@ fn detect_github_actions() -> Option<CiContext> {
     })
 }
 
+/// The job summary file GitHub Actions exposes to each step, if any.
+pub fn step_summary_path() -> Option<PathBuf> {
+    std::env::var_os("GITHUB_STEP_SUMMARY")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+/// `--github-summary`: append `markdown` to the job summary file. Outside
+/// a GitHub Actions step this only logs a warning, and a write failure
+/// never fails the run — the summary is a convenience, stdout is the
+/// report.
+pub fn write_step_summary(markdown: &str) {
+    let Some(path) = step_summary_path() else {
+        log::warn!("--github-summary: GITHUB_STEP_SUMMARY is not set; skipping job summary");
+        return;
+    };
+    if let Err(e) = append_markdown(&path, markdown) {
+        log::warn!("--github-summary: failed to write {}: {e}", path.display());
+    }
+}
+
+fn append_markdown(path: &Path, markdown: &str) -> std::io::Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.write_all(markdown.as_bytes())?;
+    if !markdown.ends_with('\n') {
+        file.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
 fn extract_push_changed_files(payload: &serde_json::Value) -> Option<Vec<ChangedFile>> {
     let commits = payload.get("commits")?.as_array()?;
     let mut by_path: std::collections::HashMap<PathBuf, ChangeStatus> =
@@ -165,6 +199,18 @@ fn extract_push_changed_files(payload: &serde_json::Value) -> Option<Vec<Changed
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_append_markdown_keeps_earlier_steps() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("summary.md");
+        std::fs::write(&path, "## Earlier step\n").unwrap();
+        append_markdown(&path, "## Metric Changes").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "## Earlier step\n## Metric Changes\n"
+        );
+    }
+
     fn paths_with_status(files: &[ChangedFile]) -> Vec<(PathBuf, ChangeStatus)> {
         files.iter().map(|f| (f.path.clone(), f.status)).collect()
     }
@@ -4,12 +4,16 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
 
+use camino::Utf8Path;
 use crossbeam::channel::{Receiver, Sender, unbounded};
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use walkdir::{DirEntry, WalkDir};
 
+use crate::detection::detect_language;
+
 /// Build a `GlobSet` from a list of glob strings, ignoring empty entries.
 ///
 /// Used by both the `diff` and `top-offenders` orchestrators to turn the
@@ -27,6 +31,38 @@ pub(crate) fn mk_globset(elems: Vec<String>) -> GlobSet {
     globset.build().map_or(GlobSet::empty(), |globset| globset)
 }
 
+/// Name of the per-repository ignore file.
+pub(crate) const MEHENIGNORE: &str = ".mehenignore";
+
+/// The repository root that contains `start`: the nearest ancestor with
+/// a `.git` or `.hg` entry (`mehen diff` reads both), or `start` itself
+/// outside a repository.
+pub(crate) fn repository_root(start: &Path) -> &Path {
+    start
+        .ancestors()
+        .find(|dir| dir.join(".git").exists() || dir.join(".hg").exists())
+        .unwrap_or(start)
+}
+
+/// Exclusion globs from the `.mehenignore` at the
+/// [repository root](repository_root) that contains `start`. One glob
+/// per line, in the same syntax as `--exclude`; blank lines and `#`
+/// comments are skipped. A missing or unreadable file yields no
+/// patterns.
+pub(crate) fn mehenignore_patterns(start: &Path) -> Vec<String> {
+    std::fs::read_to_string(repository_root(start).join(MEHENIGNORE))
+        .map(|text| parse_mehenignore(&text))
+        .unwrap_or_default()
+}
+
+fn parse_mehenignore(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
 type ProcFilesFunction<Config> = dyn Fn(PathBuf, &Config) -> std::io::Result<()> + Send + Sync;
 
 type ProcDirPathsFunction<Config> =
@@ -50,14 +86,22 @@ type JobSender<Config> = Sender<Option<JobItem<Config>>>;
 // Both args are moved into this thread entry point from a `move ||` closure;
 // pass-by-value is required because `Receiver` is consumed and `Arc` is moved.
 #[allow(clippy::needless_pass_by_value)]
-fn consumer<Config, ProcFiles>(receiver: JobReceiver<Config>, func: Arc<ProcFiles>)
-where
+fn consumer<Config, ProcFiles>(
+    receiver: JobReceiver<Config>,
+    func: Arc<ProcFiles>,
+    stop: Arc<AtomicBool>,
+) where
     ProcFiles: Fn(PathBuf, &Config) -> std::io::Result<()> + Send + Sync,
 {
     while let Ok(job) = receiver.recv() {
         if job.is_none() {
             break;
         }
+        // Keep draining the channel so the poison pills still arrive,
+        // but stop doing work once a stop was requested.
+        if stop.load(Ordering::Relaxed) {
+            continue;
+        }
         // Cannot panic because of the check immediately above.
         let job = job.unwrap();
         let path = job.path.clone();
@@ -88,12 +132,21 @@ fn is_hidden(entry: &DirEntry) -> bool {
         .is_some_and(|s| s.starts_with('.'))
 }
 
+/// Whether a file found by a directory walk has an extension one of the
+/// analyzers understands. Anything else (images, data, lock files) is
+/// dropped before the `is_file` stat and never queued for a worker,
+/// which would only skip it by extension.
+fn has_source_extension(path: &Path) -> bool {
+    Utf8Path::from_path(path).is_some_and(|path| detect_language(path).is_some())
+}
+
 fn explore<Config, ProcDirPaths, ProcPath>(
     files_data: FilesData,
     cfg: &Arc<Config>,
     proc_dir_paths: ProcDirPaths,
     proc_path: ProcPath,
     sender: &JobSender<Config>,
+    stop: &AtomicBool,
 ) -> Result<HashMap<String, Vec<PathBuf>>, ConcurrentErrors>
 where
     ProcDirPaths: Fn(&mut HashMap<String, Vec<PathBuf>>, &Path, &Config) + Send + Sync,
@@ -103,11 +156,15 @@ where
         paths,
         ref include,
         ref exclude,
+        source_only,
     } = files_data;
 
     let mut all_files: HashMap<String, Vec<PathBuf>> = HashMap::new();
 
     for path in paths {
+        if stop.load(Ordering::Relaxed) {
+            break;
+        }
         if !path.exists() {
             log::warn!("File doesn't exist: {path:?}");
             continue;
@@ -117,6 +174,9 @@ where
                 .into_iter()
                 .filter_entry(|e| !is_hidden(e))
             {
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
                 let entry = match entry {
                     Ok(entry) => entry,
                     Err(e) => return Err(ConcurrentErrors::Sender(e.to_string())),
@@ -124,6 +184,7 @@ where
                 let path = entry.path().to_path_buf();
                 if (include.is_empty() || include.is_match(&path))
                     && (exclude.is_empty() || !exclude.is_match(&path))
+                    && (!source_only || has_source_extension(&path))
                     && path.is_file()
                 {
                     proc_dir_paths(&mut all_files, &path, cfg);
@@ -185,6 +246,9 @@ pub(crate) struct FilesData {
     pub exclude: GlobSet,
     /// List of file paths.
     pub paths: Vec<PathBuf>,
+    /// Only send files with a known source extension from directory
+    /// walks. Paths given explicitly are always sent.
+    pub source_only: bool,
 }
 
 /// A runner to process files concurrently.
@@ -193,6 +257,7 @@ pub(crate) struct ConcurrentRunner<Config> {
     proc_dir_paths: Box<ProcDirPathsFunction<Config>>,
     proc_path: Box<ProcPathFunction<Config>>,
     num_jobs: usize,
+    stop: Arc<AtomicBool>,
 }
 
 impl<Config> std::fmt::Debug for ConcurrentRunner<Config> {
@@ -219,9 +284,18 @@ impl<Config: 'static + Send + Sync> ConcurrentRunner<Config> {
             proc_dir_paths: Box::new(null_proc_dir_paths),
             proc_path: Box::new(null_proc_path),
             num_jobs,
+            stop: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// Share a stop flag with the runner. Once anything sets it, the
+    /// producer stops exploring and consumers skip the files still
+    /// queued; files already being processed finish normally.
+    pub(crate) fn with_stop_flag(mut self, stop: Arc<AtomicBool>) -> Self {
+        self.stop = stop;
+        self
+    }
+
     /// Runs the producer-consumer approach to process the files
     /// contained in a directory and in its own subdirectories.
     ///
@@ -239,6 +313,7 @@ impl<Config: 'static + Send + Sync> ConcurrentRunner<Config> {
 
         let producer = {
             let sender = sender.clone();
+            let stop = Arc::clone(&self.stop);
 
             match thread::Builder::new()
                 .name(String::from("Producer"))
@@ -249,6 +324,7 @@ impl<Config: 'static + Send + Sync> ConcurrentRunner<Config> {
                         self.proc_dir_paths,
                         self.proc_path,
                         &sender,
+                        &stop,
                     )
                 }) {
                 Ok(producer) => producer,
@@ -261,11 +337,12 @@ impl<Config: 'static + Send + Sync> ConcurrentRunner<Config> {
         for i in 0..self.num_jobs {
             let receiver = receiver.clone();
             let proc_files = proc_files.clone();
+            let stop = Arc::clone(&self.stop);
 
             let t = match thread::Builder::new()
                 .name(format!("Consumer {i}"))
                 .spawn(move || {
-                    consumer(receiver, proc_files);
+                    consumer(receiver, proc_files, stop);
                 }) {
                 Ok(receiver) => receiver,
                 Err(e) => return Err(ConcurrentErrors::Thread(e.to_string())),
@@ -298,3 +375,123 @@ impl<Config: 'static + Send + Sync> ConcurrentRunner<Config> {
         all_files
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    /// Runs a single consumer over `count` files, the first of which
+    /// "fails" by raising the stop flag, and returns how many files
+    /// were processed.
+    fn processed_files(count: usize, stop_on_failure: bool) -> usize {
+        let dir = tempfile::tempdir().unwrap();
+        let paths: Vec<PathBuf> = (0..count)
+            .map(|i| {
+                let path = dir.path().join(format!("{i:02}.rs"));
+                std::fs::write(&path, "fn f() {}\n").unwrap();
+                path
+            })
+            .collect();
+        let bad = paths[0].clone();
+        let processed = Arc::new(AtomicUsize::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let counter = Arc::clone(&processed);
+        let flag = Arc::clone(&stop);
+        let runner = ConcurrentRunner::new(2, move |path: PathBuf, _: &()| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            if stop_on_failure && path == bad {
+                flag.store(true, Ordering::SeqCst);
+            }
+            Ok(())
+        })
+        .with_stop_flag(stop);
+        runner
+            .run(
+                (),
+                FilesData {
+                    include: GlobSet::empty(),
+                    exclude: GlobSet::empty(),
+                    paths,
+                    source_only: false,
+                },
+            )
+            .unwrap();
+        processed.load(Ordering::SeqCst)
+    }
+
+    #[test]
+    fn mehenignore_skips_comments_and_blank_lines() {
+        let text = "# generated code\nsrc/gen/**\n\n  **/*.pb.rs  \n";
+        assert_eq!(parse_mehenignore(text), vec!["src/gen/**", "**/*.pb.rs"]);
+    }
+
+    #[test]
+    fn mehenignore_is_read_from_the_repository_root() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        std::fs::write(dir.path().join(MEHENIGNORE), "vendor/**\n").unwrap();
+        let nested = dir.path().join("src/deep");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert_eq!(mehenignore_patterns(&nested), vec!["vendor/**"]);
+    }
+
+    #[test]
+    fn mehenignore_is_read_from_a_mercurial_root() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".hg")).unwrap();
+        std::fs::write(dir.path().join(MEHENIGNORE), "vendor/**\n").unwrap();
+        let nested = dir.path().join("src");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert_eq!(mehenignore_patterns(&nested), vec!["vendor/**"]);
+    }
+
+    /// Names of the files a run over `dir` hands to the consumers.
+    fn walked_files(dir: &Path, source_only: bool) -> Vec<String> {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        let runner = ConcurrentRunner::new(2, move |path: PathBuf, _: &()| {
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            sink.lock().unwrap().push(name);
+            Ok(())
+        });
+        runner
+            .run(
+                (),
+                FilesData {
+                    include: GlobSet::empty(),
+                    exclude: GlobSet::empty(),
+                    paths: vec![dir.to_path_buf()],
+                    source_only,
+                },
+            )
+            .unwrap();
+        let mut seen = seen.lock().unwrap().clone();
+        seen.sort();
+        seen
+    }
+
+    #[test]
+    fn directory_walks_skip_non_source_files_by_default() {
+        let tmp = tempfile::tempdir().unwrap();
+        // The temp dir itself is hidden (`.tmpXXXX`), which the walk skips.
+        let dir = tmp.path().join("repo");
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::write(dir.join("logo.png"), b"\x89PNG\r\n").unwrap();
+        std::fs::write(dir.join("photo.jpg"), b"\xff\xd8\xff").unwrap();
+        std::fs::write(dir.join("lib.rs"), "fn f() {}\n").unwrap();
+        std::fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+        assert_eq!(walked_files(&dir, true), ["lib.rs", "main.rs"]);
+        assert_eq!(
+            walked_files(&dir, false),
+            ["lib.rs", "logo.png", "main.rs", "photo.jpg"]
+        );
+    }
+
+    #[test]
+    fn stop_flag_halts_dispatch_after_a_failure() {
+        assert_eq!(processed_files(20, false), 20);
+        assert_eq!(processed_files(20, true), 1);
+    }
+}
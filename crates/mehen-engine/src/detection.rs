@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 // Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
 
+use std::path::{Component, Path};
+
 use camino::Utf8Path;
 
 use mehen_core::Language;
@@ -33,6 +35,60 @@ pub fn detect_language(path: &Utf8Path) -> Option<Language> {
     Some(lang)
 }
 
+/// Language named by a `mehen-lang:` modeline on the first line of
+/// `source`, e.g. `// mehen-lang: tsx` or `# mehen-lang: python`. It
+/// overrides the extension-based guess, for `.ts` files that hold JSX
+/// and the like. Any name or alias `--language` accepts works.
+pub fn language_from_modeline(source: &str) -> Option<Language> {
+    let first = source.lines().next()?.trim_start_matches('\u{feff}').trim();
+    let comment = ["//", "/*", "#", "<!--"]
+        .iter()
+        .find_map(|marker| first.strip_prefix(marker))?;
+    let name = comment
+        .trim_start()
+        .strip_prefix("mehen-lang:")?
+        .split_whitespace()
+        .next()?;
+    name.parse().ok()
+}
+
+/// Whether `path` looks like a test file, by the naming conventions of
+/// the supported languages:
+/// - a directory named `test`, `tests`, `__tests__`, `spec` or `testdata`;
+/// - `*_test.*` / `*_tests.*` (Go, Rust, Python), `test_*.*` (pytest),
+///   `*_spec.*` (RSpec);
+/// - `*.test.*` / `*.spec.*` (Jest, Vitest), `*.Tests.ps1` (Pester);
+/// - `*Test.kt` / `*Tests.kt` (JUnit).
+pub(crate) fn is_test_path(path: &Path) -> bool {
+    let mut components = path.components().peekable();
+    while let Some(component) = components.next() {
+        let Component::Normal(part) = component else {
+            continue;
+        };
+        let Some(part) = part.to_str() else {
+            continue;
+        };
+        if components.peek().is_some() {
+            if matches!(part, "test" | "tests" | "__tests__" | "spec" | "testdata") {
+                return true;
+            }
+            continue;
+        }
+        let stem = part.split('.').next().unwrap_or(part);
+        let inner_suffix = part.split('.').skip(1).any(|s| {
+            s.eq_ignore_ascii_case("test") || s.eq_ignore_ascii_case("spec") || s == "Tests"
+        }) && part.matches('.').count() >= 2;
+        return inner_suffix
+            || stem.starts_with("test_")
+            || stem.ends_with("_test")
+            || stem.ends_with("_tests")
+            || stem.ends_with("_spec")
+            || (stem.len() > 4 && stem.ends_with("Test"))
+            || (stem.len() > 5 && stem.ends_with("Tests"));
+    }
+    false
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -54,6 +110,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn modeline_names_the_language() {
+        assert_eq!(
+            language_from_modeline("// mehen-lang: tsx\nconst a = <div />;\n"),
+            Some(Language::Tsx)
+        );
+        assert_eq!(
+            language_from_modeline("/* mehen-lang: ts */"),
+            Some(Language::TypeScript)
+        );
+        assert_eq!(
+            language_from_modeline("# mehen-lang: py\n"),
+            Some(Language::Python)
+        );
+        assert_eq!(language_from_modeline("\n// mehen-lang: tsx\n"), None);
+        assert_eq!(language_from_modeline("// mehen-lang: cobol\n"), None);
+        assert_eq!(
+            language_from_modeline("const a = 1; // mehen-lang: tsx"),
+            None
+        );
+    }
+
+    #[test]
+    fn recognizes_test_paths() {
+        for path in [
+            "pkg/server_test.go",
+            "src/parser_tests.rs",
+            "tests/cli.rs",
+            "crates/x/tests/fixtures/a.rs",
+            "test_utils.py",
+            "lib/user_spec.rb",
+            "web/App.test.tsx",
+            "web/api.spec.ts",
+            "scripts/Build.Tests.ps1",
+            "src/ParserTest.kt",
+            "src/__tests__/a.js",
+        ] {
+            assert!(is_test_path(Path::new(path)), "{path}");
+        }
+        for path in [
+            "pkg/server.go",
+            "src/testing.rs",
+            "src/contest.py",
+            "Test.kt",
+            "lib/latest.rb",
+        ] {
+            assert!(!is_test_path(Path::new(path)), "{path}");
+        }
+    }
+
     #[test]
     fn returns_none_for_unknown() {
         assert_eq!(detect_language(Utf8Path::new("file.xyz")), None);
@@ -3,8 +3,9 @@
 
 //! `mehen diff` orchestrator.
 //!
-//! Walks `mehen-git`'s changed-file list, analyzes each file at base and
-//! head, and assembles a `DiffReport` (the post-1.0 [`analyze_diff`]
+//! Walks the changed-file list of a [`Vcs`](crate::vcs::Vcs) backend
+//! (git or Mercurial), analyzes each file at base and head, and
+//! assembles a `DiffReport` (the post-1.0 [`analyze_diff`]
 //! entry point). The pre-1.0 CLI orchestrator [`run_diff`] lives in
 //! this same module so the two share the [`has_blocking_diagnostic`]
 //! gate. Per the rewrite plan §4.6, per-file analysis is the
@@ -12,30 +13,39 @@
 //! commits will switch to a thread-per-file pool. The Markdown
 //! documentation diff renderer in `mehen-report` consumes this report.
 
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::io::Write;
 use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
 
-use camino::{Utf8Component, Utf8PathBuf};
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
 
 use mehen_core::{
-    AnalysisConfig, DiagnosticSeverity, Language, LanguageAnalysis, MetricSpace, ParseDiagnostic,
-    SourceFile, Threshold, ThresholdEvaluation,
+    AnalysisConfig, DiagnosticSeverity, Language, LanguageAnalysis, LanguageAnalyzer, MetricSpace,
+    ParseDiagnostic, SourceFile, SpaceKind, Threshold, ThresholdEvaluation,
 };
 use mehen_git::{ChangeStatus, GitError};
 use mehen_report::github_markdown_docs::{DocDiffFile, DocRenderCtx, render_doc_section};
 
+use crate::baseline::Baseline;
 use crate::ci;
-use crate::concurrent_files::mk_globset;
-use crate::detection::detect_language;
+use crate::concurrent_files::{mehenignore_patterns, mk_globset};
+use crate::detection::{detect_language, is_test_path, language_from_modeline};
+use crate::hunks::{Hunk, Side, line_hunks, touches};
 use crate::metric_selector::{
-    MetricSelector, Polarity as SelectorPolarity, parse_metric_selectors,
+    MetricSelector, Polarity as SelectorPolarity, apply_label_aliases, parse_metric_selectors_into,
     read_metric as read_selector_metric,
 };
+use crate::project_config::ProjectConfig;
 use crate::registry::AnalyzerRegistry;
 use crate::top_offenders::read_metric;
+use crate::vcs::{GitVcs, Vcs, VcsError, VcsKind};
+use crate::warnings::{Warning, WarningCode, Warnings};
 use mehen_core::{
-    AnalysisErrorRecord, DiffFile, DiffInput, DiffReport, DiffSide, ThresholdViolation,
+    AnalysisErrorRecord, DiffFile, DiffInput, DiffReport, DiffSide, PathThresholds,
+    ThresholdViolation,
 };
 
 /// Run `mehen diff` against the workspace and produce a report.
@@ -45,10 +55,13 @@ use mehen_core::{
 /// `threshold_violations`); only IO/git-fatal failures bubble up as
 /// `Err` so callers can short-circuit the rendering step.
 pub fn analyze_diff(input: DiffInput) -> Result<DiffReport, DiffError> {
-    let registry = Arc::new(AnalyzerRegistry::default_set());
     let repo = mehen_git::open_repo().map_err(DiffError::Git)?;
-    let changed =
-        mehen_git::changed_files(&repo, &input.from, &input.to).map_err(DiffError::Git)?;
+    analyze_diff_with(&GitVcs(repo), input)
+}
+
+fn analyze_diff_with(vcs: &dyn Vcs, input: DiffInput) -> Result<DiffReport, DiffError> {
+    let registry = Arc::new(AnalyzerRegistry::default_set());
+    let changed = vcs.changed_files(&input.from, &input.to)?;
 
     let mut report = DiffReport {
         schema_version: "1.0".to_string(),
@@ -79,15 +92,13 @@ pub fn analyze_diff(input: DiffInput) -> Result<DiffReport, DiffError> {
         let base_text = if cf.status == ChangeStatus::Added {
             None
         } else {
-            mehen_git::read_blob(&repo, &input.from, &cf.path)
-                .map_err(DiffError::Git)?
+            vcs.read_blob(&input.from, &cf.path)?
                 .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
         };
         let head_text = if cf.status == ChangeStatus::Deleted {
             None
         } else {
-            mehen_git::read_blob(&repo, &input.to, &cf.path)
-                .map_err(DiffError::Git)?
+            vcs.read_blob(&input.to, &cf.path)?
                 .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
         };
 
@@ -105,9 +116,14 @@ pub fn analyze_diff(input: DiffInput) -> Result<DiffReport, DiffError> {
             (head_text.as_deref(), DiffSide::Head),
         ] {
             let Some(text) = text else { continue };
+            let refined = refine_language(&registry, text, language);
+            let (language, analyzer) = refined
+                .as_ref()
+                .map_or((language, analyzer.as_ref()), |(l, a)| (*l, a.as_ref()));
             let source = SourceFile::new(utf8_path.clone(), language, text.to_string());
             match analyzer.analyze(&source, &input.config) {
-                Ok(analysis) => {
+                Ok(mut analysis) => {
+                    crate::finalize_analysis(&mut analysis.root, &input.config);
                     collect_diagnostics(&mut report, &utf8_path, side, &analysis);
                     if matches!(side, DiffSide::Head) {
                         head_analysis = Some(analysis);
@@ -135,7 +151,8 @@ pub fn analyze_diff(input: DiffInput) -> Result<DiffReport, DiffError> {
         if let Some(analysis) = head_analysis.as_ref()
             && !has_blocking_diagnostic(&analysis.diagnostics)
         {
-            evaluate_thresholds(&mut report, &utf8_path, &input.thresholds, analysis);
+            let thresholds = thresholds_for(&input.thresholds, &input.path_thresholds, &utf8_path);
+            evaluate_thresholds(&mut report, &utf8_path, thresholds, analysis);
         }
 
         if matches!(language, mehen_core::Language::Markdown) {
@@ -157,11 +174,22 @@ fn evaluate_thresholds(
     thresholds: &[Threshold],
     analysis: &LanguageAnalysis,
 ) {
-    for threshold in thresholds {
-        let actual = read_metric(&threshold.selector, &analysis.root);
-        let violated = threshold.violated_by(actual);
-        if violated {
-            report.threshold_violations.push(ThresholdViolation {
+    report
+        .threshold_violations
+        .extend(threshold_violations(path, thresholds, &analysis.root));
+}
+
+/// The rules of `thresholds` that `root` violates.
+fn threshold_violations(
+    path: &Utf8Path,
+    thresholds: &[Threshold],
+    root: &MetricSpace,
+) -> Vec<ThresholdViolation> {
+    thresholds
+        .iter()
+        .filter_map(|threshold| {
+            let actual = read_metric(&threshold.selector, root);
+            threshold.violated_by(actual).then(|| ThresholdViolation {
                 path: path.to_string(),
                 evaluation: ThresholdEvaluation {
                     selector: threshold.selector.clone(),
@@ -170,9 +198,28 @@ fn evaluate_thresholds(
                     polarity: threshold.polarity,
                     violated: true,
                 },
-            });
-        }
-    }
+            })
+        })
+        .collect()
+}
+
+/// The thresholds that apply to `path`: those of the `by_path` entry
+/// with the longest matching prefix, or `defaults` when no prefix
+/// matches.
+fn thresholds_for<'a>(
+    defaults: &'a [Threshold],
+    by_path: &'a [PathThresholds],
+    path: &Utf8Path,
+) -> &'a [Threshold] {
+    by_path
+        .iter()
+        .filter_map(|entry| {
+            let prefix = normalize_utf8_filter(&entry.prefix);
+            path.starts_with(&prefix)
+                .then(|| (prefix.components().count(), &entry.thresholds))
+        })
+        .max_by_key(|(depth, _)| *depth)
+        .map_or(defaults, |(_, thresholds)| thresholds)
 }
 
 fn path_is_selected(path: &Utf8PathBuf, paths: &[Utf8PathBuf]) -> bool {
@@ -247,6 +294,20 @@ pub(crate) fn has_blocking_diagnostic(diagnostics: &[ParseDiagnostic]) -> bool {
     })
 }
 
+/// The language and analyzer a `mehen-lang:` modeline in `text` names,
+/// when it refines the extension guess `guessed`, as in `metrics`. A
+/// modeline naming Markdown or a language this build has no analyzer
+/// for is ignored.
+fn refine_language(
+    registry: &AnalyzerRegistry,
+    text: &str,
+    guessed: Language,
+) -> Option<(Language, Box<dyn LanguageAnalyzer>)> {
+    language_from_modeline(text)
+        .filter(|&l| l != guessed && l != Language::Markdown)
+        .and_then(|l| registry.analyzer_for(l).map(|a| (l, a)))
+}
+
 fn record_unavailable(report: &mut DiffReport, path: &Utf8PathBuf, language: mehen_core::Language) {
     report.analysis_errors.push(AnalysisErrorRecord {
         path: path.clone(),
@@ -264,12 +325,23 @@ fn record_unavailable(report: &mut DiffReport, path: &Utf8PathBuf, language: meh
 #[derive(Debug)]
 pub enum DiffError {
     Git(GitError),
+    Hg(String),
 }
 
 impl core::fmt::Display for DiffError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::Git(e) => write!(f, "git: {e}"),
+            Self::Hg(msg) => write!(f, "hg: {msg}"),
+        }
+    }
+}
+
+impl From<VcsError> for DiffError {
+    fn from(e: VcsError) -> Self {
+        match e {
+            VcsError::Git(e) => Self::Git(e),
+            VcsError::Hg(msg) => Self::Hg(msg),
         }
     }
 }
@@ -284,16 +356,25 @@ impl core::error::Error for DiffError {}
 
 const LINGUIST_GENERATED_ATTR: &str = "linguist-generated";
 
+/// Files whose blobs are held in memory at once, per `-j` reader.
+const BLOB_WINDOW_PER_JOB: usize = 16;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 pub(crate) enum DiffFormat {
     Markdown,
     Json,
+    /// The `annotations` array of a GitHub Checks API run output.
+    #[value(name = "github-checks")]
+    GitHubChecks,
+    /// SonarQube's generic issue import format.
+    #[value(name = "sonarqube")]
+    SonarGeneric,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
 struct MetricDiff {
     name: &'static str,
-    label: &'static str,
+    label: Cow<'static, str>,
     current: f64,
     baseline: f64,
     delta: f64,
@@ -308,11 +389,31 @@ struct FileDiff {
     metrics: Vec<MetricDiff>,
     is_new: bool,
     is_deleted: bool,
+    /// Per-function rows, populated only under `--by-function`. Holds
+    /// just the functions whose metrics or source text changed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    functions: Vec<FunctionDiff>,
+}
+
+/// One changed function inside a [`FileDiff`] (`--by-function`).
+#[derive(Debug, Clone, serde::Serialize)]
+struct FunctionDiff {
+    /// Enclosing space names joined with `::` (e.g. `Parser::parse`).
+    name: String,
+    metrics: Vec<MetricDiff>,
+    is_new: bool,
+    is_deleted: bool,
+    /// Lines of the function on the side it exists on (head, or base
+    /// for deleted rows), for `github-checks` annotations.
+    #[serde(skip)]
+    start_line: u32,
+    #[serde(skip)]
+    end_line: u32,
 }
 
 impl FileDiff {
     fn all_unchanged(&self) -> bool {
-        self.metrics.iter().all(|m| m.delta == 0.0)
+        self.metrics.iter().all(|m| m.delta == 0.0) && self.functions.is_empty()
     }
 
     /// Sort key: total function count descending, then path ascending.
@@ -335,6 +436,15 @@ pub struct DiffOpts {
     /// Head revision to compare to.
     #[clap(long)]
     to: Option<String>,
+    /// Version-control system holding the revisions. With `hg`, GitHub
+    /// Actions context is not consulted and `--ignore-generated` has no
+    /// effect (it reads git attributes).
+    #[clap(long, value_enum, default_value_t = VcsKind::Git)]
+    vcs: VcsKind,
+    /// Number of concurrent blob readers (default: available CPUs).
+    /// Analysis itself stays single-threaded.
+    #[clap(long, short = 'j')]
+    num_jobs: Option<usize>,
     /// Comma-separated metrics to compare
     /// (default: cyclomatic,cognitive,nom.functions,loc.lloc,mi.visual_studio).
     /// Prefix with + for higher-is-better, - for lower-is-better.
@@ -355,6 +465,58 @@ pub struct DiffOpts {
     /// Show files where all metrics are unchanged.
     #[clap(long)]
     show_unchanged: bool,
+    /// Also report per-function deltas. Functions whose metrics and
+    /// source text are identical on both sides are omitted.
+    #[clap(long)]
+    by_function: bool,
+    /// Like `--by-function`, but only pair and diff the functions whose
+    /// lines overlap a changed hunk. Cheaper on large files with a few
+    /// edits; file-level rows are still reported.
+    #[clap(long)]
+    hunks_only: bool,
+    /// Print one before/after/delta row per metric, summed across every
+    /// changed file, instead of the per-file table — e.g. for release
+    /// notes between two tags (`--from v1.0 --to v1.1`).
+    #[clap(long)]
+    aggregate: bool,
+    /// Drop test files (`*_test.go`, `test_*.py`, `*.spec.ts`, files
+    /// under `tests/`, …) so the table stays on production code.
+    #[clap(long)]
+    exclude_tests: bool,
+    /// Leave newly added files out of the report. They have no baseline
+    /// to compare against, so a PR that adds many files otherwise buries
+    /// the changes to existing code under 🆕 rows.
+    #[clap(long)]
+    ignore_new: bool,
+    /// Compare against a baseline file written by
+    /// `mehen top-offenders --baseline-write` instead of analyzing the
+    /// `--from` revision. `--by-function` and `--hunks-only` are ignored
+    /// in this mode: the baseline only records file-level metrics.
+    #[clap(long, value_name = "FILE")]
+    baseline: Option<PathBuf>,
+    /// Like `--baseline`, but takes a location: a local path or a
+    /// `file://` URL, e.g. a CI artifact downloaded by an earlier job.
+    #[clap(long, value_name = "URL", conflicts_with = "baseline")]
+    baseline_url: Option<String>,
+    /// Markdown only: when more than N files changed, print a one-line
+    /// summary and fold the table into a `<details>` block so large PRs
+    /// keep a readable comment.
+    #[clap(long, value_name = "N")]
+    collapse_threshold: Option<usize>,
+    /// Markdown only: print each file's row as soon as the file is
+    /// analyzed instead of after the whole diff, so a PR comment for a
+    /// large change starts immediately. The rows are not sorted: they
+    /// keep the changed-file order instead of the usual function count,
+    /// which is only known once every file is analyzed.
+    #[clap(
+        long,
+        conflicts_with_all = ["aggregate", "collapse_threshold", "github_summary"]
+    )]
+    parallel_diff_output: bool,
+    /// Also append the markdown report to the GitHub Actions job summary
+    /// (`$GITHUB_STEP_SUMMARY`), whatever `--output-format` is.
+    #[clap(long)]
+    github_summary: bool,
     /// Skip files marked as generated via `linguist-generated` git attributes.
     #[clap(
         long,
@@ -423,26 +585,60 @@ pub fn run_diff(opts: DiffOpts) {
 
 fn run_diff_inner(opts: DiffOpts) -> Result<(), Box<dyn std::error::Error>> {
     // 1. Resolve refs
-    let ci_ctx = ci::detect();
+    //    The CI context describes a GitHub Actions git checkout, so it
+    //    only applies to the git backend.
+    let ci_ctx = match opts.vcs {
+        VcsKind::Git => ci::detect(),
+        VcsKind::Hg => None,
+    };
     let (from_ref, to_ref) = resolve_refs(&opts, &ci_ctx);
 
     // 2. Get changed file list
-    let repo = mehen_git::open_repo()?;
-    let from_label = mehen_git::friendly_ref_label(&repo, &from_ref);
-    let changed = get_changed_files(&repo, &from_ref, &to_ref, &ci_ctx)?;
+    let vcs = opts.vcs.open()?;
+    let from_label = if opts.baseline.is_some() || opts.baseline_url.is_some() {
+        "baseline".to_string()
+    } else {
+        vcs.ref_label(&from_ref)
+    };
+    let changed = get_changed_files(vcs.as_ref(), &from_ref, &to_ref, &ci_ctx)?;
+    let commits = vcs
+        .commit_id(&from_ref)
+        .zip(vcs.commit_id(&to_ref))
+        .map(|(from, to)| ResolvedCommits { from, to });
 
     // 3. Filter files
     let include = mk_globset(opts.include);
-    let exclude = mk_globset(opts.exclude);
+    let mut exclude = opts.exclude;
+    if let Ok(cwd) = std::env::current_dir() {
+        exclude.extend(mehenignore_patterns(&cwd));
+    }
+    let exclude = mk_globset(exclude);
     let paths = normalize_path_filters(&opts.paths);
-    let selectors = parse_metric_selectors(&opts.metrics);
-    let mut generated_filter = opts
-        .ignore_generated
-        .then(|| GeneratedFilter::new(&repo))
+    let mut warnings = Warnings::default();
+    let project = std::env::current_dir().map_or_else(
+        |_| Ok(ProjectConfig::default()),
+        |cwd| ProjectConfig::discover(&cwd),
+    )?;
+    let mut selectors = parse_metric_selectors_into(&opts.metrics, &mut warnings);
+    apply_label_aliases(&mut selectors, &project.metric_aliases);
+    let (thresholds, path_thresholds) = project.diff_thresholds()?;
+    let mut generated_filter = vcs
+        .git_repo()
+        .filter(|_| opts.ignore_generated)
+        .map(GeneratedFilter::new)
         .transpose()?;
 
     let registry = Arc::new(AnalyzerRegistry::default_set());
-    let analysis_config = AnalysisConfig::default();
+    let analysis_config = AnalysisConfig {
+        halstead_overrides: project.halstead_overrides.clone(),
+        ..AnalysisConfig::default()
+    };
+
+    let baseline = match (&opts.baseline, &opts.baseline_url) {
+        (Some(path), _) => Some(Baseline::read(path)?),
+        (None, Some(location)) => Some(Baseline::fetch(location)?),
+        (None, None) => None,
+    };
 
     let mut filtered: Vec<(mehen_git::ChangedFile, Utf8PathBuf, Language)> = Vec::new();
     let mut markdown_files: Vec<mehen_git::ChangedFile> = Vec::new();
@@ -451,6 +647,7 @@ fn run_diff_inner(opts: DiffOpts) -> Result<(), Box<dyn std::error::Error>> {
         if !legacy_path_is_selected(p, &paths)
             || (!include.is_empty() && !include.is_match(p))
             || (!exclude.is_empty() && exclude.is_match(p))
+            || (opts.exclude_tests && is_test_path(p))
         {
             continue;
         }
@@ -474,6 +671,21 @@ fn run_diff_inner(opts: DiffOpts) -> Result<(), Box<dyn std::error::Error>> {
             markdown_files.push(cf.clone());
             continue;
         }
+        // Languages compiled out of this build: never read their blobs.
+        if registry.analyzer_for(language).is_none() {
+            continue;
+        }
+        // `--ignore-new` files are dropped before their blobs are read.
+        // Against a baseline file, "new" means "not recorded in it".
+        if opts.ignore_new {
+            let is_new = match &baseline {
+                Some(baseline) => !baseline.records(&cf.path),
+                None => cf.status == ChangeStatus::Added,
+            };
+            if is_new {
+                continue;
+            }
+        }
 
         filtered.push((cf, utf8_path, language));
     }
@@ -488,107 +700,199 @@ fn run_diff_inner(opts: DiffOpts) -> Result<(), Box<dyn std::error::Error>> {
     //    analyzers (plan §9.3). Track whether any analyzed side reported
     //    an error/fatal so the diff exits non-zero at the end — partial
     //    metrics from a broken parse must not pass CI silently.
+    //
+    //    Blobs are read with `-j` readers, a window of files at a time
+    //    so memory stays bounded on large changes; analysis below stays
+    //    serial and takes the results in request order.
+    let reads_base =
+        |cf: &mehen_git::ChangedFile| cf.status != ChangeStatus::Added && baseline.is_none();
+    let reads_head = |cf: &mehen_git::ChangedFile| cf.status != ChangeStatus::Deleted;
+    let requests_for = |files: std::ops::Range<usize>| {
+        filtered[files]
+            .iter()
+            .flat_map(|(cf, _, _)| {
+                [
+                    reads_base(cf).then_some((from_ref.as_str(), cf.path.as_path())),
+                    reads_head(cf).then_some((to_ref.as_str(), cf.path.as_path())),
+                ]
+            })
+            .flatten()
+            .collect::<Vec<(&str, &Path)>>()
+    };
+    let num_jobs = opts.num_jobs.unwrap_or_else(|| {
+        std::thread::available_parallelism()
+            .map(|threads| threads.get())
+            .unwrap_or(2)
+    });
+    let format = opts.output_format.unwrap_or(DiffFormat::Markdown);
+    let streaming = opts.parallel_diff_output && format == DiffFormat::Markdown;
+    // A streamed row waits for its window's reads, so streaming reads
+    // one file per reader at a time to get the first row out early.
+    let window = num_jobs.max(1) * if streaming { 1 } else { BLOB_WINDOW_PER_JOB };
+    let mut blobs = Vec::new().into_iter();
+
+    let mut stream = if streaming {
+        Some(MarkdownStream::new(
+            std::io::stdout(),
+            &selectors,
+            &from_label,
+            &from_ref,
+            &to_ref,
+            commits.as_ref(),
+        )?)
+    } else {
+        None
+    };
+
     let mut diffs = Vec::new();
+    let mut violations = Vec::new();
     let mut analysis_failed = false;
-    for (cf, utf8_path, language) in &filtered {
+    for (index, (cf, utf8_path, language)) in filtered.iter().enumerate() {
+        if index % window == 0 {
+            let files = index..filtered.len().min(index + window);
+            blobs = vcs.read_blobs(&requests_for(files), num_jobs).into_iter();
+        }
         let is_deleted = cf.status == ChangeStatus::Deleted;
         let is_new = cf.status == ChangeStatus::Added;
-
-        let analyzer = match registry.analyzer_for(*language) {
-            Some(a) => a,
-            None => continue,
-        };
-
-        let mut analyze = |bytes: Vec<u8>, side: &str| -> Option<MetricSpace> {
-            let text = String::from_utf8(bytes).ok()?;
-            let source = SourceFile::new(utf8_path.clone(), *language, text);
-            let analysis = match analyzer.analyze(&source, &analysis_config) {
-                Ok(a) => a,
-                Err(err) => {
-                    log::error!("{} ({side}): analyzer failed: {err}", cf.path.display());
-                    analysis_failed = true;
-                    return None;
+        let base_blob = reads_base(cf).then(|| blobs.next().expect("one read per request"));
+        let head_blob = reads_head(cf).then(|| blobs.next().expect("one read per request"));
+
+        let analyzer = registry
+            .analyzer_for(*language)
+            .expect("files without an analyzer are filtered out");
+
+        let mut analyze =
+            |bytes: Vec<u8>, side: &str, warnings: &mut Warnings| -> Option<AnalyzedSide> {
+                let text = String::from_utf8(bytes).ok()?;
+                let refined = refine_language(&registry, &text, *language);
+                let (language, analyzer) = refined
+                    .as_ref()
+                    .map_or((*language, analyzer.as_ref()), |(l, a)| (*l, a.as_ref()));
+                let source = SourceFile::new(utf8_path.clone(), language, text);
+                let mut analysis = match analyzer.analyze(&source, &analysis_config) {
+                    Ok(a) => a,
+                    Err(err) => {
+                        log::error!("{} ({side}): analyzer failed: {err}", cf.path.display());
+                        analysis_failed = true;
+                        return None;
+                    }
+                };
+                for diag in &analysis.diagnostics {
+                    match diag.severity {
+                        DiagnosticSeverity::Warning => warnings.push(
+                            WarningCode::Diagnostic,
+                            Some(&cf.path),
+                            format!("({side}): {}: {}", diag.code, diag.message),
+                        ),
+                        DiagnosticSeverity::Error | DiagnosticSeverity::Fatal => log::error!(
+                            "{} ({side}): {}: {}",
+                            cf.path.display(),
+                            diag.code,
+                            diag.message
+                        ),
+                    }
                 }
+                crate::finalize_analysis(&mut analysis.root, &analysis_config);
+                let complete = !has_blocking_diagnostic(&analysis.diagnostics);
+                analysis_failed |= !complete;
+                Some(AnalyzedSide {
+                    root: analysis.root,
+                    text: source.text,
+                    complete,
+                })
             };
-            for diag in &analysis.diagnostics {
-                match diag.severity {
-                    DiagnosticSeverity::Warning => log::warn!(
-                        "{} ({side}): {}: {}",
-                        cf.path.display(),
-                        diag.code,
-                        diag.message
-                    ),
-                    DiagnosticSeverity::Error | DiagnosticSeverity::Fatal => log::error!(
-                        "{} ({side}): {}: {}",
-                        cf.path.display(),
-                        diag.code,
-                        diag.message
-                    ),
-                }
-            }
-            if has_blocking_diagnostic(&analysis.diagnostics) {
-                analysis_failed = true;
+
+        let recorded_baseline = baseline.as_ref().and_then(|b| b.space_for(&cf.path));
+        let baseline_side: Option<AnalyzedSide> = match base_blob {
+            Some(Ok(Some(bytes))) => analyze(bytes, "baseline", &mut warnings),
+            Some(Err(e)) => {
+                warnings.push(
+                    WarningCode::SkippedFile,
+                    Some(&cf.path),
+                    format!("skipping baseline: {e}"),
+                );
+                None
             }
-            Some(analysis.root)
+            Some(Ok(None)) | None => None,
         };
 
-        let baseline_space: Option<MetricSpace> = if is_new {
-            None
-        } else {
-            match mehen_git::read_blob(&repo, &from_ref, &cf.path) {
-                Ok(Some(bytes)) => analyze(bytes, "baseline"),
-                Ok(None) => None,
-                Err(e) => {
-                    log::warn!("Skipping baseline for {}: {e}", cf.path.display());
-                    None
-                }
+        let current_side: Option<AnalyzedSide> = match head_blob {
+            Some(Ok(Some(bytes))) => analyze(bytes, "current", &mut warnings),
+            Some(Err(e)) => {
+                warnings.push(
+                    WarningCode::SkippedFile,
+                    Some(&cf.path),
+                    format!("skipping current: {e}"),
+                );
+                None
             }
+            Some(Ok(None)) | None => None,
         };
 
-        let current_space: Option<MetricSpace> = if is_deleted {
-            None
+        // As in `analyze_diff`, a partial head analysis is not held to
+        // the thresholds.
+        if let Some(head) = current_side.as_ref().filter(|side| side.complete) {
+            let limits = thresholds_for(&thresholds, &path_thresholds, utf8_path);
+            violations.extend(threshold_violations(utf8_path, limits, &head.root));
+        }
+
+        let baseline_space = recorded_baseline
+            .as_ref()
+            .or(baseline_side.as_ref().map(|s| &s.root));
+        let current_space = current_side.as_ref().map(|s| &s.root);
+        // Against a baseline file, "new" means "not recorded in it".
+        let file_is_new = (is_new || baseline.is_some()) && baseline_space.is_none();
+        let metric_diffs = diff_metrics(
+            &selectors,
+            baseline_space,
+            current_space,
+            file_is_new,
+            is_deleted,
+        );
+
+        let functions = if opts.hunks_only && baseline.is_none() {
+            let hunks = line_hunks(
+                baseline_side.as_ref().map_or("", |s| s.text.as_str()),
+                current_side.as_ref().map_or("", |s| s.text.as_str()),
+            );
+            function_diffs(
+                &selectors,
+                baseline_side.as_ref(),
+                current_side.as_ref(),
+                Some(&hunks),
+            )
+        } else if opts.by_function && baseline.is_none() {
+            function_diffs(
+                &selectors,
+                baseline_side.as_ref(),
+                current_side.as_ref(),
+                None,
+            )
         } else {
-            match mehen_git::read_blob(&repo, &to_ref, &cf.path) {
-                Ok(Some(bytes)) => analyze(bytes, "current"),
-                Ok(None) => None,
-                Err(e) => {
-                    log::warn!("Skipping current for {}: {e}", cf.path.display());
-                    None
-                }
-            }
+            Vec::new()
         };
 
-        let metric_diffs: Vec<MetricDiff> = selectors
-            .iter()
-            .map(|sel| {
-                let baseline = baseline_space
-                    .as_ref()
-                    .map(|s| read_selector_metric(s, sel))
-                    .unwrap_or(0.0);
-                let current = current_space
-                    .as_ref()
-                    .map(|s| read_selector_metric(s, sel))
-                    .unwrap_or(0.0);
-                MetricDiff {
-                    name: sel.name,
-                    label: sel.label,
-                    current,
-                    baseline,
-                    delta: current - baseline,
-                    polarity: sel.polarity,
-                    is_new: is_new && baseline_space.is_none(),
-                    is_deleted,
-                }
-            })
-            .collect();
-
-        diffs.push(FileDiff {
+        let diff = FileDiff {
             path: cf.path.clone(),
             metrics: metric_diffs,
-            is_new: is_new && baseline_space.is_none(),
+            is_new: file_is_new,
             is_deleted,
-        });
+            functions,
+        };
+        if let Some(stream) = stream.as_mut()
+            && (opts.show_unchanged || !diff.all_unchanged())
+        {
+            stream.push(&diff)?;
+        }
+        diffs.push(diff);
     }
+    let streamed = stream.map(MarkdownStream::finish).transpose()?.is_some();
+
+    // Totals cover every changed file, including ones whose metrics
+    // did not move, so `--show-unchanged` does not change them.
+    let aggregate = opts
+        .aggregate
+        .then(|| aggregate_metrics(&selectors, &diffs));
 
     // 5. Filter unchanged
     if !opts.show_unchanged {
@@ -607,14 +911,18 @@ fn run_diff_inner(opts: DiffOpts) -> Result<(), Box<dyn std::error::Error>> {
             let base_metrics = if is_candidate_new {
                 None
             } else {
-                match mehen_git::read_blob(&repo, &from_ref, &cf.path) {
+                match vcs.read_blob(&from_ref, &cf.path) {
                     Ok(Some(bytes)) => Some(mehen_markdown::analyze_markdown(
                         &String::from_utf8_lossy(&bytes),
                         &cf.path,
                     )),
                     Ok(None) => None,
                     Err(e) => {
-                        log::warn!("Skipping baseline for {}: {e}", cf.path.display());
+                        warnings.push(
+                            WarningCode::SkippedFile,
+                            Some(&cf.path),
+                            format!("skipping baseline: {e}"),
+                        );
                         None
                     }
                 }
@@ -622,14 +930,18 @@ fn run_diff_inner(opts: DiffOpts) -> Result<(), Box<dyn std::error::Error>> {
             let head_metrics = if is_deleted {
                 None
             } else {
-                match mehen_git::read_blob(&repo, &to_ref, &cf.path) {
+                match vcs.read_blob(&to_ref, &cf.path) {
                     Ok(Some(bytes)) => Some(mehen_markdown::analyze_markdown(
                         &String::from_utf8_lossy(&bytes),
                         &cf.path,
                     )),
                     Ok(None) => None,
                     Err(e) => {
-                        log::warn!("Skipping current for {}: {e}", cf.path.display());
+                        warnings.push(
+                            WarningCode::SkippedFile,
+                            Some(&cf.path),
+                            format!("skipping current: {e}"),
+                        );
                         None
                     }
                 }
@@ -647,24 +959,47 @@ fn run_diff_inner(opts: DiffOpts) -> Result<(), Box<dyn std::error::Error>> {
     };
 
     // 7. Output
-    let format = opts.output_format.unwrap_or(DiffFormat::Markdown);
+    let markdown = (format == DiffFormat::Markdown || opts.github_summary).then(|| {
+        let mut out = match &aggregate {
+            // The table is already on stdout; only the doc section is left.
+            _ if streamed => String::new(),
+            Some(totals) => {
+                render_aggregate_markdown(totals, &from_label, &from_ref, &to_ref, commits.as_ref())
+            }
+            None => render_markdown(
+                &diffs,
+                &selectors,
+                &from_label,
+                &from_ref,
+                &to_ref,
+                commits.as_ref(),
+                opts.collapse_threshold,
+            ),
+        };
+        if !doc_files.is_empty() {
+            let mut ctx = DocRenderCtx::new(&from_label);
+            let repo_url = ci_ctx
+                .as_ref()
+                .and_then(|c| c.repository.as_ref())
+                .map(|r| format!("https://github.com/{r}"));
+            ctx.repo_url = repo_url.as_deref();
+            ctx.head_sha = Some(&to_ref);
+            if let Some(doc_md) = render_doc_section(&doc_files, &ctx) {
+                out.push('\n');
+                out.push_str(&doc_md);
+            }
+        }
+        out
+    });
+    if opts.github_summary
+        && let Some(markdown) = &markdown
+    {
+        ci::write_step_summary(markdown);
+    }
     match format {
         DiffFormat::Markdown => {
-            print_markdown(&diffs, &selectors, &from_label, &from_ref, &to_ref);
-            if !doc_files.is_empty() {
-                let mut ctx = DocRenderCtx::new(&from_label);
-                let repo_url = ci_ctx
-                    .as_ref()
-                    .and_then(|c| c.repository.as_ref())
-                    .map(|r| format!("https://github.com/{r}"));
-                ctx.repo_url = repo_url.as_deref();
-                ctx.head_sha = Some(&to_ref);
-                if let Some(doc_md) = render_doc_section(&doc_files, &ctx) {
-                    let mut stdout = std::io::stdout().lock();
-                    writeln!(stdout).ok();
-                    write!(stdout, "{doc_md}").ok();
-                }
-            }
+            let markdown = markdown.unwrap_or_default();
+            write!(std::io::stdout().lock(), "{markdown}").unwrap();
         }
         DiffFormat::Json => {
             let doc_ref: Option<&[DocDiffFile]> = if doc_files.is_empty() {
@@ -672,7 +1007,13 @@ fn run_diff_inner(opts: DiffOpts) -> Result<(), Box<dyn std::error::Error>> {
             } else {
                 Some(&doc_files)
             };
-            if let Err(e) = print_json(&diffs, doc_ref) {
+            if let Err(e) = print_json(
+                &diffs,
+                aggregate.as_deref(),
+                doc_ref,
+                commits.as_ref(),
+                warnings.as_slice(),
+            ) {
                 // Surface the error loudly — exit code 2 mirrors the
                 // --fail-on gate and is distinct from the generic exit 1
                 // that covers setup/IO errors in run_diff_inner.
@@ -680,6 +1021,34 @@ fn run_diff_inner(opts: DiffOpts) -> Result<(), Box<dyn std::error::Error>> {
                 std::process::exit(2);
             }
         }
+        DiffFormat::GitHubChecks => {
+            let annotations = github_check_annotations(&diffs);
+            if let Err(e) = print_json_value(&annotations) {
+                log::error!("diff: failed to emit JSON output: {e}");
+                std::process::exit(2);
+            }
+        }
+        DiffFormat::SonarGeneric => {
+            if let Err(e) = print_json_value(&sonar_generic_report(&diffs)) {
+                log::error!("diff: failed to emit JSON output: {e}");
+                std::process::exit(2);
+            }
+        }
+    }
+
+    // `.mehen.toml` thresholds, checked against each file's head side.
+    for violation in &violations {
+        let evaluation = &violation.evaluation;
+        log::error!(
+            "{}: {} is {}, limit {}",
+            violation.path,
+            evaluation.selector,
+            evaluation.actual,
+            evaluation.limit
+        );
+    }
+    if !violations.is_empty() {
+        std::process::exit(2);
     }
 
     // --fail-on check.
@@ -804,6 +1173,160 @@ fn evaluate_fail_on(flags: &[FailOn], docs: &[DocDiffFile]) -> Vec<String> {
     failures
 }
 
+/// One analyzed side of a changed file: the metric tree plus the text
+/// it was computed from (needed to fingerprint function bodies).
+struct AnalyzedSide {
+    root: MetricSpace,
+    text: String,
+    /// No blocking diagnostic: the metrics cover the whole file.
+    complete: bool,
+}
+
+/// Build one [`MetricDiff`] per selector. A missing side reads as `0.0`
+/// for every metric, so new/deleted entries show their full value as
+/// the delta.
+fn diff_metrics(
+    selectors: &[MetricSelector],
+    baseline: Option<&MetricSpace>,
+    current: Option<&MetricSpace>,
+    is_new: bool,
+    is_deleted: bool,
+) -> Vec<MetricDiff> {
+    selectors
+        .iter()
+        .map(|sel| {
+            let baseline = baseline
+                .map(|s| read_selector_metric(s, sel))
+                .unwrap_or(0.0);
+            let current = current.map(|s| read_selector_metric(s, sel)).unwrap_or(0.0);
+            MetricDiff {
+                name: sel.name,
+                label: sel.label.clone(),
+                current,
+                baseline,
+                delta: current - baseline,
+                polarity: sel.polarity,
+                is_new,
+                is_deleted,
+            }
+        })
+        .collect()
+}
+
+// ── Per-function diff (`--by-function`) ────────────────────────────────
+
+/// A function space located in one side of a diff, keyed by its
+/// qualified name and fingerprinted by the bytes of its span.
+struct FunctionSnapshot<'a> {
+    name: String,
+    space: &'a MetricSpace,
+    digest: u64,
+}
+
+/// Pair the function spaces of both sides by qualified name and keep
+/// only the ones that changed. A function counts as unchanged when its
+/// source text hashes identically *and* every selected metric matches,
+/// so moving a function without editing it (same text, same metrics)
+/// stays out of the report. Head order is preserved; functions that
+/// only exist in the baseline are appended as deleted rows.
+///
+/// With `hunks` (`--hunks-only`), a function is only considered when its
+/// span overlaps a changed line range on its own side; pairing still
+/// looks across every baseline function.
+fn function_diffs(
+    selectors: &[MetricSelector],
+    baseline: Option<&AnalyzedSide>,
+    current: Option<&AnalyzedSide>,
+    hunks: Option<&[Hunk]>,
+) -> Vec<FunctionDiff> {
+    let base_fns = baseline.map(collect_functions).unwrap_or_default();
+    let head_fns = current.map(collect_functions).unwrap_or_default();
+    let touched = |f: &FunctionSnapshot<'_>, side: Side| {
+        hunks.is_none_or(|h| touches(h, side, f.space.span.start_line, f.space.span.end_line))
+    };
+
+    let mut out = Vec::new();
+    for head in head_fns.iter().filter(|f| touched(f, Side::Head)) {
+        let base = base_fns.iter().find(|b| b.name == head.name);
+        let is_new = base.is_none();
+        let metrics = diff_metrics(
+            selectors,
+            base.map(|b| b.space),
+            Some(head.space),
+            is_new,
+            false,
+        );
+        let text_changed = base.is_none_or(|b| b.digest != head.digest);
+        if text_changed || metrics.iter().any(|m| m.delta != 0.0) {
+            out.push(FunctionDiff {
+                name: head.name.clone(),
+                metrics,
+                is_new,
+                is_deleted: false,
+                start_line: head.space.span.start_line,
+                end_line: head.space.span.end_line,
+            });
+        }
+    }
+    for base in base_fns.iter().filter(|f| touched(f, Side::Base)) {
+        if head_fns.iter().any(|h| h.name == base.name) {
+            continue;
+        }
+        out.push(FunctionDiff {
+            name: base.name.clone(),
+            metrics: diff_metrics(selectors, Some(base.space), None, false, true),
+            is_new: false,
+            is_deleted: true,
+            start_line: base.space.span.start_line,
+            end_line: base.space.span.end_line,
+        });
+    }
+    out
+}
+
+/// Flatten every function/closure space under `side.root` in source
+/// order. Names are qualified with their enclosing spaces (`Outer::f`);
+/// anonymous spaces read as `<anonymous>`, and repeated names get a
+/// `#N` suffix so overloads and same-named closures pair up by
+/// occurrence.
+fn collect_functions(side: &AnalyzedSide) -> Vec<FunctionSnapshot<'_>> {
+    let mut out = Vec::new();
+    // `scope[d]` holds the name of the enclosing space at depth `d + 1`;
+    // the unit root (depth 0) contributes no qualifier.
+    let mut scope: Vec<&str> = Vec::new();
+    side.root.visit(&mut |space, depth| {
+        if depth == 0 {
+            return;
+        }
+        scope.truncate(depth - 1);
+        scope.push(space.name.as_deref().unwrap_or("<anonymous>"));
+        if !matches!(space.kind, SpaceKind::Function | SpaceKind::Closure) {
+            return;
+        }
+        let bytes = side
+            .text
+            .as_bytes()
+            .get(space.span.start_byte as usize..space.span.end_byte as usize)
+            .unwrap_or_default();
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        out.push(FunctionSnapshot {
+            name: scope.join("::"),
+            space,
+            digest: hasher.finish(),
+        });
+    });
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for f in &mut out {
+        let n = seen.entry(f.name.clone()).or_insert(0);
+        *n += 1;
+        if *n > 1 {
+            f.name = format!("{}#{n}", f.name);
+        }
+    }
+    out
+}
+
 // ── Generated-file filtering ───────────────────────────────────────────
 
 struct GeneratedFilter<'repo> {
@@ -874,17 +1397,21 @@ fn resolve_refs(opts: &DiffOpts, ci_ctx: &Option<ci::CiContext>) -> (String, Str
         return (from, to);
     }
 
-    let from = opts.from.clone().unwrap_or_else(|| "main".to_string());
-    let to = opts.to.clone().unwrap_or_else(|| "HEAD".to_string());
+    let (default_from, default_to) = opts.vcs.default_refs();
+    let from = opts
+        .from
+        .clone()
+        .unwrap_or_else(|| default_from.to_string());
+    let to = opts.to.clone().unwrap_or_else(|| default_to.to_string());
     (from, to)
 }
 
 fn get_changed_files(
-    repo: &gix::Repository,
+    vcs: &dyn Vcs,
     from: &str,
     to: &str,
     ci_ctx: &Option<ci::CiContext>,
-) -> Result<Vec<mehen_git::ChangedFile>, GitError> {
+) -> Result<Vec<mehen_git::ChangedFile>, VcsError> {
     // For push events with changed_files from payload, use those
     // directly. The CI extractor folds per-commit `added` / `modified`
     // / `removed` into a final per-path `ChangeStatus` so the diff
@@ -897,7 +1424,7 @@ fn get_changed_files(
         return Ok(files.clone());
     }
 
-    mehen_git::changed_files(repo, from, to)
+    vcs.changed_files(from, to)
 }
 
 fn normalize_path_filters(paths: &[PathBuf]) -> Vec<PathBuf> {
@@ -933,53 +1460,240 @@ fn legacy_path_is_selected(path: &Path, paths: &[PathBuf]) -> bool {
 
 // ── Markdown output ────────────────────────────────────────────────────
 
-fn print_markdown(
-    diffs: &[FileDiff],
-    selectors: &[MetricSelector],
+/// `--aggregate`: sum each selector's baseline and current values over
+/// every file. Selector order matches each [`FileDiff::metrics`].
+///
+/// Maintainability index and ratios are not additive, so those are
+/// averaged instead: the baseline over the files that existed before,
+/// the current value over the files that exist after.
+fn aggregate_metrics(selectors: &[MetricSelector], diffs: &[FileDiff]) -> Vec<MetricDiff> {
+    selectors
+        .iter()
+        .enumerate()
+        .map(|(i, sel)| {
+            let values = diffs.iter().filter_map(|d| d.metrics.get(i));
+            let (baseline, current) = if is_additive(sel) {
+                values.fold((0.0, 0.0), |(b, c), m| (b + m.baseline, c + m.current))
+            } else {
+                let mean = |side: Vec<f64>| {
+                    if side.is_empty() {
+                        0.0
+                    } else {
+                        side.iter().sum::<f64>() / side.len() as f64
+                    }
+                };
+                let (before, after): (Vec<_>, Vec<_>) = values
+                    .map(|m| {
+                        (
+                            (!m.is_new).then_some(m.baseline),
+                            (!m.is_deleted).then_some(m.current),
+                        )
+                    })
+                    .unzip();
+                (
+                    mean(before.into_iter().flatten().collect()),
+                    mean(after.into_iter().flatten().collect()),
+                )
+            };
+            MetricDiff {
+                name: sel.name,
+                label: sel.label.clone(),
+                current,
+                baseline,
+                delta: current - baseline,
+                polarity: sel.polarity,
+                is_new: false,
+                is_deleted: false,
+            }
+        })
+        .collect()
+}
+
+/// Whether a file-level value of `sel` can be summed across files:
+/// counts and sizes can, the maintainability index and ratios cannot.
+fn is_additive(sel: &MetricSelector) -> bool {
+    !sel.name.starts_with("mi.") && sel.name != "halstead.operator_operand_ratio"
+}
+
+/// Commit ids `--from` and `--to` resolved to, recorded in the report
+/// so it stays auditable after branches move.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ResolvedCommits {
+    from: String,
+    to: String,
+}
+
+/// The `## Mehen Summary` heading, followed by the resolved commit ids
+/// when the backend reported them.
+fn summary_heading(from: &str, to: &str, commits: Option<&ResolvedCommits>) -> String {
+    let mut out =
+        format!("## [Mehen](https://github.com/ophidiarium/mehen) Summary (`{from}`..`{to}`)\n\n");
+    if let Some(commits) = commits {
+        out.push_str(&format!("Commits `{}`..`{}`\n\n", commits.from, commits.to));
+    }
+    out
+}
+
+fn render_aggregate_markdown(
+    totals: &[MetricDiff],
     from_label: &str,
     from: &str,
     to: &str,
-) {
+    commits: Option<&ResolvedCommits>,
+) -> String {
     let mut out = String::new();
-
-    // Source-code anchor (§39.1: sibling of the docs anchor).
     out.push_str("<!-- mehen-metrics -->\n");
-    out.push_str(&format!(
-        "## [Mehen](https://github.com/ophidiarium/mehen) Summary (`{from}`..`{to}`)\n\n"
-    ));
+    out.push_str(&summary_heading(from, to, commits));
+    out.push_str(&format!("| Metric | {from_label} | Current | Delta |\n"));
+    out.push_str("|---|---:|---:|---:|\n");
+    for m in totals {
+        let sign = if m.delta > 0.0 { "+" } else { "" };
+        out.push_str(&format!(
+            "| {} | {} | {} | {sign}{} {} |\n",
+            m.label,
+            format_f64(m.baseline),
+            format_f64(m.current),
+            format_f64(m.delta),
+            trend_emoji(m.delta, m.polarity)
+        ));
+    }
+    out
+}
+
+fn render_markdown(
+    diffs: &[FileDiff],
+    selectors: &[MetricSelector],
+    from_label: &str,
+    from: &str,
+    to: &str,
+    commits: Option<&ResolvedCommits>,
+    collapse_threshold: Option<usize>,
+) -> String {
+    let mut out = markdown_preamble(from, to, commits);
 
     if diffs.is_empty() {
-        out.push_str("No metric changes detected.\n");
-        write!(std::io::stdout().lock(), "{out}").unwrap();
-        return;
+        out.push_str(NO_CHANGES);
+        return out;
+    }
+
+    let collapsed = collapse_threshold.is_some_and(|n| diffs.len() > n);
+    if collapsed {
+        let new = diffs.iter().filter(|d| d.is_new).count();
+        let deleted = diffs.iter().filter(|d| d.is_deleted).count();
+        out.push_str(&format!(
+            "{} files changed ({new} new, {deleted} deleted).\n\n",
+            diffs.len()
+        ));
+        // GitHub only renders a table inside <details> after a blank line.
+        out.push_str("<details>\n<summary>Per-file metrics</summary>\n\n");
+    }
+
+    out.push_str(&markdown_table_header(selectors));
+    for diff in diffs {
+        out.push_str(&markdown_file_rows(diff, from_label));
     }
 
-    // Header
-    out.push_str("| File |");
+    if collapsed {
+        out.push_str("\n</details>\n");
+    }
+    out
+}
+
+const NO_CHANGES: &str = "No metric changes detected.\n";
+
+/// Source-code anchor (§39.1: sibling of the docs anchor) and heading.
+fn markdown_preamble(from: &str, to: &str, commits: Option<&ResolvedCommits>) -> String {
+    let mut out = String::from("<!-- mehen-metrics -->\n");
+    out.push_str(&summary_heading(from, to, commits));
+    out
+}
+
+/// Header and separator rows of the per-file table.
+fn markdown_table_header(selectors: &[MetricSelector]) -> String {
+    let mut out = String::from("| File |");
     for sel in selectors {
         out.push_str(&format!(" {} |", sel.label));
     }
     out.push('\n');
-
-    // Separator
     out.push_str("|---|");
     for _ in selectors {
         out.push_str("---:|");
     }
     out.push('\n');
+    out
+}
 
-    // Rows
-    for diff in diffs {
-        out.push_str(&format!("| {} |", diff.path.display()));
-        for md in &diff.metrics {
+/// One file's row, followed by a `↳` row per changed function.
+fn markdown_file_rows(diff: &FileDiff, from_label: &str) -> String {
+    let mut out = format!("| {} |", diff.path.display());
+    for md in &diff.metrics {
+        out.push(' ');
+        out.push_str(&format_metric_cell(md, from_label));
+        out.push_str(" |");
+    }
+    out.push('\n');
+    for func in &diff.functions {
+        out.push_str(&format!("| \u{21B3} `{}` |", func.name)); // ↳
+        for md in &func.metrics {
             out.push(' ');
             out.push_str(&format_metric_cell(md, from_label));
             out.push_str(" |");
         }
         out.push('\n');
     }
+    out
+}
 
-    write!(std::io::stdout().lock(), "{out}").unwrap();
+/// `render_markdown` without `--collapse-threshold`, written one file at
+/// a time for `--parallel-diff-output`: the heading goes out up front,
+/// the table header with the first row, and each row is flushed as soon
+/// as it is pushed. The text is the same as `render_markdown` for the
+/// same rows in the same order, but rows are written as they come
+/// instead of sorted by [`FileDiff::sort_key`].
+struct MarkdownStream<'a, W: Write> {
+    out: W,
+    selectors: &'a [MetricSelector],
+    from_label: &'a str,
+    rows: usize,
+}
+
+impl<'a, W: Write> MarkdownStream<'a, W> {
+    fn new(
+        mut out: W,
+        selectors: &'a [MetricSelector],
+        from_label: &'a str,
+        from: &str,
+        to: &str,
+        commits: Option<&ResolvedCommits>,
+    ) -> std::io::Result<Self> {
+        out.write_all(markdown_preamble(from, to, commits).as_bytes())?;
+        out.flush()?;
+        Ok(Self {
+            out,
+            selectors,
+            from_label,
+            rows: 0,
+        })
+    }
+
+    fn push(&mut self, diff: &FileDiff) -> std::io::Result<()> {
+        if self.rows == 0 {
+            self.out
+                .write_all(markdown_table_header(self.selectors).as_bytes())?;
+        }
+        self.rows += 1;
+        self.out
+            .write_all(markdown_file_rows(diff, self.from_label).as_bytes())?;
+        self.out.flush()
+    }
+
+    fn finish(mut self) -> std::io::Result<W> {
+        if self.rows == 0 {
+            self.out.write_all(NO_CHANGES.as_bytes())?;
+        }
+        self.out.flush()?;
+        Ok(self.out)
+    }
 }
 
 fn format_metric_cell(md: &MetricDiff, from: &str) -> String {
@@ -1023,6 +1737,7 @@ fn trend_emoji(delta: f64, polarity: SelectorPolarity) -> &'static str {
                 "\u{1F534}" // 🔴
             }
         }
+        SelectorPolarity::Neutral => "\u{26AA}", // ⚪
     }
 }
 
@@ -1036,32 +1751,188 @@ fn format_f64(v: f64) -> String {
 
 // ── JSON output ────────────────────────────────────────────────────────
 
-/// Emit a single JSON document with a `source_code` key and an optional
-/// `markdown` key. Downstream consumers (`jq`, `serde_json`) see one top-level
-/// object, not two concatenated arrays.
+/// Emit a single JSON document with a `source_code` key (`aggregate`
+/// under `--aggregate`) and an optional `markdown` key. Downstream
+/// consumers (`jq`, `serde_json`) see one top-level object, not two
+/// concatenated arrays. `from_sha` / `to_sha` carry the
+/// resolved commit ids when the backend reported them.
 ///
 /// Serialization errors bubble up as `Err` so `run_diff_inner` exits
 /// non-zero instead of silently writing an empty `""` to stdout.
 fn print_json(
     diffs: &[FileDiff],
+    aggregate: Option<&[MetricDiff]>,
     docs: Option<&[DocDiffFile]>,
+    commits: Option<&ResolvedCommits>,
+    warnings: &[Warning],
 ) -> Result<(), Box<dyn std::error::Error>> {
     let mut payload = serde_json::Map::new();
-    payload.insert("source_code".to_string(), serde_json::to_value(diffs)?);
+    if let Some(commits) = commits {
+        payload.insert("from_sha".to_string(), commits.from.clone().into());
+        payload.insert("to_sha".to_string(), commits.to.clone().into());
+    }
+    match aggregate {
+        Some(totals) => payload.insert("aggregate".to_string(), serde_json::to_value(totals)?),
+        None => payload.insert("source_code".to_string(), serde_json::to_value(diffs)?),
+    };
     if let Some(docs) = docs {
         payload.insert(
             "markdown".to_string(),
             serde_json::Value::Array(doc_json_payload(docs)),
         );
     }
+    if !warnings.is_empty() {
+        payload.insert("warnings".to_string(), serde_json::to_value(warnings)?);
+    }
     let json = serde_json::to_string_pretty(&serde_json::Value::Object(payload))?;
     writeln!(std::io::stdout().lock(), "{json}")?;
     Ok(())
 }
 
+fn print_json_value<T: serde::Serialize>(value: &T) -> Result<(), Box<dyn std::error::Error>> {
+    let json = serde_json::to_string_pretty(value)?;
+    writeln!(std::io::stdout().lock(), "{json}")?;
+    Ok(())
+}
+
+// ── GitHub Checks output ───────────────────────────────────────────────
+
+/// One entry of a check run's `output.annotations`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+struct CheckAnnotation {
+    path: String,
+    start_line: u32,
+    end_line: u32,
+    annotation_level: &'static str,
+    message: String,
+}
+
+/// Whether `m` moved in the wrong direction for its polarity.
+fn regressed(m: &MetricDiff) -> bool {
+    match m.polarity {
+        SelectorPolarity::LowerIsBetter => m.delta > 0.0,
+        SelectorPolarity::HigherIsBetter => m.delta < 0.0,
+        SelectorPolarity::Neutral => false,
+    }
+}
+
+/// `Cyclomatic 8 (was 5)` for every regressed metric, comma-separated.
+fn regression_message(metrics: &[MetricDiff]) -> String {
+    metrics
+        .iter()
+        .filter(|m| regressed(m))
+        .map(|m| {
+            format!(
+                "{} {} (was {})",
+                m.label,
+                format_f64(m.current),
+                format_f64(m.baseline)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A `warning` annotation per regressed function at its lines. A file
+/// that regressed without any regressed function rows (no
+/// `--by-function`, or only new functions) gets one at line 1. New and
+/// deleted files and functions are not regressions.
+fn github_check_annotations(diffs: &[FileDiff]) -> Vec<CheckAnnotation> {
+    let mut out = Vec::new();
+    for file in diffs.iter().filter(|f| !f.is_new && !f.is_deleted) {
+        let path = file.path.to_string_lossy().replace('\\', "/");
+        let before = out.len();
+        for function in file
+            .functions
+            .iter()
+            .filter(|f| !f.is_new && !f.is_deleted && f.metrics.iter().any(regressed))
+        {
+            out.push(CheckAnnotation {
+                path: path.clone(),
+                start_line: function.start_line,
+                end_line: function.end_line,
+                annotation_level: "warning",
+                message: format!(
+                    "{}: {}",
+                    function.name,
+                    regression_message(&function.metrics)
+                ),
+            });
+        }
+        if out.len() == before && file.metrics.iter().any(regressed) {
+            out.push(CheckAnnotation {
+                path,
+                start_line: 1,
+                end_line: 1,
+                annotation_level: "warning",
+                message: regression_message(&file.metrics),
+            });
+        }
+    }
+    out
+}
+
+// ── SonarQube generic issues output ────────────────────────────────────
+
+/// Document for SonarQube's `sonar.externalIssuesReportPaths`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+struct SonarGenericReport {
+    issues: Vec<SonarIssue>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SonarIssue {
+    engine_id: &'static str,
+    rule_id: &'static str,
+    severity: &'static str,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    primary_location: SonarLocation,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SonarLocation {
+    message: String,
+    file_path: String,
+    text_range: SonarTextRange,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SonarTextRange {
+    start_line: u32,
+    end_line: u32,
+}
+
+/// One `MAJOR` code smell per regression the GitHub Checks output
+/// would annotate, at the same lines and with the same message.
+fn sonar_generic_report(diffs: &[FileDiff]) -> SonarGenericReport {
+    let issues = github_check_annotations(diffs)
+        .into_iter()
+        .map(|annotation| SonarIssue {
+            engine_id: "mehen",
+            rule_id: "complexity-regression",
+            severity: "MAJOR",
+            kind: "CODE_SMELL",
+            primary_location: SonarLocation {
+                message: annotation.message,
+                file_path: annotation.path,
+                text_range: SonarTextRange {
+                    start_line: annotation.start_line,
+                    end_line: annotation.end_line,
+                },
+            },
+        })
+        .collect();
+    SonarGenericReport { issues }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::metric_selector::parse_metric_selectors;
 
     #[test]
     fn empty_diagnostics_are_not_blocking() {
@@ -1102,7 +1973,8 @@ mod tests {
     }
 
     use mehen_core::{
-        AnalysisBackend, Language, MetricKey, MetricSpace, Polarity, SourceSpan, SpaceId, SpaceKind,
+        AnalysisBackend, Language, MetricKey, MetricSpace, PathThresholds, Polarity, SourceSpan,
+        SpaceId, SpaceKind,
     };
 
     fn analysis_with_metric(key: &str, value: f64) -> LanguageAnalysis {
@@ -1197,6 +2069,120 @@ mod tests {
         assert_eq!(report.analysis_errors[0].diagnostics.len(), 2);
     }
 
+    /// In-memory [`Vcs`] so `analyze_diff_with` runs without a real
+    /// repository: `blobs` maps `(rev, path)` to file contents.
+    struct MockVcs {
+        changed: Vec<mehen_git::ChangedFile>,
+        blobs: HashMap<(&'static str, &'static str), &'static str>,
+    }
+
+    impl Vcs for MockVcs {
+        fn changed_files(
+            &self,
+            _from: &str,
+            _to: &str,
+        ) -> Result<Vec<mehen_git::ChangedFile>, VcsError> {
+            Ok(self.changed.clone())
+        }
+
+        fn read_blob(&self, rev: &str, path: &Path) -> Result<Option<Vec<u8>>, VcsError> {
+            let path = path.to_str().unwrap();
+            Ok(self
+                .blobs
+                .iter()
+                .find(|((r, p), _)| *r == rev && *p == path)
+                .map(|(_, text)| text.as_bytes().to_vec()))
+        }
+    }
+
+    #[test]
+    fn analyze_diff_with_reads_both_sides_through_the_vcs_trait() {
+        let changed = |path: &str, status| mehen_git::ChangedFile {
+            path: PathBuf::from(path),
+            status,
+        };
+        let vcs = MockVcs {
+            changed: vec![
+                changed("src/a.rs", ChangeStatus::Modified),
+                changed("src/gone.rs", ChangeStatus::Deleted),
+                changed("notes.txt", ChangeStatus::Added),
+            ],
+            blobs: HashMap::from([
+                (("base", "src/a.rs"), "fn f() {}\n"),
+                (
+                    ("head", "src/a.rs"),
+                    "fn f(a: bool, b: bool) {\n    if a && b {}\n}\n",
+                ),
+                (("base", "src/gone.rs"), "fn g() {}\n"),
+            ]),
+        };
+        let input = DiffInput {
+            from: "base".to_string(),
+            to: "head".to_string(),
+            paths: Vec::new(),
+            thresholds: vec![Threshold::new(
+                "cyclomatic.sum".parse().unwrap(),
+                2.0,
+                Polarity::HigherIsWorse,
+            )],
+            path_thresholds: Vec::new(),
+            config: AnalysisConfig::default(),
+        };
+        let report = analyze_diff_with(&vcs, input).unwrap();
+        let files: Vec<&str> = report.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(files, vec!["src/a.rs", "src/gone.rs"]);
+        assert_eq!(report.threshold_violations.len(), 1);
+        assert_eq!(report.threshold_violations[0].path, "src/a.rs");
+    }
+
+    #[test]
+    fn most_specific_path_prefix_sets_the_thresholds() {
+        let limit = |value| {
+            vec![Threshold::new(
+                "cyclomatic.sum".parse().unwrap(),
+                value,
+                Polarity::HigherIsWorse,
+            )]
+        };
+        let input = DiffInput {
+            from: "base".to_string(),
+            to: "head".to_string(),
+            paths: Vec::new(),
+            thresholds: limit(20.0),
+            path_thresholds: vec![
+                PathThresholds {
+                    prefix: Utf8PathBuf::from("./core"),
+                    thresholds: limit(10.0),
+                },
+                PathThresholds {
+                    prefix: Utf8PathBuf::from("core/parser"),
+                    thresholds: limit(5.0),
+                },
+            ],
+            config: AnalysisConfig::default(),
+        };
+        let thresholds_for =
+            |path: &Utf8Path| thresholds_for(&input.thresholds, &input.path_thresholds, path);
+        let limit_for = |path: &str| thresholds_for(Utf8Path::new(path))[0].value;
+        assert_eq!(limit_for("core/parser/lex.rs"), 5.0);
+        assert_eq!(limit_for("core/eval.rs"), 10.0);
+        assert_eq!(limit_for("core2/eval.rs"), 20.0);
+        assert_eq!(limit_for("experimental/try.rs"), 20.0);
+
+        let analysis = analysis_with_metric("cyclomatic.sum", 12.0);
+        let mut report = empty_report();
+        for path in ["core/eval.rs", "experimental/try.rs"] {
+            let path = Utf8PathBuf::from(path);
+            evaluate_thresholds(&mut report, &path, thresholds_for(&path), &analysis);
+        }
+        let violated: Vec<&str> = report
+            .threshold_violations
+            .iter()
+            .map(|v| v.path.as_str())
+            .collect();
+        assert_eq!(violated, vec!["core/eval.rs"]);
+    }
+
     #[test]
     fn higher_is_worse_threshold_above_limit_violates() {
         let analysis = analysis_with_metric("cognitive.sum", 42.0);
@@ -1475,6 +2461,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_neutral_metric_never_improves_or_regresses() {
+        assert_eq!(trend_emoji(1.0, SelectorPolarity::Neutral), "\u{26AA}");
+        assert_eq!(trend_emoji(-1.0, SelectorPolarity::Neutral), "\u{26AA}");
+        let m = MetricDiff {
+            name: "halstead.operator_operand_ratio",
+            label: "Op/Operand".into(),
+            current: 2.0,
+            baseline: 1.0,
+            delta: 1.0,
+            polarity: SelectorPolarity::Neutral,
+            is_new: false,
+            is_deleted: false,
+        };
+        assert!(!regressed(&m));
+    }
+
     #[test]
     fn test_format_f64_integer() {
         assert_eq!(format_f64(42.0), "42");
@@ -1491,7 +2494,7 @@ mod tests {
     fn test_format_metric_cell_new() {
         let md = MetricDiff {
             name: "cyclomatic",
-            label: "Cyclomatic",
+            label: "Cyclomatic".into(),
             current: 5.0,
             baseline: 0.0,
             delta: 5.0,
@@ -1506,7 +2509,7 @@ mod tests {
     fn test_format_metric_cell_unchanged() {
         let md = MetricDiff {
             name: "cyclomatic",
-            label: "Cyclomatic",
+            label: "Cyclomatic".into(),
             current: 5.0,
             baseline: 5.0,
             delta: 0.0,
@@ -1521,7 +2524,7 @@ mod tests {
     fn test_format_metric_cell_increase_lower_is_better() {
         let md = MetricDiff {
             name: "cyclomatic",
-            label: "Cyclomatic",
+            label: "Cyclomatic".into(),
             current: 12.0,
             baseline: 8.0,
             delta: 4.0,
@@ -1536,7 +2539,7 @@ mod tests {
     fn test_format_metric_cell_deleted() {
         let md = MetricDiff {
             name: "cyclomatic",
-            label: "Cyclomatic",
+            label: "Cyclomatic".into(),
             current: 0.0,
             baseline: 10.0,
             delta: -10.0,
@@ -1553,7 +2556,7 @@ mod tests {
             path: PathBuf::from("foo.rs"),
             metrics: vec![MetricDiff {
                 name: "cyclomatic",
-                label: "Cyclomatic",
+                label: "Cyclomatic".into(),
                 current: 5.0,
                 baseline: 5.0,
                 delta: 0.0,
@@ -1563,21 +2566,322 @@ mod tests {
             }],
             is_new: false,
             is_deleted: false,
+            functions: Vec::new(),
         };
         assert!(diff.all_unchanged());
     }
 
+    fn changed_file(path: &str) -> FileDiff {
+        FileDiff {
+            path: PathBuf::from(path),
+            metrics: vec![MetricDiff {
+                name: "cyclomatic",
+                label: "Cyclomatic".into(),
+                current: 6.0,
+                baseline: 5.0,
+                delta: 1.0,
+                polarity: SelectorPolarity::LowerIsBetter,
+                is_new: false,
+                is_deleted: false,
+            }],
+            is_new: false,
+            is_deleted: false,
+            functions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn github_checks_warns_at_regressed_function() {
+        let mut file = changed_file("src/a.rs");
+        let mut improved = file.metrics.clone();
+        improved[0].current = 4.0;
+        improved[0].delta = -1.0;
+        file.functions = vec![
+            FunctionDiff {
+                name: "worse".to_string(),
+                metrics: file.metrics.clone(),
+                is_new: false,
+                is_deleted: false,
+                start_line: 10,
+                end_line: 14,
+            },
+            FunctionDiff {
+                name: "better".to_string(),
+                metrics: improved,
+                is_new: false,
+                is_deleted: false,
+                start_line: 20,
+                end_line: 22,
+            },
+        ];
+        let annotations = github_check_annotations(&[file, changed_file("src/b.rs")]);
+        assert_eq!(
+            annotations,
+            vec![
+                CheckAnnotation {
+                    path: "src/a.rs".to_string(),
+                    start_line: 10,
+                    end_line: 14,
+                    annotation_level: "warning",
+                    message: "worse: Cyclomatic 6 (was 5)".to_string(),
+                },
+                CheckAnnotation {
+                    path: "src/b.rs".to_string(),
+                    start_line: 1,
+                    end_line: 1,
+                    annotation_level: "warning",
+                    message: "Cyclomatic 6 (was 5)".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn sonarqube_reports_one_issue_per_regressed_function() {
+        let mut file = changed_file("src/a.rs");
+        let function = |name: &str, start_line, end_line| FunctionDiff {
+            name: name.to_string(),
+            metrics: file.metrics.clone(),
+            is_new: false,
+            is_deleted: false,
+            start_line,
+            end_line,
+        };
+        let mut added = function("added", 30, 31);
+        added.is_new = true;
+        file.functions = vec![function("parse", 3, 9), function("lex", 12, 20), added];
+
+        let report = serde_json::to_value(sonar_generic_report(&[file])).unwrap();
+        let issues = report["issues"].as_array().unwrap();
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0]["engineId"], "mehen");
+        assert_eq!(issues[0]["type"], "CODE_SMELL");
+        assert_eq!(
+            issues[0]["primaryLocation"],
+            serde_json::json!({
+                "message": "parse: Cyclomatic 6 (was 5)",
+                "filePath": "src/a.rs",
+                "textRange": { "startLine": 3, "endLine": 9 },
+            })
+        );
+        assert_eq!(issues[1]["primaryLocation"]["textRange"]["startLine"], 12);
+    }
+
+    #[test]
+    fn aggregate_delta_is_the_sum_of_file_deltas() {
+        let selectors =
+            parse_metric_selectors(&["cyclomatic".to_string(), "+loc.lloc".to_string()]);
+        let file = |path: &str, values: [(f64, f64); 2], is_new: bool| FileDiff {
+            path: PathBuf::from(path),
+            metrics: selectors
+                .iter()
+                .zip(values)
+                .map(|(sel, (baseline, current))| MetricDiff {
+                    name: sel.name,
+                    label: sel.label.clone(),
+                    current,
+                    baseline,
+                    delta: current - baseline,
+                    polarity: sel.polarity,
+                    is_new,
+                    is_deleted: false,
+                })
+                .collect(),
+            is_new,
+            is_deleted: false,
+            functions: Vec::new(),
+        };
+        let diffs = vec![
+            file("a.rs", [(5.0, 7.0), (40.0, 44.0)], false),
+            file("b.rs", [(3.0, 1.0), (20.0, 12.0)], false),
+            file("c.rs", [(0.0, 4.0), (0.0, 30.0)], true),
+        ];
+        let totals = aggregate_metrics(&selectors, &diffs);
+        for (i, total) in totals.iter().enumerate() {
+            let summed: f64 = diffs.iter().map(|d| d.metrics[i].delta).sum();
+            assert_eq!(total.delta, summed, "{}", total.name);
+        }
+        assert_eq!((totals[0].baseline, totals[0].current), (8.0, 12.0));
+        assert_eq!((totals[1].baseline, totals[1].current), (60.0, 86.0));
+
+        let md = render_aggregate_markdown(&totals, "v1.0", "abc", "def", None);
+        assert!(
+            md.contains("| Cyclomatic | 8 | 12 | +4 \u{1F534} |"),
+            "{md}"
+        );
+        assert!(md.contains("| LLOC | 60 | 86 | +26 \u{1F7E2} |"), "{md}");
+    }
+
+    #[test]
+    fn aggregate_averages_maintainability_index() {
+        let selectors = parse_metric_selectors(&["mi.visual_studio".to_string()]);
+        let file = |baseline: f64, current: f64, is_new: bool, is_deleted: bool| FileDiff {
+            path: PathBuf::from("a.rs"),
+            metrics: vec![MetricDiff {
+                name: selectors[0].name,
+                label: selectors[0].label.clone(),
+                current,
+                baseline,
+                delta: current - baseline,
+                polarity: selectors[0].polarity,
+                is_new,
+                is_deleted,
+            }],
+            is_new,
+            is_deleted,
+            functions: Vec::new(),
+        };
+        let diffs = vec![
+            file(60.0, 50.0, false, false),
+            file(40.0, 0.0, false, true),
+            file(0.0, 80.0, true, false),
+        ];
+        let totals = aggregate_metrics(&selectors, &diffs);
+        assert_eq!((totals[0].baseline, totals[0].current), (50.0, 65.0));
+        assert_eq!(totals[0].delta, 15.0);
+    }
+
+    #[test]
+    fn test_markdown_collapses_above_threshold() {
+        let selectors = parse_metric_selectors(&["cyclomatic".to_string()]);
+        let diffs = vec![
+            changed_file("a.rs"),
+            changed_file("b.rs"),
+            changed_file("c.rs"),
+        ];
+
+        let open = render_markdown(&diffs, &selectors, "main", "abc", "def", None, Some(3));
+        assert!(!open.contains("<details>"), "{open}");
+
+        let folded = render_markdown(&diffs, &selectors, "main", "abc", "def", None, Some(2));
+        assert!(
+            folded.contains("3 files changed (0 new, 0 deleted)."),
+            "{folded}"
+        );
+        assert!(folded.contains("<details>"), "{folded}");
+        assert!(folded.trim_end().ends_with("</details>"), "{folded}");
+        assert!(folded.contains("| c.rs | 6 (main: 5)"), "{folded}");
+    }
+
+    #[test]
+    fn metric_alias_replaces_markdown_header_label() {
+        let project = ProjectConfig::parse("[metric-aliases]\ncyclomatic = \"McCabe\"\n").unwrap();
+        let mut selectors =
+            parse_metric_selectors(&["cyclomatic".to_string(), "loc.lloc".to_string()]);
+        apply_label_aliases(&mut selectors, &project.metric_aliases);
+        let md = render_markdown(
+            &[changed_file("a.rs")],
+            &selectors,
+            "main",
+            "abc",
+            "def",
+            None,
+            None,
+        );
+        assert!(md.contains("| File | McCabe | LLOC |\n"), "{md}");
+    }
+
+    #[cfg(feature = "lang-rust")]
+    fn analyzed_rust(text: &str) -> AnalyzedSide {
+        let analyzer = AnalyzerRegistry::default_set()
+            .analyzer_for(Language::Rust)
+            .expect("Rust analyzer registered");
+        let source = SourceFile::new("lib.rs".into(), Language::Rust, text.to_string());
+        let analysis = analyzer
+            .analyze(&source, &AnalysisConfig::default())
+            .unwrap();
+        AnalyzedSide {
+            root: analysis.root,
+            text: source.text,
+            complete: true,
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "lang-rust")]
+    fn by_function_reports_only_changed_functions() {
+        let base = analyzed_rust(
+            "fn keep_a() -> i32 {\n    1\n}\n\n\
+             fn edited(x: i32) -> i32 {\n    x\n}\n\n\
+             fn keep_b() -> i32 {\n    2\n}\n",
+        );
+        let head = analyzed_rust(
+            "fn keep_a() -> i32 {\n    1\n}\n\n\
+             fn edited(x: i32) -> i32 {\n    if x > 0 { x } else { -x }\n}\n\n\
+             fn keep_b() -> i32 {\n    2\n}\n",
+        );
+        let selectors = parse_metric_selectors(&[]);
+        let functions = function_diffs(&selectors, Some(&base), Some(&head), None);
+        assert_eq!(functions.len(), 1, "got {functions:?}");
+        assert_eq!(functions[0].name, "edited");
+        assert!(!functions[0].is_new && !functions[0].is_deleted);
+        let cyclomatic = &functions[0].metrics[0];
+        assert_eq!(cyclomatic.name, "cyclomatic");
+        assert_eq!(cyclomatic.delta, 1.0);
+    }
+
+    #[test]
+    #[cfg(feature = "lang-rust")]
+    fn by_function_reports_added_and_removed_functions() {
+        let base = analyzed_rust("fn old() {}\nfn same() {}\n");
+        let head = analyzed_rust("fn same() {}\nfn fresh() {}\n");
+        let selectors = parse_metric_selectors(&[]);
+        let functions = function_diffs(&selectors, Some(&base), Some(&head), None);
+        let summary: Vec<(&str, bool, bool)> = functions
+            .iter()
+            .map(|f| (f.name.as_str(), f.is_new, f.is_deleted))
+            .collect();
+        assert_eq!(summary, vec![("fresh", true, false), ("old", false, true)]);
+    }
+
+    #[test]
+    #[cfg(feature = "lang-rust")]
+    fn hunks_only_reports_only_the_touched_function() {
+        let base_text = "fn keep_a() -> i32 {\n    1\n}\n\n\
+                         fn edited(x: i32) -> i32 {\n    x\n}\n\n\
+                         fn keep_b() -> i32 {\n    2\n}\n";
+        let head_text = "fn keep_a() -> i32 {\n    1\n}\n\n\
+                         fn edited(x: i32) -> i32 {\n    x + 1\n}\n\n\
+                         fn keep_b() -> i32 {\n    2\n}\n";
+        let base = analyzed_rust(base_text);
+        let head = analyzed_rust(head_text);
+        let hunks = line_hunks(base_text, head_text);
+        assert_eq!(
+            hunks,
+            vec![Hunk {
+                base: 6..7,
+                head: 6..7,
+            }]
+        );
+        let selectors = parse_metric_selectors(&[]);
+        let functions = function_diffs(&selectors, Some(&base), Some(&head), Some(&hunks));
+        let names: Vec<&str> = functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["edited"]);
+    }
+
     #[test]
     fn test_resolve_refs_explicit() {
         let opts = DiffOpts {
             from: Some("abc".to_string()),
             to: Some("def".to_string()),
+            vcs: VcsKind::Git,
+            num_jobs: None,
             metrics: vec![],
             paths: vec![],
             include: vec![],
             exclude: vec![],
             output_format: None,
             show_unchanged: false,
+            by_function: false,
+            hunks_only: false,
+            aggregate: false,
+            exclude_tests: false,
+            ignore_new: false,
+            baseline: None,
+            baseline_url: None,
+            collapse_threshold: None,
+            parallel_diff_output: false,
+            github_summary: false,
             ignore_generated: true,
             fail_on: vec![],
         };
@@ -1591,12 +2895,24 @@ mod tests {
         let opts = DiffOpts {
             from: None,
             to: None,
+            vcs: VcsKind::Git,
+            num_jobs: None,
             metrics: vec![],
             paths: vec![],
             include: vec![],
             exclude: vec![],
             output_format: None,
             show_unchanged: false,
+            by_function: false,
+            hunks_only: false,
+            aggregate: false,
+            exclude_tests: false,
+            ignore_new: false,
+            baseline: None,
+            baseline_url: None,
+            collapse_threshold: None,
+            parallel_diff_output: false,
+            github_summary: false,
             ignore_generated: true,
             fail_on: vec![],
         };
@@ -1605,6 +2921,37 @@ mod tests {
         assert_eq!(to, "HEAD");
     }
 
+    #[test]
+    fn test_resolve_refs_no_ci_hg() {
+        let opts = DiffOpts {
+            from: None,
+            to: None,
+            vcs: VcsKind::Hg,
+            num_jobs: None,
+            metrics: vec![],
+            paths: vec![],
+            include: vec![],
+            exclude: vec![],
+            output_format: None,
+            show_unchanged: false,
+            by_function: false,
+            hunks_only: false,
+            aggregate: false,
+            exclude_tests: false,
+            ignore_new: false,
+            baseline: None,
+            baseline_url: None,
+            collapse_threshold: None,
+            parallel_diff_output: false,
+            github_summary: false,
+            ignore_generated: true,
+            fail_on: vec![],
+        };
+        let (from, to) = resolve_refs(&opts, &None);
+        assert_eq!(from, "default");
+        assert_eq!(to, ".");
+    }
+
     #[test]
     fn test_resolve_refs_github_pr() {
         let ctx = ci::CiContext {
@@ -1619,12 +2966,24 @@ mod tests {
         let opts = DiffOpts {
             from: None,
             to: None,
+            vcs: VcsKind::Git,
+            num_jobs: None,
             metrics: vec![],
             paths: vec![],
             include: vec![],
             exclude: vec![],
             output_format: None,
             show_unchanged: false,
+            by_function: false,
+            hunks_only: false,
+            aggregate: false,
+            exclude_tests: false,
+            ignore_new: false,
+            baseline: None,
+            baseline_url: None,
+            collapse_threshold: None,
+            parallel_diff_output: false,
+            github_summary: false,
             ignore_generated: true,
             fail_on: vec![],
         };
@@ -1647,12 +3006,24 @@ mod tests {
         let opts = DiffOpts {
             from: None,
             to: None,
+            vcs: VcsKind::Git,
+            num_jobs: None,
             metrics: vec![],
             paths: vec![],
             include: vec![],
             exclude: vec![],
             output_format: None,
             show_unchanged: false,
+            by_function: false,
+            hunks_only: false,
+            aggregate: false,
+            exclude_tests: false,
+            ignore_new: false,
+            baseline: None,
+            baseline_url: None,
+            collapse_threshold: None,
+            parallel_diff_output: false,
+            github_summary: false,
             ignore_generated: true,
             fail_on: vec![],
         };
@@ -1871,8 +3242,9 @@ src/value.txt linguist-generated=true
             metrics: vec![],
             is_new: false,
             is_deleted: false,
+            functions: Vec::new(),
         }];
-        let res = print_json(&diffs, None);
+        let res = print_json(&diffs, None, None, None, &[]);
         assert!(res.is_ok(), "valid input must serialize cleanly");
     }
 
@@ -1883,7 +3255,7 @@ src/value.txt linguist-generated=true
         // emitter used `unwrap_or_default` and silently wrote an empty
         // JSON document to stdout when serde_json failed.
         let diffs: Vec<FileDiff> = vec![];
-        let res: Result<(), Box<dyn std::error::Error>> = print_json(&diffs, None);
+        let res: Result<(), Box<dyn std::error::Error>> = print_json(&diffs, None, None, None, &[]);
         assert!(res.is_ok());
     }
 
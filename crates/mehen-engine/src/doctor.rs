@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! `mehen doctor`: one readable report of what the other commands will
+//! detect about the environment — the repository, the CI context,
+//! the analyzers in this build and the per-repository config files.
+//!
+//! Every check calls the same detection code the commands use, so the
+//! report cannot drift from their behaviour. It never fails: a broken
+//! setup is what it is there to describe.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use mehen_core::Language;
+use mehen_git::GitError;
+
+use crate::ci::{self, CiContext, CiProvider};
+use crate::concurrent_files::{MEHENIGNORE, mehenignore_patterns, repository_root};
+use crate::project_config::{CONFIG_FILE, ProjectConfig};
+use crate::registry::AnalyzerRegistry;
+use crate::vcs::hg_root;
+
+#[derive(clap::Args, Debug)]
+pub struct DoctorOpts {
+    /// Directory to check. Defaults to the current directory.
+    dir: Option<PathBuf>,
+}
+
+pub fn run_doctor(opts: DoctorOpts) {
+    let dir = opts
+        .dir
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_else(|| PathBuf::from("."));
+    let report = render(&dir, ci::detect().as_ref());
+    write!(std::io::stdout().lock(), "{report}").expect("failed to write to stdout");
+}
+
+fn render(dir: &Path, ci: Option<&CiContext>) -> String {
+    let mut out = format!("mehen {}\n\n", env!("CARGO_PKG_VERSION"));
+    out.push_str(&format!("VCS: {}\n", vcs_status(dir)));
+    out.push_str(&format!("CI: {}\n", ci_status(ci)));
+    out.push_str(&format!("Languages: {}\n", language_status()));
+    out.push_str(&format!("Config: {}\n", config_status(dir)));
+    out.push_str(&format!("Ignore file: {}\n", ignore_status(dir)));
+    out
+}
+
+/// `mehen diff` reads a git repository in-process and needs its full
+/// history, so a shallow clone is reported as the problem it is there.
+/// Outside git, a Mercurial checkout is probed through `hg`, which
+/// `mehen diff --vcs hg` shells out to as well.
+fn vcs_status(dir: &Path) -> String {
+    match mehen_git::open_repo_at(dir) {
+        Ok(_) => format!(
+            "git repository at {} (full history)",
+            repository_root(dir).display()
+        ),
+        Err(GitError::ShallowClone { hint }) => {
+            format!("shallow git clone; `mehen diff` needs full history. {hint}")
+        }
+        Err(GitError::RepoNotFound) => match hg_root(Some(dir)) {
+            Ok(root) => format!(
+                "Mercurial repository at {}; use `mehen diff --vcs hg`",
+                root.display()
+            ),
+            Err(_) => "not a git or Mercurial repository; `mehen diff` is unavailable".into(),
+        },
+        Err(e) => e.to_string(),
+    }
+}
+
+fn ci_status(ci: Option<&CiContext>) -> String {
+    let Some(ci) = ci else {
+        return "not detected (GITHUB_ACTIONS is not `true`)".into();
+    };
+    let provider = match ci.provider {
+        CiProvider::GitHubActions => "GitHub Actions",
+    };
+    let mut out = format!("{provider}, event `{}`", ci.event_name);
+    if let Some(base) = &ci.base_ref {
+        out.push_str(&format!(", base `{base}`"));
+    }
+    if let Some(pr) = ci.pr_number {
+        out.push_str(&format!(", PR #{pr}"));
+    }
+    if let Some(files) = &ci.changed_files {
+        out.push_str(&format!(", {} changed files in the event", files.len()));
+    }
+    out
+}
+
+/// Languages with an analyzer in this build; those compiled out by
+/// feature flags are listed separately.
+fn language_status() -> String {
+    let registry = AnalyzerRegistry::default_set();
+    let (enabled, missing): (Vec<Language>, Vec<Language>) = Language::ALL
+        .into_iter()
+        .partition(|&language| registry.analyzer_for(language).is_some());
+    let names = |languages: &[Language]| {
+        languages
+            .iter()
+            .map(Language::canonical)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut out = names(&enabled);
+    if !missing.is_empty() {
+        out.push_str(&format!(" (not in this build: {})", names(&missing)));
+    }
+    out
+}
+
+fn config_status(dir: &Path) -> String {
+    let path = repository_root(dir).join(CONFIG_FILE);
+    if !path.exists() {
+        return format!("none ({} not found)", path.display());
+    }
+    match ProjectConfig::discover(dir) {
+        Ok(config) => format!(
+            "{} ({} metric aliases)",
+            path.display(),
+            config.metric_aliases.len()
+        ),
+        Err(e) => e.to_string(),
+    }
+}
+
+fn ignore_status(dir: &Path) -> String {
+    let path = repository_root(dir).join(MEHENIGNORE);
+    if !path.exists() {
+        return format!("none ({} not found)", path.display());
+    }
+    format!(
+        "{} ({} patterns)",
+        path.display(),
+        mehenignore_patterns(dir).len()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_missing_ci_and_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = render(dir.path(), None);
+        assert!(report.contains("CI: not detected"), "{report}");
+        assert!(report.contains("Config: none"), "{report}");
+        assert!(report.contains("rust"), "{report}");
+        assert!(
+            report.contains("VCS: not a git or Mercurial repository"),
+            "{report}"
+        );
+    }
+
+    #[test]
+    fn reports_detected_ci_context() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        std::fs::write(dir.path().join(MEHENIGNORE), "vendor/**\n# note\ndist/**\n").unwrap();
+        let ci = CiContext {
+            provider: CiProvider::GitHubActions,
+            event_name: "pull_request".into(),
+            base_ref: Some("main".into()),
+            head_sha: None,
+            changed_files: None,
+            pr_number: Some(42),
+            repository: None,
+        };
+        let report = render(dir.path(), Some(&ci));
+        assert!(
+            report.contains("CI: GitHub Actions, event `pull_request`, base `main`, PR #42\n"),
+            "{report}"
+        );
+        assert!(report.contains("(2 patterns)"), "{report}");
+    }
+}
@@ -0,0 +1,385 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Duplicated-function detection for `mehen top-offenders --duplicates`.
+//!
+//! Every analyzed file offers its `Function` spaces to a shared
+//! [`DuplicateIndex`]. Each body is lexed into a normalized token
+//! sequence — literals collapse to placeholders and the function's own
+//! name is masked, so a copy that was only renamed still matches — and
+//! fingerprinted twice: one hash of the whole sequence for exact
+//! copies, and the set of token 5-gram hashes for near copies, grouped
+//! when their Jaccard similarity reaches [`NEAR_SIMILARITY`].
+//!
+//! Analyzers only publish Halstead token *frequencies*, not sequences,
+//! so the lexer here is a small language-agnostic one. Comments are not
+//! stripped; a copy with different comments is a near duplicate rather
+//! than an exact one.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use mehen_core::{Language, MetricSpace, SpaceKind};
+
+/// Bodies shorter than this many tokens (accessors, one-line wrappers)
+/// are too common to be worth reporting.
+pub(crate) const MIN_TOKENS: usize = 30;
+
+/// Shingle length for near-duplicate comparison.
+const NGRAM: usize = 5;
+
+/// Jaccard similarity of 5-gram sets at or above which two bodies are
+/// near duplicates.
+pub(crate) const NEAR_SIMILARITY: f64 = 0.9;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub(crate) struct DuplicateFunction {
+    pub(crate) path: PathBuf,
+    pub(crate) name: Option<String>,
+    pub(crate) line: u32,
+    pub(crate) tokens: usize,
+}
+
+/// Functions whose bodies match. `exact` is set when every member has
+/// the same normalized token sequence.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub(crate) struct DuplicateGroup {
+    pub(crate) exact: bool,
+    pub(crate) functions: Vec<DuplicateFunction>,
+}
+
+struct Fingerprint {
+    function: DuplicateFunction,
+    hash: u64,
+    shingles: HashSet<u64>,
+}
+
+#[derive(Default)]
+pub(crate) struct DuplicateIndex {
+    entries: Vec<Fingerprint>,
+}
+
+impl DuplicateIndex {
+    /// Fingerprint every `Function` space under `root`; `text` is the
+    /// `language` source the spans index into.
+    pub(crate) fn offer(
+        &mut self,
+        path: &Path,
+        language: Language,
+        root: &MetricSpace,
+        text: &str,
+    ) {
+        root.visit(&mut |space, _| {
+            if !matches!(space.kind, SpaceKind::Function) {
+                return;
+            }
+            let Some(body) = text.get(space.span.start_byte as usize..space.span.end_byte as usize)
+            else {
+                return;
+            };
+            let tokens = normalized_tokens(body, space.name.as_deref(), language);
+            if tokens.len() < MIN_TOKENS {
+                return;
+            }
+            self.entries.push(Fingerprint {
+                function: DuplicateFunction {
+                    path: path.to_path_buf(),
+                    name: space.name.clone(),
+                    line: space.span.start_line,
+                    tokens: tokens.len(),
+                },
+                hash: hash_of(&tokens),
+                shingles: tokens.windows(NGRAM).map(hash_of).collect(),
+            });
+        });
+    }
+
+    /// Group the offered functions: exact copies first, then near
+    /// copies by single-linkage over the similarity threshold. Groups
+    /// are ordered largest body first; members by path and line.
+    pub(crate) fn into_groups(self) -> Vec<DuplicateGroup> {
+        let mut entries = self.entries;
+        entries.sort_by(|a, b| {
+            a.function
+                .path
+                .cmp(&b.function.path)
+                .then(a.function.line.cmp(&b.function.line))
+        });
+
+        let mut parent: Vec<usize> = (0..entries.len()).collect();
+        let mut by_hash: BTreeMap<u64, usize> = BTreeMap::new();
+        for (i, entry) in entries.iter().enumerate() {
+            if let Some(&first) = by_hash.get(&entry.hash) {
+                union(&mut parent, first, i);
+            } else {
+                by_hash.insert(entry.hash, i);
+            }
+        }
+        // Only one representative per exact hash takes part in the
+        // pairwise pass. The Jaccard similarity of two sets is at most
+        // the ratio of their sizes, so with the representatives ordered
+        // by shingle count each one is compared only with the larger
+        // ones that can still reach the threshold.
+        let mut reps: Vec<usize> = by_hash.into_values().collect();
+        reps.sort_by_key(|&i| entries[i].shingles.len());
+        for (n, &i) in reps.iter().enumerate() {
+            let smaller = entries[i].shingles.len() as f64;
+            for &j in &reps[n + 1..] {
+                if smaller < NEAR_SIMILARITY * entries[j].shingles.len() as f64 {
+                    break;
+                }
+                if jaccard(&entries[i].shingles, &entries[j].shingles) >= NEAR_SIMILARITY {
+                    union(&mut parent, i, j);
+                }
+            }
+        }
+
+        let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for i in 0..entries.len() {
+            let root = find(&mut parent, i);
+            groups.entry(root).or_default().push(i);
+        }
+        let mut out: Vec<DuplicateGroup> = groups
+            .into_values()
+            .filter(|members| members.len() > 1)
+            .map(|members| DuplicateGroup {
+                exact: members
+                    .iter()
+                    .all(|&i| entries[i].hash == entries[members[0]].hash),
+                functions: members
+                    .iter()
+                    .map(|&i| entries[i].function.clone())
+                    .collect(),
+            })
+            .collect();
+        out.sort_by(|a, b| {
+            b.functions[0]
+                .tokens
+                .cmp(&a.functions[0].tokens)
+                .then_with(|| a.functions[0].path.cmp(&b.functions[0].path))
+                .then(a.functions[0].line.cmp(&b.functions[0].line))
+        });
+        out
+    }
+}
+
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+fn union(parent: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parent, a), find(parent, b));
+    if a != b {
+        parent[a.max(b)] = a.min(b);
+    }
+}
+
+fn jaccard(a: &HashSet<u64>, b: &HashSet<u64>) -> f64 {
+    let shared = a.intersection(b).count();
+    let total = a.len() + b.len() - shared;
+    if total == 0 {
+        1.0
+    } else {
+        shared as f64 / total as f64
+    }
+}
+
+fn hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Split `body` into identifier, literal and punctuation tokens.
+/// Numbers become `0`, quoted literals `""`, and `name` (the function's
+/// own name) `$fn`; whitespace is dropped. In Rust a `'` that does not
+/// close a one-character literal starts a lifetime or label, kept as
+/// one token.
+fn normalized_tokens<'a>(body: &'a str, name: Option<&str>, language: Language) -> Vec<&'a str> {
+    let bytes = body.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        let start = i;
+        if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        if c.is_ascii_digit() {
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+            tokens.push("0");
+        } else if c == b'_' || c == b'$' || c.is_ascii_alphabetic() || !c.is_ascii() {
+            while i < bytes.len()
+                && (bytes[i] == b'_'
+                    || bytes[i] == b'$'
+                    || bytes[i].is_ascii_alphanumeric()
+                    || !bytes[i].is_ascii())
+            {
+                i += 1;
+            }
+            let ident = &body[start..i];
+            tokens.push(if Some(ident) == name { "$fn" } else { ident });
+        } else if c == b'\'' && language == Language::Rust && !is_rust_char_literal(&body[i..]) {
+            i += 1;
+            while i < bytes.len() && (bytes[i] == b'_' || bytes[i].is_ascii_alphanumeric()) {
+                i += 1;
+            }
+            tokens.push(&body[start..i]);
+        } else if matches!(c, b'"' | b'\'' | b'`') {
+            i += 1;
+            while i < bytes.len() && bytes[i] != c && bytes[i] != b'\n' {
+                i += if bytes[i] == b'\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(bytes.len());
+            tokens.push("\"\"");
+        } else {
+            i += 1;
+            tokens.push(&body[start..i]);
+        }
+    }
+    tokens
+}
+
+/// Whether `rest`, which starts at a `'`, opens a Rust character
+/// literal (`'x'`, `'\n'`, `'\u{1F600}'`) rather than a lifetime.
+fn is_rust_char_literal(rest: &str) -> bool {
+    let mut chars = rest.chars().skip(1);
+    match chars.next() {
+        Some('\\') => true,
+        Some(_) => chars.next() == Some('\''),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "lang-rust")]
+    fn offer_rust(index: &mut DuplicateIndex, path: &str, text: &str) {
+        use mehen_core::{AnalysisConfig, Language, SourceFile};
+
+        let analyzer = crate::AnalyzerRegistry::default_set()
+            .analyzer_for(Language::Rust)
+            .expect("Rust analyzer registered");
+        let source = SourceFile::new(path.into(), Language::Rust, text.to_string());
+        let analysis = analyzer
+            .analyze(&source, &AnalysisConfig::default())
+            .unwrap();
+        index.offer(
+            Path::new(path),
+            Language::Rust,
+            &analysis.root,
+            &source.text,
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "lang-rust")]
+    fn identical_functions_in_different_files_are_grouped() {
+        let body = "(items: &[u32]) -> u32 {\n    let mut total = 0;\n    \
+                    for item in items {\n        if *item > 10 {\n            \
+                    total += item * 2;\n        } else {\n            total += item;\n        \
+                    }\n    }\n    total\n}\n";
+        let mut index = DuplicateIndex::default();
+        offer_rust(
+            &mut index,
+            "a.rs",
+            &format!("fn weigh{body}\nfn tiny() {{}}\n"),
+        );
+        offer_rust(&mut index, "b.rs", &format!("fn score{body}"));
+        offer_rust(
+            &mut index,
+            "c.rs",
+            "fn other(items: &[u32]) -> usize {\n    items.iter().filter(|i| **i > 3).count() + \
+             items.len() * 2 - items.len()\n}\n",
+        );
+        let groups = index.into_groups();
+        assert_eq!(groups.len(), 1, "{groups:?}");
+        assert!(groups[0].exact);
+        let members: Vec<(&str, Option<&str>)> = groups[0]
+            .functions
+            .iter()
+            .map(|f| (f.path.to_str().unwrap(), f.name.as_deref()))
+            .collect();
+        assert_eq!(
+            members,
+            vec![("a.rs", Some("weigh")), ("b.rs", Some("score"))]
+        );
+    }
+
+    #[test]
+    fn renamed_copy_normalizes_identically() {
+        let a = normalized_tokens(
+            "fn alpha(x: u8) -> u8 { x + 1 }",
+            Some("alpha"),
+            Language::Rust,
+        );
+        let b = normalized_tokens(
+            "fn beta(x: u8) -> u8 { x + 42 }",
+            Some("beta"),
+            Language::Rust,
+        );
+        assert_eq!(a, b);
+        assert_eq!(
+            normalized_tokens("say(\"hi \\\" there\", 'c')", None, Language::Rust),
+            vec!["say", "(", "\"\"", ",", "\"\"", ")"]
+        );
+    }
+
+    #[test]
+    fn rust_lifetimes_are_not_string_literals() {
+        assert_eq!(
+            normalized_tokens(
+                "fn f<'a>(x: &'a str, c: char) -> &'a str { x }",
+                None,
+                Language::Rust
+            ),
+            vec![
+                "fn", "f", "<", "'a", ">", "(", "x", ":", "&", "'a", "str", ",", "c", ":", "char",
+                ")", "-", ">", "&", "'a", "str", "{", "x", "}"
+            ]
+        );
+        assert_eq!(
+            normalized_tokens("['\\n', 'x']", None, Language::Rust),
+            vec!["[", "\"\"", ",", "\"\"", "]"]
+        );
+        // Elsewhere `'` always quotes a string.
+        assert_eq!(
+            normalized_tokens("say('a b')", None, Language::Python),
+            vec!["say", "(", "\"\"", ")"]
+        );
+    }
+
+    #[test]
+    fn near_duplicate_pass_skips_bodies_of_very_different_size() {
+        let shingles = |n: u64| (0..n).collect::<HashSet<u64>>();
+        let entry = |line: u32, n: u64| Fingerprint {
+            function: DuplicateFunction {
+                path: PathBuf::from("a.rs"),
+                name: None,
+                line,
+                tokens: n as usize,
+            },
+            hash: n,
+            shingles: shingles(n),
+        };
+        let index = DuplicateIndex {
+            entries: vec![entry(1, 100), entry(2, 95), entry(3, 50), entry(4, 200)],
+        };
+        let groups = index.into_groups();
+        assert_eq!(groups.len(), 1);
+        let lines: Vec<u32> = groups[0].functions.iter().map(|f| f.line).collect();
+        assert_eq!(lines, vec![1, 2]);
+        assert!(!groups[0].exact);
+    }
+}
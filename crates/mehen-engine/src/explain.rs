@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! `mehen explain-metric`: what a metric key means, how it is computed,
+//! and which direction is better.
+//!
+//! The entries come from [`METRIC_DOCS`], the registry that also labels
+//! `mehen metrics --with-metadata` output, and condense the
+//! `docs/metrics/code` pages. The polarity of every metric `diff` /
+//! `top-offenders` can select agrees with `KNOWN_METRICS`, checked by a
+//! test below.
+
+use std::io::Write;
+use std::process;
+
+use mehen_core::Polarity;
+use mehen_report::metric_metadata::{METRIC_DOCS, MetricDoc, metric_doc};
+
+#[derive(clap::Args, Debug)]
+pub struct ExplainOpts {
+    /// Metric key, e.g. `cyclomatic`, `halstead.bugs` or `mi`.
+    name: String,
+}
+
+/// Family names that stand for one of the registry keys.
+const ALIASES: &[(&str, &str)] = &[
+    ("halstead", "halstead.volume"),
+    ("loc", "loc.sloc"),
+    ("mi", "mi.visual_studio"),
+];
+
+fn find_metric_doc(name: &str) -> Option<&'static MetricDoc> {
+    let name = ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map_or(name, |(_, key)| key);
+    metric_doc(name)
+}
+
+fn render(doc: &MetricDoc) -> String {
+    let mut out = format!("{}\n\n{}\n", doc.key, doc.description);
+    if let Some(formula) = doc.formula {
+        out.push_str(&format!("\nFormula: {formula}\n"));
+    }
+    let polarity = match doc.polarity {
+        Polarity::HigherIsWorse => "lower is better",
+        Polarity::HigherIsBetter => "higher is better",
+        Polarity::Neutral => "neutral, neither direction is better",
+    };
+    out.push_str(&format!("Polarity: {polarity}\n"));
+    out
+}
+
+pub fn run_explain(opts: ExplainOpts) {
+    let Some(doc) = find_metric_doc(opts.name.trim()) else {
+        let names: Vec<&str> = METRIC_DOCS.iter().map(|d| d.key).collect();
+        log::error!(
+            "unknown metric '{}'; known metrics: {}",
+            opts.name,
+            names.join(", ")
+        );
+        process::exit(1);
+    };
+    write!(std::io::stdout().lock(), "{}", render(doc)).expect("failed to write to stdout");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metric_selector::{KNOWN_METRICS, Polarity as SelectorPolarity};
+
+    #[test]
+    fn every_selectable_metric_is_documented_with_its_polarity() {
+        for &(name, _, polarity) in KNOWN_METRICS {
+            let doc = find_metric_doc(name).unwrap_or_else(|| panic!("{name} has no doc"));
+            let expected = match polarity {
+                SelectorPolarity::LowerIsBetter => Polarity::HigherIsWorse,
+                SelectorPolarity::HigherIsBetter => Polarity::HigherIsBetter,
+                SelectorPolarity::Neutral => Polarity::Neutral,
+            };
+            assert_eq!(doc.polarity, expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn aliases_resolve_to_their_default_key() {
+        assert_eq!(find_metric_doc("mi").unwrap().key, "mi.visual_studio");
+        assert!(find_metric_doc("halstead.nope").is_none());
+        assert!(render(find_metric_doc("halstead.bugs").unwrap()).contains("B = V / 3000"));
+    }
+}
@@ -0,0 +1,220 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! `mehen metrics --external-metric NAME=CMD`: a metric computed by a
+//! user command, for experimenting with a metric before it is built in.
+//!
+//! The command runs through the shell with the file's metrics report —
+//! the JSON `mehen metrics` prints, space tree included — on stdin and
+//! `MEHEN_PATH` set to the analyzed file. It prints one number on
+//! stdout, which lands in the unit space as `external.NAME`. A command
+//! still running after [`EXTERNAL_METRIC_TIMEOUT`] is killed.
+
+use std::fmt;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// How long `mehen metrics` waits for each external metric command.
+pub const EXTERNAL_METRIC_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// One `--external-metric NAME=CMD` registration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalMetric {
+    pub name: String,
+    pub command: String,
+}
+
+impl ExternalMetric {
+    /// The key the result is published under.
+    pub fn key(&self) -> String {
+        format!("external.{}", self.name)
+    }
+
+    /// Run the command with `input` on stdin and parse its stdout as a
+    /// number. The command is killed once `timeout` has passed.
+    pub fn run(
+        &self,
+        path: &str,
+        input: &str,
+        timeout: Duration,
+    ) -> Result<f64, ExternalMetricError> {
+        let mut child = shell(&self.command)
+            .env("MEHEN_PATH", path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| self.error(format!("failed to start: {e}")))?;
+        // Written from a thread so a command that prints before reading
+        // its input cannot deadlock on a full pipe. A command that never
+        // reads stdin closes it early; that broken pipe is not an error.
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let input = input.to_owned();
+        let writer = std::thread::spawn(move || match stdin.write_all(input.as_bytes()) {
+            Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => Err(e),
+            _ => Ok(()),
+        });
+        // Read from a thread too, so the wait below can poll for the
+        // deadline while the command fills its pipe.
+        let mut stdout = child.stdout.take().expect("stdout is piped");
+        let reader = std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            stdout.read_to_end(&mut buf).map(|_| buf)
+        });
+        let deadline = Instant::now() + timeout;
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) if Instant::now() >= deadline => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(self.error(format!("timed out after {}s", timeout.as_secs_f64())));
+                }
+                Ok(None) => std::thread::sleep(Duration::from_millis(10)),
+                Err(e) => return Err(self.error(format!("failed to run: {e}"))),
+            }
+        };
+        if let Ok(Err(e)) = writer.join() {
+            return Err(self.error(format!("failed to write stdin: {e}")));
+        }
+        if !status.success() {
+            return Err(self.error(format!("exited with {status}")));
+        }
+        let stdout = match reader.join() {
+            Ok(Ok(bytes)) => bytes,
+            Ok(Err(e)) => return Err(self.error(format!("failed to read stdout: {e}"))),
+            Err(_) => return Err(self.error("failed to read stdout".to_string())),
+        };
+        let stdout = String::from_utf8_lossy(&stdout);
+        stdout
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .ok_or_else(|| self.error(format!("printed `{}`, not a number", stdout.trim())))
+    }
+
+    fn error(&self, message: String) -> ExternalMetricError {
+        ExternalMetricError::Command {
+            name: self.name.clone(),
+            message,
+        }
+    }
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+impl FromStr for ExternalMetric {
+    type Err = ExternalMetricError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let invalid = || ExternalMetricError::InvalidSpec(spec.to_string());
+        let (name, command) = spec.split_once('=').ok_or_else(invalid)?;
+        let name = name.trim();
+        let valid_name = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !valid_name || command.trim().is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            name: name.to_string(),
+            command: command.trim().to_string(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum ExternalMetricError {
+    /// Not `NAME=CMD`, or the name is not `[A-Za-z0-9_-]+`.
+    InvalidSpec(String),
+    /// The command failed to run, failed, timed out, or printed no
+    /// number.
+    Command { name: String, message: String },
+    /// The metrics report could not be rendered as the commands' input.
+    Input(serde_json::Error),
+}
+
+impl fmt::Display for ExternalMetricError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSpec(spec) => write!(
+                f,
+                "invalid --external-metric '{spec}'; expected NAME=CMD with NAME made of \
+                 letters, digits, '_' or '-'"
+            ),
+            Self::Command { name, message } => write!(f, "external metric `{name}`: {message}"),
+            Self::Input(e) => write!(f, "failed to render the report for --external-metric: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ExternalMetricError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_name_and_command() {
+        let metric: ExternalMetric = "todo_count = grep -c TODO".parse().unwrap();
+        assert_eq!(metric.name, "todo_count");
+        assert_eq!(metric.command, "grep -c TODO");
+        assert_eq!(metric.key(), "external.todo_count");
+        assert!("no-command=".parse::<ExternalMetric>().is_err());
+        assert!("a.b=echo 1".parse::<ExternalMetric>().is_err());
+        assert!("echo 1".parse::<ExternalMetric>().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reads_stdin_and_rejects_non_numeric_output() {
+        let count: ExternalMetric = "bytes=wc -c".parse().unwrap();
+        assert_eq!(
+            count.run("a.rs", "12345", EXTERNAL_METRIC_TIMEOUT).unwrap(),
+            5.0
+        );
+        let path_len: ExternalMetric =
+            r#"path_len=printf %s "$MEHEN_PATH" | wc -c"#.parse().unwrap();
+        assert_eq!(
+            path_len
+                .run("src/main.rs", "", EXTERNAL_METRIC_TIMEOUT)
+                .unwrap(),
+            11.0
+        );
+        let path: ExternalMetric = "path=echo $MEHEN_PATH".parse().unwrap();
+        assert!(matches!(
+            path.run("a.rs", "", EXTERNAL_METRIC_TIMEOUT),
+            Err(ExternalMetricError::Command { .. })
+        ));
+        let failing: ExternalMetric = "fail=exit 3".parse().unwrap();
+        assert!(failing.run("a.rs", "", EXTERNAL_METRIC_TIMEOUT).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn kills_a_command_that_outlives_the_timeout() {
+        let hanging: ExternalMetric = "hang=sleep 5; echo 1".parse().unwrap();
+        let started = Instant::now();
+        let err = hanging
+            .run("a.rs", "", Duration::from_millis(100))
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{err}");
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+}
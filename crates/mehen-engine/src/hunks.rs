@@ -0,0 +1,223 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Changed line ranges between two revisions of a file, for
+//! `mehen diff --hunks-only`.
+//!
+//! The workspace builds `gix` without its blob-diff feature, and the
+//! diff pipeline already holds both blobs in memory, so the hunks are
+//! computed here with a line-level Myers diff. Common leading and
+//! trailing lines are trimmed first, which keeps the edit graph small
+//! for the usual "a few hunks in a large file" case. A rewrite with
+//! more than [`MAX_EDIT_DISTANCE`] changed lines is reported as one
+//! hunk over everything between the common ends instead.
+
+use std::ops::Range;
+
+/// Largest edit distance the Myers search runs to. Its trace grows with
+/// the square of the distance, about 8 MB at this cap.
+const MAX_EDIT_DISTANCE: isize = 1_000;
+
+/// One contiguous change. Both ranges are 1-based, end-exclusive line
+/// numbers; an empty range marks the insertion point on that side (the
+/// change sits just before line `start`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Hunk {
+    pub(crate) base: Range<u32>,
+    pub(crate) head: Range<u32>,
+}
+
+/// Which side of a [`Hunk`] a line span refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Side {
+    Base,
+    Head,
+}
+
+/// Hunks turning `base` into `head`, in file order.
+pub(crate) fn line_hunks(base: &str, head: &str) -> Vec<Hunk> {
+    let a: Vec<&str> = base.lines().collect();
+    let b: Vec<&str> = head.lines().collect();
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let a_mid = &a[prefix..a.len() - suffix];
+    let b_mid = &b[prefix..b.len() - suffix];
+
+    let line = |i: usize| u32::try_from(prefix + i + 1).unwrap_or(u32::MAX);
+    let mut hunks = Vec::new();
+    let (mut x, mut y) = (0, 0);
+    // Past the cap no line is matched, which leaves a single hunk.
+    let matches = myers_matches(a_mid, b_mid).unwrap_or_default();
+    for (mx, my) in matches
+        .into_iter()
+        .chain(std::iter::once((a_mid.len(), b_mid.len())))
+    {
+        if mx > x || my > y {
+            hunks.push(Hunk {
+                base: line(x)..line(mx),
+                head: line(y)..line(my),
+            });
+        }
+        x = mx + 1;
+        y = my + 1;
+    }
+    hunks
+}
+
+/// Whether the 1-based inclusive lines `start..=end` on `side` overlap
+/// any hunk. An insertion point counts when it falls strictly inside the
+/// span, i.e. both neighbouring lines belong to it.
+pub(crate) fn touches(hunks: &[Hunk], side: Side, start: u32, end: u32) -> bool {
+    hunks.iter().any(|hunk| {
+        let range = match side {
+            Side::Base => &hunk.base,
+            Side::Head => &hunk.head,
+        };
+        if range.is_empty() {
+            start < range.start && range.start <= end
+        } else {
+            range.start <= end && start < range.end
+        }
+    })
+}
+
+/// Indices of the lines kept unchanged by a shortest edit script, as
+/// `(index in a, index in b)` pairs in order.
+///
+/// Only the `-d..=d` window of the frontier is recorded per round, so
+/// memory grows with the square of the edit distance rather than with
+/// the file length. `None` when the distance exceeds
+/// [`MAX_EDIT_DISTANCE`].
+fn myers_matches(a: &[&str], b: &[&str]) -> Option<Vec<(usize, usize)>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = n + m;
+    let offset = max + 1;
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    'search: for d in 0..=max {
+        if d > MAX_EDIT_DISTANCE {
+            return None;
+        }
+        trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let at = |k: isize| v[(offset + k) as usize];
+            let mut x = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+                at(k + 1)
+            } else {
+                at(k - 1) + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[(offset + k) as usize] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut matches = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for d in (1..trace.len() as isize).rev() {
+        let frontier = &trace[d as usize];
+        let at = |k: isize| frontier[(k + d) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            matches.push((x as usize, y as usize));
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    while x > 0 && y > 0 {
+        x -= 1;
+        y -= 1;
+        matches.push((x as usize, y as usize));
+    }
+    matches.reverse();
+    Some(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_text_has_no_hunks() {
+        assert!(line_hunks("a\nb\n", "a\nb\n").is_empty());
+    }
+
+    #[test]
+    fn reports_each_changed_region() {
+        let base = "a\nb\nc\nd\ne\nf\n";
+        let head = "a\nB\nc\nd\ne\nf\ng\n";
+        assert_eq!(
+            line_hunks(base, head),
+            vec![
+                Hunk {
+                    base: 2..3,
+                    head: 2..3,
+                },
+                Hunk {
+                    base: 7..7,
+                    head: 7..8,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn deletion_touches_the_enclosing_span_only() {
+        let hunks = line_hunks("a\nb\nc\nd\n", "a\nd\n");
+        assert_eq!(
+            hunks,
+            vec![Hunk {
+                base: 2..4,
+                head: 2..2,
+            }]
+        );
+        assert!(touches(&hunks, Side::Base, 3, 3));
+        assert!(touches(&hunks, Side::Head, 1, 2));
+        assert!(!touches(&hunks, Side::Head, 2, 2));
+    }
+
+    #[test]
+    fn rewrite_past_the_edit_cap_is_one_hunk() {
+        let base: String = (0..5_000).map(|i| format!("old {i}\n")).collect();
+        let head: String = (0..5_000).map(|i| format!("new {i}\n")).collect();
+        assert_eq!(
+            line_hunks(&base, &head),
+            vec![Hunk {
+                base: 1..5_001,
+                head: 1..5_001,
+            }]
+        );
+    }
+
+    #[test]
+    fn new_file_is_one_hunk() {
+        assert_eq!(
+            line_hunks("", "a\nb\n"),
+            vec![Hunk {
+                base: 1..1,
+                head: 1..3,
+            }]
+        );
+    }
+}
@@ -0,0 +1,177 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! `mehen languages`: per-language file count and SLOC for a tree, in
+//! the spirit of `tokei` / `cloc`.
+//!
+//! Only extension detection and the unit-space SLOC rule run — no file
+//! is parsed — so this stays fast on large checkouts. SLOC is the same
+//! number `mehen metrics` reports as the file's `loc.sloc`.
+
+use std::collections::{BTreeMap, HashSet};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process;
+
+use camino::Utf8PathBuf;
+
+use mehen_core::{Language, LineIndex};
+use mehen_metrics::LocStats;
+
+use crate::concurrent_files::mehenignore_patterns;
+use crate::detection::detect_language;
+use crate::top_offenders::{canonical_key, walk_paths};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum LanguagesFormat {
+    Markdown,
+    Json,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct LanguagesOpts {
+    /// Output format.
+    #[clap(long, short = 'O', value_enum, default_value_t = LanguagesFormat::Markdown)]
+    output_format: LanguagesFormat,
+
+    /// Glob to include files. Repeat the flag for multiple patterns.
+    #[clap(long, short = 'I', num_args = 1)]
+    include: Vec<String>,
+
+    /// Glob to exclude files. Repeat the flag for multiple patterns.
+    #[clap(long, short = 'X', num_args = 1)]
+    exclude: Vec<String>,
+
+    /// One or more files or directories to scan.
+    #[clap(required = true, num_args = 1..)]
+    paths: Vec<PathBuf>,
+}
+
+/// Totals for one detected language.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub(crate) struct LanguageSummary {
+    pub(crate) language: Language,
+    pub(crate) files: usize,
+    pub(crate) sloc: u64,
+}
+
+/// Walk `paths` and total files and SLOC per detected language, largest
+/// SLOC first. Files with an unknown extension or that are not UTF-8
+/// are skipped; a file reached through overlapping roots counts once.
+pub(crate) fn summarize_languages(
+    paths: &[Utf8PathBuf],
+    include: &[String],
+    exclude: &[String],
+) -> Vec<LanguageSummary> {
+    let mut totals: BTreeMap<&'static str, LanguageSummary> = BTreeMap::new();
+    let mut seen: HashSet<Utf8PathBuf> = HashSet::new();
+    for root in paths {
+        for entry in walk_paths(root, include, exclude) {
+            if !seen.insert(canonical_key(&entry)) {
+                continue;
+            }
+            let Some(language) = detect_language(entry.as_path()) else {
+                continue;
+            };
+            let Ok(text) = std::fs::read_to_string(entry.as_std_path()) else {
+                continue;
+            };
+            let summary = totals
+                .entry(language.canonical())
+                .or_insert(LanguageSummary {
+                    language,
+                    files: 0,
+                    sloc: 0,
+                });
+            summary.files += 1;
+            summary.sloc += u64::from(unit_sloc(&text));
+        }
+    }
+    let mut out: Vec<LanguageSummary> = totals.into_values().collect();
+    out.sort_by(|a, b| b.sloc.cmp(&a.sloc).then(b.files.cmp(&a.files)));
+    out
+}
+
+/// The unit space spans every row of the file (end row exclusive), as
+/// the analyzers set it.
+fn unit_sloc(text: &str) -> u32 {
+    let mut loc = LocStats::default();
+    loc.set_span(0, LineIndex::new(text).line_count() - 1, true);
+    loc.sloc()
+}
+
+pub fn run_languages(opts: LanguagesOpts) {
+    let mut paths = Vec::with_capacity(opts.paths.len());
+    for path in opts.paths {
+        match Utf8PathBuf::try_from(path) {
+            Ok(path) => paths.push(path),
+            Err(e) => {
+                log::error!("path is not valid UTF-8: {}", e.as_path().display());
+                process::exit(1);
+            }
+        }
+    }
+    let mut exclude = opts.exclude;
+    if let Ok(cwd) = std::env::current_dir() {
+        exclude.extend(mehenignore_patterns(&cwd));
+    }
+
+    let summaries = summarize_languages(&paths, &opts.include, &exclude);
+    let out = match opts.output_format {
+        LanguagesFormat::Json => {
+            serde_json::to_string_pretty(&summaries).expect("language summary is serializable")
+                + "\n"
+        }
+        LanguagesFormat::Markdown => render_markdown(&summaries),
+    };
+    write!(std::io::stdout().lock(), "{out}").expect("failed to write to stdout");
+}
+
+fn render_markdown(summaries: &[LanguageSummary]) -> String {
+    let mut out = String::from("## Languages\n\n");
+    if summaries.is_empty() {
+        out.push_str("No source files found.\n");
+        return out;
+    }
+    out.push_str("| Language | Files | SLOC |\n|---|---:|---:|\n");
+    for s in summaries {
+        out.push_str(&format!("| {} | {} | {} |\n", s.language, s.files, s.sloc));
+    }
+    let files: usize = summaries.iter().map(|s| s.files).sum();
+    let sloc: u64 = summaries.iter().map(|s| s.sloc).sum();
+    out.push_str(&format!("| **Total** | {files} | {sloc} |\n"));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_files_and_sloc_per_language() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = Utf8PathBuf::try_from(dir.path().to_path_buf()).unwrap();
+        std::fs::create_dir(root.join("nested")).unwrap();
+        std::fs::write(root.join("a.rs"), "fn a() {}\n\nfn b() {}\n").unwrap();
+        std::fs::write(root.join("nested/c.rs"), "fn c() {}\n").unwrap();
+        std::fs::write(root.join("main.go"), "package main\n").unwrap();
+        std::fs::write(root.join("notes.txt"), "not code\n").unwrap();
+
+        let summaries = summarize_languages(&[root.clone(), root.join("a.rs")], &[], &[]);
+        assert_eq!(
+            summaries,
+            vec![
+                LanguageSummary {
+                    language: Language::Rust,
+                    files: 2,
+                    sloc: 4,
+                },
+                LanguageSummary {
+                    language: Language::Go,
+                    files: 1,
+                    sloc: 1,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! `mehen metrics --vs-last`: a personal ratchet that needs no git.
+//!
+//! Every run records the file's unit-level `MetricSet` in a local state
+//! file and reports what moved since the run before. The state file is
+//! an ordinary [baseline](crate::baseline), so `mehen diff --baseline`
+//! can read it too.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use mehen_core::{MetricKey, MetricSet};
+
+use crate::baseline::{Baseline, BaselineError};
+
+/// State file `--vs-last` reads and rewrites, relative to the working
+/// directory.
+pub const LAST_RUN_FILE: &str = ".mehen/last-run.json";
+
+/// One unit-level metric that changed since the previous run. A key
+/// missing on one side reads as `0`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LastRunDelta {
+    pub key: String,
+    pub previous: f64,
+    pub current: f64,
+}
+
+/// Record `metrics` for `file` in the state at `state_path` and return
+/// what changed since the previously recorded run of the same file, in
+/// key order. `None` when `file` has no recorded run yet.
+pub fn record_last_run(
+    state_path: &Path,
+    file: &Path,
+    metrics: &MetricSet,
+) -> Result<Option<Vec<LastRunDelta>>, BaselineError> {
+    let mut state = if state_path.exists() {
+        Baseline::read(state_path)?
+    } else {
+        Baseline::default()
+    };
+    let deltas = state
+        .space_for(file)
+        .map(|previous| metric_deltas(&previous.metrics, metrics));
+    state.insert(file, metrics.clone());
+    if let Some(dir) = state_path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|e| BaselineError::Io(dir.to_path_buf(), e))?;
+    }
+    state.write(state_path)?;
+    Ok(deltas)
+}
+
+fn metric_deltas(previous: &MetricSet, current: &MetricSet) -> Vec<LastRunDelta> {
+    let keys: BTreeSet<&MetricKey> = previous
+        .iter()
+        .chain(current.iter())
+        .map(|(k, _)| k)
+        .collect();
+    keys.into_iter()
+        .filter_map(|key| {
+            let value = |set: &MetricSet| set.get(key).map_or(0.0, |v| v.as_f64());
+            let (previous, current) = (value(previous), value(current));
+            // The JSON round trip can move a float by an ulp or two;
+            // that is not a change worth reporting.
+            let tolerance = 1e-9 * previous.abs().max(current.abs());
+            ((previous - current).abs() > tolerance).then(|| LastRunDelta {
+                key: key.as_str().to_string(),
+                previous,
+                current,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn second_run_reports_changed_keys_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = dir.path().join(LAST_RUN_FILE);
+        let file = Path::new("src/a.rs");
+        let mut metrics = MetricSet::new();
+        metrics.insert("cyclomatic.sum", 2_i64);
+        metrics.insert("loc.sloc", 10_i64);
+        assert_eq!(record_last_run(&state, file, &metrics).unwrap(), None);
+
+        metrics.insert("cyclomatic.sum", 3_i64);
+        metrics.insert("max_nesting", 1_i64);
+        let deltas = record_last_run(&state, file, &metrics).unwrap().unwrap();
+        let keys: Vec<&str> = deltas.iter().map(|d| d.key.as_str()).collect();
+        assert_eq!(keys, ["cyclomatic.sum", "max_nesting"]);
+        assert_eq!((deltas[0].previous, deltas[0].current), (2.0, 3.0));
+        assert_eq!((deltas[1].previous, deltas[1].current), (0.0, 1.0));
+    }
+}
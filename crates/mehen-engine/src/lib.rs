@@ -22,16 +22,37 @@
 
 #![deny(unsafe_code)]
 
+mod baseline;
 pub mod ci;
 mod concurrent_files;
 mod detection;
 mod diff;
 mod dispatcher;
+mod doctor;
+mod duplicates;
+mod explain;
+mod external_metric;
+mod hunks;
+mod languages;
+mod last_run;
 mod metric_selector;
+mod output_template;
+mod project_config;
+mod query;
 mod registry;
+mod smells;
+mod top_functions;
 mod top_offenders;
+mod vcs;
+mod warnings;
 
 pub use diff::{DiffOpts, run_diff};
+pub use doctor::{DoctorOpts, run_doctor};
+pub use explain::{ExplainOpts, run_explain};
+pub use external_metric::{EXTERNAL_METRIC_TIMEOUT, ExternalMetric, ExternalMetricError};
+pub use languages::{LanguagesOpts, run_languages};
+pub use last_run::{LAST_RUN_FILE, LastRunDelta, record_last_run};
+pub use project_config::{ProjectConfig, ProjectConfigError};
 pub use top_offenders::{TopOffendersOpts, run_top_offenders};
 
 /// Register the embedded-code dispatch callback the moved
@@ -154,17 +175,24 @@ mod markdown_dispatch {
     }
 }
 
-pub use detection::detect_language;
+pub use baseline::BaselineError;
+pub use detection::{detect_language, language_from_modeline};
 pub use diff::analyze_diff;
 pub use dispatcher::EngineDispatcher;
 pub use mehen_core::{
     AnalysisErrorRecord, AnalyzeMetricsInput, DiffFile, DiffInput, DiffReport, DiffSide,
-    MetricsReport, TopOffenderEntry, TopOffendersInput, TopOffendersReport,
+    MetricsReport, PathThresholds, TopOffenderEntry, TopOffendersInput, TopOffendersReport,
+};
+pub use mehen_git::read_staged_file;
+pub use query::{
+    GrammarVersion, ParserVersion, QueryError, QueryMatch, QueryReport, grammar_versions,
+    parser_versions, query_source,
 };
 pub use registry::{AnalyzerRegistry, RegistryError};
+pub use smells::{FunctionSmells, SmellThresholdError, SmellThresholds, Smells, SmellsReport};
 pub use top_offenders::rank_top_offenders;
 
-use mehen_core::{AnalysisError, Result};
+use mehen_core::{AnalysisConfig, AnalysisError, MetricSpace, Result};
 
 /// Run a single-file analysis using the default registry.
 ///
@@ -182,6 +210,35 @@ pub fn analyze_metrics(input: AnalyzeMetricsInput) -> Result<MetricsReport> {
         .ok_or(AnalysisError::AnalyzerUnavailable(input.source.language))?;
     let analysis = analyzer.analyze(&input.source, &input.config)?;
     let mut report = MetricsReport::from(analysis);
+    report.root.assign_function_ids(path.as_str());
     report.path = path;
+    finalize_analysis(&mut report.root, &input.config);
     Ok(report)
 }
+
+/// The passes `config` asks for once an analyzer has returned:
+/// min/max recomputation, closure exclusion, the Halstead rescale, the
+/// debt ratio and the excluded metric families, in that order. Every
+/// entry point that reports an analysis runs them, so `metrics`,
+/// `diff` and `top-offenders` agree on the numbers.
+pub(crate) fn finalize_analysis(root: &mut MetricSpace, config: &AnalysisConfig) {
+    if config.recompute_minmax {
+        mehen_metrics::recompute_minmax(root);
+    }
+    if config.exclude_closures {
+        mehen_metrics::exclude_closures_from_nom(root);
+    }
+    if config.halstead_stroud_number != mehen_core::DEFAULT_STROUD_NUMBER
+        || config.halstead_bugs_constant != mehen_core::DEFAULT_BUGS_CONSTANT
+    {
+        mehen_metrics::rescale_halstead_derived(
+            root,
+            config.halstead_stroud_number,
+            config.halstead_bugs_constant,
+        );
+    }
+    mehen_metrics::publish_debt_ratio(root, &config.debt_weights);
+    if !config.excluded_metrics.is_empty() {
+        mehen_metrics::drop_metric_families(root, &config.excluded_metrics);
+    }
+}
@@ -8,24 +8,32 @@
 //! "better"). Production diff/top-offenders pipelines read the
 //! `MetricSpace::metrics` map via [`read_metric`].
 
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
 use mehen_core::{MetricKey, MetricSpace};
 
+use crate::warnings::{WarningCode, Warnings};
+
 /// Whether a metric is "better" when higher or lower.
 ///
 /// Used by callers to interpret deltas/rankings (e.g. `Cyclomatic` is
 /// [`Polarity::LowerIsBetter`], while `Mi` is [`Polarity::HigherIsBetter`]).
+/// A [`Polarity::Neutral`] metric never counts as improved or regressed.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub(crate) enum Polarity {
     LowerIsBetter,
     HigherIsBetter,
+    Neutral,
 }
 
 /// A selector for a single metric column: name, display label, polarity.
 #[derive(Debug, Clone)]
 pub(crate) struct MetricSelector {
     pub name: &'static str,
-    pub label: &'static str,
+    /// Built-in label, or the `.mehen.toml` alias that replaced it.
+    pub label: Cow<'static, str>,
     pub polarity: Polarity,
 }
 
@@ -41,6 +49,11 @@ pub(crate) const KNOWN_METRICS: &[MetricDef] = &[
     ("mi.sei", "MI (SEI)", Polarity::HigherIsBetter),
     ("mi.visual_studio", "MI", Polarity::HigherIsBetter),
     ("halstead.volume", "Halstead Vol", Polarity::LowerIsBetter),
+    (
+        "halstead.operator_operand_ratio",
+        "Op/Operand",
+        Polarity::Neutral,
+    ),
     ("abc", "ABC", Polarity::LowerIsBetter),
 ];
 
@@ -65,6 +78,15 @@ pub(crate) const DEFAULT_METRICS: &[&str] = &[
 /// `top-offenders`, where `--metric` is required) should enforce that at the
 /// CLI layer before calling this function.
 pub(crate) fn parse_metric_selectors(specs: &[String]) -> Vec<MetricSelector> {
+    parse_metric_selectors_into(specs, &mut Warnings::default())
+}
+
+/// [`parse_metric_selectors`], recording each unknown name in
+/// `warnings` so JSON output can report it.
+pub(crate) fn parse_metric_selectors_into(
+    specs: &[String],
+    warnings: &mut Warnings,
+) -> Vec<MetricSelector> {
     let specs: Vec<&str> = if specs.is_empty() {
         DEFAULT_METRICS.to_vec()
     } else {
@@ -85,17 +107,34 @@ pub(crate) fn parse_metric_selectors(specs: &[String]) -> Vec<MetricSelector> {
         {
             selectors.push(MetricSelector {
                 name: n,
-                label,
+                label: Cow::Borrowed(label),
                 polarity: polarity_override.unwrap_or(default_polarity),
             });
         } else {
-            log::warn!("Unknown metric '{name}', skipping.");
+            warnings.push(
+                WarningCode::UnknownMetric,
+                None,
+                format!("Unknown metric '{name}', skipping."),
+            );
         }
     }
 
     selectors
 }
 
+/// Replace each selector's label with its alias from `.mehen.toml`'s
+/// `[metric-aliases]`, keyed by metric name.
+pub(crate) fn apply_label_aliases(
+    selectors: &mut [MetricSelector],
+    aliases: &BTreeMap<String, String>,
+) {
+    for selector in selectors {
+        if let Some(alias) = aliases.get(selector.name) {
+            selector.label = Cow::Owned(alias.clone());
+        }
+    }
+}
+
 /// Translate a CLI selector name (e.g. `cyclomatic`, `nom.functions`,
 /// `mi.visual_studio`) to the `MetricSet` key the shared walker
 /// publishes onto the root `MetricSpace`.
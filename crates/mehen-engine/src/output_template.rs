@@ -0,0 +1,198 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! `mehen top-offenders --output-template`: one line of text per
+//! function from a user format string.
+//!
+//! A template is literal text with `{placeholder}` fields, e.g.
+//! `{path}:{line} {name} cyc={cyclomatic} cog={cognitive}`. `{{` and
+//! `}}` stand for literal braces. Placeholders are resolved once, when
+//! the template is parsed, so a typo fails before any file is analyzed
+//! rather than printing blanks.
+
+use std::fmt;
+
+use mehen_core::{MetricKey, MetricSet};
+
+use crate::metric_selector::{KNOWN_METRICS, metric_set_key_for};
+use crate::top_functions::FunctionOffender;
+use crate::top_offenders::format_value;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    Path,
+    Line,
+    Name,
+    /// The `--top-by` value the function was ranked on.
+    Value,
+    /// Any catalogue metric, read from the function's own space.
+    Metric(&'static str),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct OutputTemplate(Vec<Segment>);
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum TemplateError {
+    UnknownPlaceholder(String),
+    Unclosed,
+    UnmatchedClose,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownPlaceholder(name) => write!(
+                f,
+                "unknown placeholder '{{{name}}}' in --output-template; use path, line, name, \
+                 value or a metric name such as cyclomatic"
+            ),
+            Self::Unclosed => write!(f, "unclosed '{{' in --output-template"),
+            Self::UnmatchedClose => write!(
+                f,
+                "unmatched '}}' in --output-template; write '}}}}' for a literal brace"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+impl OutputTemplate {
+    pub(crate) fn parse(template: &str) -> Result<Self, TemplateError> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '}' => return Err(TemplateError::UnmatchedClose),
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(TemplateError::Unclosed),
+                        }
+                    }
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(placeholder(name.trim())?);
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Ok(Self(segments))
+    }
+
+    /// Whether rendering reads metrics other than the ranked value, in
+    /// which case each retained function must keep its metric set.
+    pub(crate) fn needs_metrics(&self) -> bool {
+        self.0.iter().any(|s| matches!(s, Segment::Metric(_)))
+    }
+
+    pub(crate) fn render(&self, function: &FunctionOffender) -> String {
+        let mut out = String::new();
+        for segment in &self.0 {
+            match segment {
+                Segment::Literal(text) => out.push_str(text),
+                Segment::Path => out.push_str(&function.path.display().to_string()),
+                Segment::Line => out.push_str(&function.line.to_string()),
+                Segment::Name => out.push_str(function.name.as_deref().unwrap_or("<anonymous>")),
+                Segment::Value => out.push_str(&format_value(function.value)),
+                Segment::Metric(name) => {
+                    out.push_str(&format_value(metric(function.metrics.as_ref(), name)))
+                }
+            }
+        }
+        out
+    }
+}
+
+fn placeholder(name: &str) -> Result<Segment, TemplateError> {
+    Ok(match name {
+        "path" => Segment::Path,
+        "line" => Segment::Line,
+        "name" => Segment::Name,
+        "value" => Segment::Value,
+        _ => match KNOWN_METRICS.iter().find(|(n, ..)| *n == name) {
+            Some(&(n, ..)) => Segment::Metric(n),
+            None => return Err(TemplateError::UnknownPlaceholder(name.to_string())),
+        },
+    })
+}
+
+fn metric(metrics: Option<&MetricSet>, name: &str) -> f64 {
+    metrics
+        .and_then(|m| m.get(&MetricKey::new(metric_set_key_for(name))))
+        .map(|v| v.as_f64())
+        .unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn offender() -> FunctionOffender {
+        let mut metrics = MetricSet::new();
+        metrics.insert("cyclomatic.sum", 7_i64);
+        metrics.insert("cognitive.sum", 12_i64);
+        FunctionOffender {
+            path: PathBuf::from("src/lib.rs"),
+            name: Some("parse".to_string()),
+            line: 42,
+            value: 12.0,
+            metrics: Some(metrics),
+        }
+    }
+
+    #[test]
+    fn substitutes_placeholders_and_escaped_braces() {
+        let template =
+            OutputTemplate::parse("{path}:{line} {name} cyc={cyclomatic} cog={ cognitive } {{ok}}")
+                .unwrap();
+        assert!(template.needs_metrics());
+        assert_eq!(
+            template.render(&offender()),
+            "src/lib.rs:42 parse cyc=7 cog=12 {ok}"
+        );
+    }
+
+    #[test]
+    fn value_placeholder_does_not_need_metrics() {
+        let template = OutputTemplate::parse("{name}\t{value}").unwrap();
+        assert!(!template.needs_metrics());
+        assert_eq!(template.render(&offender()), "parse\t12");
+    }
+
+    #[test]
+    fn rejects_unknown_placeholder() {
+        assert_eq!(
+            OutputTemplate::parse("{path} {cyclo}"),
+            Err(TemplateError::UnknownPlaceholder("cyclo".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_unbalanced_braces() {
+        assert_eq!(OutputTemplate::parse("{path"), Err(TemplateError::Unclosed));
+        assert_eq!(
+            OutputTemplate::parse("a } b"),
+            Err(TemplateError::UnmatchedClose)
+        );
+    }
+}
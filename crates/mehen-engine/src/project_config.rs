@@ -0,0 +1,195 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Per-repository settings in `.mehen.toml`.
+//!
+//! The file lives at the [repository root](crate::concurrent_files::repository_root),
+//! next to `.mehenignore`. Every section is optional, and a missing file
+//! is the same as an empty one.
+//!
+//! ```toml
+//! [metric-aliases]
+//! "loc.lloc" = "Logical lines"
+//! cyclomatic = "McCabe"
+//!
+//! [halstead-overrides]
+//! "." = "operand"
+//!
+//! [thresholds]
+//! cyclomatic = 30
+//!
+//! [path-thresholds.core]
+//! cyclomatic = 15
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use mehen_core::{HalsteadClass, MetricSelector, PathThresholds, Threshold};
+use serde::Deserialize;
+
+use crate::concurrent_files::repository_root;
+
+/// Name of the per-repository config file.
+pub(crate) const CONFIG_FILE: &str = ".mehen.toml";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Column labels to show instead of the built-in ones, keyed by
+    /// metric name as passed to `--metrics` (e.g. `loc.lloc`).
+    #[serde(default)]
+    pub metric_aliases: BTreeMap<String, String>,
+    /// Halstead class per grammar node kind, copied into
+    /// `AnalysisConfig::halstead_overrides` by every command.
+    #[serde(default)]
+    pub halstead_overrides: BTreeMap<String, HalsteadClass>,
+    /// Limits `mehen diff` holds the head side of every changed file to,
+    /// keyed by metric selector (`cyclomatic`, `cognitive.max`).
+    #[serde(default)]
+    pub thresholds: BTreeMap<String, f64>,
+    /// Limits that replace `thresholds` for the files under a
+    /// repository-relative prefix; the most specific prefix wins.
+    #[serde(default)]
+    pub path_thresholds: BTreeMap<String, BTreeMap<String, f64>>,
+}
+
+impl ProjectConfig {
+    /// Read the `.mehen.toml` of the repository that contains `start`.
+    pub fn discover(start: &Path) -> Result<Self, ProjectConfigError> {
+        let path = repository_root(start).join(CONFIG_FILE);
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text).map_err(|e| ProjectConfigError::Parse(path, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(ProjectConfigError::Io(path, e)),
+        }
+    }
+
+    pub(crate) fn parse(text: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    /// `thresholds` and `path_thresholds` as the rules
+    /// [`DiffInput`](mehen_core::DiffInput) takes. Each limit's polarity
+    /// is the metric's own, so an `mi.visual_studio` limit is a floor.
+    pub fn diff_thresholds(
+        &self,
+    ) -> Result<(Vec<Threshold>, Vec<PathThresholds>), ProjectConfigError> {
+        let defaults = rules(&self.thresholds)?;
+        let by_path = self
+            .path_thresholds
+            .iter()
+            .map(|(prefix, limits)| {
+                Ok(PathThresholds {
+                    prefix: prefix.into(),
+                    thresholds: rules(limits)?,
+                })
+            })
+            .collect::<Result<_, ProjectConfigError>>()?;
+        Ok((defaults, by_path))
+    }
+}
+
+fn rules(limits: &BTreeMap<String, f64>) -> Result<Vec<Threshold>, ProjectConfigError> {
+    limits
+        .iter()
+        .map(|(name, &limit)| {
+            // As with `--metrics`, the rolled-up scalars name the file
+            // total (see `metric_set_key_for`).
+            let spelled = match name.as_str() {
+                "cyclomatic" | "cognitive" => format!("{name}.sum"),
+                _ => name.clone(),
+            };
+            let selector: MetricSelector = spelled
+                .parse()
+                .map_err(|_| ProjectConfigError::UnknownMetric(name.clone()))?;
+            let doc = mehen_report::metric_metadata::metric_doc(selector.key.as_str())
+                .ok_or_else(|| ProjectConfigError::UnknownMetric(name.clone()))?;
+            Ok(Threshold::new(selector, limit, doc.polarity))
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+pub enum ProjectConfigError {
+    Io(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    /// A threshold names a metric the registry does not know.
+    UnknownMetric(String),
+}
+
+impl fmt::Display for ProjectConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, e) => write!(f, "config `{}`: {e}", path.display()),
+            Self::Parse(path, e) => write!(f, "config `{}` is not valid: {e}", path.display()),
+            Self::UnknownMetric(name) => write!(f, "config threshold on unknown metric `{name}`"),
+        }
+    }
+}
+
+impl std::error::Error for ProjectConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_metric_aliases() {
+        let config = ProjectConfig::parse(
+            "[metric-aliases]\n\"loc.lloc\" = \"Logical lines\"\ncyclomatic = \"McCabe\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.metric_aliases["loc.lloc"], "Logical lines");
+        assert_eq!(config.metric_aliases["cyclomatic"], "McCabe");
+        assert!(ProjectConfig::parse("[metric-alias]\n").is_err());
+    }
+
+    #[test]
+    fn parses_halstead_overrides() {
+        let config = ProjectConfig::parse(
+            "[halstead-overrides]\n\".\" = \"operand\"\n\"if\" = \"unknown\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.halstead_overrides["."], HalsteadClass::Operand);
+        assert_eq!(config.halstead_overrides["if"], HalsteadClass::Unknown);
+        assert!(ProjectConfig::parse("[halstead-overrides]\n\".\" = \"operands\"\n").is_err());
+    }
+
+    #[test]
+    fn parses_thresholds_per_path() {
+        let config = ProjectConfig::parse(
+            "[thresholds]\ncyclomatic = 30\n\n[path-thresholds.core]\n\"cognitive.max\" = 8\n\"mi.visual_studio\" = 40\n",
+        )
+        .unwrap();
+        let (defaults, by_path) = config.diff_thresholds().unwrap();
+        assert_eq!(defaults.len(), 1);
+        assert_eq!(defaults[0].selector.to_string(), "cyclomatic.sum");
+        assert_eq!(defaults[0].value, 30.0);
+        assert_eq!(by_path[0].prefix, "core");
+        let floor = &by_path[0].thresholds[1];
+        assert_eq!(floor.selector.to_string(), "mi.visual_studio");
+        assert!(floor.violated_by(39.0));
+
+        let typo = ProjectConfig::parse("[thresholds]\ncyclomatc = 30\n").unwrap();
+        assert!(matches!(
+            typo.diff_thresholds(),
+            Err(ProjectConfigError::UnknownMetric(name)) if name == "cyclomatc"
+        ));
+    }
+
+    #[test]
+    fn missing_file_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        let config = ProjectConfig::discover(dir.path()).unwrap();
+        assert!(config.metric_aliases.is_empty());
+
+        std::fs::write(dir.path().join(CONFIG_FILE), "[metric-aliases]\nabc = 1\n").unwrap();
+        assert!(matches!(
+            ProjectConfig::discover(dir.path()),
+            Err(ProjectConfigError::Parse(..))
+        ));
+    }
+}
@@ -0,0 +1,286 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! `mehen metrics --query`: count and locate the captures of a
+//! tree-sitter S-expression query.
+//!
+//! Only languages whose analyzer parses with tree-sitter (C, Go, Kotlin,
+//! PowerShell) have a grammar to compile the query against; the others
+//! use dedicated parsers (rust-analyzer, Oxc, Ruff, Prism, …) and are
+//! rejected with [`QueryError::NoGrammar`].
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use mehen_core::{Language, SourceFile, SourceSpan};
+
+#[derive(Debug, serde::Serialize)]
+pub struct QueryReport {
+    /// Number of captures per capture name, including names that
+    /// matched nothing.
+    pub counts: BTreeMap<String, usize>,
+    /// Every capture in document order.
+    pub captures: Vec<QueryMatch>,
+}
+
+impl QueryReport {
+    pub fn to_json(&self, pretty: bool) -> serde_json::Result<String> {
+        if pretty {
+            serde_json::to_string_pretty(self)
+        } else {
+            serde_json::to_string(self)
+        }
+    }
+
+    /// One row per capture name: its count and the start lines of the
+    /// captured nodes.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("| Capture | Count | Lines |\n|---|---:|---|\n");
+        for (name, count) in &self.counts {
+            let lines: Vec<String> = self
+                .captures
+                .iter()
+                .filter(|c| &c.capture == name)
+                .map(|c| c.span.start_line.to_string())
+                .collect();
+            out.push_str(&format!("| @{name} | {count} | {} |\n", lines.join(", ")));
+        }
+        out
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct QueryMatch {
+    pub capture: String,
+    pub kind: &'static str,
+    /// Kind of the enclosing node, e.g. `if_statement` for a captured
+    /// `block`; `null` for the root node.
+    pub parent_kind: Option<&'static str>,
+    /// Byte offsets locate the captured text exactly, for edits.
+    pub span: SourceSpan,
+}
+
+#[derive(Debug)]
+pub enum QueryError {
+    /// The language is not analyzed with tree-sitter in this build.
+    NoGrammar(Language),
+    /// The query failed to compile or the file failed to parse.
+    TreeSitter(mehen_tree_sitter::TreeSitterError),
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoGrammar(language) => write!(
+                f,
+                "--query needs a tree-sitter grammar; `{language:?}` is not parsed with \
+                 tree-sitter (supported: C, Go, Kotlin, PowerShell)"
+            ),
+            Self::TreeSitter(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// Run `query` over `source` and tally its captures.
+pub fn query_source(source: &SourceFile, query: &str) -> Result<QueryReport, QueryError> {
+    let grammar = grammar_for(source.language).ok_or(QueryError::NoGrammar(source.language))?;
+    let found = mehen_tree_sitter::query_captures(grammar, &source.text, query)
+        .map_err(QueryError::TreeSitter)?;
+    // Seed the tally with every declared name so captures that matched
+    // nothing still report `0`.
+    let mut counts: BTreeMap<String, usize> =
+        found.names.into_iter().map(|name| (name, 0)).collect();
+    let captures: Vec<QueryMatch> = found
+        .captures
+        .into_iter()
+        .map(|c| QueryMatch {
+            capture: c.name,
+            kind: c.kind,
+            parent_kind: c.parent_kind,
+            span: c.span,
+        })
+        .collect();
+    for capture in &captures {
+        *counts.entry(capture.capture.clone()).or_default() += 1;
+    }
+    Ok(QueryReport { counts, captures })
+}
+
+/// Version and ABI of one bundled tree-sitter grammar, for
+/// `mehen --versions`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrammarVersion {
+    pub language: Language,
+    /// `major.minor.patch` the grammar was generated with, or `None`
+    /// when it predates ABI 15 and carries no metadata.
+    pub version: Option<String>,
+    pub abi: usize,
+}
+
+/// Every tree-sitter grammar compiled into this build.
+pub fn grammar_versions() -> Vec<GrammarVersion> {
+    [
+        Language::C,
+        Language::Go,
+        Language::Kotlin,
+        Language::PowerShell,
+    ]
+    .into_iter()
+    .filter_map(|language| {
+        let grammar = grammar_for(language)?;
+        Some(GrammarVersion {
+            language,
+            version: grammar.metadata().map(|m| {
+                format!(
+                    "{}.{}.{}",
+                    m.major_version, m.minor_version, m.patch_version
+                )
+            }),
+            abi: grammar.abi_version(),
+        })
+    })
+    .collect()
+}
+
+/// Version of one bundled parser that is not a tree-sitter grammar, for
+/// `mehen --versions`. Resolved from `Cargo.lock` at build time; git
+/// dependencies report the tag they are pinned to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParserVersion {
+    pub language: Language,
+    /// Parser crate, e.g. `oxc` or `ra_ap_syntax`.
+    pub parser: &'static str,
+    /// Resolved version, or `unknown` when the lock file was missing.
+    pub version: &'static str,
+}
+
+/// Every non-tree-sitter parser compiled into this build. TypeScript's
+/// entry covers TSX, JavaScript and JSX as well.
+pub fn parser_versions() -> Vec<ParserVersion> {
+    [
+        (
+            cfg!(feature = "lang-python"),
+            Language::Python,
+            "ruff",
+            env!("MEHEN_RUFF_VERSION"),
+        ),
+        (
+            cfg!(feature = "lang-typescript"),
+            Language::TypeScript,
+            "oxc",
+            env!("MEHEN_OXC_VERSION"),
+        ),
+        (
+            cfg!(feature = "lang-php"),
+            Language::Php,
+            "mago-syntax",
+            env!("MEHEN_MAGO_SYNTAX_VERSION"),
+        ),
+        (
+            cfg!(feature = "lang-ruby"),
+            Language::Ruby,
+            "prism",
+            env!("MEHEN_RUBY_PRISM_VERSION"),
+        ),
+        (
+            cfg!(feature = "lang-rust"),
+            Language::Rust,
+            "ra_ap_syntax",
+            env!("MEHEN_RA_AP_SYNTAX_VERSION"),
+        ),
+        (
+            true,
+            Language::Markdown,
+            "pulldown-cmark",
+            env!("MEHEN_PULLDOWN_CMARK_VERSION"),
+        ),
+    ]
+    .into_iter()
+    .filter(|&(compiled, ..)| compiled)
+    .map(|(_, language, parser, version)| ParserVersion {
+        language,
+        parser,
+        version,
+    })
+    .collect()
+}
+
+fn grammar_for(language: Language) -> Option<tree_sitter::Language> {
+    match language {
+        #[cfg(feature = "lang-go")]
+        Language::Go => Some(mehen_go::__grammar_language()),
+        #[cfg(feature = "lang-c")]
+        Language::C => Some(mehen_c::__grammar_language()),
+        #[cfg(feature = "lang-kotlin")]
+        Language::Kotlin => Some(mehen_kotlin::__grammar_language()),
+        #[cfg(feature = "lang-powershell")]
+        Language::PowerShell => Some(mehen_powershell::__grammar_language()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn go(text: &str) -> SourceFile {
+        SourceFile::new("main.go".into(), Language::Go, text.to_string())
+    }
+
+    #[test]
+    fn counts_captures_per_name() {
+        let source = go("package main\n\nfunc a() {}\n\nfunc b() {\n\tif true {\n\t}\n}\n");
+        let report = query_source(
+            &source,
+            "(function_declaration name: (identifier) @name body: (block) @body) \
+             (if_statement) @if (for_statement) @for",
+        )
+        .unwrap();
+        assert_eq!(report.counts["name"], 2);
+        assert_eq!(report.counts["body"], 2);
+        assert_eq!(report.counts["if"], 1);
+        assert_eq!(report.counts["for"], 0);
+        let lines: Vec<u32> = report
+            .captures
+            .iter()
+            .filter(|c| c.capture == "name")
+            .map(|c| c.span.start_line)
+            .collect();
+        assert_eq!(lines, vec![3, 5]);
+    }
+
+    #[test]
+    fn at_sign_inside_a_predicate_string_is_not_a_capture() {
+        let source = go("package main\n\nfunc a() {}\n");
+        let report =
+            query_source(&source, r#"((identifier) @id (#not-eq? @id "user@host"))"#).unwrap();
+        assert_eq!(report.counts.keys().collect::<Vec<_>>(), vec!["id"]);
+        assert_eq!(report.counts["id"], 1);
+    }
+
+    #[test]
+    fn captures_carry_byte_offsets_and_parent_kind() {
+        let text = "package main\n\nfunc a() {\n\tif x {\n\t\ty()\n\t}\n}\n";
+        let report = query_source(&go(text), "(if_statement consequence: (block) @then)").unwrap();
+        let then = &report.captures[0];
+        let (start, end) = (then.span.start_byte as usize, then.span.end_byte as usize);
+        assert_eq!(&text[start..end], "{\n\t\ty()\n\t}");
+        assert_eq!(then.kind, "block");
+        assert_eq!(then.parent_kind, Some("if_statement"));
+    }
+
+    #[test]
+    fn rejects_invalid_query_and_non_tree_sitter_languages() {
+        assert!(matches!(
+            query_source(&go("package main\n"), "(not_a_node) @x"),
+            Err(QueryError::TreeSitter(_))
+        ));
+        let rust = SourceFile::new("lib.rs".into(), Language::Rust, "fn f() {}\n".into());
+        assert!(matches!(
+            query_source(&rust, "(block) @b"),
+            Err(QueryError::NoGrammar(Language::Rust))
+        ));
+    }
+}
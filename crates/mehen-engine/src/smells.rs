@@ -0,0 +1,319 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! `mehen metrics --smells`: per-function boolean flags derived from
+//! the published metrics and a set of thresholds.
+//!
+//! Each flag fires when the function's own value is strictly above its
+//! threshold. The defaults follow common linter limits (Sonar's
+//! cognitive 15, ESLint's `max-depth` 4 and `max-params`-style 5); any
+//! of them can be overridden with `--smell-threshold NAME=VALUE`.
+
+use std::fmt;
+
+use mehen_core::{MetricKey, MetricSpace, SpaceKind, keys};
+use serde::Serialize;
+
+/// Limits above which each smell fires.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SmellThresholds {
+    /// `loc.sloc` of the function.
+    pub long_function: f64,
+    /// `nargs` of the function.
+    pub too_many_params: f64,
+    /// `cognitive` of the function.
+    pub high_cognitive: f64,
+    /// `nexit` of the function.
+    pub too_many_exits: f64,
+    /// `max_nesting` of the function.
+    pub deeply_nested: f64,
+}
+
+impl Default for SmellThresholds {
+    fn default() -> Self {
+        Self {
+            long_function: 60.0,
+            too_many_params: 5.0,
+            high_cognitive: 15.0,
+            too_many_exits: 4.0,
+            deeply_nested: 4.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SmellThresholdError {
+    /// Not of the form `NAME=VALUE`.
+    Malformed(String),
+    UnknownSmell(String),
+    InvalidValue(String),
+}
+
+impl fmt::Display for SmellThresholdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed(spec) => write!(
+                f,
+                "invalid --smell-threshold '{spec}'; expected NAME=VALUE, e.g. long_function=80"
+            ),
+            Self::UnknownSmell(name) => write!(
+                f,
+                "unknown smell '{name}'; use long_function, too_many_params, high_cognitive, \
+                 too_many_exits or deeply_nested"
+            ),
+            Self::InvalidValue(value) => {
+                write!(
+                    f,
+                    "invalid --smell-threshold value '{value}'; expected a number"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SmellThresholdError {}
+
+impl SmellThresholds {
+    /// Apply one `NAME=VALUE` override.
+    pub fn set(&mut self, spec: &str) -> Result<(), SmellThresholdError> {
+        let Some((name, value)) = spec.split_once('=') else {
+            return Err(SmellThresholdError::Malformed(spec.to_string()));
+        };
+        let value: f64 = value
+            .trim()
+            .parse()
+            .map_err(|_| SmellThresholdError::InvalidValue(value.trim().to_string()))?;
+        let slot = match name.trim() {
+            "long_function" => &mut self.long_function,
+            "too_many_params" => &mut self.too_many_params,
+            "high_cognitive" => &mut self.high_cognitive,
+            "too_many_exits" => &mut self.too_many_exits,
+            "deeply_nested" => &mut self.deeply_nested,
+            other => return Err(SmellThresholdError::UnknownSmell(other.to_string())),
+        };
+        *slot = value;
+        Ok(())
+    }
+}
+
+/// The flags of one function.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Smells {
+    pub long_function: bool,
+    pub too_many_params: bool,
+    pub high_cognitive: bool,
+    pub too_many_exits: bool,
+    pub deeply_nested: bool,
+}
+
+impl Smells {
+    pub fn any(&self) -> bool {
+        self.long_function
+            || self.too_many_params
+            || self.high_cognitive
+            || self.too_many_exits
+            || self.deeply_nested
+    }
+
+    fn names(&self) -> Vec<&'static str> {
+        [
+            (self.long_function, "long_function"),
+            (self.too_many_params, "too_many_params"),
+            (self.high_cognitive, "high_cognitive"),
+            (self.too_many_exits, "too_many_exits"),
+            (self.deeply_nested, "deeply_nested"),
+        ]
+        .into_iter()
+        .filter_map(|(set, name)| set.then_some(name))
+        .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FunctionSmells {
+    pub name: Option<String>,
+    pub line: u32,
+    #[serde(flatten)]
+    pub smells: Smells,
+}
+
+/// Flags for every function space under `root`, in document order.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SmellsReport {
+    pub functions: Vec<FunctionSmells>,
+}
+
+impl SmellsReport {
+    pub fn from_space(root: &MetricSpace, thresholds: &SmellThresholds) -> Self {
+        let mut functions = Vec::new();
+        root.visit(&mut |space, _| {
+            if matches!(space.kind, SpaceKind::Function) {
+                functions.push(FunctionSmells {
+                    name: space.name.clone(),
+                    line: space.span.start_line,
+                    smells: smells_of(space, thresholds),
+                });
+            }
+        });
+        Self { functions }
+    }
+
+    pub fn to_json(&self, pretty: bool) -> serde_json::Result<String> {
+        if pretty {
+            serde_json::to_string_pretty(self)
+        } else {
+            serde_json::to_string(self)
+        }
+    }
+
+    /// One row per function that has at least one smell.
+    pub fn to_markdown(&self) -> String {
+        let flagged: Vec<&FunctionSmells> =
+            self.functions.iter().filter(|f| f.smells.any()).collect();
+        if flagged.is_empty() {
+            return "No smells found.\n".to_string();
+        }
+        let mut out = String::from("| Function | Line | Smells |\n|---|---:|---|\n");
+        for f in flagged {
+            out.push_str(&format!(
+                "| `{}` | {} | {} |\n",
+                f.name.as_deref().unwrap_or("<anonymous>"),
+                f.line,
+                f.smells.names().join(", ")
+            ));
+        }
+        out
+    }
+}
+
+fn smells_of(space: &MetricSpace, thresholds: &SmellThresholds) -> Smells {
+    let value = |key: &str| {
+        space
+            .metrics
+            .get(&MetricKey::new(key))
+            .map_or(0.0, |v| v.as_f64())
+    };
+    Smells {
+        long_function: value(keys::LOC_SLOC) > thresholds.long_function,
+        too_many_params: value(keys::NARGS) > thresholds.too_many_params,
+        high_cognitive: value(keys::COGNITIVE) > thresholds.high_cognitive,
+        too_many_exits: value(keys::NEXIT) > thresholds.too_many_exits,
+        deeply_nested: value(keys::MAX_NESTING) > thresholds.deeply_nested,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mehen_core::{SourceSpan, SpaceId};
+
+    fn smells_for(pairs: &[(&str, i64)]) -> Smells {
+        let mut unit = MetricSpace::new(SpaceId(0), SpaceKind::Unit, SourceSpan::new(0, 10, 1, 5));
+        let mut function = MetricSpace::new(
+            SpaceId(1),
+            SpaceKind::Function,
+            SourceSpan::new(0, 10, 2, 4),
+        );
+        function.name = Some("f".to_string());
+        for &(key, value) in pairs {
+            function.metrics.insert(key, value);
+        }
+        unit.spaces.push(function);
+        let report = SmellsReport::from_space(&unit, &SmellThresholds::default());
+        assert_eq!(report.functions.len(), 1);
+        assert_eq!(report.functions[0].line, 2);
+        report.functions[0].smells
+    }
+
+    #[test]
+    fn nothing_fires_at_the_thresholds() {
+        let smells = smells_for(&[
+            ("loc.sloc", 60),
+            ("nargs", 5),
+            ("cognitive", 15),
+            ("nexit", 4),
+            ("max_nesting", 4),
+        ]);
+        assert_eq!(smells, Smells::default());
+        assert!(!smells.any());
+    }
+
+    #[test]
+    fn long_function_fires_alone() {
+        let smells = smells_for(&[("loc.sloc", 61)]);
+        assert_eq!(
+            smells,
+            Smells {
+                long_function: true,
+                ..Smells::default()
+            }
+        );
+    }
+
+    #[test]
+    fn too_many_params_fires_alone() {
+        let smells = smells_for(&[("nargs", 6)]);
+        assert_eq!(
+            smells,
+            Smells {
+                too_many_params: true,
+                ..Smells::default()
+            }
+        );
+    }
+
+    #[test]
+    fn high_cognitive_fires_alone() {
+        let smells = smells_for(&[("cognitive", 16)]);
+        assert_eq!(
+            smells,
+            Smells {
+                high_cognitive: true,
+                ..Smells::default()
+            }
+        );
+    }
+
+    #[test]
+    fn too_many_exits_fires_alone() {
+        let smells = smells_for(&[("nexit", 5)]);
+        assert_eq!(
+            smells,
+            Smells {
+                too_many_exits: true,
+                ..Smells::default()
+            }
+        );
+    }
+
+    #[test]
+    fn deeply_nested_fires_alone() {
+        let smells = smells_for(&[("max_nesting", 5)]);
+        assert_eq!(
+            smells,
+            Smells {
+                deeply_nested: true,
+                ..Smells::default()
+            }
+        );
+    }
+
+    #[test]
+    fn thresholds_can_be_overridden() {
+        let mut thresholds = SmellThresholds::default();
+        thresholds.set("long_function = 80").unwrap();
+        assert_eq!(thresholds.long_function, 80.0);
+        assert_eq!(
+            thresholds.set("long_fn=80"),
+            Err(SmellThresholdError::UnknownSmell("long_fn".to_string()))
+        );
+        assert_eq!(
+            thresholds.set("long_function"),
+            Err(SmellThresholdError::Malformed("long_function".to_string()))
+        );
+        assert_eq!(
+            thresholds.set("nargs=x").unwrap_err().to_string(),
+            "invalid --smell-threshold value 'x'; expected a number"
+        );
+    }
+}
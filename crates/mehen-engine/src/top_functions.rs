@@ -0,0 +1,201 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Function-level ranking for `mehen top-offenders --top N`.
+//!
+//! Every analyzed file offers its `Function` spaces to a shared
+//! [`TopFunctions`], which keeps only the `N` worst seen so far in a
+//! bounded min-heap, so memory stays `O(N)` however large the run is.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::path::{Path, PathBuf};
+
+use mehen_core::{MetricSet, MetricSpace, SpaceKind};
+
+use crate::metric_selector::{MetricSelector, Polarity, read_metric};
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub(crate) struct FunctionOffender {
+    pub(crate) path: PathBuf,
+    pub(crate) name: Option<String>,
+    pub(crate) line: u32,
+    pub(crate) value: f64,
+    /// The function's full metric set, kept only for
+    /// `--output-template` placeholders beyond the ranked value.
+    #[serde(skip)]
+    pub(crate) metrics: Option<MetricSet>,
+}
+
+/// Heap entry: orders by how bad the value is under the selector's
+/// polarity, then by path and line so ties resolve the same way on
+/// every run regardless of which thread finished first.
+struct Ranked {
+    offender: FunctionOffender,
+    polarity: Polarity,
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let badness = self.offender.value.total_cmp(&other.offender.value);
+        let badness = match self.polarity {
+            // Neutral metrics rank largest first, like a plain sort.
+            Polarity::LowerIsBetter | Polarity::Neutral => badness,
+            Polarity::HigherIsBetter => badness.reverse(),
+        };
+        badness
+            .then_with(|| other.offender.path.cmp(&self.offender.path))
+            .then_with(|| other.offender.line.cmp(&self.offender.line))
+    }
+}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ranked {}
+
+pub(crate) struct TopFunctions {
+    limit: usize,
+    selector: MetricSelector,
+    keep_metrics: bool,
+    heap: BinaryHeap<Reverse<Ranked>>,
+}
+
+impl TopFunctions {
+    pub(crate) fn new(limit: usize, selector: MetricSelector) -> Self {
+        Self {
+            limit,
+            selector,
+            keep_metrics: false,
+            heap: BinaryHeap::with_capacity(limit.saturating_add(1)),
+        }
+    }
+
+    /// Keep each retained function's metric set on its
+    /// [`FunctionOffender`].
+    pub(crate) fn with_metrics(mut self) -> Self {
+        self.keep_metrics = true;
+        self
+    }
+
+    pub(crate) fn selector(&self) -> &MetricSelector {
+        &self.selector
+    }
+
+    /// Offer every `Function` space under `root`, evicting the least
+    /// bad entry whenever the heap grows past the limit.
+    pub(crate) fn offer(&mut self, path: &Path, root: &MetricSpace) {
+        if self.limit == 0 {
+            return;
+        }
+        root.visit(&mut |space, _| {
+            if !matches!(space.kind, SpaceKind::Function) {
+                return;
+            }
+            self.heap.push(Reverse(Ranked {
+                offender: FunctionOffender {
+                    path: path.to_path_buf(),
+                    name: space.name.clone(),
+                    line: space.span.start_line,
+                    value: read_metric(space, &self.selector),
+                    metrics: self.keep_metrics.then(|| space.metrics.clone()),
+                },
+                polarity: self.selector.polarity,
+            }));
+            if self.heap.len() > self.limit {
+                self.heap.pop();
+            }
+        });
+    }
+
+    /// The retained functions, worst first.
+    pub(crate) fn into_sorted(self) -> Vec<FunctionOffender> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(ranked)| ranked.offender)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mehen_core::{SourceSpan, SpaceId};
+
+    fn unit_with(values: &[(u32, f64)]) -> MetricSpace {
+        let mut root = MetricSpace::new(SpaceId(0), SpaceKind::Unit, SourceSpan::empty());
+        for (i, &(line, value)) in values.iter().enumerate() {
+            let mut f = MetricSpace::new(
+                SpaceId(i as u32 + 1),
+                SpaceKind::Function,
+                SourceSpan::new(0, 0, line, line),
+            );
+            f.metrics.insert("cognitive.sum", value);
+            root.spaces.push(f);
+        }
+        root
+    }
+
+    fn cognitive(polarity: Polarity) -> MetricSelector {
+        MetricSelector {
+            name: "cognitive",
+            label: "Cognitive".into(),
+            polarity,
+        }
+    }
+
+    #[test]
+    fn keeps_the_worst_n_sorted_descending() {
+        let mut top = TopFunctions::new(3, cognitive(Polarity::LowerIsBetter));
+        top.offer(Path::new("a.rs"), &unit_with(&[(1, 4.0), (5, 9.0)]));
+        top.offer(
+            Path::new("b.rs"),
+            &unit_with(&[(1, 1.0), (7, 12.0), (9, 6.0)]),
+        );
+        let values: Vec<(String, u32, f64)> = top
+            .into_sorted()
+            .into_iter()
+            .map(|f| (f.path.display().to_string(), f.line, f.value))
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                ("b.rs".to_string(), 7, 12.0),
+                ("a.rs".to_string(), 5, 9.0),
+                ("b.rs".to_string(), 9, 6.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn higher_is_better_keeps_the_lowest() {
+        let mut top = TopFunctions::new(1, cognitive(Polarity::HigherIsBetter));
+        top.offer(Path::new("a.rs"), &unit_with(&[(1, 4.0), (5, 9.0)]));
+        let worst = top.into_sorted();
+        assert_eq!(worst.len(), 1);
+        assert_eq!(worst[0].value, 4.0);
+    }
+
+    #[test]
+    fn ties_break_on_path_then_line() {
+        let mut top = TopFunctions::new(2, cognitive(Polarity::LowerIsBetter));
+        top.offer(Path::new("b.rs"), &unit_with(&[(1, 3.0)]));
+        top.offer(Path::new("a.rs"), &unit_with(&[(8, 3.0), (2, 3.0)]));
+        let kept: Vec<(String, u32)> = top
+            .into_sorted()
+            .into_iter()
+            .map(|f| (f.path.display().to_string(), f.line))
+            .collect();
+        assert_eq!(kept, vec![("a.rs".to_string(), 2), ("a.rs".to_string(), 8)]);
+    }
+}
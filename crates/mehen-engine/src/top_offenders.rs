@@ -11,7 +11,7 @@
 use std::collections::HashSet;
 use std::sync::Arc;
 
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 use globset::{Glob, GlobSet, GlobSetBuilder};
 
 use mehen_core::{
@@ -19,7 +19,7 @@ use mehen_core::{
 };
 use mehen_metrics::{MetricSelector, SelectorAggregator};
 
-use crate::detection::detect_language;
+use crate::detection::{detect_language, language_from_modeline};
 use crate::registry::AnalyzerRegistry;
 use mehen_core::{TopOffenderEntry, TopOffendersInput, TopOffendersReport};
 
@@ -54,6 +54,7 @@ pub fn rank_top_offenders(input: TopOffendersInput) -> TopOffendersReport {
             let Ok(text) = std::fs::read_to_string(entry.as_std_path()) else {
                 continue;
             };
+            let language = language_from_modeline(&text).unwrap_or(language);
             let Some(analyzer) = registry.analyzer_for(language) else {
                 // Language detected but no analyzer registered (the
                 // owning crate is feature-gated off in this build).
@@ -65,9 +66,10 @@ pub fn rank_top_offenders(input: TopOffendersInput) -> TopOffendersReport {
                 continue;
             };
             let source = SourceFile::new(entry.clone(), language, text);
-            let Ok(analysis) = analyzer.analyze(&source, &input.config) else {
+            let Ok(mut analysis) = analyzer.analyze(&source, &input.config) else {
                 continue;
             };
+            crate::finalize_analysis(&mut analysis.root, &input.config);
             // Migrated analyzers can return `Ok(...)` with a partial
             // tree alongside an `Error`/`Fatal` diagnostic when the
             // file doesn't parse cleanly. Per §9.3 those analyses are
@@ -133,14 +135,18 @@ fn record_unavailable(
 /// spellings of the same file collapse. Falls back to the original
 /// path when canonicalize fails — better than silently treating two
 /// "different" un-canonicalize-able paths as the same file.
-fn canonical_key(path: &Utf8PathBuf) -> Utf8PathBuf {
+pub(crate) fn canonical_key(path: &Utf8PathBuf) -> Utf8PathBuf {
     match std::fs::canonicalize(path.as_std_path()) {
         Ok(canon) => Utf8PathBuf::try_from(canon).unwrap_or_else(|_| path.clone()),
         Err(_) => path.clone(),
     }
 }
 
-fn walk_paths(root: &Utf8PathBuf, include: &[String], exclude: &[String]) -> Vec<Utf8PathBuf> {
+pub(crate) fn walk_paths(
+    root: &Utf8PathBuf,
+    include: &[String],
+    exclude: &[String],
+) -> Vec<Utf8PathBuf> {
     if !root.exists() {
         return Vec::new();
     }
@@ -219,7 +225,8 @@ fn cmp_entries(
         let ord = match polarity {
             // Worst-first: larger value is more concerning, so a > b
             // should put `a` first → reverse the natural ordering.
-            Polarity::HigherIsWorse => base.reverse(),
+            // Neutral metrics have no worst end; list largest first.
+            Polarity::HigherIsWorse | Polarity::Neutral => base.reverse(),
             // Worst-first: smaller value is more concerning, so a < b
             // should put `a` first → use the natural ordering.
             Polarity::HigherIsBetter => base,
@@ -233,13 +240,16 @@ fn cmp_entries(
 
 /// Resolve a metric's "higher is worse / better" polarity from its
 /// key. Maintainability-index variants (`mi.*`) are higher-is-better;
-/// every other metric the engine publishes (cyclomatic, cognitive,
-/// loc.*, halstead.*, abc, nom, nargs, nexit, npa, npm, wmc) is
-/// higher-is-worse. This mirrors the legacy `KNOWN_METRICS` catalog
-/// and the rewrite plan §5.1 metric contract.
+/// `halstead.operator_operand_ratio` is neutral; every other metric the
+/// engine publishes (cyclomatic, cognitive, loc.*, halstead.*, abc,
+/// nom, nargs, nexit, npa, npm, wmc) is higher-is-worse. This mirrors
+/// the legacy `KNOWN_METRICS` catalog and the rewrite plan §5.1 metric
+/// contract.
 fn default_polarity_for(selector: &MetricSelector) -> Polarity {
     if selector.key.as_str().starts_with("mi.") || selector.key.as_str() == "mi" {
         Polarity::HigherIsBetter
+    } else if selector.key.as_str() == "halstead.operator_operand_ratio" {
+        Polarity::Neutral
     } else {
         Polarity::HigherIsWorse
     }
@@ -301,18 +311,28 @@ fn suffixed_lookup(
 // entry point above. Names that overlap with the post-1.0 surface
 // (`MetricSelector`, `read_metric`) are imported under aliases.
 
+use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::fmt;
 use std::io::Write;
 use std::path::PathBuf;
 use std::process;
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
 use std::thread::available_parallelism;
 
-use crate::concurrent_files::{ConcurrentRunner, FilesData, mk_globset};
+use mehen_core::{AnalysisConfig, MetricSpace, SpaceKind, keys};
+
+use crate::baseline::Baseline;
+use crate::concurrent_files::{ConcurrentRunner, FilesData, mehenignore_patterns, mk_globset};
+use crate::duplicates::{DuplicateGroup, DuplicateIndex};
 use crate::metric_selector::{
-    MetricSelector as CliMetricSelector, Polarity as SelectorPolarity, parse_metric_selectors,
-    read_metric as read_selector_metric,
+    MetricSelector as CliMetricSelector, Polarity as SelectorPolarity, apply_label_aliases,
+    parse_metric_selectors, read_metric as read_selector_metric,
 };
+use crate::output_template::OutputTemplate;
+use crate::project_config::ProjectConfig;
+use crate::top_functions::{FunctionOffender, TopFunctions};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 pub(crate) enum TopOffendersFormat {
@@ -333,7 +353,7 @@ pub struct TopOffendersOpts {
     #[clap(
         long = "metric",
         short = 'M',
-        required = true,
+        required_unless_present = "duplicates",
         num_args = 1,
         allow_hyphen_values = true
     )]
@@ -363,6 +383,96 @@ pub struct TopOffendersOpts {
     #[clap(long, short)]
     language_type: Option<String>,
 
+    /// Only analyze files whose detected language is in this
+    /// comma-separated list (e.g. `go,rust`). Uses the same detection as
+    /// the rest of the command, so it also honors `--language-type`; the
+    /// extension must match before the file is read.
+    #[clap(long, value_delimiter = ',', value_name = "LANGS")]
+    lang_filter: Vec<String>,
+
+    /// Rank individual functions instead of files: print the N worst
+    /// functions across the whole run, with their path and line.
+    #[clap(long, value_name = "N")]
+    top: Option<usize>,
+
+    /// Instead of ranking, report groups of functions with identical or
+    /// near-identical bodies (token 5-gram similarity of 90% or more)
+    /// across every analyzed file. Bodies under 30 tokens are ignored.
+    #[clap(long, conflicts_with = "top")]
+    duplicates: bool,
+
+    /// Metric used by `--top`. Defaults to the first `--metric`.
+    #[clap(
+        long,
+        requires = "top",
+        allow_hyphen_values = true,
+        value_name = "METRIC"
+    )]
+    top_by: Option<String>,
+
+    /// Exit with code 2 when any file's primary (first) metric is worse
+    /// than this value — above it for lower-is-better metrics, below it
+    /// for higher-is-better ones. The report is still printed.
+    #[clap(long, value_name = "VALUE")]
+    fail_above: Option<f64>,
+
+    /// With `--fail-above`, stop dispatching new files as soon as one
+    /// crosses the limit. The report then only covers the files
+    /// analyzed so far.
+    #[clap(long, requires = "fail_above")]
+    fail_fast: bool,
+
+    /// List the selected offenders by path (then line, with `--top`)
+    /// instead of worst first, so CI artifacts diff cleanly between
+    /// runs. Which offenders are selected does not change.
+    #[clap(long)]
+    sorted_output: bool,
+
+    /// With `--top`, print one line per function from this template
+    /// instead of the table or JSON, e.g.
+    /// `{path}:{line} {name} cyc={cyclomatic} cog={cognitive}`.
+    /// Placeholders: `path`, `line`, `name`, `value` (the ranked
+    /// metric) and any `--metric` name. `{{` and `}}` are literal braces.
+    /// Not available with `--duplicates`.
+    #[clap(
+        long,
+        requires = "top",
+        conflicts_with = "duplicates",
+        value_name = "TEMPLATE"
+    )]
+    output_template: Option<String>,
+
+    /// Analyze every file twice and fail (exit 1) if the two metric
+    /// trees differ, logging the first difference per file. A harness
+    /// for catching nondeterminism in the analyzers.
+    #[clap(long, hide = true)]
+    verify: bool,
+
+    /// After the report, list on stderr every file that was not
+    /// analyzed and why: unsupported extension, binary or non-UTF-8
+    /// content, unreadable, or failed analysis.
+    #[clap(long)]
+    report_skipped: bool,
+
+    /// Walk every file under the given directories, not only those with
+    /// a known source extension. Implied by `--language-type` and
+    /// `--report-skipped`.
+    #[clap(long)]
+    all_files: bool,
+
+    /// Skip files without a single function body or top-level
+    /// statement, such as Rust files of trait and type declarations,
+    /// TypeScript `.d.ts` files or C headers. Their near-zero complexity
+    /// would otherwise drag averages down.
+    #[clap(long)]
+    exclude_decls_only: bool,
+
+    /// Also write every analyzed file's metrics to this baseline file,
+    /// for later comparison with `mehen diff --baseline`. Pass paths
+    /// relative to the repository root so the keys match.
+    #[clap(long, value_name = "FILE")]
+    baseline_write: Option<PathBuf>,
+
     /// One or more files or directories to analyze.
     #[clap(required = true, num_args = 1..)]
     paths: Vec<PathBuf>,
@@ -371,7 +481,7 @@ pub struct TopOffendersOpts {
 #[derive(Debug, Clone, serde::Serialize)]
 struct CliMetricValue {
     name: &'static str,
-    label: &'static str,
+    label: Cow<'static, str>,
     value: f64,
 }
 
@@ -381,53 +491,193 @@ struct FileOffender {
     metrics: Vec<CliMetricValue>,
 }
 
+/// Files left out of a run and why, shared across workers.
+type SkippedFiles = Arc<Mutex<Vec<(PathBuf, SkipReason)>>>;
+
+/// Why `act_on_file` left a file out, for `--report-skipped`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SkipReason {
+    NonUtf8Path,
+    UnsupportedLanguage,
+    Unreadable,
+    Binary,
+    InvalidUtf8,
+    NoAnalyzer,
+    AnalysisFailed,
+    DeclarationsOnly,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::NonUtf8Path => "path is not valid UTF-8",
+            Self::UnsupportedLanguage => "unsupported language",
+            Self::Unreadable => "could not be read",
+            Self::Binary => "binary file",
+            Self::InvalidUtf8 => "not valid UTF-8",
+            Self::NoAnalyzer => "analyzer not built in",
+            Self::AnalysisFailed => "analysis failed",
+            Self::DeclarationsOnly => "declarations only",
+        })
+    }
+}
+
 struct TopOffendersCfg {
     selectors: Vec<CliMetricSelector>,
     language_override: Option<Language>,
+    /// `--lang-filter`; empty means every language.
+    lang_filter: Vec<Language>,
     registry: Arc<AnalyzerRegistry>,
     results: Arc<Mutex<Vec<FileOffender>>>,
+    /// Collects unit metrics for `--baseline-write`; `None` otherwise.
+    baseline: Option<Arc<Mutex<Baseline>>>,
+    /// Bounded heap of the worst functions for `--top`; `None` otherwise.
+    top_functions: Option<Arc<Mutex<TopFunctions>>>,
+    /// Function fingerprints for `--duplicates`; `None` otherwise.
+    duplicates: Option<Arc<Mutex<DuplicateIndex>>>,
+    /// `--fail-above` limit for the primary metric.
+    fail_above: Option<f64>,
+    /// Raised when a file crosses `fail_above`. Shared with the runner
+    /// as its stop flag under `--fail-fast`.
+    violation: Arc<AtomicBool>,
+    /// `--verify`: re-analyze each file and compare.
+    verify: bool,
+    /// Raised when `--verify` finds a file whose metrics differ
+    /// between the two parses.
+    unstable: Arc<AtomicBool>,
+    /// Files left out and why, for `--report-skipped`; `None` otherwise.
+    skipped: Option<SkippedFiles>,
+    /// `--exclude-decls-only`.
+    exclude_decls_only: bool,
+    /// Settings every file is analyzed with.
+    analysis_config: AnalysisConfig,
+}
+
+impl TopOffendersCfg {
+    fn skip(&self, path: PathBuf, reason: SkipReason) -> std::io::Result<()> {
+        if let Some(skipped) = &self.skipped {
+            skipped
+                .lock()
+                .expect("top-offenders skipped mutex poisoned")
+                .push((path, reason));
+        }
+        Ok(())
+    }
 }
 
 fn act_on_file(path: PathBuf, cfg: &TopOffendersCfg) -> std::io::Result<()> {
     let utf8_path = match Utf8PathBuf::try_from(path.clone()) {
         Ok(p) => p,
-        Err(_) => return Ok(()),
+        Err(_) => return cfg.skip(path, SkipReason::NonUtf8Path),
     };
 
+    let Some(guessed) = cfg
+        .language_override
+        .or_else(|| detect_language(&utf8_path))
+    else {
+        return cfg.skip(path, SkipReason::UnsupportedLanguage);
+    };
+    // The language filter applies to the extension guess before the
+    // file is read, so a filtered-out tree costs no reads; a modeline
+    // cannot pull a file back in.
+    if !cfg.lang_filter.is_empty() && !cfg.lang_filter.contains(&guessed) {
+        return Ok(());
+    }
+
+    let bytes = match std::fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(_) => return cfg.skip(path, SkipReason::Unreadable),
+    };
+    let text = match String::from_utf8(bytes) {
+        Ok(s) => s,
+        Err(e) if e.as_bytes().contains(&0) => return cfg.skip(path, SkipReason::Binary),
+        Err(_) => return cfg.skip(path, SkipReason::InvalidUtf8),
+    };
+    // A `mehen-lang:` modeline refines the extension guess, not
+    // `--language`.
     let language = match cfg.language_override {
         Some(l) => l,
-        None => match detect_language(&utf8_path) {
-            Some(l) => l,
-            None => return Ok(()),
-        },
+        None => language_from_modeline(&text).unwrap_or(guessed),
     };
+    if !cfg.lang_filter.is_empty() && !cfg.lang_filter.contains(&language) {
+        return Ok(());
+    }
 
     let analyzer = match cfg.registry.analyzer_for(language) {
         Some(a) => a,
-        None => return Ok(()),
-    };
-
-    let text = match std::fs::read_to_string(&path) {
-        Ok(s) => s,
-        Err(_) => return Ok(()),
+        None => return cfg.skip(path, SkipReason::NoAnalyzer),
     };
 
     let source = SourceFile::new(utf8_path, language, text);
-    let analysis = match analyzer.analyze(&source, &mehen_core::AnalysisConfig::default()) {
+    let mut analysis = match analyzer.analyze(&source, &cfg.analysis_config) {
         Ok(a) => a,
-        Err(_) => return Ok(()),
+        Err(_) => return cfg.skip(path, SkipReason::AnalysisFailed),
     };
+    crate::finalize_analysis(&mut analysis.root, &cfg.analysis_config);
+    if cfg.exclude_decls_only && !has_code(&source.path, &analysis.root) {
+        return cfg.skip(path, SkipReason::DeclarationsOnly);
+    }
+    if cfg.verify {
+        let first = serde_json::to_string(&analysis.root).expect("metric tree is serializable");
+        let second = analyzer
+            .analyze(&source, &cfg.analysis_config)
+            .map(|mut a| {
+                crate::finalize_analysis(&mut a.root, &cfg.analysis_config);
+                serde_json::to_string(&a.root).expect("metric tree is serializable")
+            });
+        let mismatch = match &second {
+            Ok(second) => serialized_mismatch(&first, second),
+            Err(e) => Some(format!("second parse failed: {e}")),
+        };
+        if let Some(mismatch) = mismatch {
+            log::error!("{}: unstable metrics: {mismatch}", path.display());
+            cfg.unstable.store(true, AtomicOrdering::Relaxed);
+        }
+    }
 
     let metrics: Vec<CliMetricValue> = cfg
         .selectors
         .iter()
         .map(|sel| CliMetricValue {
             name: sel.name,
-            label: sel.label,
+            label: sel.label.clone(),
             value: read_selector_metric(&analysis.root, sel),
         })
         .collect();
 
+    if let (Some(limit), Some(primary)) = (cfg.fail_above, metrics.first())
+        && crosses_limit(primary.value, limit, cfg.selectors[0].polarity)
+    {
+        log::error!(
+            "{}: {} = {} crosses --fail-above {}",
+            path.display(),
+            primary.name,
+            format_value(primary.value),
+            format_value(limit)
+        );
+        cfg.violation.store(true, AtomicOrdering::Relaxed);
+    }
+
+    if let Some(top) = &cfg.top_functions {
+        top.lock()
+            .expect("top-offenders functions mutex poisoned")
+            .offer(&path, &analysis.root);
+    }
+
+    if let Some(duplicates) = &cfg.duplicates {
+        duplicates
+            .lock()
+            .expect("top-offenders duplicates mutex poisoned")
+            .offer(&path, source.language, &analysis.root, &source.text);
+    }
+
+    if let Some(baseline) = &cfg.baseline {
+        baseline
+            .lock()
+            .expect("top-offenders baseline mutex poisoned")
+            .insert(&path, analysis.root.metrics.clone());
+    }
+
     cfg.results
         .lock()
         .expect("top-offenders results mutex poisoned")
@@ -436,13 +686,70 @@ fn act_on_file(path: PathBuf, cfg: &TopOffendersCfg) -> std::io::Result<()> {
     Ok(())
 }
 
+/// How two serialized metric trees differ, or `None` when they are
+/// byte-identical. Key order counts: a map iterated in a different
+/// order is the nondeterminism `--verify` exists to catch.
+fn serialized_mismatch(first: &str, second: &str) -> Option<String> {
+    if first == second {
+        return None;
+    }
+    let parse = |json: &str| serde_json::from_str::<serde_json::Value>(json).ok();
+    let located = parse(first)
+        .zip(parse(second))
+        .and_then(|(a, b)| first_mismatch(&a, &b, "root"));
+    Some(located.unwrap_or_else(|| "same values serialized in a different key order".into()))
+}
+
+/// First place two metric trees disagree, as
+/// `root.spaces[0].metrics.cyclomatic: 3 != 4`. Objects are compared
+/// by key, so only values — not map iteration order — count; this
+/// only locates a difference [`serialized_mismatch`] has found.
+fn first_mismatch(a: &serde_json::Value, b: &serde_json::Value, at: &str) -> Option<String> {
+    use serde_json::Value;
+    match (a, b) {
+        (Value::Object(x), Value::Object(y)) => {
+            for (key, xv) in x {
+                let at = format!("{at}.{key}");
+                match y.get(key) {
+                    Some(yv) => {
+                        if let Some(mismatch) = first_mismatch(xv, yv, &at) {
+                            return Some(mismatch);
+                        }
+                    }
+                    None => return Some(format!("{at}: missing from the second parse")),
+                }
+            }
+            y.keys()
+                .find(|key| !x.contains_key(*key))
+                .map(|key| format!("{at}.{key}: missing from the first parse"))
+        }
+        (Value::Array(x), Value::Array(y)) if x.len() == y.len() => x
+            .iter()
+            .zip(y)
+            .enumerate()
+            .find_map(|(i, (xv, yv))| first_mismatch(xv, yv, &format!("{at}[{i}]"))),
+        (Value::Array(x), Value::Array(y)) => {
+            Some(format!("{at}: {} != {} entries", x.len(), y.len()))
+        }
+        _ if a == b => None,
+        _ => Some(format!("{at}: {a} != {b}")),
+    }
+}
+
+fn crosses_limit(value: f64, limit: f64, polarity: SelectorPolarity) -> bool {
+    match polarity {
+        SelectorPolarity::LowerIsBetter | SelectorPolarity::Neutral => value > limit,
+        SelectorPolarity::HigherIsBetter => value < limit,
+    }
+}
+
 fn cmp_offenders(a: &FileOffender, b: &FileOffender, selectors: &[CliMetricSelector]) -> Ordering {
     for (i, sel) in selectors.iter().enumerate() {
         let av = a.metrics.get(i).map(|m| m.value).unwrap_or(0.0);
         let bv = b.metrics.get(i).map(|m| m.value).unwrap_or(0.0);
         let base = av.total_cmp(&bv);
         let ord = match sel.polarity {
-            SelectorPolarity::LowerIsBetter => base.reverse(),
+            SelectorPolarity::LowerIsBetter | SelectorPolarity::Neutral => base.reverse(),
             SelectorPolarity::HigherIsBetter => base,
         };
         if ord != Ordering::Equal {
@@ -452,7 +759,47 @@ fn cmp_offenders(a: &FileOffender, b: &FileOffender, selectors: &[CliMetricSelec
     a.path.cmp(&b.path)
 }
 
-fn print_json_offenders(offenders: &[FileOffender]) {
+/// Whether the file has code to measure: a function or closure body
+/// with at least one logical line, or logical lines of its own outside
+/// every nested space, as in a top-level script. Signatures without a
+/// body (trait methods, `declare function`) either open no space or one
+/// with no LLOC, as does an empty `{}` body. TypeScript declaration
+/// files (`.d.ts`) and C headers (`.h`) hold only declarations, whose
+/// top-level lines are not code, so only function bodies count there.
+fn has_code(path: &Utf8Path, root: &MetricSpace) -> bool {
+    let lloc = |space: &MetricSpace| {
+        space
+            .metrics
+            .get(&MetricKey::new(keys::LOC_LLOC))
+            .map_or(0.0, |v| v.as_f64())
+    };
+    let mut found = false;
+    root.visit(&mut |space, _| {
+        found |=
+            matches!(space.kind, SpaceKind::Function | SpaceKind::Closure) && lloc(space) > 0.0;
+    });
+    let name = path.file_name().unwrap_or_default();
+    let declaration_file = [".d.ts", ".d.mts", ".d.cts", ".h"]
+        .iter()
+        .any(|suffix| name.ends_with(suffix));
+    let top_level = lloc(root) - root.spaces.iter().map(lloc).sum::<f64>();
+    found || (!declaration_file && top_level > 0.0)
+}
+
+/// The `--report-skipped` summary: a count line, then one
+/// `path: reason` line per file. Empty when nothing was skipped.
+fn skipped_summary(skipped: &[(PathBuf, SkipReason)]) -> String {
+    if skipped.is_empty() {
+        return String::new();
+    }
+    let mut out = format!("skipped {} file(s):\n", skipped.len());
+    for (path, reason) in skipped {
+        out.push_str(&format!("  {}: {reason}\n", path.display()));
+    }
+    out
+}
+
+fn print_json_offenders<T: serde::Serialize>(offenders: &[T]) {
     let json =
         serde_json::to_string_pretty(offenders).expect("offender list is always serializable");
     writeln!(std::io::stdout().lock(), "{json}").expect("failed to write to stdout");
@@ -497,7 +844,71 @@ fn print_markdown_offenders(offenders: &[FileOffender], selectors: &[CliMetricSe
     write!(std::io::stdout().lock(), "{out}").expect("failed to write to stdout");
 }
 
-fn format_value(v: f64) -> String {
+fn print_markdown_functions(functions: &[FunctionOffender], selector: &CliMetricSelector) {
+    let mut out = String::new();
+
+    if functions.is_empty() {
+        out.push_str("## Top Functions\n\nNo matching functions found.\n");
+        write!(std::io::stdout().lock(), "{out}").expect("failed to write to stdout");
+        return;
+    }
+
+    out.push_str(&format!("## Top Functions (by {})\n\n", selector.name));
+    out.push_str(&format!(
+        "| File | Line | Function | {} |\n",
+        selector.label
+    ));
+    out.push_str("|---|---:|---|---:|\n");
+    for f in functions {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            f.path.display(),
+            f.line,
+            f.name.as_deref().unwrap_or("<anonymous>"),
+            format_value(f.value)
+        ));
+    }
+
+    write!(std::io::stdout().lock(), "{out}").expect("failed to write to stdout");
+}
+
+fn print_markdown_duplicates(groups: &[DuplicateGroup]) {
+    let mut out = String::from("## Duplicate Functions\n\n");
+    if groups.is_empty() {
+        out.push_str("No duplicated functions found.\n");
+        write!(std::io::stdout().lock(), "{out}").expect("failed to write to stdout");
+        return;
+    }
+
+    out.push_str("| Group | Match | File | Line | Function | Tokens |\n");
+    out.push_str("|---:|---|---|---:|---|---:|\n");
+    for (i, group) in groups.iter().enumerate() {
+        let kind = if group.exact { "exact" } else { "near" };
+        for f in &group.functions {
+            out.push_str(&format!(
+                "| {} | {kind} | {} | {} | {} | {} |\n",
+                i + 1,
+                f.path.display(),
+                f.line,
+                f.name.as_deref().unwrap_or("<anonymous>"),
+                f.tokens
+            ));
+        }
+    }
+
+    write!(std::io::stdout().lock(), "{out}").expect("failed to write to stdout");
+}
+
+fn print_templated_functions(functions: &[FunctionOffender], template: &OutputTemplate) {
+    let mut out = String::new();
+    for f in functions {
+        out.push_str(&template.render(f));
+        out.push('\n');
+    }
+    write!(std::io::stdout().lock(), "{out}").expect("failed to write to stdout");
+}
+
+pub(crate) fn format_value(v: f64) -> String {
     if v.is_nan() {
         "NaN".to_string()
     } else if v == v.trunc() && v.abs() < 1e18 {
@@ -519,8 +930,21 @@ fn parse_language_override(raw: &str) -> Option<Language> {
 }
 
 pub fn run_top_offenders(opts: TopOffendersOpts) {
-    let selectors = parse_metric_selectors(&opts.metrics);
-    if selectors.is_empty() {
+    let mut selectors = if opts.metrics.is_empty() {
+        Vec::new()
+    } else {
+        parse_metric_selectors(&opts.metrics)
+    };
+    let project = match std::env::current_dir().map(|cwd| ProjectConfig::discover(&cwd)) {
+        Ok(Ok(project)) => project,
+        Ok(Err(e)) => {
+            log::error!("{e}");
+            process::exit(1);
+        }
+        Err(_) => ProjectConfig::default(),
+    };
+    apply_label_aliases(&mut selectors, &project.metric_aliases);
+    if selectors.is_empty() && !opts.duplicates {
         log::error!("No valid metrics selected. See `mehen top-offenders --help`.");
         process::exit(1);
     }
@@ -536,34 +960,187 @@ pub fn run_top_offenders(opts: TopOffendersOpts) {
         None => None,
     };
 
+    let mut lang_filter = Vec::with_capacity(opts.lang_filter.len());
+    for raw in opts
+        .lang_filter
+        .iter()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+    {
+        match parse_language_override(raw) {
+            Some(language) => lang_filter.push(language),
+            None => {
+                log::error!("Unknown language '{raw}' in --lang-filter.");
+                process::exit(1);
+            }
+        }
+    }
+
     let num_jobs = resolve_num_jobs(
         opts.num_jobs,
         available_parallelism().ok().map(|threads| threads.get()),
     );
 
     let include = mk_globset(opts.include);
-    let exclude = mk_globset(opts.exclude);
+    let mut exclude = opts.exclude;
+    if let Ok(cwd) = std::env::current_dir() {
+        exclude.extend(mehenignore_patterns(&cwd));
+    }
+    let exclude = mk_globset(exclude);
 
     let results: Arc<Mutex<Vec<FileOffender>>> = Arc::new(Mutex::new(Vec::new()));
     let registry = Arc::new(AnalyzerRegistry::default_set());
 
+    let baseline = opts
+        .baseline_write
+        .as_ref()
+        .map(|_| Arc::new(Mutex::new(Baseline::default())));
+
+    let template = match opts.output_template.as_deref().map(OutputTemplate::parse) {
+        Some(Ok(template)) => Some(template),
+        Some(Err(e)) => {
+            log::error!("{e}");
+            process::exit(1);
+        }
+        None => None,
+    };
+
+    let top_functions = match opts.top {
+        Some(limit) => {
+            let selector = match &opts.top_by {
+                Some(spec) => match parse_metric_selectors(std::slice::from_ref(spec)).pop() {
+                    Some(selector) => selector,
+                    None => {
+                        log::error!("Unknown --top-by metric '{spec}'.");
+                        process::exit(1);
+                    }
+                },
+                None => match selectors.first() {
+                    Some(selector) => selector.clone(),
+                    None => {
+                        log::error!("--top needs --metric or --top-by.");
+                        process::exit(1);
+                    }
+                },
+            };
+            let mut top = TopFunctions::new(limit, selector);
+            if template.as_ref().is_some_and(OutputTemplate::needs_metrics) {
+                top = top.with_metrics();
+            }
+            Some(Arc::new(Mutex::new(top)))
+        }
+        None => None,
+    };
+
+    let duplicates = opts
+        .duplicates
+        .then(|| Arc::new(Mutex::new(DuplicateIndex::default())));
+
+    let violation = Arc::new(AtomicBool::new(false));
+    let unstable = Arc::new(AtomicBool::new(false));
+    let skipped = opts
+        .report_skipped
+        .then(|| Arc::new(Mutex::new(Vec::new())));
+
     let cfg = TopOffendersCfg {
         selectors: selectors.clone(),
         language_override,
+        lang_filter,
         registry,
         results: results.clone(),
+        baseline: baseline.clone(),
+        top_functions: top_functions.clone(),
+        duplicates: duplicates.clone(),
+        fail_above: opts.fail_above,
+        violation: violation.clone(),
+        verify: opts.verify,
+        unstable: unstable.clone(),
+        skipped: skipped.clone(),
+        exclude_decls_only: opts.exclude_decls_only,
+        analysis_config: AnalysisConfig {
+            halstead_overrides: project.halstead_overrides,
+            ..AnalysisConfig::default()
+        },
     };
 
+    // `--language-type` analyzes any extension, and `--report-skipped`
+    // has to see the unsupported files to list them.
     let files_data = FilesData {
         include,
         exclude,
         paths: opts.paths,
+        source_only: !(opts.all_files || opts.report_skipped || language_override.is_some()),
     };
 
-    if let Err(e) = ConcurrentRunner::new(num_jobs, act_on_file).run(cfg, files_data) {
+    let mut runner = ConcurrentRunner::new(num_jobs, act_on_file);
+    if opts.fail_fast {
+        runner = runner.with_stop_flag(violation.clone());
+    }
+    if let Err(e) = runner.run(cfg, files_data) {
         log::error!("{e}");
         process::exit(1);
     }
+    if let Some(skipped) = skipped {
+        let mut skipped = Arc::try_unwrap(skipped)
+            .expect("skipped Arc still has outstanding references")
+            .into_inner()
+            .expect("skipped mutex poisoned");
+        skipped.sort_by(|a, b| a.0.cmp(&b.0));
+        eprint!("{}", skipped_summary(&skipped));
+    }
+    if unstable.load(AtomicOrdering::Relaxed) {
+        process::exit(1);
+    }
+
+    if let (Some(path), Some(baseline)) = (&opts.baseline_write, baseline) {
+        let baseline = Arc::try_unwrap(baseline)
+            .expect("baseline Arc still has outstanding references")
+            .into_inner()
+            .expect("baseline mutex poisoned");
+        if let Err(e) = baseline.write(path) {
+            log::error!("{e}");
+            process::exit(1);
+        }
+    }
+
+    if let Some(duplicates) = duplicates {
+        let groups = Arc::try_unwrap(duplicates)
+            .ok()
+            .expect("duplicates Arc still has outstanding references")
+            .into_inner()
+            .expect("duplicates mutex poisoned")
+            .into_groups();
+        match opts.output_format {
+            TopOffendersFormat::Json => print_json_offenders(&groups),
+            TopOffendersFormat::Markdown => print_markdown_duplicates(&groups),
+        }
+        if violation.load(AtomicOrdering::Relaxed) {
+            process::exit(2);
+        }
+        return;
+    }
+
+    if let Some(top) = top_functions {
+        let top = Arc::try_unwrap(top)
+            .ok()
+            .expect("top functions Arc still has outstanding references")
+            .into_inner()
+            .expect("top functions mutex poisoned");
+        let selector = top.selector().clone();
+        let mut functions = top.into_sorted();
+        if opts.sorted_output {
+            functions.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+        }
+        match (&template, opts.output_format) {
+            (Some(template), _) => print_templated_functions(&functions, template),
+            (None, TopOffendersFormat::Json) => print_json_offenders(&functions),
+            (None, TopOffendersFormat::Markdown) => print_markdown_functions(&functions, &selector),
+        }
+        if violation.load(AtomicOrdering::Relaxed) {
+            process::exit(2);
+        }
+        return;
+    }
 
     let mut offenders = Arc::try_unwrap(results)
         .expect("results Arc still has outstanding references")
@@ -572,11 +1149,18 @@ pub fn run_top_offenders(opts: TopOffendersOpts) {
 
     offenders.sort_by(|a, b| cmp_offenders(a, b, &selectors));
     offenders.truncate(opts.max_results);
+    if opts.sorted_output {
+        offenders.sort_by(|a, b| a.path.cmp(&b.path));
+    }
 
     match opts.output_format {
         TopOffendersFormat::Json => print_json_offenders(&offenders),
         TopOffendersFormat::Markdown => print_markdown_offenders(&offenders, &selectors),
     }
+
+    if violation.load(AtomicOrdering::Relaxed) {
+        process::exit(2);
+    }
 }
 
 #[cfg(test)]
@@ -1006,7 +1590,7 @@ mod tests {
     fn cli_selector(name: &'static str, polarity: SelectorPolarity) -> CliMetricSelector {
         CliMetricSelector {
             name,
-            label: name,
+            label: name.into(),
             polarity,
         }
     }
@@ -1018,7 +1602,7 @@ mod tests {
                 .iter()
                 .map(|(n, v)| CliMetricValue {
                     name: n,
-                    label: n,
+                    label: Cow::Borrowed(*n),
                     value: *v,
                 })
                 .collect(),
@@ -1174,4 +1758,33 @@ mod tests {
         let report = rank_top_offenders(input);
         assert!(report.analysis_errors.is_empty());
     }
+
+    #[test]
+    fn first_mismatch_names_the_differing_value() {
+        let a = serde_json::json!({"metrics": {"x": 1, "y": 2}, "spaces": [{"metrics": {"x": 3}}]});
+        let same =
+            serde_json::json!({"spaces": [{"metrics": {"x": 3}}], "metrics": {"y": 2, "x": 1}});
+        assert_eq!(first_mismatch(&a, &same, "root"), None);
+
+        let changed =
+            serde_json::json!({"metrics": {"x": 1, "y": 2}, "spaces": [{"metrics": {"x": 4}}]});
+        assert_eq!(
+            first_mismatch(&a, &changed, "root").as_deref(),
+            Some("root.spaces[0].metrics.x: 3 != 4")
+        );
+    }
+
+    #[test]
+    fn serialized_mismatch_catches_key_order() {
+        let a = r#"{"metrics":{"x":1,"y":2}}"#;
+        assert_eq!(serialized_mismatch(a, a), None);
+        assert_eq!(
+            serialized_mismatch(a, r#"{"metrics":{"y":2,"x":1}}"#).as_deref(),
+            Some("same values serialized in a different key order")
+        );
+        assert_eq!(
+            serialized_mismatch(a, r#"{"metrics":{"x":1,"y":3}}"#).as_deref(),
+            Some("root.metrics.y: 2 != 3")
+        );
+    }
 }
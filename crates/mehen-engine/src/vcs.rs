@@ -0,0 +1,303 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Version-control backends for `mehen diff`.
+//!
+//! The diff only needs three things from a repository: the files that
+//! changed between two revisions, a file's bytes at a revision, and a
+//! human label for the base revision. [`Vcs`] captures that surface so
+//! git (through `mehen-git`) and Mercurial (through the `hg` binary)
+//! share the same orchestration in `diff.rs`.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use mehen_git::{ChangeStatus, ChangedFile, GitError};
+
+/// Which backend `mehen diff --vcs` talks to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum VcsKind {
+    Git,
+    Hg,
+}
+
+impl VcsKind {
+    /// `(from, to)` used when neither `--from`/`--to` nor a CI context
+    /// names the revisions.
+    pub(crate) fn default_refs(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Git => ("main", "HEAD"),
+            Self::Hg => ("default", "."),
+        }
+    }
+
+    /// Open the repository containing the current directory.
+    pub(crate) fn open(self) -> Result<Box<dyn Vcs>, VcsError> {
+        Ok(match self {
+            Self::Git => Box::new(GitVcs(mehen_git::open_repo()?)),
+            Self::Hg => Box::new(HgVcs::open()?),
+        })
+    }
+}
+
+pub(crate) trait Vcs {
+    /// Files added, modified or deleted between `from` and `to`, with
+    /// repository-relative paths.
+    fn changed_files(&self, from: &str, to: &str) -> Result<Vec<ChangedFile>, VcsError>;
+
+    /// Contents of `path` at `rev`, or `None` when the file does not
+    /// exist there. A trailing run of newlines is collapsed to one, as
+    /// `mehen_git::read_blob` does.
+    fn read_blob(&self, rev: &str, path: &Path) -> Result<Option<Vec<u8>>, VcsError>;
+
+    /// [`read_blob`](Vcs::read_blob) for each `(rev, path)`, results in
+    /// request order, using up to `jobs` concurrent readers. Backends
+    /// without a cheap per-thread handle read serially.
+    fn read_blobs(&self, requests: &[(&str, &Path)], jobs: usize) -> Vec<BlobResult> {
+        let _ = jobs;
+        requests
+            .iter()
+            .map(|(rev, path)| self.read_blob(rev, path))
+            .collect()
+    }
+
+    /// Friendlier name for `rev` in the report header.
+    fn ref_label(&self, rev: &str) -> String {
+        rev.to_string()
+    }
+
+    /// Full commit id `rev` resolves to, or `None` when the backend
+    /// cannot tell.
+    fn commit_id(&self, rev: &str) -> Option<String> {
+        let _ = rev;
+        None
+    }
+
+    /// The underlying git repository, for git-only features such as the
+    /// `linguist-generated` attribute filter.
+    fn git_repo(&self) -> Option<&gix::Repository> {
+        None
+    }
+}
+
+pub(crate) type BlobResult = Result<Option<Vec<u8>>, VcsError>;
+
+#[derive(Debug)]
+pub(crate) enum VcsError {
+    Git(GitError),
+    Hg(String),
+}
+
+impl fmt::Display for VcsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Git(e) => write!(f, "{e}"),
+            Self::Hg(msg) => write!(f, "Mercurial error: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for VcsError {}
+
+impl From<GitError> for VcsError {
+    fn from(e: GitError) -> Self {
+        Self::Git(e)
+    }
+}
+
+pub(crate) struct GitVcs(pub(crate) gix::Repository);
+
+impl Vcs for GitVcs {
+    fn changed_files(&self, from: &str, to: &str) -> Result<Vec<ChangedFile>, VcsError> {
+        Ok(mehen_git::changed_files(&self.0, from, to)?)
+    }
+
+    fn read_blob(&self, rev: &str, path: &Path) -> Result<Option<Vec<u8>>, VcsError> {
+        Ok(mehen_git::read_blob(&self.0, rev, path)?)
+    }
+
+    /// Splits the requests into `jobs` contiguous chunks, each read on
+    /// its own thread with its own repository handle (`gix::Repository`
+    /// is `Send` but not `Sync`; clones share the object store).
+    fn read_blobs(&self, requests: &[(&str, &Path)], jobs: usize) -> Vec<BlobResult> {
+        let jobs = jobs.clamp(1, requests.len().max(1));
+        let chunk_len = requests.len().div_ceil(jobs).max(1);
+        std::thread::scope(|scope| {
+            let readers: Vec<_> = requests
+                .chunks(chunk_len)
+                .map(|chunk| {
+                    let repo = self.0.clone();
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|(rev, path)| Ok(mehen_git::read_blob(&repo, rev, path)?))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            readers
+                .into_iter()
+                .flat_map(|reader| reader.join().expect("blob reader panicked"))
+                .collect()
+        })
+    }
+
+    fn ref_label(&self, rev: &str) -> String {
+        mehen_git::friendly_ref_label(&self.0, rev)
+    }
+
+    fn commit_id(&self, rev: &str) -> Option<String> {
+        mehen_git::resolve_commit_id(&self.0, rev).ok()
+    }
+
+    fn git_repo(&self) -> Option<&gix::Repository> {
+        Some(&self.0)
+    }
+}
+
+/// Mercurial backend. Shells out to `hg` with `HGPLAIN` set so user
+/// configuration (aliases, colour, localized messages) cannot change
+/// the output being parsed.
+pub(crate) struct HgVcs {
+    root: PathBuf,
+}
+
+impl HgVcs {
+    fn open() -> Result<Self, VcsError> {
+        Ok(Self {
+            root: hg_root(None)?,
+        })
+    }
+}
+
+/// Root of the Mercurial repository containing `dir` (the current
+/// directory when `None`), as `hg root` reports it.
+pub(crate) fn hg_root(dir: Option<&Path>) -> Result<PathBuf, VcsError> {
+    let output = run_hg(dir, &["root"])?;
+    let root = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(PathBuf::from(root))
+}
+
+impl Vcs for HgVcs {
+    fn changed_files(&self, from: &str, to: &str) -> Result<Vec<ChangedFile>, VcsError> {
+        let output = run_hg(
+            Some(&self.root),
+            &[
+                "status",
+                "--rev",
+                from,
+                "--rev",
+                to,
+                "--modified",
+                "--added",
+                "--removed",
+                "--print0",
+            ],
+        )?;
+        Ok(parse_hg_status(&output.stdout))
+    }
+
+    fn read_blob(&self, rev: &str, path: &Path) -> Result<Option<Vec<u8>>, VcsError> {
+        // `path:` makes hg treat the argument literally rather than as
+        // a glob relative to the working directory.
+        let pattern = format!("path:{}", path.display());
+        let output = hg_command(Some(&self.root))
+            .args(["cat", "--rev", rev, &pattern])
+            .output()
+            .map_err(|e| VcsError::Hg(format!("failed to run hg: {e}")))?;
+        // `hg cat` exits 1 when no file matched at that revision; other
+        // failures (unknown revision, not a repository) exit 255.
+        match output.status.code() {
+            Some(0) => {
+                let mut data = output.stdout;
+                mehen_git::remove_blank_lines(&mut data);
+                Ok(Some(data))
+            }
+            Some(1) => Ok(None),
+            _ => Err(hg_failure("cat", &output)),
+        }
+    }
+
+    fn commit_id(&self, rev: &str) -> Option<String> {
+        let output = run_hg(
+            Some(&self.root),
+            &["log", "--rev", rev, "--limit", "1", "--template", "{node}"],
+        )
+        .ok()?;
+        let node = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (!node.is_empty()).then_some(node)
+    }
+}
+
+fn hg_command(root: Option<&Path>) -> Command {
+    let mut command = Command::new("hg");
+    command.env("HGPLAIN", "1");
+    if let Some(root) = root {
+        command.current_dir(root);
+    }
+    command
+}
+
+fn run_hg(root: Option<&Path>, args: &[&str]) -> Result<Output, VcsError> {
+    let output = hg_command(root)
+        .args(args)
+        .output()
+        .map_err(|e| VcsError::Hg(format!("failed to run hg: {e}")))?;
+    if output.status.success() {
+        Ok(output)
+    } else {
+        Err(hg_failure(args[0], &output))
+    }
+}
+
+fn hg_failure(subcommand: &str, output: &Output) -> VcsError {
+    VcsError::Hg(format!(
+        "hg {subcommand} failed: {}",
+        String::from_utf8_lossy(&output.stderr).trim()
+    ))
+}
+
+/// Parse `hg status --print0` output: NUL-terminated `X path` records
+/// where `X` is `M`, `A` or `R`. Other codes are not requested and are
+/// skipped.
+fn parse_hg_status(stdout: &[u8]) -> Vec<ChangedFile> {
+    stdout
+        .split(|&b| b == 0)
+        .filter_map(|record| {
+            let (code, path) = record.split_at_checked(2)?;
+            let status = match code {
+                b"M " => ChangeStatus::Modified,
+                b"A " => ChangeStatus::Added,
+                b"R " => ChangeStatus::Deleted,
+                _ => return None,
+            };
+            Some(ChangedFile {
+                path: PathBuf::from(String::from_utf8_lossy(path).into_owned()),
+                status,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hg_status_records() {
+        let files = parse_hg_status(b"M src/a.rs\0A src/new file.py\0R old.go\0? junk\0");
+        let got: Vec<(String, ChangeStatus)> = files
+            .into_iter()
+            .map(|f| (f.path.display().to_string(), f.status))
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                ("src/a.rs".to_string(), ChangeStatus::Modified),
+                ("src/new file.py".to_string(), ChangeStatus::Added),
+                ("old.go".to_string(), ChangeStatus::Deleted),
+            ]
+        );
+    }
+}
@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Structured warnings for machine-readable output.
+//!
+//! Recoverable problems (skipped files, unknown metric names, analyzer
+//! warning diagnostics) used to go straight to `log::warn!`, which JSON
+//! consumers never see. A [`Warnings`] collector still logs each entry
+//! as it arrives, and also keeps it so `mehen diff -O json` can emit the
+//! list under a top-level `warnings` array.
+
+use std::path::{Path, PathBuf};
+
+/// Stable machine-readable category of a [`Warning`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum WarningCode {
+    /// A `--metric` name that is not in the catalogue; it was ignored.
+    UnknownMetric,
+    /// A file side could not be read and was left out of the diff.
+    SkippedFile,
+    /// An analyzer reported a warning-severity diagnostic.
+    Diagnostic,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub(crate) struct Warning {
+    pub(crate) code: WarningCode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) path: Option<PathBuf>,
+    pub(crate) message: String,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Warnings(Vec<Warning>);
+
+impl Warnings {
+    /// Log `message` at warn level and keep it for structured output.
+    pub(crate) fn push(&mut self, code: WarningCode, path: Option<&Path>, message: String) {
+        match path {
+            Some(path) => log::warn!("{}: {message}", path.display()),
+            None => log::warn!("{message}"),
+        }
+        self.0.push(Warning {
+            code,
+            path: path.map(Path::to_path_buf),
+            message,
+        });
+    }
+
+    pub(crate) fn as_slice(&self) -> &[Warning] {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_code_in_kebab_case_and_omits_missing_path() {
+        let mut warnings = Warnings::default();
+        warnings.push(
+            WarningCode::UnknownMetric,
+            None,
+            "unknown metric 'bogus', skipping".to_string(),
+        );
+        warnings.push(
+            WarningCode::SkippedFile,
+            Some(Path::new("src/a.rs")),
+            "skipping baseline: blob missing".to_string(),
+        );
+        let json = serde_json::to_value(warnings.as_slice()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {"code": "unknown-metric", "message": "unknown metric 'bogus', skipping"},
+                {"code": "skipped-file", "path": "src/a.rs", "message": "skipping baseline: blob missing"},
+            ])
+        );
+    }
+}
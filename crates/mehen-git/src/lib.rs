@@ -16,15 +16,22 @@ use std::path::{Path, PathBuf};
 use gix::diff::tree::recorder::Change;
 use gix::objs::TreeRefIter;
 
-/// Collapses any trailing run of `\n` / `\r` into a single `\n`.
+/// Collapses any trailing run of `\n` / `\r` into a single line
+/// terminator.
+///
+/// The terminator keeps the blob's EOL style: when the last content
+/// line ends in `\r\n`, the collapsed run is `\r\n`, otherwise `\n`.
+/// Rewriting a CRLF file's final terminator to a bare `\n` would leave
+/// it with mixed line endings.
 ///
 /// When a blob has *no* trailing newline (or is empty), the buffer is
 /// left unchanged — appending a synthetic `\n` would mutate repository
 /// content and create spurious metric deltas between revisions.
 ///
 /// Inlined from the pre-1.0 `src/tools.rs` so this crate has no
-/// dependency on the legacy `mehen` library.
-fn remove_blank_lines(data: &mut Vec<u8>) {
+/// dependency on the legacy `mehen` library. Public so other diff
+/// backends normalize file contents the same way [`read_blob`] does.
+pub fn remove_blank_lines(data: &mut Vec<u8>) {
     let count_trailing = data
         .iter()
         .rev()
@@ -33,7 +40,12 @@ fn remove_blank_lines(data: &mut Vec<u8>) {
     if count_trailing == 0 {
         return;
     }
-    data.truncate(data.len() - count_trailing);
+    let content_len = data.len() - count_trailing;
+    let crlf = data[content_len..].starts_with(b"\r\n");
+    data.truncate(content_len);
+    if crlf {
+        data.push(b'\r');
+    }
     data.push(b'\n');
 }
 
@@ -84,7 +96,24 @@ pub struct ChangedFile {
 /// Discover a git repository from the current working directory.
 /// Fails fast on shallow clones.
 pub fn open_repo() -> Result<gix::Repository, GitError> {
-    let repo = gix::discover(".").map_err(|_| GitError::RepoNotFound)?;
+    open_repo_at(Path::new("."))
+}
+
+/// Discover a git repository starting at `dir` and walking up.
+///
+/// Supported layouts:
+/// - a regular checkout, from its root or any subdirectory;
+/// - a linked worktree (`git worktree add`) — refs and objects resolve
+///   through the main repository's common dir, while `HEAD` is the
+///   worktree's own;
+/// - a bare repository, from its root or any subdirectory of it.
+///
+/// `GIT_DIR` / `GIT_WORK_TREE` are honoured, so the diff also works
+/// inside git hooks that export them. Fails fast on shallow clones.
+pub fn open_repo_at(dir: &Path) -> Result<gix::Repository, GitError> {
+    let repo = gix::ThreadSafeRepository::discover_with_environment_overrides(dir)
+        .map_err(|_| GitError::RepoNotFound)?
+        .to_thread_local();
 
     if repo.is_shallow() {
         return Err(GitError::ShallowClone {
@@ -162,6 +191,64 @@ pub fn read_blob(
     Ok(Some(data))
 }
 
+/// Read the staged (index) content of `path`, a file on disk inside a
+/// work tree. The repository is discovered from the file's directory.
+/// Returns `None` when the index has no entry for the file, and an
+/// error when the file's directory cannot be resolved on disk.
+///
+/// Unlike [`read_blob`], trailing newlines are kept as staged, so the
+/// content analyzes exactly as the checked-out file would. Shallow
+/// clones are fine here: only the index and its blobs are read.
+pub fn read_staged_file(path: &Path) -> Result<Option<Vec<u8>>, GitError> {
+    let Some(name) = path.file_name() else {
+        return Ok(None);
+    };
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let dir = dir
+        .canonicalize()
+        .map_err(|e| GitError::Internal(format!("{}: {e}", dir.display())))?;
+    let repo = gix::ThreadSafeRepository::discover_with_environment_overrides(&dir)
+        .map_err(|_| GitError::RepoNotFound)?
+        .to_thread_local();
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| GitError::Internal("a bare repository has no staged files".to_string()))?
+        .canonicalize()
+        .map_err(|e| GitError::Internal(e.to_string()))?;
+    let Ok(relative) = dir.join(name).strip_prefix(&workdir).map(Path::to_path_buf) else {
+        return Ok(None);
+    };
+
+    let index = repo
+        .index_or_empty()
+        .map_err(|e| GitError::Internal(e.to_string()))?;
+    let key = gix::path::to_unix_separators_on_windows(gix::path::into_bstr(relative));
+    let Some(entry) = index.entry_by_path(key.as_ref()) else {
+        return Ok(None);
+    };
+    let object = repo
+        .find_object(entry.id)
+        .map_err(|e| GitError::Internal(e.to_string()))?;
+    Ok(Some(object.detach().data))
+}
+
+/// Full hex id of the commit `rev` resolves to, e.g. for recording
+/// exactly which commits a diff compared.
+pub fn resolve_commit_id(repo: &gix::Repository, rev: &str) -> Result<String, GitError> {
+    let id = repo
+        .rev_parse_single(rev)
+        .map_err(|_| GitError::RefNotFound(rev.to_string()))?;
+    let commit = id
+        .object()
+        .map_err(|e| GitError::Internal(e.to_string()))?
+        .peel_to_commit()
+        .map_err(|e| GitError::Internal(e.to_string()))?;
+    Ok(commit.id.to_string())
+}
+
 /// Try to resolve a rev string to a friendly symbolic branch name.
 ///
 /// Resolves `rev` to a commit OID, then scans local and remote branches for
@@ -224,3 +311,134 @@ fn resolve_tree<'a>(repo: &'a gix::Repository, rev: &str) -> Result<gix::Tree<'a
 
     commit.tree().map_err(|e| GitError::Internal(e.to_string()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collapsed(input: &[u8]) -> Vec<u8> {
+        let mut data = input.to_vec();
+        remove_blank_lines(&mut data);
+        data
+    }
+
+    #[test]
+    fn lf_trailing_run_collapses_to_one_lf() {
+        assert_eq!(collapsed(b"a\nb\n\n\n"), b"a\nb\n");
+    }
+
+    #[test]
+    fn crlf_trailing_run_keeps_crlf() {
+        assert_eq!(collapsed(b"a\r\nb\r\n\r\n\r\n"), b"a\r\nb\r\n");
+        assert_eq!(collapsed(b"a\r\nb\r\n"), b"a\r\nb\r\n");
+    }
+
+    #[test]
+    fn missing_trailing_newline_is_left_alone() {
+        assert_eq!(collapsed(b"a\r\nb"), b"a\r\nb");
+        assert_eq!(collapsed(b""), b"");
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        let out = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .env("GIT_AUTHOR_NAME", "Mehen Test")
+            .env("GIT_AUTHOR_EMAIL", "test@mehen.invalid")
+            .env("GIT_COMMITTER_NAME", "Mehen Test")
+            .env("GIT_COMMITTER_EMAIL", "test@mehen.invalid")
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .output()
+            .expect("failed to spawn git");
+        assert!(
+            out.status.success(),
+            "git {args:?} failed: {}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+
+    /// A main checkout with two commits touching `src/a.rs`.
+    fn two_commit_repo() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let main = dir.path().join("main");
+        std::fs::create_dir_all(main.join("src")).unwrap();
+        git(&main, &["init", "-q", "-b", "main"]);
+        std::fs::write(main.join("src/a.rs"), "fn a() {}\n").unwrap();
+        git(&main, &["add", "-A"]);
+        git(&main, &["commit", "-q", "-m", "base"]);
+        std::fs::write(main.join("src/a.rs"), "fn a() { b() }\n").unwrap();
+        git(&main, &["commit", "-q", "-am", "head"]);
+        dir
+    }
+
+    fn assert_sees_head_change(repo: &gix::Repository) {
+        let files = changed_files(repo, "main~1", "main").unwrap();
+        let changed = files
+            .iter()
+            .find(|f| f.path == Path::new("src/a.rs"))
+            .expect("src/a.rs must be reported as changed");
+        assert_eq!(changed.status, ChangeStatus::Modified);
+        let blob = read_blob(repo, "main", Path::new("src/a.rs")).unwrap();
+        assert_eq!(blob.as_deref(), Some(&b"fn a() { b() }\n"[..]));
+    }
+
+    #[test]
+    fn open_repo_at_linked_worktree_subdirectory() {
+        let dir = two_commit_repo();
+        let main = dir.path().join("main");
+        git(
+            &main,
+            &["worktree", "add", "-q", "--detach", "../wt", "main~1"],
+        );
+
+        let repo = open_repo_at(&dir.path().join("wt/src")).unwrap();
+        assert_sees_head_change(&repo);
+        // `HEAD` is the worktree's own, not the main checkout's.
+        let head = repo.rev_parse_single("HEAD").unwrap();
+        let base = repo.rev_parse_single("main~1").unwrap();
+        assert_eq!(head.detach(), base.detach());
+    }
+
+    #[test]
+    fn open_repo_at_bare_repository() {
+        let dir = two_commit_repo();
+        git(dir.path(), &["clone", "-q", "--bare", "main", "bare.git"]);
+
+        let repo = open_repo_at(&dir.path().join("bare.git/refs")).unwrap();
+        assert!(repo.is_bare());
+        assert_sees_head_change(&repo);
+    }
+
+    #[test]
+    fn read_staged_file_returns_index_content() {
+        let dir = two_commit_repo();
+        let file = dir.path().join("main/src/a.rs");
+        std::fs::write(&file, "fn staged() {}\n").unwrap();
+        git(&dir.path().join("main"), &["add", "src/a.rs"]);
+        std::fs::write(&file, "fn unstaged() {}\n").unwrap();
+
+        let staged = read_staged_file(&file).unwrap();
+        assert_eq!(staged.as_deref(), Some(&b"fn staged() {}\n"[..]));
+        let untracked = dir.path().join("main/src/new.rs");
+        std::fs::write(&untracked, "fn new() {}\n").unwrap();
+        assert_eq!(read_staged_file(&untracked).unwrap(), None);
+    }
+
+    #[test]
+    fn read_staged_file_reports_a_missing_directory() {
+        let dir = two_commit_repo();
+        let missing = dir.path().join("main/gone/a.rs");
+        let err = read_staged_file(&missing).unwrap_err();
+        assert!(matches!(err, GitError::Internal(_)), "{err}");
+        assert!(err.to_string().contains("gone"), "{err}");
+    }
+
+    #[test]
+    fn open_repo_at_outside_repository_fails() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        assert!(matches!(
+            open_repo_at(dir.path()),
+            Err(GitError::RepoNotFound)
+        ));
+    }
+}
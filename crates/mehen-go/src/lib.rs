@@ -20,9 +20,11 @@ use mehen_core::{
     AnalysisBackend, AnalysisConfig, Language, LanguageAnalysis, LanguageAnalyzer, ParseDiagnostic,
     Result, SourceFile, SourceSpan, byte_offset_clamped,
 };
+use mehen_metrics::node_cap_fallback;
 use mehen_tree_sitter::{TreeSitterParser, collect_recovered_errors, empty_space};
 
-/// Tree-sitter `Language` accessor for `xtask tree-sitter generate`.
+/// Tree-sitter `Language` accessor for `xtask tree-sitter generate` and
+/// `mehen metrics --query`.
 ///
 /// Exposed so the kind-enum generator reaches the grammar through this
 /// crate instead of pinning `tree-sitter-go` itself.
@@ -54,7 +56,7 @@ impl LanguageAnalyzer for GoAnalyzer {
         AnalysisBackend::TreeSitter
     }
 
-    fn analyze(&self, source: &SourceFile, _config: &AnalysisConfig) -> Result<LanguageAnalysis> {
+    fn analyze(&self, source: &SourceFile, config: &AnalysisConfig) -> Result<LanguageAnalysis> {
         let parser = match TreeSitterParser::new(
             tree_sitter_go::LANGUAGE.into(),
             source.text.clone().into_bytes(),
@@ -80,7 +82,22 @@ impl LanguageAnalyzer for GoAnalyzer {
             }
         };
 
-        let root = walker::walk_program(parser.root(), parser.source(), &source.line_index);
+        if let Some(analysis) = node_cap_fallback(
+            source,
+            config,
+            AnalysisBackend::TreeSitter,
+            parser.root().descendant_count() as u64,
+        ) {
+            return Ok(analysis);
+        }
+
+        let root = walker::walk_program(
+            parser.root(),
+            parser.source(),
+            &source.line_index,
+            &config.halstead_overrides,
+            config.halstead_tokens,
+        );
         // Tree-sitter recovers from syntax errors by inserting ERROR /
         // missing nodes; surface them as `error` diagnostics so the
         // metric output can't masquerade as clean (plan §9.3).
@@ -134,4 +151,28 @@ mod tests {
             .as_f64();
         assert!(cy >= 3.0, "expected >= 3, got {cy}");
     }
+
+    #[test]
+    fn halstead_override_reclassifies_selector_dot() {
+        let text = "package main\nfunc f() { fmt.Println(os.Args) }\n";
+        let file = SourceFile::new("a.go".into(), Language::Go, text.to_string());
+        let counts = |config: &AnalysisConfig| {
+            let a = GoAnalyzer::new().analyze(&file, config).unwrap();
+            let read = |key: &str| {
+                a.root
+                    .metrics
+                    .get(&MetricKey::new(format!("{}.{key}", keys::HALSTEAD)))
+                    .unwrap()
+                    .as_f64()
+            };
+            (read("n1"), read("n2"))
+        };
+        let (n1, n2) = counts(&AnalysisConfig::default());
+
+        let mut config = AnalysisConfig::default();
+        config
+            .halstead_overrides
+            .insert(".".to_string(), mehen_core::HalsteadClass::Operand);
+        assert_eq!(counts(&config), (n1 - 1.0, n2 + 1.0));
+    }
 }
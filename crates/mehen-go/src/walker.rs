@@ -18,7 +18,11 @@
 //! - **Cognitive**: nesting on `if_statement` (skipping the inner `if`
 //!   of an `else if`), `for_statement`,
 //!   `expression_switch_statement`, `type_switch_statement`,
-//!   `select_statement`; flat `+1` on every `else` keyword;
+//!   `select_statement`; flat `+1` on every `else` keyword and on every
+//!   jump to a label (`break L`, `continue L`, `goto L`), which breaks
+//!   linear flow the way SonarSource counts it; an unlabeled `break` /
+//!   `continue` adds nothing. Labeled jumps are not cyclomatic
+//!   decisions — they add no path, they redirect one;
 //!   boolean-sequence reset on every statement-shape node;
 //!   `not_operator("!")` for unary `!` operators; per-`&&`/`||`
 //!   sequence collapse via the shared `BoolSequence` (legacy:
@@ -46,12 +50,18 @@
 //!   `Identifier2`, `Identifier3`, `BlankIdentifier`,
 //!   `FieldIdentifier`, `LabelName`, `PackageIdentifier`,
 //!   `TypeIdentifier`) merges into a single bucket — matching the
-//!   legacy raw-byte-slice key.
+//!   legacy raw-byte-slice key. Go 1.18 generics need no extra arms:
+//!   type parameter names and constraint types are identifier-shaped
+//!   operands, the opening `[` and union `~` / `|` are operators, and
+//!   `type_parameter_declaration` is neither a decision point nor an
+//!   NArgs parameter.
 //! - **NPA / NPM / WMC**: Go has no class-like constructs; all three
 //!   are intentionally no-ops, matching the legacy
 //!   `impl X for GoCode` empty bodies.
 
-use mehen_core::{LineIndex, MetricSpace, SpaceKind};
+use std::collections::BTreeMap;
+
+use mehen_core::{HalsteadClass, LineIndex, MetricSpace, SpaceKind};
 use mehen_metrics::{HalsteadOperand, HalsteadOperator, State};
 use mehen_tree_sitter::{OpenSpaceRequest, WalkerCtx, WalkerHooks, node_span, run, text_of};
 use smol_str::SmolStr;
@@ -62,9 +72,22 @@ use crate::grammar::Go;
 /// Drive the walker over the parsed Go tree and return the populated
 /// `MetricSpace`. Plugs Go classification into the shared
 /// [`mehen_tree_sitter::run`] scaffold.
-pub(crate) fn walk_program(root: Node<'_>, source: &[u8], line_index: &LineIndex) -> MetricSpace {
+pub(crate) fn walk_program(
+    root: Node<'_>,
+    source: &[u8],
+    line_index: &LineIndex,
+    halstead_overrides: &BTreeMap<String, HalsteadClass>,
+    halstead_tokens: bool,
+) -> MetricSpace {
     let mut hooks = GoHooks;
-    run(&mut hooks, root, source, line_index)
+    run(
+        &mut hooks,
+        root,
+        source,
+        line_index,
+        halstead_overrides,
+        halstead_tokens,
+    )
 }
 
 struct GoHooks;
@@ -164,6 +187,22 @@ impl WalkerHooks for GoHooks {
             ctx.current().cyclomatic.record_decision();
         }
 
+        // Widest `switch`: every case arm, `default` included.
+        if matches!(
+            kind,
+            Go::ExpressionSwitchStatement | Go::TypeSwitchStatement
+        ) {
+            let arms = iter_children(node)
+                .filter(|c| {
+                    matches!(
+                        Go::from(c.kind_id()),
+                        Go::ExpressionCase | Go::TypeCase | Go::DefaultCase
+                    )
+                })
+                .count();
+            ctx.current().branches.observe(arms as u32);
+        }
+
         classify_cognitive(ctx, node, kind);
         classify_abc(ctx, node, kind);
 
@@ -172,6 +211,11 @@ impl WalkerHooks for GoHooks {
             ctx.current().nexit.record_exit();
         }
 
+        // One per imported package, grouped `import (…)` or not.
+        if matches!(kind, Go::ImportSpec) {
+            ctx.current().imports.record_import();
+        }
+
         classify_loc(ctx, node, kind);
         classify_halstead(ctx, node, kind);
     }
@@ -184,8 +228,9 @@ fn classify_cognitive(ctx: &mut WalkerCtx<'_>, node: &Node<'_>, kind: Go) {
         // `if` already opened a nesting level and the connecting
         // `else` keyword adds the flat `+1`.
         Go::IfStatement if !is_else_if(node) => {
-            let effective = ctx.cognitive.nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
-            ctx.current().cognitive.increase_nesting(effective);
+            let nesting = ctx.cognitive.nesting;
+            let effective = nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
+            ctx.current().cognitive.increase_nesting(effective, nesting);
             ctx.cognitive.nesting = ctx.cognitive.nesting.saturating_add(1);
         }
         Go::IfStatement => {}
@@ -193,13 +238,20 @@ fn classify_cognitive(ctx: &mut WalkerCtx<'_>, node: &Node<'_>, kind: Go) {
         | Go::ExpressionSwitchStatement
         | Go::TypeSwitchStatement
         | Go::SelectStatement => {
-            let effective = ctx.cognitive.nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
-            ctx.current().cognitive.increase_nesting(effective);
+            let nesting = ctx.cognitive.nesting;
+            let effective = nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
+            ctx.current().cognitive.increase_nesting(effective, nesting);
             ctx.cognitive.nesting = ctx.cognitive.nesting.saturating_add(1);
         }
         Go::Else => {
             ctx.current().cognitive.increment_by_one();
         }
+        Go::BreakStatement | Go::ContinueStatement if has_child_kind(node, Go::LabelName) => {
+            ctx.current().cognitive.increment_by_one();
+        }
+        Go::GotoStatement => {
+            ctx.current().cognitive.increment_by_one();
+        }
         Go::ExpressionStatement
         | Go::SendStatement
         | Go::ReceiveStatement
@@ -319,6 +371,19 @@ fn classify_loc(ctx: &mut WalkerCtx<'_>, node: &Node<'_>, kind: Go) {
         | Go::ForStatement => {
             ctx.current().loc.observe_lloc();
         }
+        Go::Block => {
+            let start = node.start_position().row as u32;
+            ctx.current().loc.observe_code_line(start);
+            // A function's own body is the space itself, not a nested
+            // block.
+            if !matches!(
+                parent_kind(node),
+                Some(Go::FunctionDeclaration | Go::MethodDeclaration | Go::FuncLiteral)
+            ) {
+                let end = node.end_position().row as u32;
+                ctx.current().loc.observe_block(start, end);
+            }
+        }
         _ => {
             let start = node.start_position().row as u32;
             ctx.current().loc.observe_code_line(start);
@@ -332,22 +397,22 @@ fn classify_halstead(ctx: &mut WalkerCtx<'_>, node: &Node<'_>, kind: Go) {
     // `merge_child_into_parent` rolls these up into the enclosing
     // scope and the unit (set-union for `n1`/`n2`, sum for
     // `N1`/`N2`).
-    match halstead_op_type(kind) {
-        HalsteadType::Operator => {
+    match ctx.halstead_class(node, halstead_op_type(kind)) {
+        HalsteadClass::Operator => {
             let kind_label: &'static str = kind.into();
             ctx.current().halstead.observe_operator(HalsteadOperator {
                 kind: SmolStr::new(kind_label),
                 text: None,
             });
         }
-        HalsteadType::Operand => {
+        HalsteadClass::Operand => {
             let text = text_of(node, ctx.source);
             ctx.current().halstead.observe_operand(HalsteadOperand {
                 kind: SmolStr::new("Operand"),
                 text: Some(SmolStr::new(text)),
             });
         }
-        HalsteadType::Unknown => {}
+        HalsteadClass::Unknown => {}
     }
 }
 
@@ -355,13 +420,7 @@ fn classify_halstead(ctx: &mut WalkerCtx<'_>, node: &Node<'_>, kind: Go) {
 // Halstead classification (legacy `Getter::get_op_type for GoCode`).
 // --------------------------------------------------------------------
 
-enum HalsteadType {
-    Operator,
-    Operand,
-    Unknown,
-}
-
-fn halstead_op_type(kind: Go) -> HalsteadType {
+fn halstead_op_type(kind: Go) -> HalsteadClass {
     match kind {
         // Operators: keywords and control-flow tokens.
         // Note: `Go::Go` is the `go` keyword (goroutine launch), not the
@@ -437,7 +496,7 @@ fn halstead_op_type(kind: Go) -> HalsteadType {
         | Go::LPAREN
         | Go::LBRACK
         | Go::LBRACE
-        | Go::DOTDOTDOT => HalsteadType::Operator,
+        | Go::DOTDOTDOT => HalsteadClass::Operator,
 
         // Operands: identifiers, type identifiers, and literals.
         Go::Identifier
@@ -457,9 +516,9 @@ fn halstead_op_type(kind: Go) -> HalsteadType {
         | Go::True
         | Go::False
         | Go::Nil
-        | Go::Iota => HalsteadType::Operand,
+        | Go::Iota => HalsteadClass::Operand,
 
-        _ => HalsteadType::Unknown,
+        _ => HalsteadClass::Unknown,
     }
 }
 
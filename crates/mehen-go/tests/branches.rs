@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Widest `switch` per space (`max_branches`).
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, MetricKey, SourceFile, keys};
+use mehen_go::GoAnalyzer;
+
+fn analyze(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let analyzer = GoAnalyzer::new();
+    let file = SourceFile::new("foo.go".into(), Language::Go, text);
+    analyzer.analyze(&file, &AnalysisConfig::default()).unwrap()
+}
+
+fn max_branches(space: &mehen_core::MetricSpace) -> Option<f64> {
+    space
+        .metrics
+        .get(&MetricKey::new(keys::MAX_BRANCHES))
+        .map(|v| v.as_f64())
+}
+
+#[test]
+fn go_counts_switch_cases_including_default() {
+    let a = analyze(
+        "package main
+
+         func f(n int) int {
+             switch n {
+             case 1:
+                 return 10
+             case 2, 3:
+                 return 20
+             case 4:
+                 return 30
+             default:
+                 return 0
+             }
+         }
+
+         func g(v any) string {
+             switch v.(type) {
+             case int:
+                 return \"int\"
+             default:
+                 return \"other\"
+             }
+         }
+
+         func h() {}",
+    );
+    assert_eq!(max_branches(&a.root.spaces[0]), Some(4.0));
+    assert_eq!(max_branches(&a.root.spaces[1]), Some(2.0));
+    assert_eq!(max_branches(&a.root.spaces[2]), None);
+    assert_eq!(max_branches(&a.root), Some(4.0));
+}
@@ -167,3 +167,68 @@ fn go_logical_operator_sequences_reset_between_declaration_specs() {
     }"###
     );
 }
+
+#[test]
+fn go_select_with_labeled_break() {
+    let a = analyze(
+        "package main
+
+            func pump(in, out chan int, done chan bool) {
+            outer:
+                for { // +1
+                    select { // +2 (nesting = 1)
+                    case v := <-in:
+                        out <- v
+                    case out <- 0:
+                    case <-done:
+                        break outer // +1
+                    }
+                }
+            }",
+    );
+    let cog = mehen_report::metrics_json::cognitive(&a.root.metrics);
+    insta::assert_json_snapshot!(
+        cog,
+        @r###"
+    {
+      "sum": 4.0,
+      "average": 4.0,
+      "min": 0.0,
+      "max": 4.0
+    }"###
+    );
+}
+
+#[test]
+fn go_labeled_continue_and_goto() {
+    let a = analyze(
+        "package main
+
+            func scan(rows [][]int) {
+            rows:
+                for _, row := range rows { // +1
+                    for _, v := range row { // +2 (nesting = 1)
+                        if v < 0 { // +3 (nesting = 2)
+                            continue rows // +1
+                        }
+                        if v == 0 { // +3 (nesting = 2)
+                            goto end // +1
+                        }
+                        break // unlabeled: +0
+                    }
+                }
+            end:
+            }",
+    );
+    let cog = mehen_report::metrics_json::cognitive(&a.root.metrics);
+    insta::assert_json_snapshot!(
+        cog,
+        @r###"
+    {
+      "sum": 11.0,
+      "average": 11.0,
+      "min": 0.0,
+      "max": 11.0
+    }"###
+    );
+}
@@ -104,6 +104,39 @@ fn go_select_default_counts() {
     );
 }
 
+#[test]
+fn go_select_three_cases_with_labeled_break() {
+    // A labeled `break` redirects a path rather than adding one, so it
+    // is not a decision.
+    let a = analyze(
+        "package main
+
+            func pump(in, out chan int, done chan bool) { // +1 (+1 unit space)
+            outer:
+                for { // +1
+                    select {
+                    case v := <-in: // +1
+                        out <- v
+                    case out <- 0: // +1
+                    case <-done: // +1
+                        break outer
+                    }
+                }
+            }",
+    );
+    let cy = mehen_report::metrics_json::cyclomatic(&a.root.metrics);
+    insta::assert_json_snapshot!(
+        cy,
+        @r###"
+    {
+      "sum": 6.0,
+      "average": 3.0,
+      "min": 1.0,
+      "max": 5.0
+    }"###
+    );
+}
+
 #[test]
 fn go_logical_operators() {
     let a = analyze(
@@ -128,3 +161,21 @@ fn go_logical_operators() {
     }"###
     );
 }
+
+/// The `|` of a union constraint is a type-set union, not a boolean
+/// operator: only the `if` adds a path.
+#[test]
+fn go_generic_union_constraint_adds_no_paths() {
+    let a = analyze(
+        "package main
+
+            func Max[T ~int | ~int64 | ~float64](a, b T) T {
+                if a > b {
+                    return a
+                }
+                return b
+            }",
+    );
+    let c = mehen_report::metrics_json::cyclomatic(&a.root.spaces[0].metrics);
+    assert_eq!(c.sum, 2.0);
+}
@@ -46,7 +46,8 @@ fn go_operators_and_operands() {
       "level": 0.17857142857142858,
       "effort": 301.1368500605771,
       "time": 16.729825003365395,
-      "bugs": 0.014975730436275946
+      "bugs": 0.014975730436275946,
+      "operator_operand_ratio": 0.875
     }"###
     );
 }
@@ -94,3 +95,41 @@ func outer() {
         serde_json::to_string(&closure_h).unwrap()
     );
 }
+
+/// Go 1.18 type parameters: parameter names and constraint types are
+/// operands like any other identifier; the `[` that opens the list and
+/// the `~` / `|` of a union constraint are operators. The closing `]`
+/// is not counted, matching `)` and `}`.
+#[test]
+fn go_generic_function_counts_type_parameters() {
+    let a = analyze(
+        "package main
+
+            func Sum[T ~int | ~float64](a, b T) T {
+                return a + b
+            }",
+    );
+    let h = mehen_report::metrics_json::halstead(&a.root.metrics);
+    // Operators: package func [ ~ | ( , { return + — `~` twice.
+    assert_eq!((h.n1, h.big_n1), (10.0, 11.0));
+    // Operands: main Sum T int float64 a b — `T`, `a`, `b` repeat.
+    assert_eq!((h.n2, h.big_n2), (7.0, 11.0));
+}
+
+/// Instantiating a generic function adds its type arguments as
+/// operands and the `[` as an operator; nothing else changes.
+#[test]
+fn go_generic_instantiation_counts_type_arguments() {
+    let a = analyze(
+        "package main
+
+            func use() int {
+                return Sum[int](1, 2)
+            }",
+    );
+    let h = mehen_report::metrics_json::halstead(&a.root.metrics);
+    // Operators: package func ( { return [ ,
+    assert_eq!((h.n1, h.big_n1), (7.0, 8.0));
+    // Operands: main use int Sum 1 2 — `int` twice.
+    assert_eq!((h.n2, h.big_n2), (6.0, 7.0));
+}
@@ -0,0 +1,40 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Imported modules per space (`import_count`).
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, MetricKey, SourceFile, keys};
+use mehen_go::GoAnalyzer;
+
+fn analyze(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let analyzer = GoAnalyzer::new();
+    let file = SourceFile::new("foo.go".into(), Language::Go, text);
+    analyzer.analyze(&file, &AnalysisConfig::default()).unwrap()
+}
+
+fn import_count(space: &mehen_core::MetricSpace) -> Option<f64> {
+    space
+        .metrics
+        .get(&MetricKey::new(keys::IMPORT_COUNT))
+        .map(|v| v.as_f64())
+}
+
+#[test]
+fn go_counts_each_import_spec() {
+    let a = analyze(
+        "package main
+
+         import \"fmt\"
+
+         import (
+             \"os\"
+             str \"strings\"
+         )
+
+         func main() { fmt.Println(os.Args, str.ToUpper(\"x\")) }",
+    );
+    assert_eq!(import_count(&a.root), Some(3.0));
+    assert_eq!(import_count(&a.root.spaces[0]), None);
+}
@@ -58,7 +58,13 @@ fn go_sloc() {
       "lloc_min": 1.0,
       "lloc_max": 1.0,
       "blank_min": 0.0,
-      "blank_max": 0.0
+      "blank_max": 0.0,
+      "avg_function_sloc": 3.0,
+      "function_sloc_p50": 3.0,
+      "function_sloc_p90": 3.0,
+      "function_sloc_p99": 3.0,
+      "sloc_min_at": 4.0,
+      "sloc_max_at": 4.0
     }"###
     );
 }
@@ -100,7 +106,13 @@ fn go_lloc() {
       "lloc_min": 4.0,
       "lloc_max": 4.0,
       "blank_min": 0.0,
-      "blank_max": 0.0
+      "blank_max": 0.0,
+      "avg_function_sloc": 7.0,
+      "function_sloc_p50": 7.0,
+      "function_sloc_p90": 7.0,
+      "function_sloc_p99": 7.0,
+      "sloc_min_at": 3.0,
+      "sloc_max_at": 3.0
     }"###
     );
 }
@@ -150,7 +162,70 @@ fn go_lloc_counts_go_declaration_specs_and_receive_statements() {
       "lloc_min": 3.0,
       "lloc_max": 3.0,
       "blank_min": 0.0,
-      "blank_max": 0.0
+      "blank_max": 0.0,
+      "avg_function_sloc": 5.0,
+      "function_sloc_p50": 5.0,
+      "function_sloc_p90": 5.0,
+      "function_sloc_p99": 5.0,
+      "sloc_min_at": 13.0,
+      "sloc_max_at": 13.0
     }"###
     );
 }
+
+/// A CRLF-terminated file must produce the same LOC totals (per space
+/// and rolled up) as its LF twin — the `\r` before each newline is part
+/// of the line terminator, not a trailing code byte or a blank line.
+#[test]
+fn go_crlf_matches_lf() {
+    let lf = "package main
+
+// leading comment
+func add(a int, b int) int {
+
+	/* block
+	   comment */
+	c := a + b // trailing
+	return c
+}
+";
+    let crlf = lf.replace('\n', "\r\n");
+    let loc_json = |text: String| {
+        let file = SourceFile::new("foo.go".into(), Language::Go, text);
+        let a = GoAnalyzer::new()
+            .analyze(&file, &AnalysisConfig::default())
+            .unwrap();
+        let root = mehen_report::metrics_json::loc(&a.root.metrics);
+        let func = mehen_report::metrics_json::loc(&a.root.spaces[0].metrics);
+        (
+            serde_json::to_string(&root).unwrap(),
+            serde_json::to_string(&func).unwrap(),
+        )
+    };
+    assert_eq!(loc_json(crlf), loc_json(lf.to_string()));
+}
+
+/// `max_block_lines` is the tallest block nested in a function — here
+/// the `for` body, braces included — not the function body itself.
+#[test]
+fn go_max_block_lines_tracks_large_loop_body() {
+    let a = analyze(
+        "package main
+
+func f(xs []int) int {
+	total := 0
+	for _, x := range xs {
+		y := x * 2
+		total += y
+		if total > 100 {
+			break
+		}
+	}
+	return total
+}",
+    );
+    let loc = mehen_report::metrics_json::loc(&a.root.spaces[0].metrics);
+    assert_eq!(loc.max_block_lines, Some(7.0));
+    let root = mehen_report::metrics_json::loc(&a.root.metrics);
+    assert_eq!(root.max_block_lines, None);
+}
@@ -89,3 +89,15 @@ fn go_func_literal_parameters_are_counted_as_closures() {
     }"###
     );
 }
+
+/// Type parameters are not arguments: only `a` and `b` count.
+#[test]
+fn go_generic_function_ignores_type_parameters() {
+    let a = analyze(
+        "package main
+
+            func Pair[K comparable, V any](a K, b V) {}",
+    );
+    let nargs = mehen_report::metrics_json::nargs(&a.root.metrics);
+    assert_eq!(nargs.total_functions, 2.0);
+}
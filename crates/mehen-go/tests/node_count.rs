@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Syntax-tree size per space (`node_count` / `leaf_count`), counted by
+//! the shared tree-sitter walker.
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, MetricKey, SourceFile, keys};
+use mehen_go::GoAnalyzer;
+
+fn analyze(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let analyzer = GoAnalyzer::new();
+    let file = SourceFile::new("foo.go".into(), Language::Go, text);
+    analyzer.analyze(&file, &AnalysisConfig::default()).unwrap()
+}
+
+#[test]
+fn go_function_node_and_leaf_counts() {
+    let a = analyze(
+        "package main
+
+         // comments are not counted
+         func add(a, b int) int {
+             return a + b
+         }",
+    );
+    let function = &a.root.spaces[0];
+    let get = |key| function.metrics.get(&MetricKey::new(key)).unwrap().as_f64();
+    let (nodes, leaves) = (get(keys::NODE_COUNT), get(keys::LEAF_COUNT));
+    // func add ( a , b int ) int { return a + b \n }
+    assert_eq!(leaves, 15.0);
+    assert!(nodes >= leaves, "nodes={nodes} leaves={leaves}");
+}
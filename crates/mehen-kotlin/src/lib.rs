@@ -16,9 +16,11 @@ use mehen_core::{
     AnalysisBackend, AnalysisConfig, Language, LanguageAnalysis, LanguageAnalyzer, ParseDiagnostic,
     Result, SourceFile, SourceSpan, byte_offset_clamped,
 };
+use mehen_metrics::node_cap_fallback;
 use mehen_tree_sitter::{TreeSitterParser, collect_recovered_errors, empty_space};
 
-/// Tree-sitter `Language` accessor for `xtask tree-sitter generate`.
+/// Tree-sitter `Language` accessor for `xtask tree-sitter generate` and
+/// `mehen metrics --query`.
 ///
 /// Exposed so the kind-enum generator reaches the grammar through this
 /// crate instead of pinning `tree-sitter-kotlin` itself.
@@ -50,7 +52,7 @@ impl LanguageAnalyzer for KotlinAnalyzer {
         AnalysisBackend::TreeSitter
     }
 
-    fn analyze(&self, source: &SourceFile, _config: &AnalysisConfig) -> Result<LanguageAnalysis> {
+    fn analyze(&self, source: &SourceFile, config: &AnalysisConfig) -> Result<LanguageAnalysis> {
         let parser = match TreeSitterParser::new(
             tree_sitter_kotlin::LANGUAGE.into(),
             source.text.clone().into_bytes(),
@@ -76,7 +78,22 @@ impl LanguageAnalyzer for KotlinAnalyzer {
             }
         };
 
-        let root = walker::walk_program(parser.root(), parser.source(), &source.line_index);
+        if let Some(analysis) = node_cap_fallback(
+            source,
+            config,
+            AnalysisBackend::TreeSitter,
+            parser.root().descendant_count() as u64,
+        ) {
+            return Ok(analysis);
+        }
+
+        let root = walker::walk_program(
+            parser.root(),
+            parser.source(),
+            &source.line_index,
+            &config.halstead_overrides,
+            config.halstead_tokens,
+        );
         // Tree-sitter recovers from syntax errors by inserting ERROR /
         // missing nodes; surface them as `error` diagnostics so the
         // metric output can't masquerade as clean (plan §9.3).
@@ -63,7 +63,9 @@
 //!   enum body, with public/non-public determined by explicit visibility
 //!   modifier (default = public).
 
-use mehen_core::{LineIndex, MetricSpace, SpaceKind};
+use std::collections::BTreeMap;
+
+use mehen_core::{HalsteadClass, LineIndex, MetricSpace, SpaceKind};
 use mehen_metrics::{ContainerKind, HalsteadOperand, HalsteadOperator, State};
 use mehen_tree_sitter::{OpenSpaceRequest, WalkerCtx, WalkerHooks, node_span, run, text_of};
 use smol_str::SmolStr;
@@ -75,9 +77,22 @@ use crate::grammar::Kotlin;
 /// `MetricSpace`. Plugs Kotlin classification (incl. class-aware
 /// member routing and WMC container finalize) into the shared
 /// [`mehen_tree_sitter::run`] scaffold.
-pub(crate) fn walk_program(root: Node<'_>, source: &[u8], line_index: &LineIndex) -> MetricSpace {
+pub(crate) fn walk_program(
+    root: Node<'_>,
+    source: &[u8],
+    line_index: &LineIndex,
+    halstead_overrides: &BTreeMap<String, HalsteadClass>,
+    halstead_tokens: bool,
+) -> MetricSpace {
     let mut hooks = KotlinHooks;
-    run(&mut hooks, root, source, line_index)
+    run(
+        &mut hooks,
+        root,
+        source,
+        line_index,
+        halstead_overrides,
+        halstead_tokens,
+    )
 }
 
 struct KotlinHooks;
@@ -279,8 +294,9 @@ fn classify_cognitive(ctx: &mut WalkerCtx<'_>, node: &Node<'_>, kind: Kotlin) {
         // `catch_block`. `try` itself does NOT bump nesting; only
         // `catch_block` does.
         Kotlin::IfExpression if !is_else_if(node) => {
-            let effective = ctx.cognitive.nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
-            ctx.current().cognitive.increase_nesting(effective);
+            let nesting = ctx.cognitive.nesting;
+            let effective = nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
+            ctx.current().cognitive.increase_nesting(effective, nesting);
             ctx.cognitive.nesting = ctx.cognitive.nesting.saturating_add(1);
         }
         Kotlin::IfExpression => {}
@@ -289,8 +305,9 @@ fn classify_cognitive(ctx: &mut WalkerCtx<'_>, node: &Node<'_>, kind: Kotlin) {
         | Kotlin::DoWhileStatement
         | Kotlin::WhenExpression
         | Kotlin::CatchBlock => {
-            let effective = ctx.cognitive.nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
-            ctx.current().cognitive.increase_nesting(effective);
+            let nesting = ctx.cognitive.nesting;
+            let effective = nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
+            ctx.current().cognitive.increase_nesting(effective, nesting);
             ctx.cognitive.nesting = ctx.cognitive.nesting.saturating_add(1);
         }
         Kotlin::Else => {
@@ -504,22 +521,22 @@ fn classify_loc(ctx: &mut WalkerCtx<'_>, node: &Node<'_>, kind: Kotlin) {
 }
 
 fn classify_halstead(ctx: &mut WalkerCtx<'_>, node: &Node<'_>, kind: Kotlin) {
-    match halstead_op_type(kind) {
-        HalsteadType::Operator => {
+    match ctx.halstead_class(node, halstead_op_type(kind)) {
+        HalsteadClass::Operator => {
             let label: &'static str = kind.into();
             ctx.current().halstead.observe_operator(HalsteadOperator {
                 kind: SmolStr::new(label),
                 text: None,
             });
         }
-        HalsteadType::Operand => {
+        HalsteadClass::Operand => {
             let text = text_of(node, ctx.source);
             ctx.current().halstead.observe_operand(HalsteadOperand {
                 kind: SmolStr::new("Operand"),
                 text: Some(SmolStr::new(text)),
             });
         }
-        HalsteadType::Unknown => {}
+        HalsteadClass::Unknown => {}
     }
 }
 
@@ -527,13 +544,7 @@ fn classify_halstead(ctx: &mut WalkerCtx<'_>, node: &Node<'_>, kind: Kotlin) {
 // Halstead classification (legacy `Getter::get_op_type for KotlinCode`).
 // --------------------------------------------------------------------
 
-enum HalsteadType {
-    Operator,
-    Operand,
-    Unknown,
-}
-
-fn halstead_op_type(kind: Kotlin) -> HalsteadType {
+fn halstead_op_type(kind: Kotlin) -> HalsteadClass {
     match kind {
         // Keywords and control-flow tokens.
         Kotlin::Fun
@@ -633,7 +644,7 @@ fn halstead_op_type(kind: Kotlin) -> HalsteadType {
         | Kotlin::DASHGT
         | Kotlin::DOTDOT
         | Kotlin::PLUSPLUS
-        | Kotlin::DASHDASH => HalsteadType::Operator,
+        | Kotlin::DASHDASH => HalsteadClass::Operator,
 
         // Operands: identifiers, literals, this/super, null, field.
         Kotlin::SimpleIdentifier
@@ -655,9 +666,9 @@ fn halstead_op_type(kind: Kotlin) -> HalsteadType {
         | Kotlin::ThisExpression
         | Kotlin::Super
         | Kotlin::SuperExpression
-        | Kotlin::Field => HalsteadType::Operand,
+        | Kotlin::Field => HalsteadClass::Operand,
 
-        _ => HalsteadType::Unknown,
+        _ => HalsteadClass::Unknown,
     }
 }
 
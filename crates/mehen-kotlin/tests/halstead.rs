@@ -78,7 +78,8 @@ fn kotlin_operators_and_operands() {
       "level": "[masked]",
       "effort": "[masked]",
       "time": "[masked]",
-      "bugs": "[masked]"
+      "bugs": "[masked]",
+      "operator_operand_ratio": 1.125
     }"###
     );
 }
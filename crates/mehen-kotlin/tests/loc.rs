@@ -62,7 +62,13 @@ fn kotlin_nested_calls_do_not_add_extra_lloc() {
       "lloc_min": 3.0,
       "lloc_max": 3.0,
       "blank_min": 0.0,
-      "blank_max": 0.0
+      "blank_max": 0.0,
+      "avg_function_sloc": 4.0,
+      "function_sloc_p50": 4.0,
+      "function_sloc_p90": 4.0,
+      "function_sloc_p99": 4.0,
+      "sloc_min_at": 1.0,
+      "sloc_max_at": 1.0
     }"###
     );
 }
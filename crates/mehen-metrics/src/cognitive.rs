@@ -27,6 +27,19 @@ pub struct CognitiveStats {
     pub cognitive_average: f64,
     pub boolean_seq: BoolSequence,
     pub minmax_seen: bool,
+    /// Deepest stack of nesting structures seen by `increase_nesting`
+    /// (a top-level `if` is 1), including nested spaces once merged.
+    /// Counts structures only: the function-depth and lambda terms that
+    /// raise the cognitive penalty do not deepen it. 0 when the space
+    /// has no nesting structure.
+    pub max_nesting: u32,
+    /// Part of `structural` paid for nesting: the `nesting` term of each
+    /// `increase_nesting` bump (and nesting-like bonuses such as
+    /// Python's enclosing-lambda increment) for the current space.
+    pub nesting_penalty: u32,
+    /// `nesting_penalty` rolled up across closed spaces, alongside
+    /// `cognitive_sum`.
+    pub nesting_sum: u32,
 }
 
 /// Same-operator sequence collapser per Sonar's whitepaper. Each
@@ -75,11 +88,23 @@ impl CognitiveStats {
         self.cognitive = self.structural;
     }
 
+    /// Like [`Self::record_increment`], but the points are attributed to
+    /// nesting rather than to the structure itself.
+    pub fn record_nesting_increment(&mut self, amount: u32) {
+        self.nesting_penalty = self.nesting_penalty.saturating_add(amount);
+        self.record_increment(amount);
+    }
+
     /// Add `nesting + 1` to the structural count. Mirrors the pre-1.0
     /// `increment(stats)` (which used `stats.structural += stats.nesting + 1`).
-    pub fn increase_nesting(&mut self, nesting: u32) {
+    /// `nesting` is the effective level, penalties included; `structures`
+    /// is how many nesting structures enclose this one inside the
+    /// function, which is what `max_nesting` records.
+    pub fn increase_nesting(&mut self, nesting: u32, structures: u32) {
         self.nesting = nesting;
         let bump = nesting.saturating_add(1);
+        self.max_nesting = self.max_nesting.max(structures.saturating_add(1));
+        self.nesting_penalty = self.nesting_penalty.saturating_add(nesting);
         self.structural = self.structural.saturating_add(bump);
         self.cognitive = self.structural;
     }
@@ -103,6 +128,8 @@ impl CognitiveStats {
     /// Combine another space's stats into this one.
     pub fn merge(&mut self, other: &CognitiveStats) {
         self.cognitive_sum = self.cognitive_sum.saturating_add(other.cognitive_sum);
+        self.nesting_sum = self.nesting_sum.saturating_add(other.nesting_sum);
+        self.max_nesting = self.max_nesting.max(other.max_nesting);
         if !other.minmax_seen {
             return;
         }
@@ -121,6 +148,7 @@ impl CognitiveStats {
     pub fn finalize_minmax(&mut self) {
         let value = self.structural;
         self.cognitive_sum = self.cognitive_sum.saturating_add(value);
+        self.nesting_sum = self.nesting_sum.saturating_add(self.nesting_penalty);
         if self.minmax_seen {
             self.min = self.min.min(value);
         } else {
@@ -179,6 +207,17 @@ mod tests {
         assert_eq!(a.cognitive_sum, 13);
     }
 
+    #[test]
+    fn nesting_penalty_is_split_from_structural_increments() {
+        let mut s = CognitiveStats::default();
+        s.increase_nesting(0, 0); // top-level `if`: +1 structural
+        s.increase_nesting(2, 2); // doubly nested: +1 structural, +2 nesting
+        s.increment_by_one(); // `else`: +1 structural
+        s.finalize_minmax();
+        assert_eq!(s.cognitive_sum, 5);
+        assert_eq!(s.nesting_sum, 2);
+    }
+
     #[test]
     fn boolean_sequence_collapses_same_operator() {
         let mut s = CognitiveStats::default();
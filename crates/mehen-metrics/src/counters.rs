@@ -18,6 +18,11 @@ use serde::Serialize;
 /// `finalize_minmax`. Without this discriminator, the unit space's
 /// always-zero `fn_nargs` would dilute any merged child's `_min` to
 /// 0 — see `closures_min` in the legacy `python_single_lambda` test.
+///
+/// `defaulted_args` counts parameters that callers may omit (Python
+/// defaults, TypeScript `x?` / `x = …`). It is a plain sum: parents add
+/// their children's counts. `max_param_name_length` is the longest
+/// parameter name, in characters, of the space and its children.
 #[derive(Default, Clone, Debug, PartialEq, Serialize)]
 pub struct NargsStats {
     pub fn_nargs: u32,
@@ -44,6 +49,8 @@ pub struct NargsStats {
     pub merged_function: bool,
     /// As `merged_function`, for closure nargs.
     pub merged_closure: bool,
+    pub defaulted_args: u32,
+    pub max_param_name_length: u32,
 }
 
 impl NargsStats {
@@ -104,7 +111,22 @@ impl NargsStats {
         self.closure_nargs_average = f64::from(self.closure_nargs_sum) / f64::from(cl_denom);
     }
 
+    /// Add the parameters of the just-opened function or closure that
+    /// have a default value.
+    pub fn record_defaulted_args(&mut self, count: u32) {
+        self.defaulted_args = self.defaulted_args.saturating_add(count);
+    }
+
+    /// Fold the length of one parameter name of the just-opened
+    /// function or closure into `max_param_name_length`.
+    pub fn record_param_name(&mut self, name: &str) {
+        let len = name.chars().count() as u32;
+        self.max_param_name_length = self.max_param_name_length.max(len);
+    }
+
     pub fn merge(&mut self, other: &NargsStats) {
+        self.defaulted_args = self.defaulted_args.saturating_add(other.defaulted_args);
+        self.max_param_name_length = self.max_param_name_length.max(other.max_param_name_length);
         self.fn_nargs_sum = self.fn_nargs_sum.saturating_add(other.fn_nargs_sum);
         self.closure_nargs_sum = self
             .closure_nargs_sum
@@ -318,6 +340,102 @@ impl NexitStats {
     }
 }
 
+/// Longest method-call chain accumulator.
+///
+/// Language crates measure each call's chain length (`a.b().c()` is 2)
+/// and report it through `observe`; `max` keeps the longest. A parent
+/// takes the max of its children, so the unit reports the longest chain
+/// anywhere in the file. `max` stays 0 for spaces without calls and for
+/// languages that do not measure chains, and is then not published.
+#[derive(Default, Clone, Debug, PartialEq, Serialize)]
+pub struct CallChainStats {
+    pub max: u32,
+}
+
+impl CallChainStats {
+    pub fn observe(&mut self, len: u32) {
+        self.max = self.max.max(len);
+    }
+
+    pub fn merge(&mut self, other: &CallChainStats) {
+        self.max = self.max.max(other.max);
+    }
+}
+
+/// Largest boolean condition accumulator.
+///
+/// Language crates count the leaf operands of each `&&` / `||` tree
+/// (`a && (b || c)` is 3) and report every such node through
+/// `observe`; the outermost operator of a condition always has the most
+/// leaves, so `max` ends up as the largest condition in the space.
+/// Parents take the max of their children. `max` stays 0 where no
+/// boolean operator was seen, and is then not published.
+#[derive(Default, Clone, Debug, PartialEq, Serialize)]
+pub struct ConditionTermsStats {
+    pub max: u32,
+}
+
+impl ConditionTermsStats {
+    pub fn observe(&mut self, terms: u32) {
+        self.max = self.max.max(terms);
+    }
+
+    pub fn merge(&mut self, other: &ConditionTermsStats) {
+        self.max = self.max.max(other.max);
+    }
+}
+
+/// Widest multi-way branch accumulator.
+///
+/// Language crates count the arms of each `match` / `switch` (the
+/// `default` arm included) and report it through `observe`, so `max`
+/// is the widest single branch point in the space. Parents take the
+/// max of their children. `max` stays 0 where no such construct was
+/// seen, and is then not published.
+#[derive(Default, Clone, Debug, PartialEq, Serialize)]
+pub struct BranchesStats {
+    pub max: u32,
+}
+
+impl BranchesStats {
+    pub fn observe(&mut self, arms: u32) {
+        self.max = self.max.max(arms);
+    }
+
+    pub fn merge(&mut self, other: &BranchesStats) {
+        self.max = self.max.max(other.max);
+    }
+}
+
+/// Deepest expression nesting accumulator.
+///
+/// Language crates call `enter` / `leave` around each expression node
+/// they count (binary operators, calls, indexing), so `depth` is the
+/// number of such nodes currently open and `max` the deepest seen:
+/// `f(a + b[i])` is 3. A closure opens its own space and starts from 0.
+/// Parents take the max of their children. `max` stays 0 where no
+/// counted expression was seen, and is then not published.
+#[derive(Default, Clone, Debug, PartialEq, Serialize)]
+pub struct ExprDepthStats {
+    pub depth: u32,
+    pub max: u32,
+}
+
+impl ExprDepthStats {
+    pub fn enter(&mut self) {
+        self.depth += 1;
+        self.max = self.max.max(self.depth);
+    }
+
+    pub fn leave(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    pub fn merge(&mut self, other: &ExprDepthStats) {
+        self.max = self.max.max(other.max);
+    }
+}
+
 /// Number of public attributes accumulator (NPA).
 ///
 /// Mirrors the pre-1.0 `npa::Stats`. Tracks per-class and per-interface
@@ -659,3 +777,72 @@ mod tests {
         assert_eq!(s.average, 2.0);
     }
 }
+
+/// Import statement accumulator (`import_count`).
+///
+/// Language crates call `record_import` once per imported module: a
+/// Python `import` / `from … import` statement, a Rust `use` item, a Go
+/// import spec. Parents add their children's counts, so the unit
+/// reports every import in the file. `count` stays 0 for languages that
+/// do not count imports, and is then not published.
+#[derive(Default, Clone, Debug, PartialEq, Serialize)]
+pub struct ImportStats {
+    pub count: u32,
+}
+
+impl ImportStats {
+    pub fn record_import(&mut self) {
+        self.count = self.count.saturating_add(1);
+    }
+
+    pub fn merge(&mut self, other: &ImportStats) {
+        self.count = self.count.saturating_add(other.count);
+    }
+}
+
+/// Magic-number accumulator (`magic_numbers`).
+///
+/// Language crates call `record` once per numeric literal that is not
+/// 0, 1 or -1 and does not initialize a named constant (a Rust `const`
+/// / `static`, a Python module- or class-level `UPPER_CASE` name).
+/// Parents add their children's counts. `count` stays 0 for languages
+/// that do not look for magic numbers, and is then not published.
+#[derive(Default, Clone, Debug, PartialEq, Serialize)]
+pub struct MagicNumberStats {
+    pub count: u32,
+}
+
+impl MagicNumberStats {
+    pub fn record(&mut self) {
+        self.count = self.count.saturating_add(1);
+    }
+
+    pub fn merge(&mut self, other: &MagicNumberStats) {
+        self.count = self.count.saturating_add(other.count);
+    }
+}
+
+/// Syntax-tree size accumulator: every non-trivia node the walker
+/// visits, and how many of those are leaves (tokens). Comments and
+/// whitespace are not counted. Parents add their children's counts, so
+/// a function's totals include its closures. Both stay 0 for analyzers
+/// that do not count nodes, and are then not published.
+#[derive(Default, Clone, Debug, PartialEq, Serialize)]
+pub struct NodeCountStats {
+    pub nodes: u32,
+    pub leaves: u32,
+}
+
+impl NodeCountStats {
+    pub fn observe(&mut self, is_leaf: bool) {
+        self.nodes = self.nodes.saturating_add(1);
+        if is_leaf {
+            self.leaves = self.leaves.saturating_add(1);
+        }
+    }
+
+    pub fn merge(&mut self, other: &NodeCountStats) {
+        self.nodes = self.nodes.saturating_add(other.nodes);
+        self.leaves = self.leaves.saturating_add(other.leaves);
+    }
+}
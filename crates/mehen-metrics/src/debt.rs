@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+use mehen_core::{DebtWeights, MetricKey, MetricSpace, keys};
+
+/// SQALE-style technical debt ratio: estimated remediation time over
+/// estimated development time, both in seconds.
+///
+/// Remediation is charged per decision point and per point of cognitive
+/// complexity (`weights` are in minutes); development time is Halstead's
+/// `T`. `None` when there is no development time to divide by.
+pub fn debt_ratio(
+    decisions: f64,
+    cognitive: f64,
+    halstead_time: f64,
+    weights: &DebtWeights,
+) -> Option<f64> {
+    if halstead_time <= 0.0 {
+        return None;
+    }
+    let remediation_minutes = weights
+        .cyclomatic_minutes
+        .mul_add(decisions, weights.cognitive_minutes * cognitive);
+    Some(remediation_minutes * 60.0 / halstead_time)
+}
+
+/// Publish `debt_ratio` on the unit space of `root`, from its published
+/// cognitive sum and Halstead time plus the decision points of every
+/// space in the tree. Runs after the analyzer, like the other
+/// engine-level passes, so it sees the final `halstead.time`.
+pub fn publish_debt_ratio(root: &mut MetricSpace, weights: &DebtWeights) {
+    let metrics = &root.metrics;
+    let value = |key: &str| metrics.get(&MetricKey::new(key)).map(|v| v.as_f64());
+    let Some(time) = value(&format!("{}.time", keys::HALSTEAD)) else {
+        return;
+    };
+    let cognitive = value(&format!("{}.sum", keys::COGNITIVE)).unwrap_or(0.0);
+    if let Some(ratio) = debt_ratio(decision_points(root), cognitive, time, weights) {
+        root.metrics.insert(MetricKey::new(keys::DEBT_RATIO), ratio);
+    }
+}
+
+/// Sum of `cyclomatic - 1` over `space` and its descendants: each space's
+/// own branches, without the base path every space starts with.
+fn decision_points(space: &MetricSpace) -> f64 {
+    let own = space
+        .metrics
+        .get(&MetricKey::new(keys::CYCLOMATIC))
+        .map_or(0.0, |v| (v.as_f64() - 1.0).max(0.0));
+    own + space.spaces.iter().map(decision_points).sum::<f64>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ratio_grows_with_complexity_and_needs_development_time() {
+        let weights = DebtWeights::default();
+        assert_eq!(debt_ratio(3.0, 4.0, 0.0, &weights), None);
+        assert_eq!(debt_ratio(0.0, 0.0, 30.0, &weights), Some(0.0));
+        // 3 decisions * 1 min + 4 cognitive * 2 min = 11 min over 110 s.
+        assert_eq!(debt_ratio(3.0, 4.0, 110.0, &weights), Some(6.0));
+    }
+}
@@ -4,6 +4,8 @@
 use serde::ser::SerializeStruct;
 use serde::{Serialize, Serializer};
 
+use mehen_core::{DEFAULT_BUGS_CONSTANT, DEFAULT_STROUD_NUMBER};
+
 use crate::halstead_builder::HalsteadCounts;
 
 /// Finalized Halstead measurements for one space.
@@ -98,22 +100,45 @@ impl HalsteadStats {
 
     /// Time to write the program in seconds, per Halstead's heuristic.
     pub fn time(&self) -> f64 {
-        self.effort() / 18.0
+        time_from_effort(self.effort(), DEFAULT_STROUD_NUMBER)
     }
 
     /// Estimated number of bugs delivered, per Halstead's
     /// `B = E^(2/3) / 3000` formula. Matches the pre-1.0 implementation
     /// in `crates/mehen-engine/src/legacy/metrics/halstead.rs::bugs`.
     pub fn bugs(&self) -> f64 {
-        self.effort().powf(2.0 / 3.0) / 3000.0
+        bugs_from_effort(self.effort(), DEFAULT_BUGS_CONSTANT)
+    }
+
+    /// Total operators per total operand, `N1 / N2`. High values point at
+    /// dense expressions, low ones at data-heavy code (tables, literal
+    /// lists). `0.0` when there are no operands.
+    pub fn operator_operand_ratio(&self) -> f64 {
+        if self.operands == 0 {
+            0.0
+        } else {
+            self.operators as f64 / self.operands as f64
+        }
     }
 }
 
+/// `T = E / S`, where `S` is the Stroud number (18 in Halstead's work).
+pub fn time_from_effort(effort: f64, stroud_number: f64) -> f64 {
+    effort / stroud_number
+}
+
+/// `B = E^(2/3) / K`, where `K` is an empirical divisor (3000 in
+/// Halstead's work). The mental discriminations per second are the
+/// Stroud number of [`time_from_effort`], not `K`.
+pub fn bugs_from_effort(effort: f64, bugs_constant: f64) -> f64 {
+    effort.powf(2.0 / 3.0) / bugs_constant
+}
+
 impl Serialize for HalsteadStats {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         // Field set kept in sync with the pre-1.0 output shape so parity
         // snapshots can compare directly.
-        let mut st = serializer.serialize_struct("halstead", 14)?;
+        let mut st = serializer.serialize_struct("halstead", 15)?;
         st.serialize_field("n1", &(self.u_operators as f64))?;
         st.serialize_field("N1", &(self.operators as f64))?;
         st.serialize_field("n2", &(self.u_operands as f64))?;
@@ -128,6 +153,7 @@ impl Serialize for HalsteadStats {
         st.serialize_field("effort", &self.effort())?;
         st.serialize_field("time", &self.time())?;
         st.serialize_field("bugs", &self.bugs())?;
+        st.serialize_field("operator_operand_ratio", &self.operator_operand_ratio())?;
         st.end()
     }
 }
@@ -143,6 +169,7 @@ mod tests {
         let s = HalsteadStats::default();
         assert_eq!(s.volume(), 0.0);
         assert_eq!(s.difficulty(), 0.0);
+        assert_eq!(s.operator_operand_ratio(), 0.0);
     }
 
     #[test]
@@ -172,4 +199,21 @@ mod tests {
         assert_eq!(stats.vocabulary(), 4.0);
         assert_eq!(stats.length(), 4.0);
     }
+
+    #[test]
+    fn time_scales_inversely_with_stroud_number() {
+        let stats = HalsteadStats {
+            u_operators: 3,
+            operators: 7,
+            u_operands: 4,
+            operands: 9,
+        };
+        let effort = stats.effort();
+        assert_eq!(
+            time_from_effort(effort, DEFAULT_STROUD_NUMBER),
+            stats.time()
+        );
+        assert_eq!(time_from_effort(effort, 9.0), 2.0 * stats.time());
+        assert_eq!(bugs_from_effort(effort, 1500.0), 2.0 * stats.bugs());
+    }
 }
@@ -1,7 +1,9 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 // Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap};
+
+use mehen_core::{HalsteadTokens, TokenCount};
 
 use smol_str::SmolStr;
 
@@ -55,8 +57,10 @@ pub struct HalsteadCounts {
 /// operand if the language crate chooses to).
 #[derive(Default, Debug, Clone)]
 pub struct HalsteadBuilder {
-    operators: HashSet<HalsteadOperator>,
-    operands: HashSet<HalsteadOperand>,
+    /// Distinct operators and how often each was observed.
+    operators: HashMap<HalsteadOperator, u32>,
+    /// Distinct operands and how often each was observed.
+    operands: HashMap<HalsteadOperand, u32>,
     big_n1: u32,
     big_n2: u32,
 }
@@ -68,12 +72,12 @@ impl HalsteadBuilder {
 
     pub fn observe_operator(&mut self, op: HalsteadOperator) {
         self.big_n1 = self.big_n1.saturating_add(1);
-        self.operators.insert(op);
+        *self.operators.entry(op).or_default() += 1;
     }
 
     pub fn observe_operand(&mut self, op: HalsteadOperand) {
         self.big_n2 = self.big_n2.saturating_add(1);
-        self.operands.insert(op);
+        *self.operands.entry(op).or_default() += 1;
     }
 
     pub fn counts(&self) -> HalsteadCounts {
@@ -87,27 +91,66 @@ impl HalsteadBuilder {
 
     /// Iterator over distinct operator entries — for tests / diagnostics.
     pub fn operators(&self) -> impl Iterator<Item = &HalsteadOperator> {
-        self.operators.iter()
+        self.operators.keys()
     }
 
     /// Iterator over distinct operand entries — for tests / diagnostics.
     pub fn operands(&self) -> impl Iterator<Item = &HalsteadOperand> {
-        self.operands.iter()
+        self.operands.keys()
+    }
+
+    /// Per-token frequencies, most frequent first. Tokens are keyed by
+    /// their text (falling back to the kind), so entries the builder
+    /// keeps apart only by kind are reported together.
+    pub fn token_frequencies(&self) -> HalsteadTokens {
+        HalsteadTokens {
+            operators: ranked(
+                self.operators
+                    .iter()
+                    .map(|(op, n)| (op.text.as_ref().unwrap_or(&op.kind), *n)),
+            ),
+            operands: ranked(
+                self.operands
+                    .iter()
+                    .map(|(op, n)| (op.text.as_ref().unwrap_or(&op.kind), *n)),
+            ),
+        }
     }
 
     /// Merge counts from a child space (post-finalize) into this one.
     pub fn merge(&mut self, other: &HalsteadBuilder) {
-        for op in &other.operators {
-            self.operators.insert(op.clone());
+        for (op, n) in &other.operators {
+            *self.operators.entry(op.clone()).or_default() += n;
         }
-        for op in &other.operands {
-            self.operands.insert(op.clone());
+        for (op, n) in &other.operands {
+            *self.operands.entry(op.clone()).or_default() += n;
         }
         self.big_n1 = self.big_n1.saturating_add(other.big_n1);
         self.big_n2 = self.big_n2.saturating_add(other.big_n2);
     }
 }
 
+/// Collapse per-kind counts into per-token totals, busiest first.
+///
+/// The builder's maps iterate in hash order, which differs between
+/// processes; totals go through a `BTreeMap` and the count sort is stable,
+/// so equal counts always come out in token order.
+fn ranked<'a>(tokens: impl Iterator<Item = (&'a SmolStr, u32)>) -> Vec<TokenCount> {
+    let mut totals: BTreeMap<&str, u32> = BTreeMap::new();
+    for (token, n) in tokens {
+        *totals.entry(token.as_str()).or_default() += n;
+    }
+    let mut out: Vec<TokenCount> = totals
+        .into_iter()
+        .map(|(token, count)| TokenCount {
+            token: token.to_string(),
+            count,
+        })
+        .collect();
+    out.sort_by_key(|t| std::cmp::Reverse(t.count));
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -159,4 +202,34 @@ mod tests {
         assert_eq!(c.n1, 2);
         assert_eq!(c.big_n1, 2);
     }
+
+    #[test]
+    fn token_frequencies_rank_by_count() {
+        let mut a = HalsteadBuilder::new();
+        a.observe_operand(operand("x"));
+        a.observe_operand(operand("y"));
+        a.observe_operator(op("+"));
+        let mut b = HalsteadBuilder::new();
+        b.observe_operand(operand("x"));
+        b.observe_operator(op("+"));
+        b.observe_operator(op("*"));
+        a.merge(&b);
+
+        let tokens = a.token_frequencies();
+        assert_eq!(
+            tokens.operands,
+            vec![
+                TokenCount {
+                    token: "x".into(),
+                    count: 2
+                },
+                TokenCount {
+                    token: "y".into(),
+                    count: 1
+                },
+            ]
+        );
+        assert_eq!(tokens.operators[0].token, "+");
+        assert_eq!(tokens.operators[0].count, 2);
+    }
 }
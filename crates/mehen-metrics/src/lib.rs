@@ -25,24 +25,35 @@ mod abc;
 mod cognitive;
 mod counters;
 mod cyclomatic;
+mod debt;
 mod halstead;
 mod halstead_builder;
 mod halstead_routing;
 mod loc;
 mod mi;
+mod node_cap;
 mod state;
 mod tree_builder;
 
 pub use abc::AbcStats;
 pub use cognitive::CognitiveStats;
-pub use counters::{ContainerKind, NargsStats, NexitStats, NomStats, NpaStats, NpmStats, WmcStats};
+pub use counters::{
+    BranchesStats, CallChainStats, ConditionTermsStats, ContainerKind, ExprDepthStats, ImportStats,
+    MagicNumberStats, NargsStats, NexitStats, NodeCountStats, NomStats, NpaStats, NpmStats,
+    WmcStats,
+};
 pub use cyclomatic::CyclomaticStats;
-pub use halstead::HalsteadStats;
+pub use debt::{debt_ratio, publish_debt_ratio};
+pub use halstead::{HalsteadStats, bugs_from_effort, time_from_effort};
 pub use halstead_builder::{HalsteadBuilder, HalsteadCounts, HalsteadOperand, HalsteadOperator};
 pub use halstead_routing::SpaceRangeTracker;
-pub use loc::{LineClass, LocStats};
+pub use loc::{LineClass, LocStats, publish_function_loc};
 pub use mi::MiStats;
-pub use state::{State, apply_state_to, close_space, finalize_state, merge_child_into_parent};
+pub use node_cap::{NODE_CAP_DIAGNOSTIC, node_cap_fallback};
+pub use state::{
+    State, apply_state_to, close_space, drop_metric_families, exclude_closures_from_nom,
+    finalize_state, merge_child_into_parent, recompute_minmax, rescale_halstead_derived,
+};
 pub use tree_builder::MetricTreeBuilder;
 
 // Re-export the metric key namespace and the selector/threshold contract
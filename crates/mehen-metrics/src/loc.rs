@@ -3,9 +3,12 @@
 
 use std::collections::HashSet;
 
+use mehen_core::{MetricKey, MetricSpace, SpaceKind};
 use serde::ser::SerializeStruct;
 use serde::{Serialize, Serializer};
 
+use crate::keys;
+
 /// Legacy line-class enum, kept for the small number of generic helpers
 /// (`default_line_classifier`) that still classify whole physical lines
 /// rather than AST nodes. Per-language LOC computation now goes through
@@ -58,9 +61,16 @@ pub struct LocStats {
     only_comment_lines: u32,
     /// Comments that share their line with a code line.
     code_comment_lines: u32,
+    /// Every row a comment covers, so [`Self::blank_rows`] can tell
+    /// which lines `blank` leaves over.
+    comment_rows: HashSet<u32>,
     /// End row of the most recent comment, used to detect a comment
     /// that ends just before a code line.
     last_comment_end: Option<u32>,
+    /// Longest nested block (loop/branch body, …) seen in this space,
+    /// in physical lines. Per space only — never merged into the
+    /// parent — and `None` until the walker reports a block.
+    max_block_lines: Option<u32>,
 
     // Rolled-up min/max bounds across spaces.
     pub sloc_min: u32,
@@ -126,6 +136,14 @@ impl LocStats {
             .saturating_sub(self.only_comment_lines)
     }
 
+    /// Which rows, from 0 to the end of the span, are blank in the sense
+    /// of [`Self::blank`]: neither a code line nor covered by a comment.
+    pub fn blank_rows(&self) -> Vec<bool> {
+        (0..=self.span_end)
+            .map(|row| !self.ploc_lines.contains(&row) && !self.comment_rows.contains(&row))
+            .collect()
+    }
+
     /// Record a code line — a non-comment, non-container, non-LLOC
     /// node started on this row. Mirrors the `_` arm of the pre-1.0
     /// per-language `Loc::compute` match.
@@ -134,6 +152,24 @@ impl LocStats {
         self.ploc_lines.insert(start_row);
     }
 
+    /// Record a nested block spanning rows `[start, end]` (inclusive).
+    /// Walkers call this for blocks *inside* a space — loop, branch and
+    /// arm bodies — not for the body that makes up the space itself.
+    pub fn observe_block(&mut self, start_row: u32, end_row: u32) {
+        let lines = end_row.saturating_sub(start_row) + 1;
+        self.max_block_lines = Some(self.max_block_lines.map_or(lines, |m| m.max(lines)));
+    }
+
+    /// Per-space longest nested block, if the walker reported any.
+    /// Not published for the unit space.
+    pub fn max_block_lines(&self) -> Option<u32> {
+        if self.span_is_unit {
+            None
+        } else {
+            self.max_block_lines
+        }
+    }
+
     /// Record an LLOC statement.
     pub fn observe_lloc(&mut self) {
         self.lloc_count = self.lloc_count.saturating_add(1);
@@ -142,6 +178,7 @@ impl LocStats {
     /// Record a comment node spanning rows `[start, end]` (inclusive).
     /// Mirrors `add_cloc_lines` semantics.
     pub fn observe_comment(&mut self, start_row: u32, end_row: u32) {
+        self.comment_rows.extend(start_row..=end_row);
         let comment_diff = end_row.saturating_sub(start_row);
         let is_after_code = self.ploc_lines.contains(&start_row);
         if is_after_code && comment_diff == 0 {
@@ -232,6 +269,7 @@ impl LocStats {
         for line in &other.ploc_lines {
             self.ploc_lines.insert(*line);
         }
+        self.comment_rows.extend(&other.comment_rows);
         self.only_comment_lines = self
             .only_comment_lines
             .saturating_add(other.only_comment_lines);
@@ -252,6 +290,7 @@ impl LocStats {
         for line in &other.ploc_lines {
             self.ploc_lines.insert(*line);
         }
+        self.comment_rows.extend(&other.comment_rows);
         self.lloc_count = self.lloc_count.saturating_add(other.lloc_count);
         self.only_comment_lines = self
             .only_comment_lines
@@ -311,6 +350,157 @@ impl LocStats {
     }
 }
 
+/// Publish the function-level LOC keys that need the finished tree:
+///
+/// - `loc.blank_blocks` — the number of distinct runs of consecutive
+///   blank lines. `blank_rows` is the unit's [`LocStats::blank_rows`],
+///   so a blank line is one `loc.blank` counts: neither code nor
+///   comment. A whitespace-only line inside a block comment is not
+///   blank. Lines inside nested spaces count toward the enclosing
+///   function too, like `loc.blank`.
+/// - `loc.share` — the function's SLOC as a fraction of the file's
+///   SLOC, which is only known once the unit space is closed.
+///
+/// It also publishes the distribution of function sizes on `root`:
+/// `loc.avg_function_sloc` and the nearest-rank percentiles
+/// `loc.function_sloc_p50` / `_p90` / `_p99` over every function space
+/// (nested ones included, closures not). Files without functions get
+/// none of these.
+///
+/// Finally it points `loc.sloc.min` / `loc.sloc.max` back at their
+/// source: `loc.sloc_min_at` / `loc.sloc_max_at` on `root` hold the
+/// start line of the first space, in document order, whose SLOC is the
+/// bound. The bounds only cover spaces without children (see
+/// [`LocStats::finalize_minmax`]), so only those are candidates. A file
+/// with no spaces below the unit gets neither key.
+///
+/// Walkers call this right after `MetricTreeBuilder::finish`.
+pub fn publish_function_loc(root: &mut MetricSpace, blank_rows: &[bool]) {
+    let unit_sloc = root
+        .metrics
+        .get(&MetricKey::new(keys::LOC_SLOC))
+        .map(|v| v.as_f64())
+        .unwrap_or(0.0);
+    annotate_functions(root, blank_rows, unit_sloc);
+    publish_sloc_bound_lines(root);
+
+    let mut slocs = Vec::new();
+    collect_function_slocs(root, &mut slocs);
+    if slocs.is_empty() {
+        return;
+    }
+    slocs.sort_by(f64::total_cmp);
+    let avg = slocs.iter().sum::<f64>() / slocs.len() as f64;
+    for (key, value) in [
+        (keys::LOC_AVG_FUNCTION_SLOC, avg),
+        (keys::LOC_FUNCTION_SLOC_P50, percentile(&slocs, 50.0)),
+        (keys::LOC_FUNCTION_SLOC_P90, percentile(&slocs, 90.0)),
+        (keys::LOC_FUNCTION_SLOC_P99, percentile(&slocs, 99.0)),
+    ] {
+        root.metrics.insert(MetricKey::new(key), value);
+    }
+}
+
+fn publish_sloc_bound_lines(root: &mut MetricSpace) {
+    let mut leaves = Vec::new();
+    for child in &root.spaces {
+        collect_leaf_slocs(child, &mut leaves);
+    }
+    for (bound, key) in [
+        ("min", keys::LOC_SLOC_MIN_AT),
+        ("max", keys::LOC_SLOC_MAX_AT),
+    ] {
+        let Some(value) = root
+            .metrics
+            .get(&MetricKey::new(format!("{}.{bound}", keys::LOC_SLOC)))
+            .map(|v| v.as_f64())
+        else {
+            continue;
+        };
+        if let Some(&(line, _)) = leaves.iter().find(|(_, sloc)| *sloc == value) {
+            root.metrics.insert(MetricKey::new(key), line as i64);
+        }
+    }
+}
+
+/// `(start_line, sloc)` of every space without children, in document
+/// order.
+fn collect_leaf_slocs(space: &MetricSpace, out: &mut Vec<(u32, f64)>) {
+    if space.spaces.is_empty() {
+        let sloc = space
+            .metrics
+            .get(&MetricKey::new(keys::LOC_SLOC))
+            .map(|v| v.as_f64())
+            .unwrap_or(0.0);
+        out.push((space.span.start_line, sloc));
+    }
+    for child in &space.spaces {
+        collect_leaf_slocs(child, out);
+    }
+}
+
+fn collect_function_slocs(space: &MetricSpace, out: &mut Vec<f64>) {
+    if matches!(space.kind, SpaceKind::Function) {
+        out.push(
+            space
+                .metrics
+                .get(&MetricKey::new(keys::LOC_SLOC))
+                .map(|v| v.as_f64())
+                .unwrap_or(0.0),
+        );
+    }
+    for child in &space.spaces {
+        collect_function_slocs(child, out);
+    }
+}
+
+/// Nearest-rank percentile of an ascending, non-empty slice: the
+/// smallest value with at least `p`% of the values at or below it.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn annotate_functions(space: &mut MetricSpace, blank: &[bool], unit_sloc: f64) {
+    if matches!(space.kind, SpaceKind::Function) {
+        let blocks = count_blank_runs(blank, space.span.start_line, space.span.end_line);
+        space
+            .metrics
+            .insert(MetricKey::new(keys::LOC_BLANK_BLOCKS), blocks as i64);
+        if unit_sloc > 0.0 {
+            let sloc = space
+                .metrics
+                .get(&MetricKey::new(keys::LOC_SLOC))
+                .map(|v| v.as_f64())
+                .unwrap_or(0.0);
+            space
+                .metrics
+                .insert(MetricKey::new(keys::LOC_SHARE), sloc / unit_sloc);
+        }
+    }
+    for child in &mut space.spaces {
+        annotate_functions(child, blank, unit_sloc);
+    }
+}
+
+/// Count runs of blank lines within the 1-based inclusive line range.
+fn count_blank_runs(blank: &[bool], start_line: u32, end_line: u32) -> u32 {
+    let start = start_line.saturating_sub(1) as usize;
+    let end = (end_line as usize).min(blank.len());
+    let Some(lines) = blank.get(start..end) else {
+        return 0;
+    };
+    let mut runs = 0;
+    let mut in_run = false;
+    for &is_blank in lines {
+        if is_blank && !in_run {
+            runs += 1;
+        }
+        in_run = is_blank;
+    }
+    runs
+}
+
 fn average(numerator: u32, denominator: u32) -> f64 {
     if denominator == 0 {
         0.0
@@ -376,4 +566,21 @@ mod tests {
         assert_eq!(a.space_count, 2);
         assert_eq!(a.cloc(), 1);
     }
+
+    #[test]
+    fn percentile_uses_nearest_rank() {
+        let values = [1.0, 3.0, 3.0, 5.0, 10.0];
+        assert_eq!(percentile(&values, 50.0), 3.0);
+        assert_eq!(percentile(&values, 90.0), 10.0);
+        assert_eq!(percentile(&values, 0.0), 1.0);
+        assert_eq!(percentile(&[7.0], 99.0), 7.0);
+    }
+
+    #[test]
+    fn blank_runs_count_each_group_once() {
+        let blank = [false, true, true, false, true, false];
+        assert_eq!(count_blank_runs(&blank, 1, 6), 2);
+        assert_eq!(count_blank_runs(&blank, 4, 6), 1);
+        assert_eq!(count_blank_runs(&blank, 1, 1), 0);
+    }
 }
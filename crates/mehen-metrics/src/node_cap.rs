@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! `AnalysisConfig::max_nodes`: the guard analyzers check right after
+//! parsing, before the walk, so a generated or minified file with an
+//! enormous syntax tree costs a line count instead of a full analysis.
+
+use mehen_core::{
+    AnalysisBackend, AnalysisConfig, LanguageAnalysis, MetricSpace, ParseDiagnostic, SourceFile,
+    SourceSpan, SpaceId, SpaceKind, byte_offset_clamped,
+};
+
+use crate::LocStats;
+use crate::state::publish_loc;
+
+/// Diagnostic code of the warning a downgraded file carries.
+pub const NODE_CAP_DIAGNOSTIC: &str = "analysis.node_cap";
+
+/// The LOC-only analysis of `source` when its syntax tree has more than
+/// `config.max_nodes` nodes, `None` when there is no cap or the tree is
+/// within it.
+///
+/// The unit space gets only the `loc.*` family, computed from the text:
+/// every non-blank line is a code line, since without the walk nothing
+/// tells comments apart. The file carries a `warning`, so the report
+/// says why the deep metrics are missing without failing the run.
+pub fn node_cap_fallback(
+    source: &SourceFile,
+    config: &AnalysisConfig,
+    backend: AnalysisBackend,
+    nodes: u64,
+) -> Option<LanguageAnalysis> {
+    let cap = config.max_nodes.filter(|&cap| nodes > cap)?;
+    let line_count = source.line_index.line_count();
+    let mut loc = LocStats::default();
+    loc.set_span(0, line_count.saturating_sub(1), true);
+    for (row, line) in source.text.lines().enumerate() {
+        if !line.trim().is_empty() {
+            loc.observe_code_line(row as u32);
+        }
+    }
+    loc.finalize_minmax();
+    let span = SourceSpan {
+        start_byte: 0,
+        end_byte: byte_offset_clamped(source.text.len()),
+        start_line: 1,
+        end_line: line_count,
+    };
+    let mut root = MetricSpace::new(SpaceId(0), SpaceKind::Unit, span);
+    publish_loc(&loc, &mut root.metrics);
+    Some(LanguageAnalysis {
+        language: source.language,
+        backend,
+        diagnostics: vec![ParseDiagnostic::warning(
+            NODE_CAP_DIAGNOSTIC,
+            format!("syntax tree has {nodes} nodes, over --max-nodes {cap}; only LOC was computed"),
+        )],
+        root,
+        contributions: Vec::new(),
+    })
+}
@@ -25,12 +25,13 @@
 //!   language crate.
 //! - the parser-side walking strategy (tree-sitter cursor vs Oxc visitor).
 
-use mehen_core::{MetricKey, MetricSet, SpaceKind};
+use mehen_core::{MetricKey, MetricSet, MetricSpace, SpaceKind};
 
 use crate::{
-    AbcStats, CognitiveStats, ContainerKind, CyclomaticStats, HalsteadBuilder, HalsteadStats,
-    LocStats, MetricTreeBuilder, MiStats, NargsStats, NexitStats, NomStats, NpaStats, NpmStats,
-    SpaceRangeTracker, WmcStats, keys,
+    AbcStats, BranchesStats, CallChainStats, CognitiveStats, ConditionTermsStats, ContainerKind,
+    CyclomaticStats, ExprDepthStats, HalsteadBuilder, HalsteadStats, ImportStats, LocStats,
+    MagicNumberStats, MetricTreeBuilder, MiStats, NargsStats, NexitStats, NodeCountStats, NomStats,
+    NpaStats, NpmStats, SpaceRangeTracker, WmcStats, bugs_from_effort, keys, time_from_effort,
 };
 
 /// Per-space accumulator state. Analyzers push one of these for the
@@ -49,6 +50,13 @@ pub struct State {
     pub npa: NpaStats,
     pub npm: NpmStats,
     pub wmc: WmcStats,
+    pub call_chain: CallChainStats,
+    pub condition_terms: ConditionTermsStats,
+    pub branches: BranchesStats,
+    pub expr_depth: ExprDepthStats,
+    pub node_count: NodeCountStats,
+    pub imports: ImportStats,
+    pub magic_numbers: MagicNumberStats,
 }
 
 impl State {
@@ -176,6 +184,13 @@ pub fn merge_child_into_parent(parent: &mut State, child: &State) {
     parent.npa.merge(&child.npa);
     parent.npm.merge(&child.npm);
     parent.wmc.merge(&child.wmc);
+    parent.call_chain.merge(&child.call_chain);
+    parent.condition_terms.merge(&child.condition_terms);
+    parent.branches.merge(&child.branches);
+    parent.expr_depth.merge(&child.expr_depth);
+    parent.node_count.merge(&child.node_count);
+    parent.imports.merge(&child.imports);
+    parent.magic_numbers.merge(&child.magic_numbers);
     parent.cognitive.merge(&child.cognitive);
     parent.cognitive.finalize(parent.nom.total());
 }
@@ -192,6 +207,66 @@ pub fn apply_state_to(state: State, target: &mut MetricSet) {
     publish_nargs(&state.nargs, &state.nom, target);
     publish_nexit(&state.nexit, target);
     publish_cognitive(&state.cognitive, target);
+    if state.call_chain.max > 0 {
+        target.insert(
+            MetricKey::new(keys::MAX_CALL_CHAIN),
+            state.call_chain.max as i64,
+        );
+    }
+    if state.condition_terms.max > 0 {
+        target.insert(
+            MetricKey::new(keys::MAX_CONDITION_TERMS),
+            state.condition_terms.max as i64,
+        );
+    }
+    if state.branches.max > 0 {
+        target.insert(
+            MetricKey::new(keys::MAX_BRANCHES),
+            state.branches.max as i64,
+        );
+    }
+    if state.expr_depth.max > 0 {
+        target.insert(
+            MetricKey::new(keys::MAX_EXPR_DEPTH),
+            state.expr_depth.max as i64,
+        );
+    }
+    if state.node_count.nodes > 0 {
+        target.insert(
+            MetricKey::new(keys::NODE_COUNT),
+            state.node_count.nodes as i64,
+        );
+        target.insert(
+            MetricKey::new(keys::LEAF_COUNT),
+            state.node_count.leaves as i64,
+        );
+    }
+    if state.imports.count > 0 {
+        target.insert(
+            MetricKey::new(keys::IMPORT_COUNT),
+            state.imports.count as i64,
+        );
+    }
+    if state.magic_numbers.count > 0 {
+        target.insert(
+            MetricKey::new(keys::MAGIC_NUMBERS),
+            state.magic_numbers.count as i64,
+        );
+    }
+    if state.cognitive.max_nesting > 0 {
+        target.insert(
+            MetricKey::new(keys::MAX_NESTING),
+            state.cognitive.max_nesting as i64,
+        );
+    }
+    if state.cognitive.cognitive_sum > 0 {
+        let nesting = state.cognitive.nesting_sum;
+        target.insert(MetricKey::new(keys::COGNITIVE_NESTING), nesting as i64);
+        target.insert(
+            MetricKey::new(keys::COGNITIVE_STRUCTURAL),
+            state.cognitive.cognitive_sum.saturating_sub(nesting) as i64,
+        );
+    }
 
     let halstead = HalsteadStats::from_counts(state.halstead.counts());
     publish_halstead(&halstead, target);
@@ -389,6 +464,10 @@ pub(crate) fn publish_halstead(stats: &HalsteadStats, target: &mut MetricSet) {
         MetricKey::new(format!("{}.bugs", keys::HALSTEAD)),
         stats.bugs(),
     );
+    target.insert(
+        MetricKey::new(format!("{}.operator_operand_ratio", keys::HALSTEAD)),
+        stats.operator_operand_ratio(),
+    );
 }
 
 fn publish_abc(stats: &AbcStats, target: &mut MetricSet) {
@@ -481,6 +560,18 @@ fn publish_nargs(stats: &NargsStats, nom: &NomStats, target: &mut MetricSet) {
         MetricKey::new(format!("{}.closures_max", keys::NARGS)),
         stats.closure_nargs_max as i64,
     );
+    if stats.defaulted_args > 0 {
+        target.insert(
+            MetricKey::new(keys::DEFAULTED_ARGS),
+            stats.defaulted_args as i64,
+        );
+    }
+    if stats.max_param_name_length > 0 {
+        target.insert(
+            MetricKey::new(keys::MAX_PARAM_NAME_LENGTH),
+            stats.max_param_name_length as i64,
+        );
+    }
 }
 
 fn publish_nom(stats: &NomStats, target: &mut MetricSet) {
@@ -523,6 +614,184 @@ fn publish_nom(stats: &NomStats, target: &mut MetricSet) {
     );
 }
 
+/// Rewrite the published NOM keys of every space under `root` as if no
+/// closure had been recorded (`AnalysisConfig::exclude_closures`).
+///
+/// `nom.average` is `(functions + closures) / spaces`, so without
+/// closures it equals `nom.functions_average`.
+pub fn exclude_closures_from_nom(root: &mut MetricSpace) {
+    let nom = |suffix: &str| MetricKey::new(format!("{}.{suffix}", keys::NOM));
+    let metrics = &mut root.metrics;
+    if metrics.get(&MetricKey::new(keys::NOM)).is_some() {
+        let functions = metrics.get(&nom("functions")).map_or(0.0, |v| v.as_f64());
+        let functions_average = metrics
+            .get(&nom("functions_average"))
+            .map_or(0.0, |v| v.as_f64());
+        metrics.insert(MetricKey::new(keys::NOM), functions as i64);
+        metrics.insert(nom("average"), functions_average);
+        metrics.insert(nom("closures"), 0_i64);
+        metrics.insert(nom("closures_average"), 0.0);
+        metrics.insert(nom("closures_min"), 0_i64);
+        metrics.insert(nom("closures_max"), 0_i64);
+    }
+    for child in &mut root.spaces {
+        exclude_closures_from_nom(child);
+    }
+}
+
+/// A published `{min, max}` pair and the rolled-up sum it bounds. A
+/// space's own value is its sum minus its children's sums. With `kind`
+/// set, only spaces of that kind contribute a value.
+struct MinMaxKeys {
+    sum: String,
+    min: String,
+    max: String,
+    kind: Option<SpaceKind>,
+}
+
+fn minmax_keys() -> Vec<MinMaxKeys> {
+    let keys = |sum: String, min: String, max: String, kind| MinMaxKeys {
+        sum,
+        min,
+        max,
+        kind,
+    };
+    let plain = |root: &str| {
+        keys(
+            format!("{root}.sum"),
+            format!("{root}.min"),
+            format!("{root}.max"),
+            None,
+        )
+    };
+    let suffixed = |root: &str, part: &str, sum: &str, kind| {
+        keys(
+            format!("{root}.{sum}"),
+            format!("{root}.{part}_min"),
+            format!("{root}.{part}_max"),
+            kind,
+        )
+    };
+    vec![
+        plain(keys::CYCLOMATIC),
+        plain(keys::COGNITIVE),
+        plain(keys::NEXIT),
+        suffixed(keys::ABC, "assignments", "assignments", None),
+        suffixed(keys::ABC, "branches", "branches", None),
+        suffixed(keys::ABC, "conditions", "conditions", None),
+        suffixed(keys::NOM, "functions", "functions", None),
+        suffixed(keys::NOM, "closures", "closures", None),
+        suffixed(
+            keys::NARGS,
+            "functions",
+            "total_functions",
+            Some(SpaceKind::Function),
+        ),
+        suffixed(
+            keys::NARGS,
+            "closures",
+            "total_closures",
+            Some(SpaceKind::Closure),
+        ),
+    ]
+}
+
+/// Drop every metric of the listed families from the spaces under
+/// `root` (`AnalysisConfig::excluded_metrics`): a family's bare key and
+/// every key under `family.` or `family_` (`cognitive_nesting`).
+pub fn drop_metric_families(root: &mut MetricSpace, families: &[String]) {
+    root.metrics.retain(|key| {
+        let key = key.as_str();
+        !families.iter().any(|family| {
+            key.strip_prefix(family.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '_']))
+        })
+    });
+    for child in &mut root.spaces {
+        drop_metric_families(child, families);
+    }
+}
+
+/// Recompute every published min/max of the spaces under `root` from
+/// the per-space values of the fully merged tree
+/// (`AnalysisConfig::recompute_minmax`).
+///
+/// The analyzers fold bounds while closing spaces, so their result can
+/// depend on the order children were merged in. This pass reads each
+/// space's own value back from the rolled-up sums and takes min/max
+/// over the space and all its descendants, which depends on the tree
+/// alone. LOC bounds are left alone: SLOC and PLOC are spans, not sums,
+/// so a space's own share cannot be recovered.
+pub fn recompute_minmax(root: &mut MetricSpace) {
+    let families = minmax_keys();
+    recompute_space_minmax(root, &families);
+}
+
+fn recompute_space_minmax(
+    space: &mut MetricSpace,
+    families: &[MinMaxKeys],
+) -> Vec<Option<(i64, i64)>> {
+    let children: Vec<_> = space
+        .spaces
+        .iter_mut()
+        .map(|child| recompute_space_minmax(child, families))
+        .collect();
+    let sum = |space: &MetricSpace, key: &str| {
+        space
+            .metrics
+            .get(&MetricKey::new(key))
+            .map(|v| v.as_f64() as i64)
+    };
+    families
+        .iter()
+        .enumerate()
+        .map(|(i, family)| {
+            let total = sum(space, &family.sum)?;
+            let nested: i64 = space
+                .spaces
+                .iter()
+                .filter_map(|child| sum(child, &family.sum))
+                .sum();
+            let own = family
+                .kind
+                .as_ref()
+                .is_none_or(|kind| *kind == space.kind)
+                .then_some(total - nested);
+            let bounds = children
+                .iter()
+                .filter_map(|child| child[i])
+                .chain(own.map(|v| (v, v)))
+                .reduce(|(lo, hi), (a, b)| (lo.min(a), hi.max(b)));
+            let (lo, hi) = bounds.unwrap_or((0, 0));
+            space.metrics.insert(MetricKey::new(&family.min), lo);
+            space.metrics.insert(MetricKey::new(&family.max), hi);
+            bounds
+        })
+        .collect()
+}
+
+/// Recompute `halstead.time` and `halstead.bugs` of every space under
+/// `root` from its published effort, with the given Stroud number and
+/// bugs divisor (`AnalysisConfig::halstead_stroud_number` /
+/// `halstead_bugs_constant`).
+pub fn rescale_halstead_derived(root: &mut MetricSpace, stroud_number: f64, bugs_constant: f64) {
+    let metrics = &mut root.metrics;
+    if let Some(effort) = metrics.get(&MetricKey::new(keys::HALSTEAD_EFFORT)) {
+        let effort = effort.as_f64();
+        metrics.insert(
+            MetricKey::new(format!("{}.time", keys::HALSTEAD)),
+            time_from_effort(effort, stroud_number),
+        );
+        metrics.insert(
+            MetricKey::new(format!("{}.bugs", keys::HALSTEAD)),
+            bugs_from_effort(effort, bugs_constant),
+        );
+    }
+    for child in &mut root.spaces {
+        rescale_halstead_derived(child, stroud_number, bugs_constant);
+    }
+}
+
 fn publish_nexit(stats: &NexitStats, target: &mut MetricSet) {
     target.insert(MetricKey::new(keys::NEXIT), stats.exits as i64);
     target.insert(
@@ -564,13 +833,16 @@ fn publish_cyclomatic(stats: &CyclomaticStats, target: &mut MetricSet) {
     );
 }
 
-fn publish_loc(stats: &LocStats, target: &mut MetricSet) {
+pub(crate) fn publish_loc(stats: &LocStats, target: &mut MetricSet) {
     target.insert(MetricKey::new(keys::LOC_LLOC), stats.lloc() as i64);
     target.insert(MetricKey::new(keys::LOC_SLOC), stats.sloc() as i64);
     target.insert(MetricKey::new(keys::LOC_PLOC), stats.ploc() as i64);
     target.insert(MetricKey::new(keys::LOC_CLOC), stats.cloc() as i64);
     target.insert(MetricKey::new(keys::LOC_BLANK), stats.blank() as i64);
     target.insert(MetricKey::new(keys::LOC), stats.sloc() as i64);
+    if let Some(lines) = stats.max_block_lines() {
+        target.insert(MetricKey::new(keys::LOC_MAX_BLOCK_LINES), lines as i64);
+    }
 
     target.insert(
         MetricKey::new(format!("{}.min", keys::LOC_SLOC)),
@@ -633,3 +905,68 @@ fn publish_loc(stats: &LocStats, target: &mut MetricSet) {
         stats.blank_average(),
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use mehen_core::{SourceSpan, SpaceId};
+
+    use super::*;
+
+    fn space(kind: SpaceKind, cyclomatic: i64, children: Vec<MetricSpace>) -> MetricSpace {
+        let mut space = MetricSpace::new(SpaceId(0), kind, SourceSpan::new(0, 0, 1, 1));
+        let nested: i64 = children
+            .iter()
+            .map(|c| {
+                c.metrics
+                    .get(&MetricKey::new("cyclomatic.sum"))
+                    .unwrap()
+                    .as_f64() as i64
+            })
+            .sum();
+        space.metrics.insert("cyclomatic.sum", cyclomatic + nested);
+        space.spaces = children;
+        space
+    }
+
+    fn bounds(space: &MetricSpace) -> (f64, f64) {
+        let get = |key: &str| space.metrics.get(&MetricKey::new(key)).unwrap().as_f64();
+        (get("cyclomatic.min"), get("cyclomatic.max"))
+    }
+
+    #[test]
+    fn recomputed_minmax_does_not_depend_on_merge_order() {
+        let tree = |reversed: bool| {
+            let mut children = vec![
+                space(SpaceKind::Function, 3, vec![]),
+                space(
+                    SpaceKind::Function,
+                    2,
+                    vec![space(SpaceKind::Closure, 6, vec![])],
+                ),
+                space(SpaceKind::Function, 1, vec![]),
+            ];
+            if reversed {
+                children.reverse();
+            }
+            let mut unit = space(SpaceKind::Unit, 1, children);
+            // Stale bounds that differ between the two orders.
+            unit.metrics
+                .insert("cyclomatic.min", i64::from(reversed) * 4);
+            unit.metrics.insert("cyclomatic.max", 2_i64);
+            unit
+        };
+
+        let mut forward = tree(false);
+        let mut backward = tree(true);
+        recompute_minmax(&mut forward);
+        recompute_minmax(&mut backward);
+        assert_eq!(bounds(&forward), (1.0, 6.0));
+        assert_eq!(bounds(&backward), bounds(&forward));
+        let outer = forward
+            .spaces
+            .iter()
+            .find(|s| !s.spaces.is_empty())
+            .unwrap();
+        assert_eq!(bounds(outer), (2.0, 6.0));
+    }
+}
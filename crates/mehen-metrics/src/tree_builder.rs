@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 // Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
 
-use mehen_core::{MetricSet, MetricSpace, SourceSpan, SpaceId, SpaceKind};
+use mehen_core::{HalsteadTokens, MetricSet, MetricSpace, SourceSpan, SpaceId, SpaceKind};
 
 /// Helper that assembles a `MetricSpace` tree with stable, monotonically
 /// increasing `SpaceId`s.
@@ -59,6 +59,24 @@ impl MetricTreeBuilder {
             .metrics
     }
 
+    /// Flag the innermost space as directly recursive. Walkers call this
+    /// when a call expression inside a function names that function.
+    pub fn mark_recursive(&mut self) {
+        self.stack
+            .last_mut()
+            .expect("MetricTreeBuilder: stack is empty")
+            .is_recursive = true;
+    }
+
+    /// Attach Halstead token frequencies to the innermost space. Walkers
+    /// call this on the unit just before [`Self::finish`].
+    pub fn set_halstead_tokens(&mut self, tokens: HalsteadTokens) {
+        self.stack
+            .last_mut()
+            .expect("MetricTreeBuilder: stack is empty")
+            .halstead_tokens = Some(tokens);
+    }
+
     /// `SpaceId` of the innermost open space, or `None` when only the
     /// unit scope is on the stack. Walkers reach for this in their
     /// `close_space` hook to associate the about-to-close state with
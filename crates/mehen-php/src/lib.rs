@@ -53,7 +53,7 @@ impl LanguageAnalyzer for PhpAnalyzer {
         AnalysisBackend::Mago
     }
 
-    fn analyze(&self, source: &SourceFile, _config: &AnalysisConfig) -> Result<LanguageAnalysis> {
+    fn analyze(&self, source: &SourceFile, config: &AnalysisConfig) -> Result<LanguageAnalysis> {
         // mago-syntax allocates everything into a bump arena. The
         // arena lives only for this `analyze` call; everything we
         // put into `LanguageAnalysis` must be owned (no borrow
@@ -74,7 +74,12 @@ impl LanguageAnalyzer for PhpAnalyzer {
             .map(|err| ParseDiagnostic::error("php.parse_error", format!("mago-syntax: {err}")))
             .collect();
 
-        let root = walker::walk_program(program, &source.text, &source.line_index);
+        let root = walker::walk_program(
+            program,
+            &source.text,
+            &source.line_index,
+            config.halstead_tokens,
+        );
 
         Ok(LanguageAnalysis {
             language: Language::Php,
@@ -68,7 +68,7 @@ use mago_syntax_core::input::Input;
 use mehen_core::{LineIndex, MetricSpace, SourceSpan, SpaceKind};
 use mehen_metrics::{
     ContainerKind, HalsteadOperand, HalsteadOperator, MetricTreeBuilder, SpaceRangeTracker, State,
-    apply_state_to, finalize_state, merge_child_into_parent,
+    apply_state_to, finalize_state, merge_child_into_parent, publish_function_loc,
 };
 use smol_str::SmolStr;
 
@@ -78,6 +78,7 @@ pub(crate) fn walk_program<'arena>(
     program: &Program<'arena>,
     source: &str,
     line_index: &LineIndex,
+    halstead_tokens: bool,
 ) -> MetricSpace {
     let unit_span = SourceSpan {
         start_byte: 0,
@@ -99,7 +100,7 @@ pub(crate) fn walk_program<'arena>(
     // at zero).
     visitor.observe_trivia(&program.trivia);
 
-    visitor.finish()
+    visitor.finish(halstead_tokens)
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -207,7 +208,7 @@ impl<'a> Visitor<'a> {
         }
     }
 
-    fn finish(mut self) -> MetricSpace {
+    fn finish(mut self, halstead_tokens: bool) -> MetricSpace {
         // Final ploc/lloc accounting from a single source-text scan
         // (mirrors the rust analyzer's token sweep — done once at
         // the unit level so we don't re-walk the whole arena to
@@ -227,7 +228,12 @@ impl<'a> Visitor<'a> {
             .finalize_into_tree(&mut tree, &mut unit_halstead, &mut unit_loc);
         unit_state.halstead = unit_halstead;
         unit_state.loc = unit_loc;
+        if halstead_tokens {
+            tree.halstead_tokens = Some(unit_state.halstead.token_frequencies());
+        }
+        let blank_rows = unit_state.loc.blank_rows();
         apply_state_to(unit_state, &mut tree.metrics);
+        publish_function_loc(&mut tree, &blank_rows);
         tree
     }
 
@@ -965,8 +971,9 @@ impl<'arena> Walker<'_, 'arena, Visitor<'_>> for MehenPhpWalker {
         } else {
             ctx.current().cyclomatic.record_decision();
             ctx.current().abc.record_condition();
-            let effective = ctx.cognitive.nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
-            ctx.current().cognitive.increase_nesting(effective);
+            let nesting = ctx.cognitive.nesting;
+            let effective = nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
+            ctx.current().cognitive.increase_nesting(effective, nesting);
             true
         };
 
@@ -1055,8 +1062,9 @@ impl<'arena> Walker<'_, 'arena, Visitor<'_>> for MehenPhpWalker {
         ctx.current().loc.observe_lloc();
         ctx.current().cyclomatic.record_decision();
         ctx.current().abc.record_condition();
-        let effective = ctx.cognitive.nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
-        ctx.current().cognitive.increase_nesting(effective);
+        let nesting = ctx.cognitive.nesting;
+        let effective = nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
+        ctx.current().cognitive.increase_nesting(effective, nesting);
         ctx.current().cognitive.boolean_seq.reset();
         ctx.save_cognitive();
         ctx.cognitive.nesting = ctx.cognitive.nesting.saturating_add(1);
@@ -1069,8 +1077,9 @@ impl<'arena> Walker<'_, 'arena, Visitor<'_>> for MehenPhpWalker {
         ctx.current().loc.observe_lloc();
         ctx.current().cyclomatic.record_decision();
         ctx.current().abc.record_condition();
-        let effective = ctx.cognitive.nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
-        ctx.current().cognitive.increase_nesting(effective);
+        let nesting = ctx.cognitive.nesting;
+        let effective = nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
+        ctx.current().cognitive.increase_nesting(effective, nesting);
         ctx.current().cognitive.boolean_seq.reset();
         ctx.save_cognitive();
         ctx.cognitive.nesting = ctx.cognitive.nesting.saturating_add(1);
@@ -1083,8 +1092,9 @@ impl<'arena> Walker<'_, 'arena, Visitor<'_>> for MehenPhpWalker {
         ctx.current().loc.observe_lloc();
         ctx.current().cyclomatic.record_decision();
         ctx.current().abc.record_condition();
-        let effective = ctx.cognitive.nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
-        ctx.current().cognitive.increase_nesting(effective);
+        let nesting = ctx.cognitive.nesting;
+        let effective = nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
+        ctx.current().cognitive.increase_nesting(effective, nesting);
         ctx.current().cognitive.boolean_seq.reset();
         ctx.save_cognitive();
         ctx.cognitive.nesting = ctx.cognitive.nesting.saturating_add(1);
@@ -1097,8 +1107,9 @@ impl<'arena> Walker<'_, 'arena, Visitor<'_>> for MehenPhpWalker {
         ctx.current().loc.observe_lloc();
         ctx.current().cyclomatic.record_decision();
         ctx.current().abc.record_condition();
-        let effective = ctx.cognitive.nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
-        ctx.current().cognitive.increase_nesting(effective);
+        let nesting = ctx.cognitive.nesting;
+        let effective = nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
+        ctx.current().cognitive.increase_nesting(effective, nesting);
         ctx.current().cognitive.boolean_seq.reset();
         ctx.save_cognitive();
         ctx.cognitive.nesting = ctx.cognitive.nesting.saturating_add(1);
@@ -1113,8 +1124,9 @@ impl<'arena> Walker<'_, 'arena, Visitor<'_>> for MehenPhpWalker {
         // `case` does. Cognitive: opens a nesting frame so nested
         // control flow inside cases gets the depth penalty.
         ctx.current().abc.record_condition();
-        let effective = ctx.cognitive.nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
-        ctx.current().cognitive.increase_nesting(effective);
+        let nesting = ctx.cognitive.nesting;
+        let effective = nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
+        ctx.current().cognitive.increase_nesting(effective, nesting);
         ctx.current().cognitive.boolean_seq.reset();
         ctx.save_cognitive();
         ctx.cognitive.nesting = ctx.cognitive.nesting.saturating_add(1);
@@ -1149,8 +1161,9 @@ impl<'arena> Walker<'_, 'arena, Visitor<'_>> for MehenPhpWalker {
 
     fn walk_in_match(&self, _m: &Match<'arena>, ctx: &mut Visitor<'_>) {
         ctx.current().abc.record_condition();
-        let effective = ctx.cognitive.nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
-        ctx.current().cognitive.increase_nesting(effective);
+        let nesting = ctx.cognitive.nesting;
+        let effective = nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
+        ctx.current().cognitive.increase_nesting(effective, nesting);
         ctx.current().cognitive.boolean_seq.reset();
         ctx.save_cognitive();
         ctx.cognitive.nesting = ctx.cognitive.nesting.saturating_add(1);
@@ -1178,8 +1191,9 @@ impl<'arena> Walker<'_, 'arena, Visitor<'_>> for MehenPhpWalker {
 
     fn walk_in_try(&self, _t: &Try<'arena>, ctx: &mut Visitor<'_>) {
         ctx.current().loc.observe_lloc();
-        let effective = ctx.cognitive.nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
-        ctx.current().cognitive.increase_nesting(effective);
+        let nesting = ctx.cognitive.nesting;
+        let effective = nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
+        ctx.current().cognitive.increase_nesting(effective, nesting);
         ctx.save_cognitive();
         ctx.cognitive.nesting = ctx.cognitive.nesting.saturating_add(1);
     }
@@ -1194,8 +1208,9 @@ impl<'arena> Walker<'_, 'arena, Visitor<'_>> for MehenPhpWalker {
     ) {
         ctx.current().cyclomatic.record_decision();
         ctx.current().abc.record_condition();
-        let effective = ctx.cognitive.nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
-        ctx.current().cognitive.increase_nesting(effective);
+        let nesting = ctx.cognitive.nesting;
+        let effective = nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
+        ctx.current().cognitive.increase_nesting(effective, nesting);
         ctx.save_cognitive();
         ctx.cognitive.nesting = ctx.cognitive.nesting.saturating_add(1);
     }
@@ -1211,8 +1226,9 @@ impl<'arena> Walker<'_, 'arena, Visitor<'_>> for MehenPhpWalker {
         // PHP ternary `cond ? then : else` is a cyclomatic decision.
         ctx.current().cyclomatic.record_decision();
         ctx.current().abc.record_condition();
-        let effective = ctx.cognitive.nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
-        ctx.current().cognitive.increase_nesting(effective);
+        let nesting = ctx.cognitive.nesting;
+        let effective = nesting + ctx.cognitive.depth + ctx.cognitive.lambda;
+        ctx.current().cognitive.increase_nesting(effective, nesting);
         ctx.save_cognitive();
         ctx.cognitive.nesting = ctx.cognitive.nesting.saturating_add(1);
     }
@@ -51,7 +51,8 @@ fn php_halstead_simple_function() {
       "level": 0.125,
       "effort": 458.875200092308,
       "time": 25.49306667179489,
-      "bugs": 0.01983087162785987
+      "bugs": 0.01983087162785987,
+      "operator_operand_ratio": 1.0
     }"###
     );
 }
@@ -13,12 +13,19 @@ use mehen_core::{
     AnalysisBackend, AnalysisConfig, Language, LanguageAnalysis, LanguageAnalyzer, ParseDiagnostic,
     Result, SourceFile, SourceSpan, SpaceKind, byte_offset_clamped,
 };
+use mehen_metrics::node_cap_fallback;
 use mehen_tree_sitter::{
     CognitiveFact, LanguageRules, NodeFacts, ScopeOpen, TreeSitterParser, collect_recovered_errors,
     empty_space, text_of, walk,
 };
 use tree_sitter::Node;
 
+/// Tree-sitter `Language` accessor for `mehen metrics --query`.
+#[doc(hidden)]
+pub fn __grammar_language() -> tree_sitter::Language {
+    tree_sitter_pwsh::LANGUAGE.into()
+}
+
 pub struct PowerShellAnalyzer;
 
 impl PowerShellAnalyzer {
@@ -473,7 +480,7 @@ impl LanguageAnalyzer for PowerShellAnalyzer {
         AnalysisBackend::TreeSitter
     }
 
-    fn analyze(&self, source: &SourceFile, _config: &AnalysisConfig) -> Result<LanguageAnalysis> {
+    fn analyze(&self, source: &SourceFile, config: &AnalysisConfig) -> Result<LanguageAnalysis> {
         let parser = match TreeSitterParser::new(
             tree_sitter_pwsh::LANGUAGE.into(),
             source.text.clone().into_bytes(),
@@ -499,11 +506,21 @@ impl LanguageAnalyzer for PowerShellAnalyzer {
             }
         };
 
+        if let Some(analysis) = node_cap_fallback(
+            source,
+            config,
+            AnalysisBackend::TreeSitter,
+            parser.root().descendant_count() as u64,
+        ) {
+            return Ok(analysis);
+        }
+
         let result = walk(
             parser.root(),
             parser.source(),
             &source.line_index,
             &PowerShellRules,
+            config.halstead_tokens,
         );
         // Tree-sitter recovers from syntax errors by inserting ERROR /
         // missing nodes; surface them as `error` diagnostics so the
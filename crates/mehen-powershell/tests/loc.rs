@@ -62,7 +62,13 @@ fn powershell_simple_loc() {
       "lloc_min": 2.0,
       "lloc_max": 2.0,
       "blank_min": 0.0,
-      "blank_max": 0.0
+      "blank_max": 0.0,
+      "avg_function_sloc": 3.0,
+      "function_sloc_p50": 3.0,
+      "function_sloc_p90": 3.0,
+      "function_sloc_p99": 3.0,
+      "sloc_min_at": 2.0,
+      "sloc_max_at": 2.0
     }
     "###
     );
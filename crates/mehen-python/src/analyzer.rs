@@ -5,7 +5,7 @@ use mehen_core::{
     AnalysisBackend, AnalysisConfig, Language, LanguageAnalysis, LanguageAnalyzer, ParseDiagnostic,
     Result, SourceFile, SourceSpan, byte_offset_clamped,
 };
-use mehen_metrics::MetricTreeBuilder;
+use mehen_metrics::{MetricTreeBuilder, node_cap_fallback};
 use ruff_python_parser::parse_module;
 
 use crate::walker::walk_module;
@@ -40,7 +40,7 @@ impl LanguageAnalyzer for PythonAnalyzer {
         AnalysisBackend::PythonRuff
     }
 
-    fn analyze(&self, source: &SourceFile, _config: &AnalysisConfig) -> Result<LanguageAnalysis> {
+    fn analyze(&self, source: &SourceFile, config: &AnalysisConfig) -> Result<LanguageAnalysis> {
         let parsed = match parse_module(source.text.as_str()) {
             Ok(p) => p,
             Err(err) => {
@@ -65,7 +65,21 @@ impl LanguageAnalyzer for PythonAnalyzer {
             }
         };
 
-        let root = walk_module(&parsed, &source.text, &source.line_index);
+        // Ruff has no cheap node count; the token stream stands in.
+        let tokens = parsed.tokens().len() as u64;
+        if let Some(analysis) =
+            node_cap_fallback(source, config, AnalysisBackend::PythonRuff, tokens)
+        {
+            return Ok(analysis);
+        }
+
+        let root = walk_module(
+            &parsed,
+            &source.text,
+            &source.line_index,
+            config.exclude_imports_from_lloc,
+            config.halstead_tokens,
+        );
         // Recovered Ruff syntax errors are surfaced as `error` (not
         // `warning`) so the diagnostic contract (plan §9.3) treats the
         // analysis as incomplete: `mehen metrics` exits 1 and
@@ -135,4 +149,35 @@ mod tests {
             .as_f64();
         assert!(cyclomatic >= 4.0, "expected >= 4, got {cyclomatic}");
     }
+
+    #[test]
+    fn max_nodes_caps_the_token_count() {
+        let file = SourceFile::new(
+            "test.py".into(),
+            Language::Python,
+            "def f(x):\n    return 1 if x else 0\n".to_string(),
+        );
+        let capped = AnalysisConfig {
+            max_nodes: Some(5),
+            ..AnalysisConfig::default()
+        };
+        let a = PythonAnalyzer::new().analyze(&file, &capped).unwrap();
+        assert!(a.root.spaces.is_empty());
+        assert_eq!(a.diagnostics[0].code, mehen_metrics::NODE_CAP_DIAGNOSTIC);
+        assert_eq!(
+            a.root
+                .metrics
+                .get(&MetricKey::new(keys::LOC_SLOC))
+                .map(|v| v.as_f64()),
+            Some(2.0)
+        );
+
+        let generous = AnalysisConfig {
+            max_nodes: Some(1_000),
+            ..AnalysisConfig::default()
+        };
+        let a = PythonAnalyzer::new().analyze(&file, &generous).unwrap();
+        assert_eq!(a.root.spaces.len(), 1);
+        assert!(a.diagnostics.is_empty());
+    }
 }
@@ -41,14 +41,14 @@
 
 use mehen_core::{LineIndex, MetricSpace, SourceSpan, SpaceKind};
 use mehen_metrics::{
-    ContainerKind, HalsteadOperand, HalsteadOperator, MetricTreeBuilder, SpaceRangeTracker, State,
-    apply_state_to, close_space, finalize_state,
+    ContainerKind, HalsteadOperand, HalsteadOperator, MetricTreeBuilder, NargsStats,
+    SpaceRangeTracker, State, apply_state_to, close_space, finalize_state, publish_function_loc,
 };
 use ruff_python_ast::token::TokenKind;
 use ruff_python_ast::visitor::source_order::{SourceOrderVisitor, walk_expr, walk_stmt};
 use ruff_python_ast::{
     self as ast, BoolOp, Comprehension, ElifElseClause, ExceptHandler, Expr, MatchCase, ModModule,
-    Stmt, UnaryOp,
+    Number, Stmt, UnaryOp,
 };
 use ruff_python_parser::Parsed;
 use ruff_text_size::{Ranged, TextRange};
@@ -61,6 +61,8 @@ pub(crate) fn walk_module(
     parsed: &Parsed<ModModule>,
     source: &str,
     line_index: &LineIndex,
+    exclude_imports_from_lloc: bool,
+    halstead_tokens: bool,
 ) -> MetricSpace {
     let module = parsed.syntax();
     let unit_span = SourceSpan {
@@ -71,12 +73,13 @@ pub(crate) fn walk_module(
     };
 
     let mut visitor = Visitor::new(source, line_index, unit_span);
+    visitor.exclude_imports_from_lloc = exclude_imports_from_lloc;
     visitor.record_module_docstring(&module.body);
     visitor.visit_body(&module.body);
 
     visitor.emit_halstead_from_tokens(parsed.tokens());
 
-    visitor.finish()
+    visitor.finish(halstead_tokens)
 }
 
 struct Visitor<'a> {
@@ -105,6 +108,15 @@ struct Visitor<'a> {
     /// even though the unit rollup is correct (PR #95
     /// discussion_r3265658502).
     halstead_routing: SpaceRangeTracker,
+    /// Names of the enclosing `def`s, innermost last. Used to flag
+    /// direct self-calls as recursion.
+    function_names: Vec<&'a str>,
+    /// `AnalysisConfig::exclude_imports_from_lloc`.
+    exclude_imports_from_lloc: bool,
+    /// Set while visiting the value of a module- or class-level
+    /// `UPPER_CASE = …` assignment — Python's named constant, whose
+    /// numeric literals are not magic numbers.
+    in_named_constant: bool,
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -137,6 +149,9 @@ impl<'a> Visitor<'a> {
             cognitive: CognitiveContext::default(),
             docstring_ranges: Vec::new(),
             halstead_routing: SpaceRangeTracker::new(),
+            function_names: Vec::new(),
+            exclude_imports_from_lloc: false,
+            in_named_constant: false,
         }
     }
 
@@ -148,7 +163,7 @@ impl<'a> Visitor<'a> {
         self.kinds.last().cloned().unwrap_or(SpaceKind::Unit)
     }
 
-    fn finish(mut self) -> MetricSpace {
+    fn finish(mut self, halstead_tokens: bool) -> MetricSpace {
         let mut unit_state = self.stack.pop().expect("walker stack underflow");
         finalize_state(&mut unit_state);
         // Route post-AST tokens (Halstead operator/operand events,
@@ -167,7 +182,12 @@ impl<'a> Visitor<'a> {
         // Re-run the unit publish so its Halstead, LOC, and MI keys
         // reflect the rolled-up values that include token-driven
         // events routed to nested scopes.
+        if halstead_tokens {
+            tree.halstead_tokens = Some(unit_state.halstead.token_frequencies());
+        }
+        let blank_rows = unit_state.loc.blank_rows();
         apply_state_to(unit_state, &mut tree.metrics);
+        publish_function_loc(&mut tree, &blank_rows);
         tree
     }
 
@@ -224,8 +244,13 @@ impl<'a> Visitor<'a> {
             func.range,
             Some(func.name.id.as_str().to_string()),
         );
+        self.function_names.push(func.name.id.as_str());
         let argc = func.parameters.len() as u32;
         self.current().nargs.record_function_args(argc);
+        self.current()
+            .nargs
+            .record_defaulted_args(defaulted_params(&func.parameters));
+        record_param_names(&mut self.current().nargs, &func.parameters);
 
         // Cognitive — function entry resets nesting/lambda and bumps
         // depth when nested inside another function.
@@ -260,9 +285,58 @@ impl<'a> Visitor<'a> {
         self.visit_body(&func.body);
 
         self.cognitive = saved;
+        self.function_names.pop();
         self.close_space();
     }
 
+    /// Do `targets` bind a named constant: every target an `UPPER_CASE`
+    /// name, at module or class level?
+    fn binds_named_constant(&self, targets: &[Expr]) -> bool {
+        matches!(self.parent_kind(), SpaceKind::Unit | SpaceKind::Class)
+            && !targets.is_empty()
+            && targets.iter().all(|t| match t {
+                Expr::Name(n) => {
+                    let id = n.id.as_str();
+                    id.chars().any(|c| c.is_ascii_uppercase())
+                        && !id.chars().any(|c| c.is_ascii_lowercase())
+                }
+                _ => false,
+            })
+    }
+
+    /// Visit an assignment statement, marking its value as a named
+    /// constant's when `targets` bind one.
+    fn walk_assignment(&mut self, stmt: &'a Stmt, targets: &[Expr]) {
+        let named = self.binds_named_constant(targets);
+        let outer = std::mem::replace(&mut self.in_named_constant, named);
+        walk_stmt(self, stmt);
+        self.in_named_constant = outer;
+    }
+
+    /// Whether `callee` names the innermost enclosing function — either
+    /// a bare `f(...)` or a `self.f(...)` / `cls.f(...)` method call.
+    /// Calls from a nested lambda or class body do not count: the
+    /// innermost space must be the function itself.
+    fn is_self_call(&self, callee: &Expr) -> bool {
+        if !matches!(self.parent_kind(), SpaceKind::Function) {
+            return false;
+        }
+        let Some(name) = self.function_names.last() else {
+            return false;
+        };
+        match callee {
+            Expr::Name(n) => n.id.as_str() == *name,
+            Expr::Attribute(attr) => {
+                attr.attr.as_str() == *name
+                    && matches!(
+                        attr.value.as_ref(),
+                        Expr::Name(recv) if matches!(recv.id.as_str(), "self" | "cls")
+                    )
+            }
+            _ => false,
+        }
+    }
+
     fn enter_class(&mut self, class: &'a ast::StmtClassDef) {
         for decorator in &class.decorator_list {
             self.visit_expr(&decorator.expression);
@@ -335,6 +409,34 @@ impl<'a> Visitor<'a> {
         }
     }
 
+    /// Walk a list / set / dict comprehension or generator expression:
+    /// its `for` / `if` clauses in order, then the element (`key` and
+    /// `value` for a dict), which runs innermost and so sees the full
+    /// nesting of every clause.
+    fn enter_comprehension(&mut self, generators: &'a [Comprehension], elts: &[&'a Expr]) {
+        let saved = self.cognitive.nesting;
+        for comp in generators {
+            self.visit_comprehension(comp);
+        }
+        for elt in elts {
+            self.visit_expr(elt);
+        }
+        self.cognitive.nesting = saved;
+    }
+
+    /// One `for` or `if` clause of a comprehension: +1 plus nesting
+    /// cognitive, like the statement it stands for, and one more level
+    /// for the clauses and element after it.
+    fn comprehension_clause(&mut self) {
+        let nesting = self.cognitive.nesting;
+        let effective = nesting + self.cognitive.depth + self.cognitive.lambda;
+        self.current()
+            .cognitive
+            .increase_nesting(effective, nesting);
+        self.current().cognitive.boolean_seq.reset();
+        self.cognitive.nesting += 1;
+    }
+
     fn enter_lambda(&mut self, lam: &'a ast::ExprLambda) {
         self.open_space(SpaceKind::Closure, lam.range, None);
         let argc = lam
@@ -343,6 +445,12 @@ impl<'a> Visitor<'a> {
             .map(|p| p.len() as u32)
             .unwrap_or(0);
         self.current().nargs.record_closure_args(argc);
+        if let Some(params) = lam.parameters.as_deref() {
+            self.current()
+                .nargs
+                .record_defaulted_args(defaulted_params(params));
+            record_param_names(&mut self.current().nargs, params);
+        }
 
         let mut ctx = self.cognitive;
         ctx.lambda = ctx.lambda.saturating_add(1);
@@ -364,23 +472,45 @@ impl<'a> Visitor<'a> {
             .line_index
             .line_at(range.start().to_u32())
             .saturating_sub(1);
+        let skip_import =
+            self.exclude_imports_from_lloc && matches!(stmt, Stmt::Import(_) | Stmt::ImportFrom(_));
         let cur = self.current();
         // LLOC: only "actionable" statements. Container statements
         // (`def`, `class`) are not LLOC per the legacy
         // `legacy/metrics/loc.rs::PythonCode::compute` enumeration —
         // their bodies contain the lloc-bumping nodes. Match expr and
         // bare keywords (`pass`/`break`/etc.) are still bumped via the
-        // generic `Stmt::*` arm.
-        let is_lloc = !matches!(
-            stmt,
-            Stmt::FunctionDef(_) | Stmt::ClassDef(_) | Stmt::TypeAlias(_)
-        );
+        // generic `Stmt::*` arm. Imports are unless the config opts out.
+        let is_lloc = !skip_import
+            && !matches!(
+                stmt,
+                Stmt::FunctionDef(_) | Stmt::ClassDef(_) | Stmt::TypeAlias(_)
+            );
         if is_lloc {
             cur.loc.observe_lloc();
         }
         cur.loc.observe_code_line(start_row);
     }
 
+    /// Visit a control-flow suite (`if`/loop/`try`/`with` body, `elif`,
+    /// `except` or `case` clause), recording its line span for
+    /// `loc.max_block_lines`. Function and module bodies go straight
+    /// through `visit_body`: they are the space, not a nested block.
+    fn visit_block(&mut self, body: &'a [Stmt]) {
+        if let (Some(first), Some(last)) = (body.first(), body.last()) {
+            let start_row = self
+                .line_index
+                .line_at(first.range().start().to_u32())
+                .saturating_sub(1);
+            let end_row = self
+                .line_index
+                .line_at(last.range().end().to_u32())
+                .saturating_sub(1);
+            self.current().loc.observe_block(start_row, end_row);
+        }
+        self.visit_body(body);
+    }
+
     /// Token-stream Halstead emission — runs after the AST walk.
     ///
     /// Each Ruff token is mapped to one of `Operator(kind)`,
@@ -519,9 +649,11 @@ impl<'a> SourceOrderVisitor<'a> for Visitor<'a> {
             }) => {
                 self.current().cyclomatic.record_decision();
                 self.current().abc.record_condition();
-                let effective =
-                    self.cognitive.nesting + self.cognitive.depth + self.cognitive.lambda;
-                self.current().cognitive.increase_nesting(effective);
+                let nesting = self.cognitive.nesting;
+                let effective = nesting + self.cognitive.depth + self.cognitive.lambda;
+                self.current()
+                    .cognitive
+                    .increase_nesting(effective, nesting);
                 // Match legacy `increase_nesting` (mehen-engine cognitive.rs:239):
                 // a new control-flow scope resets the boolean sequence so two
                 // sibling `if a and b: ...` blocks each contribute +1 for their
@@ -529,7 +661,7 @@ impl<'a> SourceOrderVisitor<'a> for Visitor<'a> {
                 self.current().cognitive.boolean_seq.reset();
                 self.cognitive.nesting += 1;
                 self.visit_expr(test);
-                self.visit_body(body);
+                self.visit_block(body);
                 // Elif/else clauses inherit the nesting bump from their
                 // owning `if` (legacy walks them as children of `if_statement`,
                 // so they see the parent's nesting via the nesting map).
@@ -548,14 +680,16 @@ impl<'a> SourceOrderVisitor<'a> for Visitor<'a> {
             }) => {
                 self.current().cyclomatic.record_decision();
                 self.current().abc.record_condition();
-                let effective =
-                    self.cognitive.nesting + self.cognitive.depth + self.cognitive.lambda;
-                self.current().cognitive.increase_nesting(effective);
+                let nesting = self.cognitive.nesting;
+                let effective = nesting + self.cognitive.depth + self.cognitive.lambda;
+                self.current()
+                    .cognitive
+                    .increase_nesting(effective, nesting);
                 self.current().cognitive.boolean_seq.reset();
                 self.cognitive.nesting += 1;
                 self.visit_expr(target);
                 self.visit_expr(iter);
-                self.visit_body(body);
+                self.visit_block(body);
                 self.cognitive.nesting -= 1;
                 if !orelse.is_empty() {
                     // `for ... else` — the else-branch runs only if
@@ -565,7 +699,7 @@ impl<'a> SourceOrderVisitor<'a> for Visitor<'a> {
                     self.current().cyclomatic.record_decision();
                     self.current().cognitive.increment_by_one();
                     self.current().abc.record_condition();
-                    self.visit_body(orelse);
+                    self.visit_block(orelse);
                 }
             }
             Stmt::While(ast::StmtWhile {
@@ -573,19 +707,21 @@ impl<'a> SourceOrderVisitor<'a> for Visitor<'a> {
             }) => {
                 self.current().cyclomatic.record_decision();
                 self.current().abc.record_condition();
-                let effective =
-                    self.cognitive.nesting + self.cognitive.depth + self.cognitive.lambda;
-                self.current().cognitive.increase_nesting(effective);
+                let nesting = self.cognitive.nesting;
+                let effective = nesting + self.cognitive.depth + self.cognitive.lambda;
+                self.current()
+                    .cognitive
+                    .increase_nesting(effective, nesting);
                 self.current().cognitive.boolean_seq.reset();
                 self.cognitive.nesting += 1;
                 self.visit_expr(test);
-                self.visit_body(body);
+                self.visit_block(body);
                 self.cognitive.nesting -= 1;
                 if !orelse.is_empty() {
                     self.current().cyclomatic.record_decision();
                     self.current().cognitive.increment_by_one();
                     self.current().abc.record_condition();
-                    self.visit_body(orelse);
+                    self.visit_block(orelse);
                 }
             }
             Stmt::Try(ast::StmtTry {
@@ -603,22 +739,24 @@ impl<'a> SourceOrderVisitor<'a> for Visitor<'a> {
                 // Ruff AST, but children of the `try_statement` in
                 // tree-sitter — both should see the same nesting).
                 self.current().abc.record_condition();
-                let effective =
-                    self.cognitive.nesting + self.cognitive.depth + self.cognitive.lambda;
-                self.current().cognitive.increase_nesting(effective);
+                let nesting = self.cognitive.nesting;
+                let effective = nesting + self.cognitive.depth + self.cognitive.lambda;
+                self.current()
+                    .cognitive
+                    .increase_nesting(effective, nesting);
                 self.current().cognitive.boolean_seq.reset();
                 self.cognitive.nesting += 1;
-                self.visit_body(body);
+                self.visit_block(body);
                 for handler in handlers {
                     self.visit_except_handler(handler);
                 }
                 if !orelse.is_empty() {
                     self.current().cognitive.increment_by_one();
-                    self.visit_body(orelse);
+                    self.visit_block(orelse);
                 }
                 if !finalbody.is_empty() {
                     self.current().cognitive.increment_by_one();
-                    self.visit_body(finalbody);
+                    self.visit_block(finalbody);
                 }
                 self.cognitive.nesting -= 1;
             }
@@ -627,9 +765,11 @@ impl<'a> SourceOrderVisitor<'a> for Visitor<'a> {
                 // `case` does. ABC records `match` as a condition once
                 // (the match itself is a structural branch).
                 self.current().abc.record_condition();
-                let effective =
-                    self.cognitive.nesting + self.cognitive.depth + self.cognitive.lambda;
-                self.current().cognitive.increase_nesting(effective);
+                let nesting = self.cognitive.nesting;
+                let effective = nesting + self.cognitive.depth + self.cognitive.lambda;
+                self.current()
+                    .cognitive
+                    .increase_nesting(effective, nesting);
                 self.current().cognitive.boolean_seq.reset();
                 self.cognitive.nesting += 1;
                 self.visit_expr(subject);
@@ -642,9 +782,11 @@ impl<'a> SourceOrderVisitor<'a> for Visitor<'a> {
                 // `with` is not a cyclomatic decision (no branching),
                 // but it does add cognitive nesting (a structural
                 // scope) and one ABC condition equivalent.
-                let effective =
-                    self.cognitive.nesting + self.cognitive.depth + self.cognitive.lambda;
-                self.current().cognitive.increase_nesting(effective);
+                let nesting = self.cognitive.nesting;
+                let effective = nesting + self.cognitive.depth + self.cognitive.lambda;
+                self.current()
+                    .cognitive
+                    .increase_nesting(effective, nesting);
                 self.current().cognitive.boolean_seq.reset();
                 self.cognitive.nesting += 1;
                 for item in items {
@@ -653,7 +795,7 @@ impl<'a> SourceOrderVisitor<'a> for Visitor<'a> {
                         self.visit_expr(opt_vars);
                     }
                 }
-                self.visit_body(body);
+                self.visit_block(body);
                 self.cognitive.nesting -= 1;
             }
             Stmt::Return(ast::StmtReturn { value, .. }) => {
@@ -671,15 +813,19 @@ impl<'a> SourceOrderVisitor<'a> for Visitor<'a> {
                     self.visit_expr(c);
                 }
             }
-            Stmt::Assign(_) | Stmt::AugAssign(_) => {
+            Stmt::Assign(ast::StmtAssign { targets, .. }) => {
+                self.current().abc.record_assignment();
+                self.walk_assignment(stmt, targets);
+            }
+            Stmt::AugAssign(_) => {
                 self.current().abc.record_assignment();
                 walk_stmt(self, stmt);
             }
-            Stmt::AnnAssign(ast::StmtAnnAssign { value, .. }) => {
+            Stmt::AnnAssign(ast::StmtAnnAssign { target, value, .. }) => {
                 if value.is_some() {
                     self.current().abc.record_assignment();
                 }
-                walk_stmt(self, stmt);
+                self.walk_assignment(stmt, std::slice::from_ref(target.as_ref()));
             }
             Stmt::Expr(_) => {
                 // ExpressionStatement resets the boolean sequence (for
@@ -693,18 +839,22 @@ impl<'a> SourceOrderVisitor<'a> for Visitor<'a> {
                 self.current().abc.record_assignment();
                 walk_stmt(self, stmt);
             }
+            // One `import_count` per statement, however many names it
+            // binds.
+            Stmt::Import(_) | Stmt::ImportFrom(_) => {
+                self.current().imports.record_import();
+                walk_stmt(self, stmt);
+            }
             // Plain descent — defaults handle the children we'd visit
             // anyway. Statements with no decision/assignment side
             // effect: bare keywords (`pass`/`break`/`continue`),
-            // imports, name declarations (`global`/`nonlocal`), `del`,
+            // name declarations (`global`/`nonlocal`), `del`,
             // `assert`, IPython escape commands.
             Stmt::Break(_)
             | Stmt::Continue(_)
             | Stmt::Pass(_)
             | Stmt::Global(_)
             | Stmt::Nonlocal(_)
-            | Stmt::Import(_)
-            | Stmt::ImportFrom(_)
             | Stmt::Delete(_)
             | Stmt::Assert(_)
             | Stmt::IpyEscapeCommand(_) => {
@@ -734,7 +884,9 @@ impl<'a> SourceOrderVisitor<'a> for Visitor<'a> {
                     0
                 };
                 if lambda_bonus > 0 {
-                    self.current().cognitive.record_increment(lambda_bonus);
+                    self.current()
+                        .cognitive
+                        .record_nesting_increment(lambda_bonus);
                 }
                 let label = match op {
                     BoolOp::And => "and",
@@ -766,9 +918,11 @@ impl<'a> SourceOrderVisitor<'a> for Visitor<'a> {
                 // Conditional expression `a if b else c` — one decision.
                 self.current().cyclomatic.record_decision();
                 self.current().abc.record_condition();
-                let effective =
-                    self.cognitive.nesting + self.cognitive.depth + self.cognitive.lambda;
-                self.current().cognitive.increase_nesting(effective);
+                let nesting = self.cognitive.nesting;
+                let effective = nesting + self.cognitive.depth + self.cognitive.lambda;
+                self.current()
+                    .cognitive
+                    .increase_nesting(effective, nesting);
                 self.current().cognitive.boolean_seq.reset();
                 self.cognitive.nesting += 1;
                 self.visit_expr(test);
@@ -776,6 +930,25 @@ impl<'a> SourceOrderVisitor<'a> for Visitor<'a> {
                 self.visit_expr(orelse);
                 self.cognitive.nesting -= 1;
             }
+            Expr::ListComp(ast::ExprListComp {
+                elt, generators, ..
+            })
+            | Expr::SetComp(ast::ExprSetComp {
+                elt, generators, ..
+            })
+            | Expr::Generator(ast::ExprGenerator {
+                elt, generators, ..
+            }) => {
+                self.enter_comprehension(generators, &[elt.as_ref()]);
+            }
+            Expr::DictComp(ast::ExprDictComp {
+                key,
+                value,
+                generators,
+                ..
+            }) => {
+                self.enter_comprehension(generators, &[key.as_ref(), value.as_ref()]);
+            }
             Expr::Compare(ast::ExprCompare { comparators, .. }) => {
                 // Comparison ops (`==`, `<`, ...) — each pair counts as
                 // one ABC condition.
@@ -784,8 +957,24 @@ impl<'a> SourceOrderVisitor<'a> for Visitor<'a> {
                 }
                 walk_expr(self, expr);
             }
-            Expr::Call(_) => {
+            // `-1` is a `UnaryOp` over `1`, so 0 / 1 cover -1 too.
+            Expr::NumberLiteral(ast::ExprNumberLiteral { value, .. }) => {
+                let trivial = match value {
+                    Number::Int(int) => int.as_u64().is_some_and(|v| v <= 1),
+                    Number::Float(f) => *f == 0.0 || *f == 1.0,
+                    Number::Complex { real, imag } => {
+                        *real == 0.0 && (*imag == 0.0 || *imag == 1.0)
+                    }
+                };
+                if !trivial && !self.in_named_constant {
+                    self.current().magic_numbers.record();
+                }
+            }
+            Expr::Call(call) => {
                 self.current().abc.record_branch();
+                if self.is_self_call(&call.func) {
+                    self.tree.mark_recursive();
+                }
                 walk_expr(self, expr);
             }
             // Halstead-wise, `a.b` is two operand tokens (`a` and `b`)
@@ -827,7 +1016,7 @@ impl<'a> SourceOrderVisitor<'a> for Visitor<'a> {
         if let Some(test) = &clause.test {
             self.visit_expr(test);
         }
-        self.visit_body(&clause.body);
+        self.visit_block(&clause.body);
     }
 
     fn visit_except_handler(&mut self, handler: &'a ExceptHandler) {
@@ -835,22 +1024,28 @@ impl<'a> SourceOrderVisitor<'a> for Visitor<'a> {
             handler;
         self.current().cyclomatic.record_decision();
         self.current().abc.record_condition();
-        let effective = self.cognitive.nesting + self.cognitive.depth + self.cognitive.lambda;
-        self.current().cognitive.increase_nesting(effective);
+        let nesting = self.cognitive.nesting;
+        let effective = nesting + self.cognitive.depth + self.cognitive.lambda;
+        self.current()
+            .cognitive
+            .increase_nesting(effective, nesting);
         self.current().cognitive.boolean_seq.reset();
         self.cognitive.nesting += 1;
         if let Some(t) = type_ {
             self.visit_expr(t);
         }
-        self.visit_body(body);
+        self.visit_block(body);
         self.cognitive.nesting -= 1;
     }
 
     fn visit_match_case(&mut self, case: &'a MatchCase) {
         self.current().cyclomatic.record_decision();
         self.current().abc.record_condition();
-        let effective = self.cognitive.nesting + self.cognitive.depth + self.cognitive.lambda;
-        self.current().cognitive.increase_nesting(effective);
+        let nesting = self.cognitive.nesting;
+        let effective = nesting + self.cognitive.depth + self.cognitive.lambda;
+        self.current()
+            .cognitive
+            .increase_nesting(effective, nesting);
         self.current().cognitive.boolean_seq.reset();
         self.cognitive.nesting += 1;
         // We deliberately do NOT call `self.visit_pattern(&case.pattern)` —
@@ -859,7 +1054,7 @@ impl<'a> SourceOrderVisitor<'a> for Visitor<'a> {
         if let Some(g) = &case.guard {
             self.visit_expr(g);
         }
-        self.visit_body(&case.body);
+        self.visit_block(&case.body);
         self.cognitive.nesting -= 1;
     }
 
@@ -872,9 +1067,16 @@ impl<'a> SourceOrderVisitor<'a> for Visitor<'a> {
             self.current().cyclomatic.record_decision();
             self.current().abc.record_condition();
         }
+        // Cognitive: the clauses read as nested statements —
+        // `[x for row in m if row for x in row]` is `for row: if row:
+        // for x: x` — so each `for` and each `if` costs its nesting and
+        // nests everything after it. `enter_comprehension` restores the
+        // nesting once the whole comprehension is walked.
+        self.comprehension_clause();
         self.visit_expr(&comp.target);
         self.visit_expr(&comp.iter);
         for f in &comp.ifs {
+            self.comprehension_clause();
             self.visit_expr(f);
         }
     }
@@ -899,6 +1101,28 @@ fn leading_docstring_range(body: &[Stmt]) -> Option<TextRange> {
     None
 }
 
+/// Positional and keyword parameters with a default value. `*args` and
+/// `**kwargs` never have one.
+fn defaulted_params(params: &ast::Parameters) -> u32 {
+    params
+        .iter_non_variadic_params()
+        .filter(|p| p.default.is_some())
+        .count() as u32
+}
+
+/// Every parameter name, `*args` and `**kwargs` included.
+fn record_param_names(nargs: &mut NargsStats, params: &ast::Parameters) {
+    for p in params.iter_non_variadic_params() {
+        nargs.record_param_name(p.parameter.name.id.as_str());
+    }
+    for p in [params.vararg.as_deref(), params.kwarg.as_deref()]
+        .into_iter()
+        .flatten()
+    {
+        nargs.record_param_name(p.name.id.as_str());
+    }
+}
+
 fn python_method_is_public(name: &str) -> bool {
     if name.starts_with("__") && name.ends_with("__") {
         return true;
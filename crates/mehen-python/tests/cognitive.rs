@@ -402,3 +402,50 @@ fn python_real_function() {
     }"###
     );
 }
+
+#[test]
+fn python_nested_comprehension_with_filter() {
+    let a = analyze(
+        "def f(m):
+                 return [
+                     [x for x in row if x > 0]  # +3 for, +4 if (nesting = 2, 3)
+                     for row in m               # +1
+                     if row                     # +2 (nesting = 1)
+                 ]",
+        "foo.py",
+    );
+    let cog = mehen_report::metrics_json::cognitive(&a.root.metrics);
+    insta::assert_json_snapshot!(
+        cog,
+        @r###"
+    {
+      "sum": 10.0,
+      "average": 10.0,
+      "min": 0.0,
+      "max": 10.0
+    }"###
+    );
+}
+
+#[test]
+fn python_comprehension_inside_loop() {
+    let a = analyze(
+        "def f(groups):
+                 for g in groups:                          # +1
+                     total = sum(x for x in g if x)        # +2 for, +3 if
+                     names = {k: v for k, v in g.items()}  # +2
+                 return total, names",
+        "foo.py",
+    );
+    let cog = mehen_report::metrics_json::cognitive(&a.root.metrics);
+    insta::assert_json_snapshot!(
+        cog,
+        @r###"
+    {
+      "sum": 8.0,
+      "average": 8.0,
+      "min": 0.0,
+      "max": 8.0
+    }"###
+    );
+}
@@ -81,7 +81,8 @@ fn python_operators_and_operands() {
       "level": 0.3,
       "effort": 342.6619429851844,
       "time": 19.03677461028802,
-      "bugs": 0.01632259960095138
+      "bugs": 0.01632259960095138,
+      "operator_operand_ratio": 1.25
     }
     "#
     );
@@ -115,7 +116,8 @@ fn python_wrong_operators() {
       "level": 0.0,
       "effort": 0.0,
       "time": 0.0,
-      "bugs": 0.0
+      "bugs": 0.0,
+      "operator_operand_ratio": 0.0
     }
     "#
     );
@@ -146,7 +148,8 @@ fn python_check_metrics() {
       "level": 0.5,
       "effort": 23.21928094887362,
       "time": 1.289960052715201,
-      "bugs": 0.002712967490108627
+      "bugs": 0.002712967490108627,
+      "operator_operand_ratio": 4.0
     }
     "#
     );
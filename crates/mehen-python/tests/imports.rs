@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Imported modules per space (`import_count`).
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, MetricKey, SourceFile, keys};
+use mehen_python::PythonAnalyzer;
+
+fn analyze(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let analyzer = PythonAnalyzer::new();
+    let file = SourceFile::new("foo.py".into(), Language::Python, text);
+    analyzer.analyze(&file, &AnalysisConfig::default()).unwrap()
+}
+
+fn import_count(space: &mehen_core::MetricSpace) -> Option<f64> {
+    space
+        .metrics
+        .get(&MetricKey::new(keys::IMPORT_COUNT))
+        .map(|v| v.as_f64())
+}
+
+#[test]
+fn python_counts_import_statements() {
+    let a = analyze(
+        "import os\n\
+         import sys, json\n\
+         from collections import OrderedDict, defaultdict\n\
+         \n\
+         def f():\n    from typing import Any\n    return Any",
+    );
+    assert_eq!(import_count(&a.root), Some(4.0));
+    assert_eq!(import_count(&a.root.spaces[0]), Some(1.0));
+}
@@ -200,7 +200,13 @@ fn python_no_zero_blank() {
       "lloc_min": 6.0,
       "lloc_max": 6.0,
       "blank_min": 3.0,
-      "blank_max": 3.0
+      "blank_max": 3.0,
+      "avg_function_sloc": 9.0,
+      "function_sloc_p50": 9.0,
+      "function_sloc_p90": 9.0,
+      "function_sloc_p99": 9.0,
+      "sloc_min_at": 1.0,
+      "sloc_max_at": 1.0
     }"###
     );
 }
@@ -247,7 +253,13 @@ fn python_no_blank() {
       "lloc_min": 6.0,
       "lloc_max": 6.0,
       "blank_min": 2.0,
-      "blank_max": 2.0
+      "blank_max": 2.0,
+      "avg_function_sloc": 8.0,
+      "function_sloc_p50": 8.0,
+      "function_sloc_p90": 8.0,
+      "function_sloc_p99": 8.0,
+      "sloc_min_at": 1.0,
+      "sloc_max_at": 1.0
     }"###
     );
 }
@@ -295,7 +307,13 @@ fn python_no_zero_blank_more_comments() {
       "lloc_min": 6.0,
       "lloc_max": 6.0,
       "blank_min": 3.0,
-      "blank_max": 3.0
+      "blank_max": 3.0,
+      "avg_function_sloc": 9.0,
+      "function_sloc_p50": 9.0,
+      "function_sloc_p90": 9.0,
+      "function_sloc_p99": 9.0,
+      "sloc_min_at": 1.0,
+      "sloc_max_at": 1.0
     }"###
     );
 }
@@ -474,7 +492,13 @@ fn python_general_loc() {
       "lloc_min": 3.0,
       "lloc_max": 3.0,
       "blank_min": 3.0,
-      "blank_max": 3.0
+      "blank_max": 3.0,
+      "avg_function_sloc": 6.0,
+      "function_sloc_p50": 6.0,
+      "function_sloc_p90": 6.0,
+      "function_sloc_p99": 6.0,
+      "sloc_min_at": 1.0,
+      "sloc_max_at": 1.0
     }"###
     );
 }
@@ -528,7 +552,13 @@ fn python_real_loc() {
       "lloc_min": 8.0,
       "lloc_max": 8.0,
       "blank_min": 1.0,
-      "blank_max": 1.0
+      "blank_max": 1.0,
+      "avg_function_sloc": 9.0,
+      "function_sloc_p50": 9.0,
+      "function_sloc_p90": 9.0,
+      "function_sloc_p99": 9.0,
+      "sloc_min_at": 1.0,
+      "sloc_max_at": 1.0
     }"###
     );
 }
@@ -566,3 +596,43 @@ fn python_nested_function_cloc_routes_to_active_space() {
         serde_json::to_string(&loc).unwrap()
     );
 }
+
+#[test]
+fn python_imports_can_be_left_out_of_lloc() {
+    let source = "import os\nfrom sys import argv, path\n\nprint(os.sep, argv, path)\n";
+    let lloc = |exclude_imports_from_lloc: bool| {
+        let file = SourceFile::new("foo.py".into(), Language::Python, source.to_string());
+        let config = AnalysisConfig {
+            exclude_imports_from_lloc,
+            ..AnalysisConfig::default()
+        };
+        let a = PythonAnalyzer::new().analyze(&file, &config).unwrap();
+        mehen_report::metrics_json::loc(&a.root.metrics).lloc
+    };
+    assert_eq!(lloc(false), 3.0);
+    assert_eq!(lloc(true), 1.0);
+}
+
+/// `max_block_lines` is the tallest suite nested in a function — here
+/// the `for` body, from its first to its last statement — not the
+/// function body itself.
+#[test]
+fn python_max_block_lines_tracks_large_loop_body() {
+    let a = analyze(
+        "
+    def f(xs):
+        total = 0
+        for x in xs:
+            y = x * 2
+            total += y
+            if total > 100:
+                break
+        return total
+    ",
+        "foo.py",
+    );
+    let loc = mehen_report::metrics_json::loc(&a.root.spaces[0].metrics);
+    assert_eq!(loc.max_block_lines, Some(4.0));
+    let root = mehen_report::metrics_json::loc(&a.root.metrics);
+    assert_eq!(root.max_block_lines, None);
+}
@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Numeric literals outside named constants (`magic_numbers`).
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, MetricKey, SourceFile, keys};
+use mehen_python::PythonAnalyzer;
+
+fn analyze(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let analyzer = PythonAnalyzer::new();
+    let file = SourceFile::new("foo.py".into(), Language::Python, text);
+    analyzer.analyze(&file, &AnalysisConfig::default()).unwrap()
+}
+
+fn magic_numbers(space: &mehen_core::MetricSpace) -> Option<f64> {
+    space
+        .metrics
+        .get(&MetricKey::new(keys::MAGIC_NUMBERS))
+        .map(|v| v.as_f64())
+}
+
+#[test]
+fn python_counts_literals_but_not_named_constants() {
+    let a = analyze(
+        "LIMIT = 100\n\
+         \n\
+         def f(x):\n    if x > LIMIT:\n        return x * 42\n    return x + 3.5",
+    );
+    assert_eq!(magic_numbers(&a.root.spaces[0]), Some(2.0));
+    assert_eq!(magic_numbers(&a.root), Some(2.0));
+}
+
+#[test]
+fn python_zero_one_and_minus_one_are_not_magic() {
+    let a = analyze("def f(x):\n    return x[0] + 1 - 0.0 if x else -1");
+    assert_eq!(magic_numbers(&a.root), None);
+}
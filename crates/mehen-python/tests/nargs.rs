@@ -4,7 +4,7 @@
 //! Nargs ports from
 //! `crates/mehen-engine/src/legacy/metrics/nargs.rs` Python tests.
 
-use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, SourceFile};
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, MetricKey, SourceFile, keys};
 use mehen_python::PythonAnalyzer;
 
 fn analyze(source: &str, filename: &str) -> mehen_core::LanguageAnalysis {
@@ -204,3 +204,57 @@ fn python_nested_functions() {
     }"###
     );
 }
+
+fn defaulted_args(space: &mehen_core::MetricSpace) -> Option<f64> {
+    space
+        .metrics
+        .get(&MetricKey::new(keys::DEFAULTED_ARGS))
+        .map(|v| v.as_f64())
+}
+
+#[test]
+fn python_defaulted_args() {
+    let a = analyze(
+        "def f(a, b=1, *args, c, d=None, **kwargs):
+    return lambda x, y=2: x + y",
+        "foo.py",
+    );
+    let f = &a.root.spaces[0];
+    assert_eq!(defaulted_args(f), Some(3.0));
+    assert_eq!(defaulted_args(&f.spaces[0]), Some(1.0));
+    assert_eq!(defaulted_args(&a.root), Some(3.0));
+}
+
+#[test]
+fn python_no_defaulted_args() {
+    let a = analyze(
+        "def f(a, b, *args, **kwargs):
+    return a",
+        "foo.py",
+    );
+    assert_eq!(defaulted_args(&a.root.spaces[0]), None);
+    assert_eq!(defaulted_args(&a.root), None);
+}
+
+#[test]
+fn python_max_param_name_length() {
+    let a = analyze(
+        "def f(a, *arguments, key=None, **options):
+    return lambda longest_name: a
+
+def g():
+    pass",
+        "foo.py",
+    );
+    let length = |space: &mehen_core::MetricSpace| {
+        space
+            .metrics
+            .get(&MetricKey::new(keys::MAX_PARAM_NAME_LENGTH))
+            .map(|v| v.as_f64())
+    };
+    let f = &a.root.spaces[0];
+    assert_eq!(length(&f.spaces[0]), Some(12.0));
+    assert_eq!(length(f), Some(12.0));
+    assert_eq!(length(&a.root.spaces[1]), None);
+    assert_eq!(length(&a.root), Some(12.0));
+}
@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Direct-recursion detection on function spaces.
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, SourceFile};
+use mehen_python::PythonAnalyzer;
+
+fn analyze(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let analyzer = PythonAnalyzer::new();
+    let file = SourceFile::new("foo.py".into(), Language::Python, text);
+    analyzer.analyze(&file, &AnalysisConfig::default()).unwrap()
+}
+
+#[test]
+fn python_recursive_factorial() {
+    let a = analyze(
+        "def factorial(n):
+    if n == 0:
+        return 1
+    return n * factorial(n - 1)",
+    );
+    assert!(a.root.spaces[0].is_recursive);
+    assert!(!a.root.is_recursive);
+}
+
+#[test]
+fn python_non_recursive_function() {
+    let a = analyze(
+        "def double(n):
+    return helper(n) * 2",
+    );
+    assert!(!a.root.spaces[0].is_recursive);
+}
+
+#[test]
+fn python_recursive_method_call() {
+    let a = analyze(
+        "class Node:
+    def depth(self):
+        return 1 + self.depth()",
+    );
+    assert!(a.root.spaces[0].spaces[0].is_recursive);
+}
@@ -1,9 +1,15 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 // Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
 
-use mehen_core::{DiffReport, MetricsReport};
+use std::collections::BTreeMap;
 
+use mehen_core::{DiffReport, MetricSpace, MetricsReport};
+use serde_json::{Value, json};
+
+use crate::metric_metadata::annotate_metrics;
 use crate::metrics_json::MetricsFamilies;
+use crate::ops_summary::OpsSummary;
+use crate::spans::span_records;
 
 /// Render a `MetricsReport` as JSON. Pretty-printed when `pretty=true`.
 ///
@@ -14,15 +20,210 @@ use crate::metrics_json::MetricsFamilies;
 /// (e.g. `cyclomatic.max`) keep working alongside the published
 /// schema.
 pub fn render_metrics_json(report: &MetricsReport, pretty: bool) -> serde_json::Result<String> {
+    let value = metrics_value(report)?;
+    to_string(&value, pretty)
+}
+
+/// Output switches for [`render_metrics_json_with`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MetricsJsonOptions<'a> {
+    pub pretty: bool,
+    /// Drop `root.spaces` so dashboards get one row per file instead of
+    /// the function tree.
+    pub file_level: bool,
+    /// Wrap each value of the `metrics` object as
+    /// `{ value, unit, description }` (see [`crate::metric_metadata`]).
+    pub with_metadata: bool,
+    /// Emit the columnar shape of [`flat_metrics_value`] instead of the
+    /// space tree. `file_level` and `with_metadata` do not apply.
+    pub flat: bool,
+    /// Leave out the derived aggregates (`*_average`, `*_min`, `*_max`,
+    /// …) and keep only the raw per-space values, for consumers that
+    /// aggregate on their own.
+    pub raw: bool,
+    /// Family objects to leave out of `metrics`, by their name in
+    /// [`crate::metrics_json::METRIC_FAMILIES`]. Their keys are expected
+    /// to be gone from the report already (`--exclude-metric`).
+    pub excluded: &'a [String],
+}
+
+/// [`render_metrics_json`] with the `--file-level` and `--with-metadata`
+/// shapes applied.
+pub fn render_metrics_json_with(
+    report: &MetricsReport,
+    options: MetricsJsonOptions<'_>,
+) -> serde_json::Result<String> {
+    if options.flat {
+        let mut value = flat_metrics_value(report);
+        if options.raw
+            && let Some(Value::Object(columns)) = value.get_mut("metrics")
+        {
+            columns.retain(|key, _| !is_aggregate_key(key));
+        }
+        return to_string(&value, options.pretty);
+    }
+    let mut value = metrics_value(report)?;
+    if let Some(Value::Object(families)) = value.get_mut("metrics") {
+        for name in options.excluded {
+            families.remove(name);
+        }
+    }
+    if options.raw {
+        strip_aggregates(&mut value);
+    }
+    if options.file_level
+        && let Some(serde_json::Value::Object(root)) = value.get_mut("root")
+    {
+        root.remove("spaces");
+    }
+    if options.with_metadata
+        && let Some(metrics) = value.get_mut("metrics")
+    {
+        annotate_metrics(metrics);
+    }
+    to_string(&value, options.pretty)
+}
+
+fn metrics_value(report: &MetricsReport) -> serde_json::Result<serde_json::Value> {
     let mut value = serde_json::to_value(report)?;
     let families = serde_json::to_value(MetricsFamilies::from_metrics(&report.root.metrics))?;
     if let serde_json::Value::Object(map) = &mut value {
         map.insert("metrics".to_string(), families);
     }
+    Ok(value)
+}
+
+/// Whether the flat metric `key` (`family.field`) is a derived
+/// aggregate rather than a raw value: `cyclomatic.min`, `loc.sloc.avg`,
+/// `loc.sloc_max`, `nom.functions_average`, `nargs.average_closures`,
+/// `loc.avg_function_sloc`, `loc.sloc_min_at`, `loc.function_sloc_p90`,
+/// …. Only the last segment is the field, so nested keys such as
+/// `loc.sloc.max` are caught. Bare keys such as `max_nesting` are
+/// per-space values.
+fn is_aggregate_key(key: &str) -> bool {
+    key.rsplit_once('.')
+        .is_some_and(|(_, field)| is_aggregate_field(field))
+}
+
+fn is_aggregate_field(field: &str) -> bool {
+    matches!(field, "min" | "max" | "average" | "avg")
+        || ["_min", "_max", "_average", "_min_at", "_max_at"]
+            .iter()
+            .any(|suffix| field.ends_with(suffix))
+        || field.starts_with("average_")
+        || field.starts_with("avg_")
+        || is_percentile_field(field)
+}
+
+/// `function_sloc_p50` and the like: a percentile over child spaces.
+fn is_percentile_field(field: &str) -> bool {
+    field
+        .rsplit_once("_p")
+        .is_some_and(|(_, rank)| !rank.is_empty() && rank.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// `--raw`: drop the aggregate fields from the per-family `metrics`
+/// object and the aggregate keys from every space of the `root` tree.
+fn strip_aggregates(value: &mut Value) {
+    if let Some(Value::Object(families)) = value.get_mut("metrics") {
+        for family in families.values_mut() {
+            if let Value::Object(fields) = family {
+                fields.retain(|field, _| !is_aggregate_field(field));
+            }
+        }
+    }
+    if let Some(root) = value.get_mut("root") {
+        strip_space_aggregates(root);
+    }
+}
+
+fn strip_space_aggregates(space: &mut Value) {
+    if let Some(Value::Object(metrics)) = space.get_mut("metrics") {
+        metrics.retain(|key, _| !is_aggregate_key(key));
+    }
+    if let Some(Value::Array(children)) = space.get_mut("spaces") {
+        children.iter_mut().for_each(strip_space_aggregates);
+    }
+}
+
+/// The `--flat-json` shape: the report header, a `functions` array
+/// with one entry per space below the unit in document order, and a
+/// `metrics` object mapping each flat metric key to an array parallel
+/// to `functions` — `null` where that space does not publish the key.
+fn flat_metrics_value(report: &MetricsReport) -> Value {
+    let mut spaces: Vec<&MetricSpace> = Vec::new();
+    report.root.visit(&mut |space, depth| {
+        if depth > 0 {
+            spaces.push(space);
+        }
+    });
+
+    let mut columns: BTreeMap<&str, Vec<Value>> = BTreeMap::new();
+    for (i, space) in spaces.iter().enumerate() {
+        for (key, value) in space.metrics.iter() {
+            let column = columns
+                .entry(key.as_str())
+                .or_insert_with(|| vec![Value::Null; spaces.len()]);
+            column[i] = serde_json::to_value(value).unwrap_or(Value::Null);
+        }
+    }
+    let functions: Vec<Value> = spaces
+        .iter()
+        .map(|space| {
+            json!({
+                "kind": space.kind,
+                "name": space.name,
+                "function_id": space.function_id,
+                "start_line": space.span.start_line,
+                "end_line": space.span.end_line,
+            })
+        })
+        .collect();
+
+    json!({
+        "schema_version": report.schema_version,
+        "tool": report.tool,
+        "path": report.path,
+        "language": report.language,
+        "analysis_backend": report.analysis_backend,
+        "diagnostics": report.diagnostics,
+        "functions": functions,
+        "metrics": columns,
+    })
+}
+
+fn to_string(value: &serde_json::Value, pretty: bool) -> serde_json::Result<String> {
+    if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    }
+}
+
+/// Render the `--ops-summary` view of a `MetricsReport`: the `top` most
+/// frequent Halstead operators and operands across the whole file.
+pub fn render_ops_summary_json(
+    report: &MetricsReport,
+    top: usize,
+    pretty: bool,
+) -> serde_json::Result<String> {
+    let summary = OpsSummary::from_report(report, top);
+    if pretty {
+        serde_json::to_string_pretty(&summary)
+    } else {
+        serde_json::to_string(&summary)
+    }
+}
+
+/// Render the `--emit-spans` view of a `MetricsReport`: a flat array
+/// with the kind, name, nesting depth and byte/line range of every
+/// space, unit first, in document order.
+pub fn render_spans_json(report: &MetricsReport, pretty: bool) -> serde_json::Result<String> {
+    let records = span_records(&report.root);
     if pretty {
-        serde_json::to_string_pretty(&value)
+        serde_json::to_string_pretty(&records)
     } else {
-        serde_json::to_string(&value)
+        serde_json::to_string(&records)
     }
 }
 
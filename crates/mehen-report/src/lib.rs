@@ -15,7 +15,16 @@
 pub mod github_markdown_docs;
 mod json;
 mod markdown;
+pub mod metric_metadata;
 pub mod metrics_json;
+mod ops_summary;
+mod spans;
 
-pub use json::{render_diff_json, render_metrics_json};
-pub use markdown::{render_diff_github_markdown, render_metrics_markdown};
+pub use json::{
+    MetricsJsonOptions, render_diff_json, render_metrics_json, render_metrics_json_with,
+    render_ops_summary_json, render_spans_json,
+};
+pub use markdown::{
+    render_diff_github_markdown, render_metrics_markdown, render_metrics_markdown_with,
+    render_ops_summary_markdown,
+};
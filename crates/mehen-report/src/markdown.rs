@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 // Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
 
+use std::collections::BTreeMap;
 use std::fmt::Write;
 
 use mehen_core::{
@@ -11,6 +12,7 @@ use mehen_core::{
 use crate::metrics_json::{
     Abc, Cognitive, Cyclomatic, Halstead, Loc, MetricsFamilies, Nargs, Nexits, Nom, Npa, Npm, Wmc,
 };
+use crate::ops_summary::OpsSummary;
 
 /// Render a single-file metrics report as Markdown.
 ///
@@ -34,6 +36,18 @@ use crate::metrics_json::{
 ///    "Unknown" or empty trees collapse to a single "no nested
 ///    spaces" line.
 pub fn render_metrics_markdown(report: &MetricsReport) -> String {
+    render_metrics_markdown_with(report, &BTreeMap::new(), &[])
+}
+
+/// [`render_metrics_markdown`] with table headings and columns renamed
+/// by `labels`, the `.mehen.toml` `[metric-aliases]`, and without the
+/// tables of the `excluded` families (`--exclude-metric`).
+pub fn render_metrics_markdown_with(
+    report: &MetricsReport,
+    labels: &BTreeMap<String, String>,
+    excluded: &[String],
+) -> String {
+    let tables = Tables { labels, excluded };
     let mut out = String::new();
     let _ = writeln!(out, "# {}", report.path);
     let _ = writeln!(out);
@@ -42,12 +56,34 @@ pub fn render_metrics_markdown(report: &MetricsReport) -> String {
     let _ = writeln!(out, "- schema: `{}`", report.schema_version);
 
     write_diagnostics(&mut out, &report.diagnostics);
-    write_unit_metrics(&mut out, &report.root.metrics, report.language);
-    write_nested_spaces(&mut out, &report.root.spaces, 0, report.language);
+    write_unit_metrics(&mut out, &tables, &report.root.metrics, report.language);
+    write_nested_spaces(&mut out, &tables, &report.root.spaces, 0, report.language);
 
     out
 }
 
+/// Render the `--ops-summary` view as two Markdown tables — top
+/// operators, then top operands.
+pub fn render_ops_summary_markdown(report: &MetricsReport, top: usize) -> String {
+    let summary = OpsSummary::from_report(report, top);
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}", summary.path);
+    for (title, tokens) in [
+        ("Operators", summary.operators),
+        ("Operands", summary.operands),
+    ] {
+        let _ = writeln!(out);
+        let _ = writeln!(out, "## {title}");
+        let _ = writeln!(out);
+        let _ = writeln!(out, "| token | count |");
+        let _ = writeln!(out, "|---|---:|");
+        for t in tokens {
+            let _ = writeln!(out, "| `{}` | {} |", escape_table_cell(&t.token), t.count);
+        }
+    }
+    out
+}
+
 fn write_diagnostics(out: &mut String, diagnostics: &[ParseDiagnostic]) {
     if diagnostics.is_empty() {
         return;
@@ -73,7 +109,12 @@ fn write_diagnostics(out: &mut String, diagnostics: &[ParseDiagnostic]) {
     }
 }
 
-fn write_unit_metrics(out: &mut String, metrics: &MetricSet, language: Language) {
+fn write_unit_metrics(
+    out: &mut String,
+    tables: &Tables<'_>,
+    metrics: &MetricSet,
+    language: Language,
+) {
     let _ = writeln!(out);
     let _ = writeln!(out, "## Metrics");
 
@@ -92,17 +133,17 @@ fn write_unit_metrics(out: &mut String, metrics: &MetricSet, language: Language)
     }
 
     let families = MetricsFamilies::from_metrics(metrics);
-    write_cyclomatic(out, &families.cyclomatic);
-    write_cognitive(out, &families.cognitive);
-    write_loc(out, &families.loc);
-    write_halstead(out, &families.halstead);
-    write_abc(out, &families.abc);
-    write_nargs(out, &families.nargs);
-    write_nom(out, &families.nom);
-    write_nexits(out, &families.nexits);
-    write_npa(out, &families.npa);
-    write_npm(out, &families.npm);
-    write_wmc(out, &families.wmc);
+    write_cyclomatic(out, tables, &families.cyclomatic);
+    write_cognitive(out, tables, &families.cognitive);
+    write_loc(out, tables, &families.loc);
+    write_halstead(out, tables, &families.halstead);
+    write_abc(out, tables, &families.abc);
+    write_nargs(out, tables, &families.nargs);
+    write_nom(out, tables, &families.nom);
+    write_nexits(out, tables, &families.nexits);
+    write_npa(out, tables, &families.npa);
+    write_npm(out, tables, &families.npm);
+    write_wmc(out, tables, &families.wmc);
 }
 
 /// Render the `markdown.*` metric family as Markdown tables.
@@ -321,7 +362,13 @@ fn read_metric(metrics: &MetricSet, key: &str) -> f64 {
         .unwrap_or(0.0)
 }
 
-fn write_nested_spaces(out: &mut String, spaces: &[MetricSpace], depth: usize, language: Language) {
+fn write_nested_spaces(
+    out: &mut String,
+    tables: &Tables<'_>,
+    spaces: &[MetricSpace],
+    depth: usize,
+    language: Language,
+) {
     if depth == 0 {
         // Print a section header only when at the top of the
         // recursion *and* there's something to show.
@@ -347,12 +394,18 @@ fn write_nested_spaces(out: &mut String, spaces: &[MetricSpace], depth: usize, l
         // that case rather than emit misleading numbers.
         if language != Language::Markdown {
             let families = MetricsFamilies::from_metrics(&space.metrics);
-            write_cyclomatic(out, &families.cyclomatic);
-            write_cognitive(out, &families.cognitive);
-            write_loc(out, &families.loc);
+            write_cyclomatic(out, tables, &families.cyclomatic);
+            write_cognitive(out, tables, &families.cognitive);
+            write_loc(out, tables, &families.loc);
         }
         if !space.spaces.is_empty() {
-            write_nested_spaces(out, &space.spaces, depth.saturating_add(1), language);
+            write_nested_spaces(
+                out,
+                tables,
+                &space.spaces,
+                depth.saturating_add(1),
+                language,
+            );
         }
     }
 }
@@ -373,195 +426,206 @@ fn space_kind_label(kind: &SpaceKind) -> &'static str {
 
 // --- Per-family helpers --------------------------------------------
 
-fn write_cyclomatic(out: &mut String, m: &Cyclomatic) {
-    let _ = writeln!(out);
-    let _ = writeln!(out, "### Cyclomatic");
-    let _ = writeln!(out);
-    let _ = writeln!(out, "| sum | average | min | max |");
-    let _ = writeln!(out, "|---:|---:|---:|---:|");
-    let _ = writeln!(
+fn write_cyclomatic(out: &mut String, tables: &Tables<'_>, m: &Cyclomatic) {
+    tables.write(
         out,
-        "| {} | {} | {} | {} |",
-        fmt_metric(m.sum),
-        fmt_metric(m.average),
-        fmt_metric(m.min),
-        fmt_metric(m.max),
+        "cyclomatic",
+        "Cyclomatic",
+        &[
+            ("sum", m.sum),
+            ("average", m.average),
+            ("min", m.min),
+            ("max", m.max),
+        ],
     );
 }
 
-fn write_cognitive(out: &mut String, m: &Cognitive) {
-    let _ = writeln!(out);
-    let _ = writeln!(out, "### Cognitive");
-    let _ = writeln!(out);
-    let _ = writeln!(out, "| sum | average | min | max |");
-    let _ = writeln!(out, "|---:|---:|---:|---:|");
-    let _ = writeln!(
+fn write_cognitive(out: &mut String, tables: &Tables<'_>, m: &Cognitive) {
+    tables.write(
         out,
-        "| {} | {} | {} | {} |",
-        fmt_metric(m.sum),
-        fmt_metric(m.average),
-        fmt_metric(m.min),
-        fmt_metric(m.max),
+        "cognitive",
+        "Cognitive",
+        &[
+            ("sum", m.sum),
+            ("average", m.average),
+            ("min", m.min),
+            ("max", m.max),
+        ],
     );
 }
 
-fn write_loc(out: &mut String, m: &Loc) {
-    let _ = writeln!(out);
-    let _ = writeln!(out, "### LOC");
-    let _ = writeln!(out);
-    let _ = writeln!(out, "| sloc | ploc | lloc | cloc | blank |");
-    let _ = writeln!(out, "|---:|---:|---:|---:|---:|");
-    let _ = writeln!(
+fn write_loc(out: &mut String, tables: &Tables<'_>, m: &Loc) {
+    tables.write(
         out,
-        "| {} | {} | {} | {} | {} |",
-        fmt_metric(m.sloc),
-        fmt_metric(m.ploc),
-        fmt_metric(m.lloc),
-        fmt_metric(m.cloc),
-        fmt_metric(m.blank),
+        "loc",
+        "LOC",
+        &[
+            ("sloc", m.sloc),
+            ("ploc", m.ploc),
+            ("lloc", m.lloc),
+            ("cloc", m.cloc),
+            ("blank", m.blank),
+        ],
     );
 }
 
-fn write_halstead(out: &mut String, m: &Halstead) {
-    let _ = writeln!(out);
-    let _ = writeln!(out, "### Halstead");
-    let _ = writeln!(out);
-    let _ = writeln!(out, "| n1 | N1 | n2 | N2 | volume | difficulty | effort |");
-    let _ = writeln!(out, "|---:|---:|---:|---:|---:|---:|---:|");
-    let _ = writeln!(
+fn write_halstead(out: &mut String, tables: &Tables<'_>, m: &Halstead) {
+    tables.write(
         out,
-        "| {} | {} | {} | {} | {} | {} | {} |",
-        fmt_metric(m.n1),
-        fmt_metric(m.big_n1),
-        fmt_metric(m.n2),
-        fmt_metric(m.big_n2),
-        fmt_metric(m.volume),
-        fmt_metric(m.difficulty),
-        fmt_metric(m.effort),
+        "halstead",
+        "Halstead",
+        &[
+            ("n1", m.n1),
+            ("N1", m.big_n1),
+            ("n2", m.n2),
+            ("N2", m.big_n2),
+            ("volume", m.volume),
+            ("difficulty", m.difficulty),
+            ("effort", m.effort),
+        ],
     );
 }
 
-fn write_abc(out: &mut String, m: &Abc) {
-    let _ = writeln!(out);
-    let _ = writeln!(out, "### ABC");
-    let _ = writeln!(out);
-    let _ = writeln!(out, "| assignments | branches | conditions | magnitude |");
-    let _ = writeln!(out, "|---:|---:|---:|---:|");
-    let _ = writeln!(
+fn write_abc(out: &mut String, tables: &Tables<'_>, m: &Abc) {
+    tables.write(
         out,
-        "| {} | {} | {} | {} |",
-        fmt_metric(m.assignments),
-        fmt_metric(m.branches),
-        fmt_metric(m.conditions),
-        fmt_metric(m.magnitude),
+        "abc",
+        "ABC",
+        &[
+            ("assignments", m.assignments),
+            ("branches", m.branches),
+            ("conditions", m.conditions),
+            ("magnitude", m.magnitude),
+        ],
     );
 }
 
-fn write_nargs(out: &mut String, m: &Nargs) {
-    let _ = writeln!(out);
-    let _ = writeln!(out, "### NArgs");
-    let _ = writeln!(out);
-    let _ = writeln!(
+fn write_nargs(out: &mut String, tables: &Tables<'_>, m: &Nargs) {
+    tables.write(
         out,
-        "| total_functions | total_closures | average | total |"
-    );
-    let _ = writeln!(out, "|---:|---:|---:|---:|");
-    let _ = writeln!(
-        out,
-        "| {} | {} | {} | {} |",
-        fmt_metric(m.total_functions),
-        fmt_metric(m.total_closures),
-        fmt_metric(m.average),
-        fmt_metric(m.total),
+        "nargs",
+        "NArgs",
+        &[
+            ("total_functions", m.total_functions),
+            ("total_closures", m.total_closures),
+            ("average", m.average),
+            ("total", m.total),
+        ],
     );
 }
 
-fn write_nom(out: &mut String, m: &Nom) {
-    let _ = writeln!(out);
-    let _ = writeln!(out, "### NOM");
-    let _ = writeln!(out);
-    let _ = writeln!(out, "| functions | closures | total |");
-    let _ = writeln!(out, "|---:|---:|---:|");
-    let _ = writeln!(
+fn write_nom(out: &mut String, tables: &Tables<'_>, m: &Nom) {
+    tables.write(
         out,
-        "| {} | {} | {} |",
-        fmt_metric(m.functions),
-        fmt_metric(m.closures),
-        fmt_metric(m.total),
+        "nom",
+        "NOM",
+        &[
+            ("functions", m.functions),
+            ("closures", m.closures),
+            ("total", m.total),
+        ],
     );
 }
 
-fn write_nexits(out: &mut String, m: &Nexits) {
-    let _ = writeln!(out);
-    let _ = writeln!(out, "### NExits");
-    let _ = writeln!(out);
-    let _ = writeln!(out, "| sum | average | min | max |");
-    let _ = writeln!(out, "|---:|---:|---:|---:|");
-    let _ = writeln!(
+fn write_nexits(out: &mut String, tables: &Tables<'_>, m: &Nexits) {
+    tables.write(
         out,
-        "| {} | {} | {} | {} |",
-        fmt_metric(m.sum),
-        fmt_metric(m.average),
-        fmt_metric(m.min),
-        fmt_metric(m.max),
+        "nexits",
+        "NExits",
+        &[
+            ("sum", m.sum),
+            ("average", m.average),
+            ("min", m.min),
+            ("max", m.max),
+        ],
     );
 }
 
-fn write_npa(out: &mut String, m: &Npa) {
-    let _ = writeln!(out);
-    let _ = writeln!(out, "### NPA");
-    let _ = writeln!(out);
-    let _ = writeln!(
+fn write_npa(out: &mut String, tables: &Tables<'_>, m: &Npa) {
+    tables.write(
         out,
-        "| classes | interfaces | class_attributes | interface_attributes | total |"
-    );
-    let _ = writeln!(out, "|---:|---:|---:|---:|---:|");
-    let _ = writeln!(
-        out,
-        "| {} | {} | {} | {} | {} |",
-        fmt_metric(m.classes),
-        fmt_metric(m.interfaces),
-        fmt_metric(m.class_attributes),
-        fmt_metric(m.interface_attributes),
-        fmt_metric(m.total),
+        "npa",
+        "NPA",
+        &[
+            ("classes", m.classes),
+            ("interfaces", m.interfaces),
+            ("class_attributes", m.class_attributes),
+            ("interface_attributes", m.interface_attributes),
+            ("total", m.total),
+        ],
     );
 }
 
-fn write_npm(out: &mut String, m: &Npm) {
-    let _ = writeln!(out);
-    let _ = writeln!(out, "### NPM");
-    let _ = writeln!(out);
-    let _ = writeln!(
-        out,
-        "| classes | interfaces | class_methods | interface_methods | total |"
-    );
-    let _ = writeln!(out, "|---:|---:|---:|---:|---:|");
-    let _ = writeln!(
+fn write_npm(out: &mut String, tables: &Tables<'_>, m: &Npm) {
+    tables.write(
         out,
-        "| {} | {} | {} | {} | {} |",
-        fmt_metric(m.classes),
-        fmt_metric(m.interfaces),
-        fmt_metric(m.class_methods),
-        fmt_metric(m.interface_methods),
-        fmt_metric(m.total),
+        "npm",
+        "NPM",
+        &[
+            ("classes", m.classes),
+            ("interfaces", m.interfaces),
+            ("class_methods", m.class_methods),
+            ("interface_methods", m.interface_methods),
+            ("total", m.total),
+        ],
     );
 }
 
-fn write_wmc(out: &mut String, m: &Wmc) {
-    let _ = writeln!(out);
-    let _ = writeln!(out, "### WMC");
-    let _ = writeln!(out);
-    let _ = writeln!(out, "| classes | interfaces | total |");
-    let _ = writeln!(out, "|---:|---:|---:|");
-    let _ = writeln!(
+fn write_wmc(out: &mut String, tables: &Tables<'_>, m: &Wmc) {
+    tables.write(
         out,
-        "| {} | {} | {} |",
-        fmt_metric(m.classes),
-        fmt_metric(m.interfaces),
-        fmt_metric(m.total),
+        "wmc",
+        "WMC",
+        &[
+            ("classes", m.classes),
+            ("interfaces", m.interfaces),
+            ("total", m.total),
+        ],
     );
 }
 
+/// What shapes the family tables of a metrics report.
+struct Tables<'a> {
+    /// The `.mehen.toml` metric aliases: a family name (`cyclomatic`)
+    /// renames the heading, and `family.column` (`loc.lloc`) renames a
+    /// column.
+    labels: &'a BTreeMap<String, String>,
+    /// Families left out by `--exclude-metric`; their tables are not
+    /// written at all, rather than as rows of zeros.
+    excluded: &'a [String],
+}
+
+impl Tables<'_> {
+    /// Write one family table.
+    fn write(&self, out: &mut String, family: &str, title: &str, columns: &[(&str, f64)]) {
+        if self.excluded.iter().any(|name| name == family) {
+            return;
+        }
+        let _ = writeln!(out);
+        let _ = writeln!(
+            out,
+            "### {}",
+            self.labels.get(family).map_or(title, String::as_str)
+        );
+        let _ = writeln!(out);
+        let mut header = String::from("|");
+        let mut rule = String::from("|");
+        let mut row = String::from("|");
+        for (column, value) in columns {
+            let label = self
+                .labels
+                .get(&format!("{family}.{column}"))
+                .map_or(*column, String::as_str);
+            let _ = write!(header, " {} |", escape_table_cell(label));
+            rule.push_str("---:|");
+            let _ = write!(row, " {} |", fmt_metric(*value));
+        }
+        let _ = writeln!(out, "{header}");
+        let _ = writeln!(out, "{rule}");
+        let _ = writeln!(out, "{row}");
+    }
+}
+
 /// Render an integer-valued metric as an integer when its
 /// fractional component is zero (the common case for counts), and
 /// as a 4-decimal float otherwise (for averages / ratios). NaN
@@ -678,6 +742,20 @@ mod tests {
         assert!(md.contains(r"long line \| with pipe"));
     }
 
+    #[test]
+    fn metric_aliases_rename_headings_and_columns() {
+        let report = report_with_metrics(&[("cyclomatic.sum", 7.0), ("loc.lloc", 30.0)]);
+        let labels = BTreeMap::from([
+            ("cyclomatic".to_string(), "McCabe".to_string()),
+            ("loc.lloc".to_string(), "Logical lines".to_string()),
+        ]);
+        let md = render_metrics_markdown_with(&report, &labels, &[]);
+        assert!(md.contains("### McCabe\n"));
+        assert!(!md.contains("### Cyclomatic"));
+        assert!(md.contains("| sloc | ploc | Logical lines | cloc | blank |"));
+        assert!(md.contains("| 0 | 0 | 30 | 0 | 0 |"));
+    }
+
     #[test]
     fn skips_diagnostics_section_when_empty() {
         let report = report_with_metrics(&[("cyclomatic.sum", 1.0)]);
@@ -0,0 +1,604 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! The metric reference: unit, description, formula and polarity per
+//! metric key.
+//!
+//! [`METRIC_DOCS`] is the one registry both consumers read.
+//! `mehen metrics --with-metadata` wraps every value of the `metrics`
+//! object as `{ value, unit, description }` so dashboards can label
+//! axes without hard-coding the metric catalogue, and
+//! `mehen explain-metric` prints an entry in full. JSON fields are
+//! mapped to keys by [`metric_metadata`]: the roll-up fields of a family
+//! (`sum`, `average`, …) share the family's entry, Halstead, LOC and MI
+//! fields have their own.
+
+use mehen_core::Polarity::{self, HigherIsBetter, HigherIsWorse, Neutral};
+
+/// Reference entry for one metric key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetricDoc {
+    /// Dotted key as `--metric` takes it, e.g. `halstead.bugs`; the
+    /// family name alone when the family has one measure.
+    pub key: &'static str,
+    pub unit: &'static str,
+    pub description: &'static str,
+    pub formula: Option<&'static str>,
+    pub polarity: Polarity,
+}
+
+const fn doc(
+    key: &'static str,
+    unit: &'static str,
+    description: &'static str,
+    formula: Option<&'static str>,
+    polarity: Polarity,
+) -> MetricDoc {
+    MetricDoc {
+        key,
+        unit,
+        description,
+        formula,
+        polarity,
+    }
+}
+
+pub const METRIC_DOCS: &[MetricDoc] = &[
+    doc(
+        "cyclomatic",
+        "paths",
+        "McCabe's cyclomatic complexity: the number of linearly independent paths through \
+         the code. Every decision point (if, loop, case, catch, && / ||) adds one.",
+        Some("CC = decisions + 1"),
+        HigherIsWorse,
+    ),
+    doc(
+        "cognitive",
+        "increments",
+        "How hard the code is to understand (SonarSource). Breaks in linear flow add one, \
+         plus one per level of nesting they sit in.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "loc.sloc",
+        "lines",
+        "Source lines: every physical line of the space, comments and blanks included.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "loc.ploc",
+        "lines",
+        "Physical lines that hold code, excluding comment-only and blank lines.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "loc.lloc",
+        "statements",
+        "Logical lines: statements as the language's grammar defines them.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "loc.cloc",
+        "lines",
+        "Comment lines: line, block and doc comments.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "loc.blank",
+        "lines",
+        "Whitespace-only lines.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "loc.blank_blocks",
+        "blocks",
+        "Runs of consecutive blank lines.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "loc.share",
+        "ratio",
+        "Fraction of the file's SLOC in this function.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "loc.max_block_lines",
+        "lines",
+        "Lines of the longest nested block.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "loc.avg_function_sloc",
+        "lines",
+        "Mean source lines per function.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "loc.function_sloc_p50",
+        "lines",
+        "Median source lines per function.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "loc.function_sloc_p90",
+        "lines",
+        "90th percentile of source lines per function.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "loc.function_sloc_p99",
+        "lines",
+        "99th percentile of source lines per function.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "nom",
+        "functions",
+        "Number of functions and closures.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "nom.functions",
+        "functions",
+        "Number of named functions and methods in the space.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "nom.closures",
+        "functions",
+        "Number of closures and lambdas in the space.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "nargs",
+        "arguments",
+        "Parameters declared by the function; summed over functions for larger spaces.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "nexit",
+        "exits",
+        "Exit points of a function: return statements and other early exits.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "npa",
+        "attributes",
+        "Number of public attributes across classes and interfaces.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "npm",
+        "methods",
+        "Number of public methods across classes and interfaces.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "wmc",
+        "paths",
+        "Weighted methods per class: the cyclomatic complexity of a class's methods, summed.",
+        Some("WMC = Σ CC(method)"),
+        HigherIsWorse,
+    ),
+    doc(
+        "abc",
+        "count",
+        "Magnitude of the ABC vector: assignments (A), branches such as calls (B) and \
+         conditions (C).",
+        Some("|ABC| = sqrt(A² + B² + C²)"),
+        HigherIsWorse,
+    ),
+    doc(
+        "halstead.n1",
+        "operators",
+        "Distinct operators.",
+        Some("η₁"),
+        HigherIsWorse,
+    ),
+    doc(
+        "halstead.N1",
+        "operators",
+        "Total operator occurrences.",
+        Some("N₁"),
+        HigherIsWorse,
+    ),
+    doc(
+        "halstead.n2",
+        "operands",
+        "Distinct operands.",
+        Some("η₂"),
+        HigherIsWorse,
+    ),
+    doc(
+        "halstead.N2",
+        "operands",
+        "Total operand occurrences.",
+        Some("N₂"),
+        HigherIsWorse,
+    ),
+    doc(
+        "halstead.length",
+        "tokens",
+        "Total operator and operand occurrences.",
+        Some("N = N₁ + N₂"),
+        HigherIsWorse,
+    ),
+    doc(
+        "halstead.estimated_program_length",
+        "tokens",
+        "Program length predicted from the vocabulary alone.",
+        Some("N̂ = η₁ · log₂(η₁) + η₂ · log₂(η₂)"),
+        HigherIsWorse,
+    ),
+    doc(
+        "halstead.purity_ratio",
+        "ratio",
+        "Estimated over actual program length.",
+        Some("N̂ / N"),
+        HigherIsBetter,
+    ),
+    doc(
+        "halstead.vocabulary",
+        "tokens",
+        "Distinct operators plus distinct operands.",
+        Some("η = η₁ + η₂"),
+        HigherIsWorse,
+    ),
+    doc(
+        "halstead.volume",
+        "bits",
+        "Size of the implementation in bits, from its operator and operand counts.",
+        Some("V = N · log₂(η), N = N₁ + N₂, η = η₁ + η₂"),
+        HigherIsWorse,
+    ),
+    doc(
+        "halstead.difficulty",
+        "ratio",
+        "How hard the code is to write or understand, driven by distinct operators and \
+         operand reuse.",
+        Some("D = (η₁ / 2) · (N₂ / η₂)"),
+        HigherIsWorse,
+    ),
+    doc(
+        "halstead.level",
+        "ratio",
+        "Program level, the inverse of difficulty.",
+        Some("L = 1 / D"),
+        HigherIsBetter,
+    ),
+    doc(
+        "halstead.effort",
+        "discriminations",
+        "Mental effort to develop the code, in elementary mental discriminations.",
+        Some("E = D · V"),
+        HigherIsWorse,
+    ),
+    doc(
+        "halstead.time",
+        "seconds",
+        "Estimated implementation time in seconds. A rough estimate.",
+        Some("T = E / 18"),
+        HigherIsWorse,
+    ),
+    doc(
+        "halstead.bugs",
+        "bugs",
+        "Estimated delivered bugs. A rough estimate with limited empirical backing.",
+        Some("B = V / 3000"),
+        HigherIsWorse,
+    ),
+    doc(
+        "halstead.operator_operand_ratio",
+        "ratio",
+        "Total operators per total operand. High values point at dense expressions, low \
+         ones at data-heavy code such as tables and literal lists. 0 without operands.",
+        Some("N₁ / N₂"),
+        Neutral,
+    ),
+    doc(
+        "mi.original",
+        "index",
+        "Maintainability index, original scale (up to 171). Combines Halstead volume, \
+         cyclomatic complexity and lines of code.",
+        Some("MI = 171 − 5.2 · ln(V) − 0.23 · CC − 16.2 · ln(SLOC)"),
+        HigherIsBetter,
+    ),
+    doc(
+        "mi.sei",
+        "index",
+        "Maintainability index, SEI variant, which also rewards comments.",
+        Some("MI = 171 − 5.2 · log₂(V) − 0.23 · CC − 16.2 · log₂(SLOC) + 50 · sin(sqrt(2.4 · CM))"),
+        HigherIsBetter,
+    ),
+    doc(
+        "mi.visual_studio",
+        "index",
+        "Maintainability index rescaled to 0–100, as Visual Studio reports it.",
+        Some("MI = max(0, MI_original · 100 / 171)"),
+        HigherIsBetter,
+    ),
+    doc(
+        "max_nesting",
+        "levels",
+        "Deepest nesting of control-flow structures, as cognitive complexity counts it.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "cognitive_nesting",
+        "increments",
+        "Part of cognitive complexity charged for nesting depth. When it outweighs \
+         `cognitive_structural`, flattening (early returns, extracted helpers) pays off \
+         more than removing branches.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "cognitive_structural",
+        "increments",
+        "Part of cognitive complexity charged for the structures themselves: one point per \
+         branch, loop, `else` and boolean-operator sequence, whatever the depth.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "max_call_chain",
+        "calls",
+        "Longest `.`-connected method-call chain; `a.b().c()` is 2.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "max_condition_terms",
+        "terms",
+        "Most && / || operands in a single condition.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "max_branches",
+        "arms",
+        "Most arms in a single match / switch, the default arm included.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "max_expr_depth",
+        "levels",
+        "Deepest nesting of binary operators, calls and indexing in one expression; \
+         `f(a + b[i])` is 3.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "node_count",
+        "nodes",
+        "Syntax-tree nodes in the space, comments and whitespace excluded.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "leaf_count",
+        "nodes",
+        "Syntax-tree leaves (tokens) in the space, comments excluded.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "import_count",
+        "imports",
+        "Imported modules; the file value is the total. A high count can point at a \
+         god-module.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "magic_numbers",
+        "literals",
+        "Numeric literals other than 0, 1 and -1 that are not the value of a named \
+         constant. Each one is a candidate for a `const`.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "unused_private",
+        "functions",
+        "Private functions never referenced elsewhere in the same file: a lightweight, \
+         intra-file dead-code signal.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "defaulted_args",
+        "parameters",
+        "Parameters callers may omit because they have a default value.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "max_param_name_length",
+        "characters",
+        "Longest parameter name of any function or closure in the space.",
+        None,
+        HigherIsWorse,
+    ),
+    doc(
+        "debt_ratio",
+        "ratio",
+        "SQALE-style technical debt ratio of the file: remediation minutes charged per \
+         decision point and cognitive-complexity point, over Halstead `time`.",
+        None,
+        HigherIsWorse,
+    ),
+];
+
+/// The registry entry for `key`.
+pub fn metric_doc(key: &str) -> Option<&'static MetricDoc> {
+    METRIC_DOCS.iter().find(|d| d.key == key)
+}
+
+/// Unit and description attached to one JSON metric field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetricMetadata {
+    pub unit: &'static str,
+    pub description: &'static str,
+}
+
+impl From<&MetricDoc> for MetricMetadata {
+    fn from(doc: &MetricDoc) -> Self {
+        Self {
+            unit: doc.unit,
+            description: doc.description,
+        }
+    }
+}
+
+/// Metadata for `field` of the `family` object, or `None` when the
+/// family is not part of the published schema.
+pub fn metric_metadata(family: &str, field: &str) -> Option<MetricMetadata> {
+    let key = match family {
+        "halstead" | "mi" => format!("{family}.{field}"),
+        "loc" => return Some(loc(field)),
+        "nom" if matches!(field, "functions" | "closures") => format!("nom.{field}"),
+        "nexits" => "nexit".to_string(),
+        _ => family.to_string(),
+    };
+    match metric_doc(&key) {
+        Some(doc) => Some(doc.into()),
+        None if family == "halstead" => Some(MetricMetadata {
+            unit: "count",
+            description: "Halstead measure.",
+        }),
+        None => None,
+    }
+}
+
+fn loc(field: &str) -> MetricMetadata {
+    // Positions rather than measurements, so not registry entries.
+    if matches!(field, "sloc_min_at" | "sloc_max_at") {
+        return MetricMetadata {
+            unit: "line",
+            description: "Start line of the space with the SLOC bound.",
+        };
+    }
+    // Roll-ups such as `sloc_average` share their measure's entry.
+    let doc = metric_doc(&format!("loc.{field}"))
+        .or_else(|| metric_doc(&format!("loc.{}", field.split('_').next().unwrap_or(field))));
+    doc.map_or(
+        MetricMetadata {
+            unit: "lines",
+            description: "Lines of code.",
+        },
+        MetricMetadata::from,
+    )
+}
+
+/// Wrap every numeric field of a serialized `metrics` object (as built
+/// from [`MetricsFamilies`](crate::metrics_json::MetricsFamilies)) as
+/// `{ value, unit, description }`. Scalar families such as
+/// `max_call_chain` are wrapped in place; unknown families are left
+/// untouched.
+pub fn annotate_metrics(metrics: &mut serde_json::Value) {
+    let serde_json::Value::Object(families) = metrics else {
+        return;
+    };
+    for (family, value) in families.iter_mut() {
+        match value {
+            serde_json::Value::Object(fields) => {
+                for (field, value) in fields.iter_mut() {
+                    wrap(value, metric_metadata(family, field));
+                }
+            }
+            _ => wrap(value, metric_metadata(family, family)),
+        }
+    }
+}
+
+fn wrap(value: &mut serde_json::Value, metadata: Option<MetricMetadata>) {
+    let Some(metadata) = metadata else {
+        return;
+    };
+    if !value.is_number() {
+        return;
+    }
+    *value = serde_json::json!({
+        "value": value.take(),
+        "unit": metadata.unit,
+        "description": metadata.description,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_family_fields_and_scalar_families() {
+        let mut metrics = serde_json::json!({
+            "halstead": {"time": 12.5, "volume": 40.0},
+            "max_call_chain": 3.0,
+        });
+        annotate_metrics(&mut metrics);
+        assert_eq!(metrics["halstead"]["time"]["value"], 12.5);
+        assert_eq!(metrics["halstead"]["time"]["unit"], "seconds");
+        assert_eq!(metrics["halstead"]["volume"]["unit"], "bits");
+        assert_eq!(metrics["max_call_chain"]["unit"], "calls");
+    }
+
+    #[test]
+    fn json_fields_resolve_to_registry_entries() {
+        let entry = |family, field| metric_metadata(family, field).unwrap().description;
+        assert_eq!(
+            entry("cyclomatic", "average"),
+            metric_doc("cyclomatic").unwrap().description
+        );
+        assert_eq!(
+            entry("nexits", "sum"),
+            metric_doc("nexit").unwrap().description
+        );
+        assert_eq!(
+            entry("nom", "closures"),
+            metric_doc("nom.closures").unwrap().description
+        );
+        assert_eq!(
+            entry("loc", "lloc_average"),
+            metric_doc("loc.lloc").unwrap().description
+        );
+        assert_eq!(
+            entry("mi", "sei"),
+            metric_doc("mi.sei").unwrap().description
+        );
+        assert!(metric_metadata("not_a_family", "x").is_none());
+    }
+
+    #[test]
+    fn registry_keys_are_unique() {
+        for (i, doc) in METRIC_DOCS.iter().enumerate() {
+            assert!(
+                METRIC_DOCS[i + 1..].iter().all(|d| d.key != doc.key),
+                "{}",
+                doc.key
+            );
+        }
+    }
+}
@@ -205,6 +205,7 @@ pub fn halstead(metrics: &MetricSet) -> Halstead {
         effort: as_f64(metrics, "halstead.effort"),
         time: as_f64(metrics, "halstead.time"),
         bugs: as_f64(metrics, "halstead.bugs"),
+        operator_operand_ratio: as_f64(metrics, "halstead.operator_operand_ratio"),
     }
 }
 
@@ -226,6 +227,7 @@ pub struct Halstead {
     pub effort: f64,
     pub time: f64,
     pub bugs: f64,
+    pub operator_operand_ratio: f64,
 }
 
 /// Render the `abc` family object: 13 fields covering A/B/C totals,
@@ -334,7 +336,8 @@ pub struct Nom {
 /// Render the `loc` family object: 20 fields covering SLOC / PLOC /
 /// LLOC / CLOC / blank with rolled-up totals, per-line-class
 /// averages, and per-line-class min/max bounds. The ordering matches
-/// the pre-1.0 `Loc::Stats::serialize` field order.
+/// the pre-1.0 `Loc::Stats::serialize` field order; `blank_blocks`,
+/// `share` and `max_block_lines` are appended where published.
 pub fn loc(metrics: &MetricSet) -> Loc {
     Loc {
         sloc: as_f64(metrics, "loc.sloc"),
@@ -357,6 +360,33 @@ pub fn loc(metrics: &MetricSet) -> Loc {
         lloc_max: as_f64(metrics, "loc.lloc.max"),
         blank_min: as_f64(metrics, "loc.blank.min"),
         blank_max: as_f64(metrics, "loc.blank.max"),
+        blank_blocks: metrics
+            .get(&MetricKey::new("loc.blank_blocks"))
+            .map(|v| v.as_f64()),
+        share: metrics
+            .get(&MetricKey::new("loc.share"))
+            .map(|v| v.as_f64()),
+        max_block_lines: metrics
+            .get(&MetricKey::new("loc.max_block_lines"))
+            .map(|v| v.as_f64()),
+        avg_function_sloc: metrics
+            .get(&MetricKey::new("loc.avg_function_sloc"))
+            .map(|v| v.as_f64()),
+        function_sloc_p50: metrics
+            .get(&MetricKey::new("loc.function_sloc_p50"))
+            .map(|v| v.as_f64()),
+        function_sloc_p90: metrics
+            .get(&MetricKey::new("loc.function_sloc_p90"))
+            .map(|v| v.as_f64()),
+        function_sloc_p99: metrics
+            .get(&MetricKey::new("loc.function_sloc_p99"))
+            .map(|v| v.as_f64()),
+        sloc_min_at: metrics
+            .get(&MetricKey::new("loc.sloc_min_at"))
+            .map(|v| v.as_f64()),
+        sloc_max_at: metrics
+            .get(&MetricKey::new("loc.sloc_max_at"))
+            .map(|v| v.as_f64()),
     }
 }
 
@@ -382,6 +412,34 @@ pub struct Loc {
     pub lloc_max: f64,
     pub blank_min: f64,
     pub blank_max: f64,
+    /// Runs of consecutive blank lines. Only published on function
+    /// spaces, so it is omitted elsewhere.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blank_blocks: Option<f64>,
+    /// This function's SLOC as a fraction of the file's SLOC. Function
+    /// spaces only, like `blank_blocks`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub share: Option<f64>,
+    /// Longest nested block in the space, in lines. Only published for
+    /// languages whose walker reports blocks (Rust, Go, Python).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_block_lines: Option<f64>,
+    /// Mean SLOC of the function spaces in the file. Unit space only,
+    /// and only when the file has functions; likewise the percentiles.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_function_sloc: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function_sloc_p50: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function_sloc_p90: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function_sloc_p99: Option<f64>,
+    /// Start line of the space `sloc_min` / `sloc_max` came from. Unit
+    /// space only, and only when the file has nested spaces.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sloc_min_at: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sloc_max_at: Option<f64>,
 }
 
 fn as_f64(metrics: &MetricSet, key: &str) -> f64 {
@@ -392,6 +450,25 @@ fn as_f64(metrics: &MetricSet, key: &str) -> f64 {
     }
 }
 
+/// The metric families `mehen metrics --exclude-metric` can leave out,
+/// as `(name, key prefix)`: the name of the family object in
+/// [`MetricsFamilies`] (or, for `mi`, of the keys in the space tree) and
+/// the prefix its values are published under.
+pub const METRIC_FAMILIES: &[(&str, &str)] = &[
+    ("cyclomatic", "cyclomatic"),
+    ("cognitive", "cognitive"),
+    ("nexits", "nexit"),
+    ("nom", "nom"),
+    ("nargs", "nargs"),
+    ("npa", "npa"),
+    ("npm", "npm"),
+    ("wmc", "wmc"),
+    ("abc", "abc"),
+    ("halstead", "halstead"),
+    ("loc", "loc"),
+    ("mi", "mi"),
+];
+
 /// All metric families pivoted into the documented per-family shape.
 /// This is what the CLI emits as the `metrics` field of `mehen metrics
 /// --format json`, replacing the flat `metric_key → value` map of the
@@ -409,6 +486,72 @@ pub struct MetricsFamilies {
     pub abc: Abc,
     pub halstead: Halstead,
     pub loc: Loc,
+    /// Longest method-call chain (`a.b().c()` is 2). Only published for
+    /// languages that measure chains (Rust, TypeScript) and spaces with
+    /// at least one call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_call_chain: Option<f64>,
+    /// Most `&&` / `||` operands in a single condition. Only published
+    /// for languages that measure it (Rust, TypeScript) and spaces with
+    /// at least one boolean operator.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_condition_terms: Option<f64>,
+    /// Most arms in a single `match` / `switch`, `default` included.
+    /// Only published for languages that measure it (Rust, Go) and
+    /// spaces with at least one such construct.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_branches: Option<f64>,
+    /// Deepest nesting of binary operators, calls and indexing in one
+    /// expression. Only published for languages that measure it (Rust,
+    /// TypeScript) and spaces with at least one such expression.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_expr_depth: Option<f64>,
+    /// Deepest nesting of control-flow structures, as cognitive
+    /// complexity counts it (a top-level `if` is 1). Only published for
+    /// spaces with at least one such structure.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_nesting: Option<f64>,
+    /// Part of `cognitive.sum` charged for nesting depth. Only published
+    /// for spaces with non-zero cognitive complexity.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cognitive_nesting: Option<f64>,
+    /// The rest of `cognitive.sum`: one point per structure, `else`
+    /// clause and boolean-operator sequence.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cognitive_structural: Option<f64>,
+    /// Syntax-tree nodes in the space, comments excluded. Published by
+    /// the Rust and tree-sitter analyzers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_count: Option<f64>,
+    /// How many of `node_count` are leaves (tokens).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leaf_count: Option<f64>,
+    /// Imported modules in the space and its children; the unit holds
+    /// the file total. Published by the Python, Rust and Go analyzers
+    /// for spaces with at least one import.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub import_count: Option<f64>,
+    /// Numeric literals other than 0, 1 and -1 outside named constants,
+    /// in the space and its children. Published by the Rust and Python
+    /// analyzers for spaces with at least one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub magic_numbers: Option<f64>,
+    /// Private functions never referenced elsewhere in the file. Unit
+    /// space only; published by the Rust analyzer when there are any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unused_private: Option<f64>,
+    /// Estimated remediation time over Halstead development time. Unit
+    /// space only; omitted when the file has no Halstead time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debt_ratio: Option<f64>,
+    /// Parameters with a default value (Python) or that are optional or
+    /// defaulted (TypeScript), summed over the space and its children.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub defaulted_args: Option<f64>,
+    /// Longest parameter name, in characters, of any function or
+    /// closure in the space (Python and TypeScript).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_param_name_length: Option<f64>,
 }
 
 impl MetricsFamilies {
@@ -425,6 +568,51 @@ impl MetricsFamilies {
             abc: abc(metrics),
             halstead: halstead(metrics),
             loc: loc(metrics),
+            max_call_chain: metrics
+                .get(&MetricKey::new("max_call_chain"))
+                .map(|v| v.as_f64()),
+            max_condition_terms: metrics
+                .get(&MetricKey::new("max_condition_terms"))
+                .map(|v| v.as_f64()),
+            max_branches: metrics
+                .get(&MetricKey::new("max_branches"))
+                .map(|v| v.as_f64()),
+            max_expr_depth: metrics
+                .get(&MetricKey::new("max_expr_depth"))
+                .map(|v| v.as_f64()),
+            max_nesting: metrics
+                .get(&MetricKey::new("max_nesting"))
+                .map(|v| v.as_f64()),
+            cognitive_nesting: metrics
+                .get(&MetricKey::new("cognitive_nesting"))
+                .map(|v| v.as_f64()),
+            cognitive_structural: metrics
+                .get(&MetricKey::new("cognitive_structural"))
+                .map(|v| v.as_f64()),
+            node_count: metrics
+                .get(&MetricKey::new("node_count"))
+                .map(|v| v.as_f64()),
+            leaf_count: metrics
+                .get(&MetricKey::new("leaf_count"))
+                .map(|v| v.as_f64()),
+            import_count: metrics
+                .get(&MetricKey::new("import_count"))
+                .map(|v| v.as_f64()),
+            magic_numbers: metrics
+                .get(&MetricKey::new("magic_numbers"))
+                .map(|v| v.as_f64()),
+            unused_private: metrics
+                .get(&MetricKey::new("unused_private"))
+                .map(|v| v.as_f64()),
+            debt_ratio: metrics
+                .get(&MetricKey::new("debt_ratio"))
+                .map(|v| v.as_f64()),
+            defaulted_args: metrics
+                .get(&MetricKey::new("defaulted_args"))
+                .map(|v| v.as_f64()),
+            max_param_name_length: metrics
+                .get(&MetricKey::new("max_param_name_length"))
+                .map(|v| v.as_f64()),
         }
     }
 }
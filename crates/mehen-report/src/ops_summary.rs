@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! File-level Halstead token summary behind `mehen metrics --ops-summary`.
+//!
+//! Analyzers attach the file-wide operator/operand frequencies to the
+//! unit space ([`mehen_core::MetricSpace::halstead_tokens`]); this view
+//! keeps the `top` most frequent of each. Languages without Halstead
+//! events (Markdown, PowerShell) produce empty lists.
+
+use mehen_core::{Language, MetricsReport, TokenCount};
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub(crate) struct OpsSummary<'a> {
+    pub(crate) path: &'a str,
+    pub(crate) language: Language,
+    pub(crate) operators: &'a [TokenCount],
+    pub(crate) operands: &'a [TokenCount],
+}
+
+impl<'a> OpsSummary<'a> {
+    pub(crate) fn from_report(report: &'a MetricsReport, top: usize) -> Self {
+        let (operators, operands) = match &report.root.halstead_tokens {
+            Some(tokens) => (
+                &tokens.operators[..top.min(tokens.operators.len())],
+                &tokens.operands[..top.min(tokens.operands.len())],
+            ),
+            None => (&[][..], &[][..]),
+        };
+        Self {
+            path: report.path.as_str(),
+            language: report.language,
+            operators,
+            operands,
+        }
+    }
+}
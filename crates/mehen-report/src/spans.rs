@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Flat span listing behind `mehen metrics --emit-spans`.
+//!
+//! One record per space (unit, classes, functions, closures) in document
+//! order, with byte and line ranges and no metrics, for editor
+//! navigation. `depth` is 0 for the unit and grows by one per enclosing
+//! space.
+
+use mehen_core::{MetricSpace, SpaceKind};
+use serde::Serialize;
+
+#[derive(Debug, PartialEq, Serialize)]
+pub(crate) struct SpanRecord<'a> {
+    pub(crate) kind: &'a SpaceKind,
+    pub(crate) name: Option<&'a str>,
+    pub(crate) depth: usize,
+    pub(crate) start_byte: u32,
+    pub(crate) end_byte: u32,
+    pub(crate) start_line: u32,
+    pub(crate) end_line: u32,
+}
+
+pub(crate) fn span_records(root: &MetricSpace) -> Vec<SpanRecord<'_>> {
+    let mut out = Vec::new();
+    root.visit(&mut |space, depth| {
+        out.push(SpanRecord {
+            kind: &space.kind,
+            name: space.name.as_deref(),
+            depth,
+            start_byte: space.span.start_byte,
+            end_byte: space.span.end_byte,
+            start_line: space.span.start_line,
+            end_line: space.span.end_line,
+        });
+    });
+    out
+}
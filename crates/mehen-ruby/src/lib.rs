@@ -43,9 +43,14 @@ impl LanguageAnalyzer for RubyAnalyzer {
         AnalysisBackend::Prism
     }
 
-    fn analyze(&self, source: &SourceFile, _config: &AnalysisConfig) -> Result<LanguageAnalysis> {
+    fn analyze(&self, source: &SourceFile, config: &AnalysisConfig) -> Result<LanguageAnalysis> {
         let parse = ruby_prism::parse(source.text.as_bytes());
-        let root = walker::walk_program(&parse, &source.text, &source.line_index);
+        let root = walker::walk_program(
+            &parse,
+            &source.text,
+            &source.line_index,
+            config.halstead_tokens,
+        );
         // Recovered Prism syntax errors are surfaced as `error` (not
         // `warning`) so the diagnostic contract (plan §9.3) treats the
         // analysis as incomplete: `mehen metrics` exits 1 and
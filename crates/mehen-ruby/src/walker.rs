@@ -67,7 +67,7 @@
 use mehen_core::{LineIndex, MetricSpace, SourceSpan, SpaceKind};
 use mehen_metrics::{
     ContainerKind, HalsteadOperand, HalsteadOperator, MetricTreeBuilder, State, apply_state_to,
-    close_space, finalize_state,
+    close_space, finalize_state, publish_function_loc,
 };
 use ruby_prism::{
     AndNode, BeginNode, BlockNode, BreakNode, CallNode, CallOperatorWriteNode, CaseMatchNode,
@@ -99,6 +99,7 @@ pub(crate) fn walk_program(
     parse: &ParseResult<'_>,
     source: &str,
     line_index: &LineIndex,
+    halstead_tokens: bool,
 ) -> MetricSpace {
     let unit_span = SourceSpan {
         start_byte: 0,
@@ -133,7 +134,7 @@ pub(crate) fn walk_program(
         );
     }
 
-    visitor.finish()
+    visitor.finish(halstead_tokens)
 }
 
 struct Visitor<'a> {
@@ -201,7 +202,7 @@ impl<'a> Visitor<'a> {
         self.stack.last_mut().expect("walker stack empty")
     }
 
-    fn finish(mut self) -> MetricSpace {
+    fn finish(mut self, halstead_tokens: bool) -> MetricSpace {
         let mut unit_state = self.stack.pop().expect("walker stack underflow");
         finalize_state(&mut unit_state);
         // Route post-AST observations (only LOC for Ruby — Halstead is
@@ -218,7 +219,12 @@ impl<'a> Visitor<'a> {
             .finalize_into_tree(&mut tree, &mut unit_halstead, &mut unit_loc);
         unit_state.halstead = unit_halstead;
         unit_state.loc = unit_loc;
+        if halstead_tokens {
+            tree.halstead_tokens = Some(unit_state.halstead.token_frequencies());
+        }
+        let blank_rows = unit_state.loc.blank_rows();
         apply_state_to(unit_state, &mut tree.metrics);
+        publish_function_loc(&mut tree, &blank_rows);
         tree
     }
 
@@ -275,8 +281,11 @@ impl<'a> Visitor<'a> {
     /// Increase nesting + boolean-seq reset, mirroring legacy
     /// `increase_nesting` from `cognitive.rs:239`.
     fn cognitive_increase_nesting(&mut self) {
-        let effective = self.cognitive.nesting + self.cognitive.depth + self.cognitive.lambda;
-        self.current().cognitive.increase_nesting(effective);
+        let nesting = self.cognitive.nesting;
+        let effective = nesting + self.cognitive.depth + self.cognitive.lambda;
+        self.current()
+            .cognitive
+            .increase_nesting(effective, nesting);
         self.current().cognitive.boolean_seq.reset();
     }
 
@@ -49,7 +49,8 @@ fn ruby_operators_and_operands() {
       "level": "[masked]",
       "effort": "[masked]",
       "time": "[masked]",
-      "bugs": "[masked]"
+      "bugs": "[masked]",
+      "operator_operand_ratio": 0.8
     }"###
     );
 }
@@ -13,12 +13,14 @@
 
 #![forbid(unsafe_code)]
 
+mod unused;
 mod walker;
 
 use mehen_core::{
     AnalysisBackend, AnalysisConfig, Language, LanguageAnalysis, LanguageAnalyzer, LineIndex,
-    ParseDiagnostic, Result, SourceFile,
+    MetricKey, ParseDiagnostic, Result, SourceFile, keys,
 };
+use mehen_metrics::node_cap_fallback;
 use ra_ap_syntax::{Edition, SourceFile as RustSourceFile};
 
 pub struct RustAnalyzer;
@@ -44,7 +46,7 @@ impl LanguageAnalyzer for RustAnalyzer {
         AnalysisBackend::RaApSyntax
     }
 
-    fn analyze(&self, source: &SourceFile, _config: &AnalysisConfig) -> Result<LanguageAnalysis> {
+    fn analyze(&self, source: &SourceFile, config: &AnalysisConfig) -> Result<LanguageAnalysis> {
         // ra_ap_syntax always returns a tree, even on parse errors. Errors
         // are surfaced through `parse.errors()`; we don't fail the
         // analysis on recoverable errors — the legacy tree-sitter
@@ -54,9 +56,27 @@ impl LanguageAnalyzer for RustAnalyzer {
         // incomplete: `mehen metrics` exits 1 and `analyze_diff`
         // records the file under `analysis_errors`.
         let parse = RustSourceFile::parse(&source.text, Edition::CURRENT);
+        let nodes = parse.syntax_node().descendants_with_tokens().count() as u64;
+        if let Some(analysis) =
+            node_cap_fallback(source, config, AnalysisBackend::RaApSyntax, nodes)
+        {
+            return Ok(analysis);
+        }
         let file = parse.tree();
         let line_index = LineIndex::new(&source.text);
-        let root = walker::walk_source_file(&file, &source.text, &line_index);
+        let mut root = walker::walk_source_file(
+            &file,
+            &source.text,
+            &line_index,
+            config.count_match_guards,
+            config.inline_closures,
+            config.halstead_tokens,
+        );
+        let unused = unused::unused_private_fns(&file);
+        if unused > 0 {
+            root.metrics
+                .insert(MetricKey::new(keys::UNUSED_PRIVATE), unused as i64);
+        }
         let diagnostics: Vec<ParseDiagnostic> = parse
             .errors()
             .iter()
@@ -0,0 +1,119 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Intra-file dead-code signal: `unused_private`.
+//!
+//! Counts private functions and methods that nothing else in the same
+//! file refers to. One pass collects every candidate `fn` and every name
+//! reference (a `NAME_REF`, or an identifier inside a macro's token
+//! tree, where calls are not parsed); a candidate is unused when no
+//! reference with its name lies outside its own body, so a function
+//! that only calls itself still counts.
+//!
+//! Matching is by name, without resolution: a private `new` is taken as
+//! used by any `X::new()` in the file. Candidates leave out everything
+//! reachable from elsewhere without a visible call: items with any
+//! visibility modifier, `main`, trait and trait-impl methods and
+//! `extern` declarations. Any attribute outside [`ADVISORY_ATTRS`], such
+//! as `#[test]`, `#[no_mangle]` or a proc-macro attribute, excludes the
+//! function too.
+//!
+//! Only this file is searched. A private fn used solely from a child
+//! module in another file (`super::helper` from `tests.rs`) is counted
+//! as unused.
+
+use std::collections::HashMap;
+
+use ra_ap_syntax::{
+    AstNode, NodeOrToken, SourceFile, SyntaxKind, TextRange, TextSize,
+    ast::{self, HasAttrs, HasName, HasVisibility},
+};
+
+/// Attributes that do not make a function reachable from outside.
+const ADVISORY_ATTRS: &[&str] = &[
+    "allow",
+    "cold",
+    "deny",
+    "doc",
+    "expect",
+    "inline",
+    "must_use",
+    "track_caller",
+    "warn",
+];
+
+/// Number of private functions in `file` that are never referenced
+/// outside their own body.
+pub(crate) fn unused_private_fns(file: &SourceFile) -> usize {
+    let mut candidates: Vec<(String, TextRange)> = Vec::new();
+    let mut references: HashMap<String, Vec<TextSize>> = HashMap::new();
+    for element in file.syntax().descendants_with_tokens() {
+        match element {
+            NodeOrToken::Node(node) => {
+                if let Some(func) = ast::Fn::cast(node.clone())
+                    && is_candidate(&func)
+                    && let Some(name) = func.name()
+                {
+                    candidates.push((name.text().to_string(), node.text_range()));
+                } else if node.kind() == SyntaxKind::NAME_REF {
+                    references
+                        .entry(node.text().to_string())
+                        .or_default()
+                        .push(node.text_range().start());
+                }
+            }
+            NodeOrToken::Token(token) => {
+                if token.kind() == SyntaxKind::IDENT
+                    && token
+                        .parent()
+                        .is_some_and(|p| p.kind() == SyntaxKind::TOKEN_TREE)
+                {
+                    references
+                        .entry(token.text().to_string())
+                        .or_default()
+                        .push(token.text_range().start());
+                }
+            }
+        }
+    }
+    candidates
+        .iter()
+        .filter(|(name, range)| {
+            !references
+                .get(name)
+                .is_some_and(|at| at.iter().any(|offset| !range.contains(*offset)))
+        })
+        .count()
+}
+
+fn is_candidate(func: &ast::Fn) -> bool {
+    if func.visibility().is_some() || func.body().is_none() {
+        return false;
+    }
+    if func.name().is_some_and(|name| name.text() == "main") {
+        return false;
+    }
+    let advisory_only = func.attrs().all(|attr| {
+        attr.path()
+            .is_some_and(|path| ADVISORY_ATTRS.contains(&path.syntax().text().to_string().as_str()))
+    });
+    if !advisory_only {
+        return false;
+    }
+    // Trait items and trait-impl methods are called through the trait.
+    match func
+        .syntax()
+        .parent()
+        .and_then(|list| list.parent())
+        .map(|owner| owner.kind())
+    {
+        Some(SyntaxKind::TRAIT) => false,
+        Some(SyntaxKind::IMPL) => func
+            .syntax()
+            .parent()
+            .and_then(|list| list.parent())
+            .and_then(ast::Impl::cast)
+            .is_some_and(|imp| imp.trait_().is_none()),
+        _ => true,
+    }
+}
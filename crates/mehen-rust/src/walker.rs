@@ -20,15 +20,24 @@
 //!   `+1` (no nesting bump). It's a real short-circuit on `Err`/`None`,
 //!   matching legacy and Sonar.
 //! - **Match arms**: each arm contributes +1 cyclomatic. The `match`
-//!   expression itself opens a cognitive nesting frame.
+//!   expression itself opens a cognitive nesting frame. An arm guard
+//!   (`Some(x) if x > 0 =>`) adds nothing by default, as in legacy;
+//!   [`crate::RustAnalyzer::with_match_guards`] makes it +1 — a failing
+//!   guard falls through to the next arm, the way clippy counts it.
+//! - **`if let` / `while let`**: counted like plain `if` / `while`;
+//!   each `&&` in a let-chain adds +1 like any other `&&`.
 //! - **`else if`**: the inner `if` does NOT add cognitive nesting (legacy
 //!   `is_else_if` rule); only the outer `if` does. The `else` branch
 //!   contributes a flat +1 instead.
 //! - **Macro contents are opaque**: tokens *inside* a `MacroCall`
 //!   argument list (or `macro_rules!` body) do not contribute to
-//!   cyclomatic, cognitive, ABC, or exit counters. The macro name itself
-//!   counts as a branch. This matches the legacy
-//!   `is_inside_rust_macro_tokens` filter.
+//!   cyclomatic, cognitive, ABC, exit, LLOC or Halstead counters. The
+//!   macro name itself counts as a branch. This matches the legacy
+//!   `is_inside_rust_macro_tokens` filter. In particular the arms of a
+//!   `macro_rules!` are not branches: they are matched at compile time,
+//!   and the code they expand to is measured (if at all) where it is
+//!   written, not once per call site. The lines of a macro body still
+//!   count as physical code lines (`loc.ploc`), never as blank.
 //! - **Type annotations contribute to Halstead**: type identifiers like
 //!   `Vec<T>` are Halstead operands. Rust types are not erased — they
 //!   describe runtime values. (Same reasoning as Python; opposite of TS.)
@@ -42,7 +51,7 @@
 use mehen_core::{LineIndex, MetricSpace, SourceSpan, SpaceKind};
 use mehen_metrics::{
     ContainerKind, HalsteadOperand, HalsteadOperator, MetricTreeBuilder, SpaceRangeTracker, State,
-    apply_state_to, close_space, finalize_state,
+    apply_state_to, close_space, finalize_state, publish_function_loc,
 };
 use ra_ap_syntax::{
     AstNode, NodeOrToken, SourceFile, SyntaxKind, SyntaxNode, SyntaxToken, TextRange, WalkEvent,
@@ -57,14 +66,19 @@ pub(crate) fn walk_source_file(
     file: &SourceFile,
     source: &str,
     line_index: &LineIndex,
+    count_match_guards: bool,
+    inline_closures: bool,
+    halstead_tokens: bool,
 ) -> MetricSpace {
     let unit_range = file.syntax().text_range();
     let unit_span = text_range_to_source_span(unit_range, line_index);
 
     let mut visitor = Visitor::new(source, line_index, unit_span);
+    visitor.count_match_guards = count_match_guards;
+    visitor.inline_closures = inline_closures;
     visitor.walk(file.syntax());
     visitor.emit_halstead_from_tokens(file.syntax());
-    visitor.finish()
+    visitor.finish(halstead_tokens)
 }
 
 #[derive(Clone, Copy)]
@@ -108,6 +122,15 @@ struct Visitor<'a> {
     /// flagged the same gap on the Python walker; the Rust walker had
     /// the same `stack[0]`-only behaviour.
     halstead_routing: SpaceRangeTracker,
+    /// Names of the enclosing `fn` items, innermost last, each with
+    /// whether it is an associated fn of an `impl` or `trait`. Used to
+    /// flag direct self-calls as recursion.
+    function_names: Vec<(String, bool)>,
+    /// Whether a `match` arm guard is a cyclomatic decision.
+    count_match_guards: bool,
+    /// Walk closure bodies as part of the enclosing space
+    /// (`AnalysisConfig::inline_closures`).
+    inline_closures: bool,
 }
 
 impl<'a> Visitor<'a> {
@@ -128,6 +151,9 @@ impl<'a> Visitor<'a> {
             macro_opaque_ranges: Vec::new(),
             macro_opaque_depth: 0,
             halstead_routing: SpaceRangeTracker::new(),
+            function_names: Vec::new(),
+            count_match_guards: false,
+            inline_closures: false,
         }
     }
 
@@ -135,7 +161,7 @@ impl<'a> Visitor<'a> {
         self.stack.last_mut().expect("walker stack empty")
     }
 
-    fn finish(mut self) -> MetricSpace {
+    fn finish(mut self, halstead_tokens: bool) -> MetricSpace {
         let mut unit_state = self.stack.pop().expect("walker stack underflow");
         finalize_state(&mut unit_state);
         // Route post-AST tokens (Halstead operator/operand,
@@ -148,7 +174,12 @@ impl<'a> Visitor<'a> {
             .finalize_into_tree(&mut tree, &mut unit_halstead, &mut unit_loc);
         unit_state.halstead = unit_halstead;
         unit_state.loc = unit_loc;
+        if halstead_tokens {
+            tree.halstead_tokens = Some(unit_state.halstead.token_frequencies());
+        }
+        let blank_rows = unit_state.loc.blank_rows();
         apply_state_to(unit_state, &mut tree.metrics);
+        publish_function_loc(&mut tree, &blank_rows);
         tree
     }
 
@@ -173,6 +204,9 @@ impl<'a> Visitor<'a> {
     }
 
     fn close_space(&mut self) {
+        if matches!(self.kinds.last(), Some(SpaceKind::Function)) {
+            self.function_names.pop();
+        }
         close_space(
             &mut self.stack,
             &mut self.kinds,
@@ -181,6 +215,45 @@ impl<'a> Visitor<'a> {
         );
     }
 
+    /// Whether a call node names the innermost enclosing `fn` — a bare
+    /// `f(..)` from a free fn, a `Self::f(..)` path, or a `self.f(..)`
+    /// method call. Rust has no implicit `self`, so a bare `f(..)` in a
+    /// method calls some other, free `f`. Calls from a nested closure do
+    /// not count: the innermost space must be the function itself.
+    fn is_self_call(&self, node: &SyntaxNode) -> bool {
+        if !matches!(self.kinds.last(), Some(SpaceKind::Function)) {
+            return false;
+        }
+        let Some((name, is_assoc)) = self.function_names.last() else {
+            return false;
+        };
+        if let Some(call) = ast::CallExpr::cast(node.clone()) {
+            let Some(ast::Expr::PathExpr(callee)) = call.expr() else {
+                return false;
+            };
+            let Some(path) = callee.path() else {
+                return false;
+            };
+            let qualified_by_self = match path.qualifier() {
+                None => !is_assoc,
+                Some(q) => q.syntax().text() == "Self",
+            };
+            return qualified_by_self
+                && path
+                    .segment()
+                    .and_then(|s| s.name_ref())
+                    .is_some_and(|n| n.text() == name.as_str());
+        }
+        if let Some(call) = ast::MethodCallExpr::cast(node.clone()) {
+            let receiver_is_self = matches!(
+                call.receiver(),
+                Some(ast::Expr::PathExpr(p)) if p.syntax().text() == "self"
+            );
+            return receiver_is_self && call.name_ref().is_some_and(|n| n.text() == name.as_str());
+        }
+        false
+    }
+
     /// Drive a preorder walk over the syntax tree. Uses an explicit
     /// `WalkEvent` loop so we can finalize the per-space stack on
     /// `Leave` events.
@@ -190,9 +263,18 @@ impl<'a> Visitor<'a> {
             match event {
                 WalkEvent::Enter(node) => {
                     let action = self.enter_node(&node);
+                    // After `enter_node`, so an item that opens a space
+                    // counts towards that space.
+                    self.count_node(&node);
+                    if is_depth_expr(&node) {
+                        self.current().expr_depth.enter();
+                    }
                     actions.push(action);
                 }
-                WalkEvent::Leave(_) => {
+                WalkEvent::Leave(node) => {
+                    if is_depth_expr(&node) {
+                        self.current().expr_depth.leave();
+                    }
                     let action = actions.pop().expect("walker action stack underflow");
                     match action {
                         LeaveAction::None => {}
@@ -213,6 +295,22 @@ impl<'a> Visitor<'a> {
         }
     }
 
+    /// `node_count` / `leaf_count`: the node itself plus its non-trivia
+    /// tokens, which are the leaves of a rowan tree. Every token has one
+    /// parent node, so each is counted exactly once.
+    fn count_node(&mut self, node: &SyntaxNode) {
+        let counts = &mut self.current().node_count;
+        counts.observe(false);
+        for token in node
+            .children_with_tokens()
+            .filter_map(NodeOrToken::into_token)
+        {
+            if !token.kind().is_trivia() {
+                counts.observe(true);
+            }
+        }
+    }
+
     /// Handle a node-enter event. Returns the matching leave action.
     fn enter_node(&mut self, node: &SyntaxNode) -> LeaveAction {
         let kind = node.kind();
@@ -282,6 +380,11 @@ impl<'a> Visitor<'a> {
                 }
                 self.cognitive = ctx;
 
+                let is_assoc = node
+                    .parent()
+                    .is_some_and(|p| p.kind() == SyntaxKind::ASSOC_ITEM_LIST);
+                self.function_names
+                    .push((name.clone().unwrap_or_default(), is_assoc));
                 self.open_space(SpaceKind::Function, node.text_range(), name);
 
                 let argc = func
@@ -298,7 +401,17 @@ impl<'a> Visitor<'a> {
                 ctx.lambda = ctx.lambda.saturating_add(1);
                 self.cognitive = ctx;
 
-                self.open_space(SpaceKind::Closure, node.text_range(), None);
+                // Inlined: the body is walked as part of the current
+                // space, so it adds no space, NOM entry or closure args.
+                if self.inline_closures {
+                    return LeaveAction::RestoreCognitive(saved);
+                }
+
+                self.open_space(
+                    SpaceKind::Closure,
+                    node.text_range(),
+                    closure_binding_name(node),
+                );
 
                 if let Some(closure) = ast::ClosureExpr::cast(node.clone()) {
                     let argc = closure
@@ -329,9 +442,11 @@ impl<'a> Visitor<'a> {
                 self.current().cyclomatic.record_decision();
                 self.current().abc.record_condition();
                 let bumped_nesting = if !is_else_if(node) {
-                    let effective =
-                        self.cognitive.nesting + self.cognitive.depth + self.cognitive.lambda;
-                    self.current().cognitive.increase_nesting(effective);
+                    let nesting = self.cognitive.nesting;
+                    let effective = nesting + self.cognitive.depth + self.cognitive.lambda;
+                    self.current()
+                        .cognitive
+                        .increase_nesting(effective, nesting);
                     true
                 } else {
                     // `else if` — the legacy walker emits the +1 (flat)
@@ -362,9 +477,11 @@ impl<'a> Visitor<'a> {
             SyntaxKind::WHILE_EXPR | SyntaxKind::FOR_EXPR | SyntaxKind::LOOP_EXPR => {
                 self.current().cyclomatic.record_decision();
                 self.current().abc.record_condition();
-                let effective =
-                    self.cognitive.nesting + self.cognitive.depth + self.cognitive.lambda;
-                self.current().cognitive.increase_nesting(effective);
+                let nesting = self.cognitive.nesting;
+                let effective = nesting + self.cognitive.depth + self.cognitive.lambda;
+                self.current()
+                    .cognitive
+                    .increase_nesting(effective, nesting);
                 self.current().cognitive.boolean_seq.reset();
                 let saved = self.cognitive;
                 self.cognitive.nesting = self.cognitive.nesting.saturating_add(1);
@@ -372,9 +489,17 @@ impl<'a> Visitor<'a> {
             }
             SyntaxKind::MATCH_EXPR => {
                 self.current().abc.record_condition();
-                let effective =
-                    self.cognitive.nesting + self.cognitive.depth + self.cognitive.lambda;
-                self.current().cognitive.increase_nesting(effective);
+                if let Some(arms) = ast::MatchExpr::cast(node.clone())
+                    .and_then(|m| m.match_arm_list())
+                    .map(|list| list.arms().count())
+                {
+                    self.current().branches.observe(arms as u32);
+                }
+                let nesting = self.cognitive.nesting;
+                let effective = nesting + self.cognitive.depth + self.cognitive.lambda;
+                self.current()
+                    .cognitive
+                    .increase_nesting(effective, nesting);
                 self.current().cognitive.boolean_seq.reset();
                 let saved = self.cognitive;
                 self.cognitive.nesting = self.cognitive.nesting.saturating_add(1);
@@ -385,6 +510,10 @@ impl<'a> Visitor<'a> {
                 self.current().abc.record_condition();
                 LeaveAction::None
             }
+            SyntaxKind::MATCH_GUARD if self.count_match_guards => {
+                self.current().cyclomatic.record_decision();
+                LeaveAction::None
+            }
             SyntaxKind::TRY_EXPR => {
                 // `?` short-circuits on Err/None: +1 cyclomatic, +1 cognitive
                 // (no nesting), +1 ABC condition, +1 exit.
@@ -413,11 +542,17 @@ impl<'a> Visitor<'a> {
                             self.current().cyclomatic.record_decision();
                             self.current().abc.record_condition();
                             self.current().cognitive.observe_boolean("&&");
+                            self.current()
+                                .condition_terms
+                                .observe(condition_terms(node));
                         }
                         BinaryOp::LogicOp(LogicOp::Or) => {
                             self.current().cyclomatic.record_decision();
                             self.current().abc.record_condition();
                             self.current().cognitive.observe_boolean("||");
+                            self.current()
+                                .condition_terms
+                                .observe(condition_terms(node));
                         }
                         BinaryOp::CmpOp(_) => {
                             self.current().abc.record_condition();
@@ -455,12 +590,27 @@ impl<'a> Visitor<'a> {
                 self.current().loc.observe_lloc();
                 LeaveAction::None
             }
+            // One per `use` item; a `use a::{b, c};` tree counts once.
+            SyntaxKind::USE => {
+                self.current().imports.record_import();
+                LeaveAction::None
+            }
+            SyntaxKind::LITERAL => {
+                if is_magic_number(node) {
+                    self.current().magic_numbers.record();
+                }
+                LeaveAction::None
+            }
 
             // -----------------------------------------------------------------
             // Branches (B in ABC)
             // -----------------------------------------------------------------
             SyntaxKind::CALL_EXPR | SyntaxKind::METHOD_CALL_EXPR => {
                 self.current().abc.record_branch();
+                self.current().call_chain.observe(call_chain_len(node));
+                if self.is_self_call(node) {
+                    self.tree.mark_recursive();
+                }
                 LeaveAction::None
             }
             SyntaxKind::MACRO_CALL => {
@@ -508,6 +658,29 @@ impl<'a> Visitor<'a> {
                 LeaveAction::None
             }
 
+            // -----------------------------------------------------------------
+            // Nested blocks (`loc.max_block_lines`). A function's or
+            // closure's own body is the space itself, not a nested block.
+            // -----------------------------------------------------------------
+            SyntaxKind::BLOCK_EXPR => {
+                let is_body = node
+                    .parent()
+                    .is_some_and(|p| matches!(p.kind(), SyntaxKind::FN | SyntaxKind::CLOSURE_EXPR));
+                if !is_body {
+                    let range = node.text_range();
+                    let start_row = self
+                        .line_index
+                        .line_at(range.start().into())
+                        .saturating_sub(1);
+                    let end_row = self
+                        .line_index
+                        .line_at(range.end().into())
+                        .saturating_sub(1);
+                    self.current().loc.observe_block(start_row, end_row);
+                }
+                LeaveAction::None
+            }
+
             _ => LeaveAction::None,
         }
     }
@@ -581,8 +754,9 @@ impl<'a> Visitor<'a> {
         // Macro-opaque ranges: any token whose span is *strictly inside*
         // a macro-opaque range (not at the boundary — the macro name and
         // the trailing `!` live outside the body) is excluded from
-        // Halstead.
+        // Halstead. Its line is still code, not blank.
         if self.is_inside_macro_body(range) {
+            self.observe_code_line(range);
             return;
         }
 
@@ -625,11 +799,15 @@ impl<'a> Visitor<'a> {
             TokenClass::Skip => {}
         }
 
-        // PLOC: any non-whitespace, non-comment token's starting line
-        // is a code line — routed to the deepest enclosing scope so
-        // per-space `loc.ploc` reflects the function/closure body.
-        // Lines outside every recorded scope go into the unit
-        // (top-level use statements, free constants, etc.).
+        self.observe_code_line(range);
+    }
+
+    /// PLOC: any non-whitespace, non-comment token's starting line
+    /// is a code line — routed to the deepest enclosing scope so
+    /// per-space `loc.ploc` reflects the function/closure body.
+    /// Lines outside every recorded scope go into the unit
+    /// (top-level use statements, free constants, etc.).
+    fn observe_code_line(&mut self, range: TextRange) {
         let start_row = self
             .line_index
             .line_at(range.start().into())
@@ -682,6 +860,20 @@ fn text_range_to_source_span(range: TextRange, line_index: &LineIndex) -> Source
     }
 }
 
+/// Name a closure after the `let` binding it initializes
+/// (`let f = || ..` → `f`). Closures passed inline as arguments, or
+/// bound to a destructuring pattern, stay anonymous.
+fn closure_binding_name(node: &SyntaxNode) -> Option<String> {
+    let stmt = ast::LetStmt::cast(node.parent()?)?;
+    if stmt.initializer()?.syntax() != node {
+        return None;
+    }
+    match stmt.pat()? {
+        ast::Pat::IdentPat(pat) => pat.name().map(|n| n.text().to_string()),
+        _ => None,
+    }
+}
+
 fn count_params(pl: &ast::ParamList) -> usize {
     let regular = pl.params().count();
     let self_param = pl.self_param().is_some() as usize;
@@ -708,6 +900,64 @@ fn has_label_child(node: &SyntaxNode) -> bool {
         .any(|c| matches!(c.kind(), SyntaxKind::LIFETIME))
 }
 
+/// Calls in the `.`-connected chain ending at `call`:
+/// `a.b().c()` is 2, `Vec::new().iter().count()` is 3. Field access,
+/// `?`, `.await` and parentheses between calls do not break the chain.
+fn call_chain_len(call: &SyntaxNode) -> u32 {
+    let mut len = 0;
+    let mut node = Some(call.clone());
+    while let Some(current) = node {
+        node = match current.kind() {
+            SyntaxKind::CALL_EXPR | SyntaxKind::METHOD_CALL_EXPR => {
+                len += 1;
+                current.first_child()
+            }
+            SyntaxKind::FIELD_EXPR
+            | SyntaxKind::TRY_EXPR
+            | SyntaxKind::AWAIT_EXPR
+            | SyntaxKind::PAREN_EXPR => current.first_child(),
+            _ => None,
+        };
+    }
+    len
+}
+
+/// Does `node` count towards `max_expr_depth`? Binary operators other
+/// than assignments, calls, method calls and indexing.
+fn is_depth_expr(node: &SyntaxNode) -> bool {
+    match node.kind() {
+        SyntaxKind::BIN_EXPR => ast::BinExpr::cast(node.clone())
+            .and_then(|bin| bin.op_kind())
+            .is_some_and(|op| !matches!(op, BinaryOp::Assignment { .. })),
+        SyntaxKind::CALL_EXPR | SyntaxKind::METHOD_CALL_EXPR | SyntaxKind::INDEX_EXPR => true,
+        _ => false,
+    }
+}
+
+/// Leaf operands of the `&&` / `||` tree rooted at `expr`:
+/// `a && (b || c)` is 3. Parentheses are looked through; any other
+/// expression, including `!(…)`, is a single term.
+fn condition_terms(expr: &SyntaxNode) -> u32 {
+    match expr.kind() {
+        SyntaxKind::BIN_EXPR => {
+            let Some(bin) = ast::BinExpr::cast(expr.clone()) else {
+                return 1;
+            };
+            if !matches!(bin.op_kind(), Some(BinaryOp::LogicOp(_))) {
+                return 1;
+            }
+            [bin.lhs(), bin.rhs()]
+                .into_iter()
+                .map(|side| side.map_or(1, |side| condition_terms(side.syntax())))
+                .sum()
+        }
+        SyntaxKind::PAREN_EXPR => ast::ParenExpr::cast(expr.clone())
+            .and_then(|paren| paren.expr())
+            .map_or(1, |inner| condition_terms(inner.syntax())),
+        _ => 1,
+    }
+}
+
 /// Is this node the *tail expression* of a `STMT_LIST` (i.e. the final
 /// expression of a block body, with no terminating `;`)? The legacy
 /// `is_rust_tail_expression` rule treated such expressions as a logical
@@ -734,6 +984,60 @@ fn is_block_tail_expression(node: &SyntaxNode) -> bool {
     }
 }
 
+/// Is this `LITERAL` a magic number: an integer or float literal whose
+/// value is not 0 or 1 (`-1` is a `PREFIX_EXPR` over `1`), outside a
+/// `const` / `static` item and an enum discriminant? Literals inside
+/// macro arguments never reach here — the macro body is opaque.
+fn is_magic_number(literal: &SyntaxNode) -> bool {
+    let Some(token) = literal.first_token() else {
+        return false;
+    };
+    if !matches!(
+        token.kind(),
+        SyntaxKind::INT_NUMBER | SyntaxKind::FLOAT_NUMBER
+    ) {
+        return false;
+    }
+    if literal.ancestors().any(|a| {
+        matches!(
+            a.kind(),
+            SyntaxKind::CONST | SyntaxKind::STATIC | SyntaxKind::VARIANT
+        )
+    }) {
+        return false;
+    }
+    !is_trivial_number(token.text())
+}
+
+/// `0`, `1`, `0x1`, `1_u8`, `0.0`, `1.`, `1e0` … — literal text whose
+/// value is 0 or 1 once separators, radix prefix and type suffix go.
+fn is_trivial_number(text: &str) -> bool {
+    let digits: String = text.chars().filter(|&c| c != '_').collect();
+    let lower = digits.to_ascii_lowercase();
+    let (radix, body) = match lower.get(..2) {
+        Some("0x") => (16, &lower[2..]),
+        Some("0o") => (8, &lower[2..]),
+        Some("0b") => (2, &lower[2..]),
+        _ => (10, lower.as_str()),
+    };
+    let body = [
+        "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+    ]
+    .iter()
+    .chain(if radix == 10 {
+        &["f32", "f64"][..]
+    } else {
+        &[][..]
+    })
+    .find_map(|suffix| body.strip_suffix(suffix))
+    .unwrap_or(body);
+    if radix == 10 {
+        body.parse::<f64>().is_ok_and(|v| v == 0.0 || v == 1.0)
+    } else {
+        u128::from_str_radix(body, radix).is_ok_and(|v| v <= 1)
+    }
+}
+
 enum TokenClass {
     Operator(&'static str),
     Operand(&'static str),
@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Widest `match` per space (`max_branches`).
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, MetricKey, SourceFile, keys};
+use mehen_rust::RustAnalyzer;
+
+fn analyze(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let analyzer = RustAnalyzer::new();
+    let file = SourceFile::new("foo.rs".into(), Language::Rust, text);
+    analyzer.analyze(&file, &AnalysisConfig::default()).unwrap()
+}
+
+fn max_branches(space: &mehen_core::MetricSpace) -> Option<f64> {
+    space
+        .metrics
+        .get(&MetricKey::new(keys::MAX_BRANCHES))
+        .map(|v| v.as_f64())
+}
+
+#[test]
+fn rust_counts_match_arms() {
+    let a = analyze(
+        "fn f(n: u8) -> u8 {
+             let small = match n {
+                 0 => true,
+                 _ => false,
+             };
+             match n {
+                 0 => 10,
+                 1 => 20,
+                 2 | 3 => 30,
+                 4 if small => 40,
+                 5..=9 => 50,
+                 _ => 0,
+             }
+         }
+         fn g() {}",
+    );
+    assert_eq!(max_branches(&a.root.spaces[0]), Some(6.0));
+    assert_eq!(max_branches(&a.root.spaces[1]), None);
+    assert_eq!(max_branches(&a.root), Some(6.0));
+}
@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Longest `.`-connected method-call chain (`max_call_chain`).
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, MetricKey, SourceFile, keys};
+use mehen_rust::RustAnalyzer;
+
+fn analyze(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let analyzer = RustAnalyzer::new();
+    let file = SourceFile::new("foo.rs".into(), Language::Rust, text);
+    analyzer.analyze(&file, &AnalysisConfig::default()).unwrap()
+}
+
+fn max_call_chain(space: &mehen_core::MetricSpace) -> Option<f64> {
+    space
+        .metrics
+        .get(&MetricKey::new(keys::MAX_CALL_CHAIN))
+        .map(|v| v.as_f64())
+}
+
+#[test]
+fn rust_five_call_chain() {
+    let a = analyze(
+        "fn f(xs: Vec<i32>) -> Vec<i32> {
+             let short = xs.len();
+             xs.into_iter()
+                 .filter(|x| *x > 0)
+                 .map(|x| x * 2)
+                 .rev()
+                 .collect()
+         }",
+    );
+    assert_eq!(max_call_chain(&a.root.spaces[0]), Some(5.0));
+    assert_eq!(max_call_chain(&a.root), Some(5.0));
+}
+
+#[test]
+fn rust_chain_continues_through_try_and_fields() {
+    let a = analyze(
+        "fn f(path: &str) -> std::io::Result<usize> {
+             Ok(std::fs::read_to_string(path)?.trim().lines().count())
+         }",
+    );
+    // read_to_string → trim → lines → count; the outer `Ok(..)` is its own chain.
+    assert_eq!(max_call_chain(&a.root.spaces[0]), Some(4.0));
+}
+
+#[test]
+fn rust_no_calls_publishes_nothing() {
+    let a = analyze("fn f(a: i32) -> i32 { a + 1 }");
+    assert_eq!(max_call_chain(&a.root.spaces[0]), None);
+}
@@ -4,7 +4,7 @@
 //! Cognitive complexity tests, ported from
 //! `crates/mehen-engine/src/legacy/metrics/cognitive.rs::tests`.
 
-use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, SourceFile};
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, MetricKey, SourceFile, keys};
 use mehen_rust::RustAnalyzer;
 
 fn analyze(source: &str) -> mehen_core::LanguageAnalysis {
@@ -389,3 +389,70 @@ fn rust_loop_and_try() {
     }"###
     );
 }
+
+fn breakdown(a: &mehen_core::LanguageAnalysis) -> (f64, f64) {
+    let get = |key| {
+        a.root.spaces[0]
+            .metrics
+            .get(&MetricKey::new(key))
+            .map_or(0.0, |v| v.as_f64())
+    };
+    (
+        get(keys::COGNITIVE_NESTING),
+        get(keys::COGNITIVE_STRUCTURAL),
+    )
+}
+
+#[test]
+fn rust_deep_nesting_dominates_cognitive_breakdown() {
+    let a = analyze(
+        "fn f(xs: &[i32]) {
+             for x in xs {             // +1
+                 if *x > 0 {           // +2 (nesting = 1)
+                     while g() {       // +3 (nesting = 2)
+                         if h() {      // +4 (nesting = 3)
+                             k();
+                         }
+                     }
+                 }
+             }
+         }",
+    );
+    assert_eq!(breakdown(&a), (6.0, 4.0));
+}
+
+#[test]
+fn rust_flat_branches_have_no_nesting_penalty() {
+    let a = analyze(
+        "fn f(x: i32) {
+             if x > 0 { g(); }         // +1
+             if x > 1 { g(); }         // +1
+             if x > 2 { g(); } else {} // +2
+         }",
+    );
+    assert_eq!(breakdown(&a), (0.0, 4.0));
+}
+
+#[test]
+fn rust_max_nesting_counts_structures_not_penalties() {
+    // The closure raises the cognitive penalty of the inner `if` to 2,
+    // but only one structure encloses it inside the function.
+    let a = analyze(
+        "fn f(x: i32) {
+             let c = || {
+                 if x > 0 {            // +2 (lambda = 1)
+                     g();
+                 }
+             };
+             c();
+         }",
+    );
+    let f = &a.root.spaces[0];
+    assert_eq!(
+        f.metrics
+            .get(&MetricKey::new(keys::MAX_NESTING))
+            .map(|v| v.as_f64()),
+        Some(1.0)
+    );
+    assert_eq!(breakdown(&a), (1.0, 1.0));
+}
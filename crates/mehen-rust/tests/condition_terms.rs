@@ -0,0 +1,54 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Most `&&` / `||` operands in a single condition (`max_condition_terms`).
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, MetricKey, SourceFile, keys};
+use mehen_rust::RustAnalyzer;
+
+fn analyze(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let analyzer = RustAnalyzer::new();
+    let file = SourceFile::new("foo.rs".into(), Language::Rust, text);
+    analyzer.analyze(&file, &AnalysisConfig::default()).unwrap()
+}
+
+fn max_condition_terms(space: &mehen_core::MetricSpace) -> Option<f64> {
+    space
+        .metrics
+        .get(&MetricKey::new(keys::MAX_CONDITION_TERMS))
+        .map(|v| v.as_f64())
+}
+
+#[test]
+fn rust_eight_term_condition() {
+    let a = analyze(
+        "fn f(a: bool, b: bool, c: bool, d: bool, e: bool, g: bool, h: bool, i: bool) -> u8 {
+             if a && b { return 1; }
+             if a && b && (c || d) && !(e && g) && h && i && a == b {
+                 return 2;
+             }
+             0
+         }",
+    );
+    // a, b, c, d, !(e && g), h, i, a == b — the negation is one term.
+    assert_eq!(max_condition_terms(&a.root.spaces[0]), Some(8.0));
+    assert_eq!(max_condition_terms(&a.root), Some(8.0));
+}
+
+#[test]
+fn rust_eight_anded_terms() {
+    let a = analyze(
+        "fn f(a: bool, b: bool, c: bool, d: bool, e: bool, g: bool, h: bool, i: bool) -> bool {
+             a && b && c && d && e && g && h && i
+         }",
+    );
+    assert_eq!(max_condition_terms(&a.root.spaces[0]), Some(8.0));
+}
+
+#[test]
+fn rust_no_boolean_operators_publishes_nothing() {
+    let a = analyze("fn f(a: i32) -> bool { a > 1 }");
+    assert_eq!(max_condition_terms(&a.root.spaces[0]), None);
+}
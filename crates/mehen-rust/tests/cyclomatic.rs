@@ -56,3 +56,80 @@ fn rust_macro_tokens_are_opaque_for_cyclomatic() {
     // `if`) do not count — they are not parsed Rust control flow.
     assert_eq!(cy.sum, 2.0, "got {}", serde_json::to_string(&cy).unwrap());
 }
+
+#[test]
+fn rust_if_let_chain_and_while_let() {
+    let a = analyze(
+        "fn f(a: Option<i32>, mut it: std::vec::IntoIter<i32>) -> i32 {
+             let mut n = 0;
+             if let Some(x) = a && let Some(y) = it.next() {
+                 n += x + y;
+             }
+             while let Some(v) = it.next() {
+                 n += v;
+             }
+             n
+         }",
+    );
+    let cy = mehen_report::metrics_json::cyclomatic(&a.root.metrics);
+    // Function: baseline + `if let` + the chain's `&&` + `while let`.
+    insta::assert_json_snapshot!(
+        cy,
+        @r###"
+    {
+      "sum": 5.0,
+      "average": 2.5,
+      "min": 1.0,
+      "max": 4.0
+    }"###
+    );
+}
+
+const GUARDED_MATCH: &str = "fn classify(x: Option<i32>) -> &'static str {
+         match x {
+             Some(n) if n > 0 && n < 10 => \"digit\",
+             Some(n) if n < 0 => \"negative\",
+             _ => \"other\",
+         }
+     }";
+
+#[test]
+fn rust_match_guards_are_not_decisions_by_default() {
+    let a = analyze(GUARDED_MATCH);
+    let cy = mehen_report::metrics_json::cyclomatic(&a.root.metrics);
+    // Function: baseline + 3 arms + the guard's `&&`.
+    insta::assert_json_snapshot!(
+        cy,
+        @r###"
+    {
+      "sum": 6.0,
+      "average": 3.0,
+      "min": 1.0,
+      "max": 5.0
+    }"###
+    );
+}
+
+#[test]
+fn rust_match_guards_count_when_enabled() {
+    let mut text = GUARDED_MATCH.to_string();
+    text.push('\n');
+    let config = AnalysisConfig {
+        count_match_guards: true,
+        ..AnalysisConfig::default()
+    };
+    let file = SourceFile::new("foo.rs".into(), Language::Rust, text);
+    let a = RustAnalyzer::new().analyze(&file, &config).unwrap();
+    let cy = mehen_report::metrics_json::cyclomatic(&a.root.metrics);
+    // Function: baseline + 3 arms + 2 guards + the guard's `&&`.
+    insta::assert_json_snapshot!(
+        cy,
+        @r###"
+    {
+      "sum": 8.0,
+      "average": 4.0,
+      "min": 1.0,
+      "max": 7.0
+    }"###
+    );
+}
@@ -66,3 +66,24 @@ fn rust_return_type_is_not_an_exit() {
     assert_eq!(nx.sum, 2.0, "got {}", serde_json::to_string(&nx).unwrap());
     assert_eq!(nx.max, 1.0, "got {}", serde_json::to_string(&nx).unwrap());
 }
+
+/// `async fn` is a plain function space: the `-> Result<..>` return
+/// type is not an exit, and each `?` counts once — including the ones
+/// inside a nested `async` block, which belong to the enclosing fn.
+#[test]
+fn rust_async_fn_question_marks() {
+    let a = analyze(
+        "async fn load(path: &str) -> Result<String, Error> {
+             let raw = read(path).await?;
+             let parsed = async { parse(&raw)?; decode(&raw) }.await?;
+             Ok(parsed)
+         }",
+    );
+    let nom = mehen_report::metrics_json::nom(&a.root.metrics);
+    assert_eq!(nom.functions, 1.0);
+    assert_eq!(nom.closures, 0.0);
+    let func = &a.root.spaces[0];
+    assert_eq!(func.name.as_deref(), Some("load"));
+    let nx = mehen_report::metrics_json::nexits(&func.metrics);
+    assert_eq!(nx.sum, 3.0, "got {}", serde_json::to_string(&nx).unwrap());
+}
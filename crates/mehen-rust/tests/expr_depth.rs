@@ -0,0 +1,66 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Deepest nesting of operators, calls and indexing (`max_expr_depth`).
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, MetricKey, SourceFile, keys};
+use mehen_rust::RustAnalyzer;
+
+fn analyze(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let analyzer = RustAnalyzer::new();
+    let file = SourceFile::new("foo.rs".into(), Language::Rust, text);
+    analyzer.analyze(&file, &AnalysisConfig::default()).unwrap()
+}
+
+fn max_expr_depth(space: &mehen_core::MetricSpace) -> Option<f64> {
+    space
+        .metrics
+        .get(&MetricKey::new(keys::MAX_EXPR_DEPTH))
+        .map(|v| v.as_f64())
+}
+
+#[test]
+fn rust_nested_arithmetic() {
+    let a = analyze(
+        "fn f(a: i32, b: i32, c: i32, d: i32) -> i32 {
+             let x = a + b;
+             ((a * (b - (c / (d + 1)))) % 7) + x
+         }",
+    );
+    // +, %, *, -, /, + — parentheses do not count.
+    assert_eq!(max_expr_depth(&a.root.spaces[0]), Some(6.0));
+    assert_eq!(max_expr_depth(&a.root), Some(6.0));
+}
+
+#[test]
+fn rust_calls_and_indexing_nest() {
+    let a = analyze(
+        "fn f(v: &[i32], i: usize) -> i32 {
+             let mut y = 0;
+             y = g(v[i + 1]);
+             y
+         }",
+    );
+    // call → index → `+`; the assignment does not count.
+    assert_eq!(max_expr_depth(&a.root.spaces[0]), Some(3.0));
+}
+
+#[test]
+fn rust_closure_starts_from_zero() {
+    let a = analyze(
+        "fn f(v: Vec<i32>) -> Vec<i32> {
+             v.into_iter().map(|x| x * 2).collect()
+         }",
+    );
+    let f = &a.root.spaces[0];
+    assert_eq!(max_expr_depth(f), Some(3.0));
+    assert_eq!(max_expr_depth(&f.spaces[0]), Some(1.0));
+}
+
+#[test]
+fn rust_plain_values_publish_nothing() {
+    let a = analyze("fn f(a: i32) -> i32 { let b = a; b }");
+    assert_eq!(max_expr_depth(&a.root.spaces[0]), None);
+}
@@ -108,3 +108,46 @@ fn rust_nested_function_halstead_is_non_zero() {
         serde_json::to_string(&inner_h).unwrap()
     );
 }
+
+#[test]
+fn rust_operator_operand_ratio_separates_dense_and_data_heavy_code() {
+    let ratio = |source: &str| {
+        let a = analyze(source);
+        mehen_report::metrics_json::halstead(&a.root.spaces[0].metrics).operator_operand_ratio
+    };
+    let dense = ratio(
+        "fn dense(a: i64, b: i64) -> i64 {
+             -((a + b) * (a - b)) / -((a * b) % (a - b)) << (a & b | a ^ b)
+         }",
+    );
+    let data = ratio(
+        "fn table() -> [&'static str; 12] {
+             [\"jan\", \"feb\", \"mar\", \"apr\", \"may\", \"jun\",
+              \"jul\", \"aug\", \"sep\", \"oct\", \"nov\", \"dec\"]
+         }",
+    );
+    assert_eq!(dense, 1.5);
+    assert_eq!(data, 1.1875);
+}
+
+#[test]
+fn rust_token_frequencies_kept_only_on_request() {
+    let text = "fn f(a: u32) -> u32 { a + a }\n".to_string();
+    let file = SourceFile::new("foo.rs".into(), Language::Rust, text);
+    let a = RustAnalyzer::new()
+        .analyze(&file, &AnalysisConfig::default())
+        .unwrap();
+    assert!(a.root.halstead_tokens.is_none());
+    let config = AnalysisConfig {
+        halstead_tokens: true,
+        ..AnalysisConfig::default()
+    };
+    let a = RustAnalyzer::new().analyze(&file, &config).unwrap();
+    let tokens = a.root.halstead_tokens.expect("requested tokens");
+    assert!(
+        tokens
+            .operands
+            .iter()
+            .any(|t| t.token == "a" && t.count == 3)
+    );
+}
@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Imported modules per space (`import_count`).
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, MetricKey, SourceFile, keys};
+use mehen_rust::RustAnalyzer;
+
+fn analyze(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let analyzer = RustAnalyzer::new();
+    let file = SourceFile::new("foo.rs".into(), Language::Rust, text);
+    analyzer.analyze(&file, &AnalysisConfig::default()).unwrap()
+}
+
+fn import_count(space: &mehen_core::MetricSpace) -> Option<f64> {
+    space
+        .metrics
+        .get(&MetricKey::new(keys::IMPORT_COUNT))
+        .map(|v| v.as_f64())
+}
+
+#[test]
+fn rust_counts_use_items() {
+    let a = analyze(
+        "use std::fmt;
+         use std::collections::{BTreeMap, HashMap};
+         pub use crate::inner::*;
+
+         fn f() {
+             use std::io::Write;
+         }",
+    );
+    assert_eq!(import_count(&a.root), Some(4.0));
+    assert_eq!(import_count(&a.root.spaces[0]), Some(1.0));
+}
+
+#[test]
+fn rust_file_without_use_has_no_import_count() {
+    let a = analyze("fn f() {}");
+    assert_eq!(import_count(&a.root), None);
+}
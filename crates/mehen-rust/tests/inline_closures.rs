@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! `AnalysisConfig::inline_closures` folds closures into their parent.
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, MetricKey, SourceFile, keys};
+use mehen_rust::RustAnalyzer;
+
+const SOURCE: &str = "fn f(xs: &[i32]) -> i32 {
+    let evens = xs.iter().filter(|x| **x % 2 == 0 && **x > 0).count();
+    let sum: i32 = xs.iter().map(|x| if *x > 10 { 10 } else { *x }).sum();
+    evens as i32 + sum
+}
+";
+
+fn analyze(inline_closures: bool) -> mehen_core::LanguageAnalysis {
+    let config = AnalysisConfig {
+        inline_closures,
+        ..AnalysisConfig::default()
+    };
+    let file = SourceFile::new("foo.rs".into(), Language::Rust, SOURCE.to_string());
+    RustAnalyzer::new().analyze(&file, &config).unwrap()
+}
+
+fn metric(space: &mehen_core::MetricSpace, key: &str) -> f64 {
+    space.metrics.get(&MetricKey::new(key)).unwrap().as_f64()
+}
+
+#[test]
+fn rust_parent_absorbs_inlined_closures() {
+    let split = analyze(false);
+    let f = &split.root.spaces[0];
+    assert_eq!(f.spaces.len(), 2);
+    assert_eq!(metric(f, keys::CYCLOMATIC), 1.0);
+    assert_eq!(metric(&split.root, "nom.closures"), 2.0);
+
+    let inlined = analyze(true);
+    let f = &inlined.root.spaces[0];
+    assert!(f.spaces.is_empty());
+    // `&&` and `if` now count toward `f` itself.
+    assert_eq!(metric(f, keys::CYCLOMATIC), 3.0);
+    assert_eq!(
+        metric(f, keys::COGNITIVE),
+        metric(&split.root, keys::COGNITIVE)
+    );
+    assert_eq!(metric(&inlined.root, "nom.closures"), 0.0);
+    assert_eq!(metric(&inlined.root, "nom.functions"), 1.0);
+}
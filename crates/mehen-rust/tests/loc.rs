@@ -359,3 +359,195 @@ fn rust_nested_function_loc_ploc_routes_to_active_space() {
         serde_json::to_string(&loc).unwrap()
     );
 }
+
+/// A CRLF-terminated file must produce the same LOC totals (per space
+/// and rolled up) as its LF twin — the `\r` before each newline is part
+/// of the line terminator, not a trailing code byte or a blank line.
+#[test]
+fn rust_crlf_matches_lf() {
+    let lf = "// leading comment
+fn add(a: i32, b: i32) -> i32 {
+
+    /* block
+       comment */
+    let c = a + b; // trailing
+    c
+}
+";
+    let crlf = lf.replace('\n', "\r\n");
+    let loc_json = |text: String| {
+        let file = SourceFile::new("foo.rs".into(), Language::Rust, text);
+        let a = RustAnalyzer::new()
+            .analyze(&file, &AnalysisConfig::default())
+            .unwrap();
+        let root = mehen_report::metrics_json::loc(&a.root.metrics);
+        let func = mehen_report::metrics_json::loc(&a.root.spaces[0].metrics);
+        (
+            serde_json::to_string(&root).unwrap(),
+            serde_json::to_string(&func).unwrap(),
+        )
+    };
+    assert_eq!(loc_json(crlf), loc_json(lf.to_string()));
+}
+
+/// `blank_blocks` counts runs of blank lines, not blank lines: two
+/// separate groups (one of them two lines tall) give `2`. It is only
+/// published on function spaces.
+#[test]
+fn rust_blank_blocks_counts_groups() {
+    let a = analyze(
+        "fn f() {
+    let a = 1;
+
+    let b = 2;
+
+
+    let c = a + b;
+}",
+    );
+    let root = mehen_report::metrics_json::loc(&a.root.metrics);
+    let func = mehen_report::metrics_json::loc(&a.root.spaces[0].metrics);
+    assert_eq!(func.blank_blocks, Some(2.0));
+    assert_eq!(func.blank, 3.0);
+    assert_eq!(root.blank_blocks, None);
+}
+
+/// `blank_blocks` finds its runs among the lines `blank` counts, so a
+/// whitespace-only line inside a block comment is not a run of its own.
+/// The rows inside a multi-line raw string are neither code nor comment
+/// and form one run with the empty line among them.
+#[test]
+fn rust_blank_blocks_agrees_with_blank() {
+    let a = analyze(
+        r##"fn f() -> &'static str {
+    /*
+
+    */
+    r"a
+
+b"
+}"##,
+    );
+    let func = mehen_report::metrics_json::loc(&a.root.spaces[0].metrics);
+    assert_eq!(func.blank, 2.0);
+    assert_eq!(func.blank_blocks, Some(1.0));
+}
+
+/// `share` is each function's SLOC over the file's SLOC; with no lines
+/// outside the two functions the shares add up to one.
+#[test]
+fn rust_function_loc_share_sums_to_one() {
+    let a = analyze(
+        "fn a() {
+    let x = 1;
+}
+fn b() {
+}",
+    );
+    let shares: Vec<f64> = a
+        .root
+        .spaces
+        .iter()
+        .map(|f| {
+            mehen_report::metrics_json::loc(&f.metrics)
+                .share
+                .expect("function spaces publish loc.share")
+        })
+        .collect();
+    assert_eq!(shares.len(), 2);
+    assert!((shares[0] - 0.6).abs() < 1e-9, "{shares:?}");
+    assert!(
+        (shares.iter().sum::<f64>() - 1.0).abs() < 1e-9,
+        "{shares:?}"
+    );
+    assert_eq!(mehen_report::metrics_json::loc(&a.root.metrics).share, None);
+}
+
+/// `max_block_lines` is the tallest block nested in a function — here
+/// the `for` body, braces included — not the function body itself.
+#[test]
+fn rust_max_block_lines_tracks_large_loop_body() {
+    let a = analyze(
+        "fn f(xs: &[i32]) -> i32 {
+    let mut total = 0;
+    for x in xs {
+        let y = x * 2;
+        let z = y + 1;
+        total += z;
+        if total > 100 {
+            break;
+        }
+    }
+    total
+}",
+    );
+    let loc = mehen_report::metrics_json::loc(&a.root.spaces[0].metrics);
+    assert_eq!(loc.max_block_lines, Some(8.0));
+    let root = mehen_report::metrics_json::loc(&a.root.metrics);
+    assert_eq!(root.max_block_lines, None);
+}
+
+#[test]
+fn rust_function_sloc_distribution() {
+    let a = analyze(
+        "fn one() {}
+fn three() {
+    let _ = 1;
+}
+fn three_again() {
+    let _ = 2;
+}
+fn five() {
+    let _ = 1;
+    let _ = 2;
+    let _ = 3;
+}
+fn ten() {
+    let _ = 1;
+    let _ = 2;
+    let _ = 3;
+    let _ = 4;
+    let _ = 5;
+    let _ = 6;
+    let _ = 7;
+    let _ = 8;
+}",
+    );
+    let loc = mehen_report::metrics_json::loc(&a.root.metrics);
+    assert_eq!(loc.avg_function_sloc, Some(4.4));
+    assert_eq!(loc.function_sloc_p50, Some(3.0));
+    assert_eq!(loc.function_sloc_p90, Some(10.0));
+    assert_eq!(loc.function_sloc_p99, Some(10.0));
+    let function = mehen_report::metrics_json::loc(&a.root.spaces[0].metrics);
+    assert_eq!(function.avg_function_sloc, None);
+}
+
+#[test]
+fn rust_sloc_bounds_point_at_their_space() {
+    let a = analyze(
+        "fn small() {}
+struct S;
+impl S {
+    fn largest(&self) {
+        let _ = 1;
+        let _ = 2;
+        let _ = 3;
+    }
+    fn also_small(&self) {}
+}
+fn medium() {
+    let _ = 1;
+}",
+    );
+    let loc = mehen_report::metrics_json::loc(&a.root.metrics);
+    assert_eq!(loc.sloc_max, 5.0);
+    assert_eq!(loc.sloc_max_at, Some(4.0));
+    assert_eq!(loc.sloc_min, 1.0);
+    assert_eq!(loc.sloc_min_at, Some(1.0));
+    let function = mehen_report::metrics_json::loc(&a.root.spaces[0].metrics);
+    assert_eq!(function.sloc_max_at, None);
+
+    let empty = analyze("const X: u8 = 1;");
+    let loc = mehen_report::metrics_json::loc(&empty.root.metrics);
+    assert_eq!(loc.sloc_max_at, None);
+}
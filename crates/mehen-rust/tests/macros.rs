@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Macro bodies: opaque to complexity, LLOC and Halstead, but their lines
+//! are still code.
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, MetricKey, SourceFile, keys};
+use mehen_rust::RustAnalyzer;
+
+fn analyze(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let analyzer = RustAnalyzer::new();
+    let file = SourceFile::new("foo.rs".into(), Language::Rust, text);
+    analyzer.analyze(&file, &AnalysisConfig::default()).unwrap()
+}
+
+fn metric(space: &mehen_core::MetricSpace, key: &str) -> f64 {
+    space
+        .metrics
+        .get(&MetricKey::new(key))
+        .map_or(0.0, |v| v.as_f64())
+}
+
+#[test]
+fn rust_macro_rules_arms_are_not_branches() {
+    let a = analyze(
+        "macro_rules! pick {
+             (a) => { if true { 1 } else { 2 } };
+             (b) => { 3 };
+             ($x:expr) => {{
+                 let y = $x;
+                 y + 1
+             }};
+         }",
+    );
+    let root = &a.root;
+    assert_eq!(metric(root, "cyclomatic.sum"), 1.0);
+    assert_eq!(metric(root, keys::COGNITIVE), 0.0);
+    assert_eq!(metric(root, keys::MAX_BRANCHES), 0.0);
+    assert_eq!(metric(root, keys::LOC_LLOC), 0.0);
+    assert_eq!(metric(root, "halstead.N1"), 0.0);
+    assert_eq!(metric(root, keys::LOC_SLOC), 8.0);
+    assert_eq!(metric(root, keys::LOC_PLOC), 8.0);
+    assert_eq!(metric(root, keys::LOC_BLANK), 0.0);
+}
+
+#[test]
+fn rust_multiline_macro_call_lines_are_code() {
+    let a = analyze(
+        "fn f(x: u32) {
+             println!(
+                 \"{}\",
+                 if x > 0 { x } else { 0 }
+             );
+         }",
+    );
+    let f = &a.root.spaces[0];
+    assert_eq!(metric(f, keys::CYCLOMATIC), 1.0);
+    assert_eq!(metric(f, keys::LOC_PLOC), 6.0);
+    assert_eq!(metric(f, keys::LOC_BLANK), 0.0);
+}
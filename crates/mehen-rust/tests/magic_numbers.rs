@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Numeric literals outside named constants (`magic_numbers`).
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, MetricKey, SourceFile, keys};
+use mehen_rust::RustAnalyzer;
+
+fn analyze(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let analyzer = RustAnalyzer::new();
+    let file = SourceFile::new("foo.rs".into(), Language::Rust, text);
+    analyzer.analyze(&file, &AnalysisConfig::default()).unwrap()
+}
+
+fn magic_numbers(space: &mehen_core::MetricSpace) -> Option<f64> {
+    space
+        .metrics
+        .get(&MetricKey::new(keys::MAGIC_NUMBERS))
+        .map(|v| v.as_f64())
+}
+
+#[test]
+fn rust_counts_literals_but_not_named_constants() {
+    let a = analyze(
+        "const LIMIT: u32 = 100;
+
+         fn f(x: u32) -> u32 {
+             if x > LIMIT {
+                 return x * 42;
+             }
+             x + 3_600
+         }",
+    );
+    let f = &a.root.spaces[0];
+    assert_eq!(magic_numbers(f), Some(2.0));
+    assert_eq!(magic_numbers(&a.root), Some(2.0));
+}
+
+#[test]
+fn rust_zero_one_and_minus_one_are_not_magic() {
+    let a = analyze(
+        "fn f(x: i64) -> i64 {
+             let y = x + 1 - 0x0 * 1_i64;
+             if y < 0 { -1 } else { y * 1.0 as i64 }
+         }",
+    );
+    assert_eq!(magic_numbers(&a.root), None);
+}
+
+#[test]
+fn rust_skips_statics_discriminants_and_macro_arguments() {
+    let a = analyze(
+        "static TABLE: [u8; 3] = [7, 8, 9];
+
+         enum Code { A = 10, B = 20 }
+
+         fn f() {
+             println!(\"{}\", 99);
+             let t = 2.5;
+         }",
+    );
+    assert_eq!(magic_numbers(&a.root), Some(1.0));
+}
@@ -0,0 +1,54 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! `AnalysisConfig::max_nodes`: files over the syntax-tree cap get LOC
+//! only.
+
+use mehen_core::{
+    AnalysisConfig, DiagnosticSeverity, Language, LanguageAnalyzer, MetricKey, SourceFile, keys,
+};
+use mehen_rust::RustAnalyzer;
+
+/// A generated-looking file: many small functions, one blank line and
+/// one comment line between them.
+fn huge_source() -> String {
+    (0..500)
+        .map(|i| format!("// generated\nfn f{i}(x: u32) -> u32 {{\n    if x > {i} {{ x }} else {{ 0 }}\n}}\n\n"))
+        .collect()
+}
+
+fn analyze(text: String, max_nodes: Option<u64>) -> mehen_core::LanguageAnalysis {
+    let config = AnalysisConfig {
+        max_nodes,
+        ..AnalysisConfig::default()
+    };
+    let file = SourceFile::new("generated.rs".into(), Language::Rust, text);
+    RustAnalyzer::new().analyze(&file, &config).unwrap()
+}
+
+fn metric(space: &mehen_core::MetricSpace, key: &str) -> Option<f64> {
+    space.metrics.get(&MetricKey::new(key)).map(|v| v.as_f64())
+}
+
+#[test]
+fn file_over_the_cap_is_downgraded_to_loc() {
+    let a = analyze(huge_source(), Some(1_000));
+    assert!(a.root.spaces.is_empty());
+    assert_eq!(metric(&a.root, keys::LOC_SLOC), Some(2_500.0));
+    // Comments are not told apart without the walk.
+    assert_eq!(metric(&a.root, keys::LOC_PLOC), Some(2_000.0));
+    assert_eq!(metric(&a.root, keys::LOC_BLANK), Some(500.0));
+    assert_eq!(metric(&a.root, keys::CYCLOMATIC), None);
+    assert_eq!(metric(&a.root, keys::HALSTEAD_VOLUME), None);
+    assert_eq!(a.diagnostics.len(), 1);
+    assert_eq!(a.diagnostics[0].severity, DiagnosticSeverity::Warning);
+    assert_eq!(a.diagnostics[0].code, "analysis.node_cap");
+}
+
+#[test]
+fn file_within_the_cap_is_walked() {
+    let a = analyze(huge_source(), Some(1_000_000));
+    assert_eq!(a.root.spaces.len(), 500);
+    assert!(metric(&a.root, keys::CYCLOMATIC).is_some());
+    assert!(a.diagnostics.is_empty());
+}
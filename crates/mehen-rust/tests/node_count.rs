@@ -0,0 +1,52 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Syntax-tree size per space (`node_count` / `leaf_count`).
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, MetricKey, SourceFile, keys};
+use mehen_rust::RustAnalyzer;
+
+fn analyze(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let analyzer = RustAnalyzer::new();
+    let file = SourceFile::new("foo.rs".into(), Language::Rust, text);
+    analyzer.analyze(&file, &AnalysisConfig::default()).unwrap()
+}
+
+fn counts(space: &mehen_core::MetricSpace) -> (f64, f64) {
+    let get = |key| space.metrics.get(&MetricKey::new(key)).unwrap().as_f64();
+    (get(keys::NODE_COUNT), get(keys::LEAF_COUNT))
+}
+
+#[test]
+fn rust_function_node_and_leaf_counts() {
+    let a = analyze(
+        "fn add(a: u8, b: u8) -> u8 {
+             // comments are trivia
+             a + b
+         }",
+    );
+    let (nodes, leaves) = counts(&a.root.spaces[0]);
+    // fn add ( a : u8 , b : u8 ) -> u8 { a + b }
+    assert_eq!(leaves, 18.0);
+    assert!(nodes >= leaves, "nodes={nodes} leaves={leaves}");
+    // The unit adds its own SOURCE_FILE node.
+    assert_eq!(counts(&a.root), (nodes + 1.0, leaves));
+}
+
+#[test]
+fn rust_closure_counts_roll_up_into_function() {
+    let a = analyze(
+        "fn f() -> u8 {
+             let g = |x: u8| x;
+             g(1)
+         }",
+    );
+    let function = &a.root.spaces[0];
+    let closure = &function.spaces[0];
+    let (fn_nodes, fn_leaves) = counts(function);
+    let (cl_nodes, cl_leaves) = counts(closure);
+    assert!(cl_nodes >= cl_leaves);
+    assert!(fn_nodes > cl_nodes && fn_leaves > cl_leaves);
+}
@@ -43,3 +43,23 @@ fn rust_nom() {
     }"###
     );
 }
+
+#[test]
+fn rust_async_fn_and_async_closure() {
+    // `async fn` counts as a function and `async move |..|` as a
+    // closure; a bare `async` block opens no space.
+    let a = analyze(
+        "async fn fetch() -> u8 { 1 }
+         fn spawn_all() {
+             let job = async move |x: u8| x + 1;
+             let fut = async { fetch().await };
+         }",
+    );
+    let nom = mehen_report::metrics_json::nom(&a.root.metrics);
+    assert_eq!(
+        (nom.functions, nom.closures),
+        (2.0, 1.0),
+        "got {}",
+        serde_json::to_string(&nom).unwrap()
+    );
+}
@@ -0,0 +1,89 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Direct-recursion detection on function spaces.
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, SourceFile};
+use mehen_rust::RustAnalyzer;
+
+fn analyze(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let analyzer = RustAnalyzer::new();
+    let file = SourceFile::new("foo.rs".into(), Language::Rust, text);
+    analyzer.analyze(&file, &AnalysisConfig::default()).unwrap()
+}
+
+#[test]
+fn rust_recursive_factorial() {
+    let a = analyze(
+        "fn factorial(n: u64) -> u64 {
+             if n == 0 { 1 } else { n * factorial(n - 1) }
+         }",
+    );
+    assert!(a.root.spaces[0].is_recursive);
+    assert!(!a.root.is_recursive);
+}
+
+#[test]
+fn rust_non_recursive_function() {
+    let a = analyze(
+        "fn double(n: u64) -> u64 {
+             helper(n) * 2
+         }",
+    );
+    assert!(!a.root.spaces[0].is_recursive);
+}
+
+#[test]
+fn rust_recursive_method_call() {
+    let a = analyze(
+        "impl Node {
+             fn depth(&self) -> usize {
+                 self.child.as_ref().map_or(0, |c| c.depth()) + self.depth()
+             }
+             fn size(&self) -> usize {
+                 Self::size(self)
+             }
+         }",
+    );
+    let methods = &a.root.spaces[0].spaces;
+    assert!(methods[0].is_recursive);
+    assert!(methods[1].is_recursive);
+}
+
+#[test]
+fn rust_bare_call_in_a_method_is_not_recursion() {
+    // No implicit `self`: `len(x)` calls the free fn, not the method.
+    let a = analyze(
+        "impl X {
+             fn len(&self) -> usize {
+                 len(&self.items)
+             }
+         }",
+    );
+    assert!(!a.root.spaces[0].spaces[0].is_recursive);
+}
+
+#[test]
+fn rust_closure_call_is_not_recursion() {
+    // The call to `walk` sits in the closure's space, not in `walk`'s.
+    let a = analyze(
+        "fn walk(n: u32) -> u32 {
+             let f = |x| walk(x);
+             f(n)
+         }",
+    );
+    assert!(!a.root.spaces[0].is_recursive);
+}
+
+#[test]
+fn rust_recursive_flag_serialized_only_when_set() {
+    let a = analyze(
+        "fn f() { f() }
+         fn g() {}",
+    );
+    let json = serde_json::to_value(&a.root).unwrap();
+    assert_eq!(json["spaces"][0]["is_recursive"], true);
+    assert!(json["spaces"][1].get("is_recursive").is_none());
+}
@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Space naming — closures take the name of the `let` they initialize.
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, SourceFile, SpaceKind};
+use mehen_rust::RustAnalyzer;
+
+fn analyze(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let analyzer = RustAnalyzer::new();
+    let file = SourceFile::new("foo.rs".into(), Language::Rust, text);
+    analyzer.analyze(&file, &AnalysisConfig::default()).unwrap()
+}
+
+fn closure_names(source: &str) -> Vec<Option<String>> {
+    let a = analyze(source);
+    let mut names = Vec::new();
+    a.root.visit(&mut |space, _| {
+        if matches!(space.kind, SpaceKind::Closure) {
+            names.push(space.name.clone());
+        }
+    });
+    names
+}
+
+#[test]
+fn rust_let_bound_closure_takes_binding_name() {
+    let names = closure_names(
+        "fn f() {
+             let add_one = |x: u32| x + 1;
+             let mut counter = move || 0;
+         }",
+    );
+    assert_eq!(
+        names,
+        vec![Some("add_one".to_string()), Some("counter".to_string())]
+    );
+}
+
+#[test]
+fn rust_inline_argument_closure_stays_anonymous() {
+    let names = closure_names(
+        "fn f(v: Vec<u32>) {
+             let total = v.iter().map(|x| x * 2).sum::<u32>();
+             let (a, b) = (|| 1, || 2);
+         }",
+    );
+    assert_eq!(names, vec![None, None, None]);
+}
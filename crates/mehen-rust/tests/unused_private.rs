@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Private functions never referenced in the file (`unused_private`).
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, MetricKey, SourceFile, keys};
+use mehen_rust::RustAnalyzer;
+
+fn analyze(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let analyzer = RustAnalyzer::new();
+    let file = SourceFile::new("foo.rs".into(), Language::Rust, text);
+    analyzer.analyze(&file, &AnalysisConfig::default()).unwrap()
+}
+
+fn unused_private(source: &str) -> Option<f64> {
+    analyze(source)
+        .root
+        .metrics
+        .get(&MetricKey::new(keys::UNUSED_PRIVATE))
+        .map(|v| v.as_f64())
+}
+
+#[test]
+fn rust_called_private_fn_is_used() {
+    assert_eq!(
+        unused_private(
+            "pub fn api() -> u32 { helper() }
+             fn helper() -> u32 { 1 }",
+        ),
+        None
+    );
+}
+
+#[test]
+fn rust_uncalled_private_fn_is_unused() {
+    assert_eq!(
+        unused_private(
+            "pub fn api() -> u32 { 1 }
+             fn orphan() -> u32 { 2 }",
+        ),
+        Some(1.0)
+    );
+}
+
+#[test]
+fn rust_references_outside_plain_calls_count() {
+    assert_eq!(
+        unused_private(
+            "pub struct S;
+             impl S {
+                 pub fn run(&self) -> Vec<u32> {
+                     println!(\"{}\", fmt_it());
+                     self.step();
+                     vec![1].into_iter().map(double).collect()
+                 }
+                 fn step(&self) {}
+             }
+             fn fmt_it() -> u32 { 0 }
+             fn double(x: u32) -> u32 { x * 2 }",
+        ),
+        None
+    );
+}
+
+#[test]
+fn rust_self_recursion_and_exempt_fns() {
+    assert_eq!(
+        unused_private(
+            "fn main() {}
+             fn countdown(n: u32) { if n > 0 { countdown(n - 1) } }
+             pub(crate) fn internal() {}
+             trait T { fn hook(&self) {} }
+             impl T for u8 { fn hook(&self) {} }
+             #[test]
+             fn a_test() {}
+             #[inline]
+             fn unused_inline() {}",
+        ),
+        Some(2.0)
+    );
+}
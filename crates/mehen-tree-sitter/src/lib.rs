@@ -15,7 +15,9 @@
 //!   provides byte-offset traversal helpers,
 //! - `node_span`: a helper that lifts a tree-sitter node into mehen's
 //!   `SourceSpan`,
-//! - `text_of`: a helper that fetches the source text covered by a node.
+//! - `text_of`: a helper that fetches the source text covered by a node,
+//! - `query_captures`: runs a tree-sitter S-expression query over a file
+//!   for `mehen metrics --query`.
 //!
 //! The generator and generated kind-enum utilities will land in this crate
 //! when phase 7's `cargo xtask tree-sitter generate <language>` is wired up.
@@ -24,12 +26,14 @@
 #![forbid(unsafe_code)]
 
 mod parser;
+mod query;
 mod scaffold;
 mod span;
 mod walker;
 
 pub use mehen_metrics::{State, apply_state_to};
 pub use parser::{TreeSitterError, TreeSitterParser, collect_recovered_errors};
+pub use query::{QueryCapture, QueryCaptures, query_captures};
 pub use scaffold::{CognitiveContext, OpenSpaceRequest, WalkerCtx, WalkerHooks, run};
 pub use span::{node_span, text_of};
 pub use walker::{
@@ -11,6 +11,8 @@ use tree_sitter::{Language, Node, Parser, Tree};
 pub enum TreeSitterError {
     SetLanguage(String),
     Parse,
+    /// The query did not compile against the grammar.
+    Query(String),
 }
 
 impl fmt::Display for TreeSitterError {
@@ -18,6 +20,7 @@ impl fmt::Display for TreeSitterError {
         match self {
             TreeSitterError::SetLanguage(s) => write!(f, "set_language failed: {s}"),
             TreeSitterError::Parse => write!(f, "tree-sitter returned no tree"),
+            TreeSitterError::Query(s) => write!(f, "invalid query: {s}"),
         }
     }
 }
@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+use mehen_core::{LineIndex, SourceSpan};
+use tree_sitter::{Language, Query, QueryCursor, StreamingIterator};
+
+use crate::{TreeSitterError, TreeSitterParser, node_span};
+
+/// One node captured by a tree-sitter query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryCapture {
+    /// Capture name without the `@`.
+    pub name: String,
+    /// Grammar node kind of the captured node.
+    pub kind: &'static str,
+    /// Grammar node kind of the captured node's parent; `None` for the
+    /// root node.
+    pub parent_kind: Option<&'static str>,
+    pub span: SourceSpan,
+}
+
+/// The captures of one query run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryCaptures {
+    /// Every capture name the query declares, in declaration order,
+    /// including names that matched nothing.
+    pub names: Vec<String>,
+    /// Every capture in document order.
+    pub captures: Vec<QueryCapture>,
+}
+
+/// Parse `source` with `language` and run the S-expression `query`
+/// over the whole tree, returning every capture in document order.
+///
+/// The query is compiled against the same grammar the analyzer uses,
+/// so node kinds are the grammar's own (`function_declaration`,
+/// `block`, …), not mehen's space kinds.
+pub fn query_captures(
+    language: Language,
+    source: &str,
+    query: &str,
+) -> Result<QueryCaptures, TreeSitterError> {
+    let query = Query::new(&language, query).map_err(|e| TreeSitterError::Query(e.to_string()))?;
+    let parser = TreeSitterParser::new(language, source.as_bytes().to_vec())?;
+    let line_index = LineIndex::new(source);
+    let names = query.capture_names();
+
+    let mut out = Vec::new();
+    let mut cursor = QueryCursor::new();
+    let mut captures = cursor.captures(&query, parser.root(), parser.source());
+    while let Some((m, index)) = captures.next() {
+        let capture = m.captures[*index];
+        out.push(QueryCapture {
+            name: names[capture.index as usize].to_string(),
+            kind: capture.node.kind(),
+            parent_kind: capture.node.parent().map(|parent| parent.kind()),
+            span: node_span(&capture.node, &line_index),
+        });
+    }
+    Ok(QueryCaptures {
+        names: names.iter().map(|name| name.to_string()).collect(),
+        captures: out,
+    })
+}
@@ -23,9 +23,12 @@
 //! the space opens — see `WalkerHooks::pre_open`. Languages without
 //! class-aware metrics leave it as the default no-op.
 
-use mehen_core::{LineIndex, MetricSpace, SourceSpan, SpaceKind};
+use std::collections::BTreeMap;
+
+use mehen_core::{HalsteadClass, LineIndex, MetricSpace, SourceSpan, SpaceKind};
 use mehen_metrics::{
     MetricTreeBuilder, State, apply_state_to, finalize_state, merge_child_into_parent,
+    publish_function_loc,
 };
 use tree_sitter::Node;
 
@@ -124,6 +127,8 @@ pub struct WalkerCtx<'a> {
     pub stack: &'a mut Vec<State>,
     pub kinds: &'a mut Vec<SpaceKind>,
     pub cognitive: &'a mut CognitiveContext,
+    /// `AnalysisConfig::halstead_overrides`, keyed by grammar node kind.
+    pub halstead_overrides: &'a BTreeMap<String, HalsteadClass>,
 }
 
 impl<'a> WalkerCtx<'a> {
@@ -145,6 +150,16 @@ impl<'a> WalkerCtx<'a> {
     pub fn ancestor_kinds(&self) -> impl Iterator<Item = &SpaceKind> + '_ {
         self.kinds.iter().rev().skip(1)
     }
+
+    /// How `node` counts towards Halstead: the configured class for its
+    /// grammar kind when the user overrode it, `builtin` (the host's own
+    /// operator/operand table) otherwise.
+    pub fn halstead_class(&self, node: &Node<'_>, builtin: HalsteadClass) -> HalsteadClass {
+        self.halstead_overrides
+            .get(node.kind())
+            .copied()
+            .unwrap_or(builtin)
+    }
 }
 
 /// Description returned by [`WalkerHooks::open_space`]. Carries the
@@ -171,16 +186,21 @@ struct Walker<'a> {
     stack: Vec<State>,
     kinds: Vec<SpaceKind>,
     cognitive: CognitiveContext,
+    halstead_overrides: &'a BTreeMap<String, HalsteadClass>,
 }
 
 /// Drive the shared walker over `root`. Mirrors the per-crate
 /// `walk_program` entries that previously existed in `mehen-c`,
-/// `mehen-go`, and `mehen-kotlin`.
+/// `mehen-go`, and `mehen-kotlin`. `halstead_overrides` is exposed to
+/// the hooks through [`WalkerCtx::halstead_class`]; `halstead_tokens`
+/// keeps the unit's token frequencies (`AnalysisConfig::halstead_tokens`).
 pub fn run<H: WalkerHooks>(
     hooks: &mut H,
     root: Node<'_>,
     source: &[u8],
     line_index: &LineIndex,
+    halstead_overrides: &BTreeMap<String, HalsteadClass>,
+    halstead_tokens: bool,
 ) -> MetricSpace {
     let unit_span = node_span(&root, line_index);
 
@@ -198,13 +218,22 @@ pub fn run<H: WalkerHooks>(
         stack: vec![unit_state],
         kinds: vec![SpaceKind::Unit],
         cognitive: CognitiveContext::default(),
+        halstead_overrides,
     };
     walker.visit(hooks, root);
 
     let mut unit_state = walker.stack.pop().expect("walker stack underflow");
     finalize_state(&mut unit_state);
+    if halstead_tokens {
+        walker
+            .tree
+            .set_halstead_tokens(unit_state.halstead.token_frequencies());
+    }
+    let blank_rows = unit_state.loc.blank_rows();
     apply_state_to(unit_state, walker.tree.metrics_mut());
-    walker.tree.finish()
+    let mut tree = walker.tree.finish();
+    publish_function_loc(&mut tree, &blank_rows);
+    tree
 }
 
 impl Walker<'_> {
@@ -215,6 +244,7 @@ impl Walker<'_> {
             stack: &mut self.stack,
             kinds: &mut self.kinds,
             cognitive: &mut self.cognitive,
+            halstead_overrides: self.halstead_overrides,
         }
     }
 
@@ -237,6 +267,12 @@ impl Walker<'_> {
         };
 
         hooks.classify(&mut self.ctx(), &node);
+        if !node.is_extra() {
+            let is_leaf = node.child_count() == 0;
+            if let Some(state) = self.stack.last_mut() {
+                state.node_count.observe(is_leaf);
+            }
+        }
 
         let mut cursor = node.walk();
         if cursor.goto_first_child() {
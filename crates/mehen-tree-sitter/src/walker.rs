@@ -17,6 +17,7 @@
 use mehen_core::{LineIndex, MetricSpace, SourceSpan, SpaceId, SpaceKind};
 use mehen_metrics::{
     MetricTreeBuilder, State, apply_state_to, finalize_state, merge_child_into_parent,
+    publish_function_loc,
 };
 use tree_sitter::Node;
 
@@ -194,11 +195,15 @@ pub struct WalkResult {
 ///    `loc`,
 /// 5. on close, publishes the per-space metric set via the shared
 ///    [`apply_state_to`] helper, then folds it back into the parent.
+///
+/// `halstead_tokens` keeps the unit's operator/operand frequencies in
+/// [`MetricSpace::halstead_tokens`].
 pub fn walk<R: LanguageRules>(
     root_node: Node<'_>,
     source_text: &[u8],
     line_index: &LineIndex,
     rules: &R,
+    halstead_tokens: bool,
 ) -> WalkResult {
     let unit_span = node_span(&root_node, line_index);
     let mut walker = Walker {
@@ -218,10 +223,16 @@ pub fn walk<R: LanguageRules>(
     walker.visit(root_node);
     let mut unit_state = walker.stack.pop().expect("walker stack underflow");
     finalize_state(&mut unit_state);
-    apply_state_to(unit_state, walker.tree.metrics_mut());
-    WalkResult {
-        root: walker.tree.finish(),
+    if halstead_tokens {
+        walker
+            .tree
+            .set_halstead_tokens(unit_state.halstead.token_frequencies());
     }
+    let blank_rows = unit_state.loc.blank_rows();
+    apply_state_to(unit_state, walker.tree.metrics_mut());
+    let mut root = walker.tree.finish();
+    publish_function_loc(&mut root, &blank_rows);
+    WalkResult { root }
 }
 
 struct Walker<'a, R: LanguageRules> {
@@ -309,6 +320,10 @@ impl<'a, R: LanguageRules> Walker<'a, R> {
         let opened_space = opened_kind.is_some();
 
         let facts = self.rules.classify(&node);
+        if !node.is_extra() {
+            let is_leaf = node.child_count() == 0;
+            self.current().node_count.observe(is_leaf);
+        }
         if facts.cyclomatic_decision {
             self.current().cyclomatic.record_decision();
         }
@@ -319,7 +334,9 @@ impl<'a, R: LanguageRules> Walker<'a, R> {
             CognitiveFact::None => {}
             CognitiveFact::IncreaseNesting => {
                 let effective_nesting = ctx.nesting + ctx.depth + ctx.lambda;
-                self.current().cognitive.increase_nesting(effective_nesting);
+                self.current()
+                    .cognitive
+                    .increase_nesting(effective_nesting, ctx.nesting);
                 ctx.nesting += 1;
             }
             CognitiveFact::NonNestingPlusOne => {
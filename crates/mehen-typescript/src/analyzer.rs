@@ -12,7 +12,7 @@ use mehen_core::{
     AnalysisBackend, AnalysisConfig, Language, LanguageAnalysis, LanguageAnalyzer, ParseDiagnostic,
     Result, SourceFile, SourceSpan, byte_offset_clamped,
 };
-use mehen_metrics::MetricTreeBuilder;
+use mehen_metrics::{MetricTreeBuilder, node_cap_fallback};
 use oxc_allocator::Allocator;
 use oxc_parser::Parser;
 use oxc_parser::config::TokensParserConfig;
@@ -68,6 +68,7 @@ fn refine_source_type(default: SourceType, source: &SourceFile) -> SourceType {
 fn analyze_with_source_type(
     language: Language,
     source: &SourceFile,
+    config: &AnalysisConfig,
     source_type: SourceType,
 ) -> LanguageAnalysis {
     let source_type = refine_source_type(source_type, source);
@@ -100,11 +101,22 @@ fn analyze_with_source_type(
         };
     }
 
+    // Oxc has no cheap node count; the token stream stands in.
+    if let Some(analysis) = node_cap_fallback(
+        source,
+        config,
+        AnalysisBackend::Oxc,
+        parser_return.tokens.len() as u64,
+    ) {
+        return analysis;
+    }
+
     let root = walker::walk_program(
         &parser_return.program,
         &parser_return.tokens,
         source.text.as_str(),
         &source.line_index,
+        config.halstead_tokens,
     );
 
     // Oxc commonly returns a non-panicking parse with `errors` populated
@@ -154,9 +166,14 @@ macro_rules! ts_analyzer {
             fn analyze(
                 &self,
                 source: &SourceFile,
-                _config: &AnalysisConfig,
+                config: &AnalysisConfig,
             ) -> Result<LanguageAnalysis> {
-                Ok(analyze_with_source_type($lang, source, $source_type))
+                Ok(analyze_with_source_type(
+                    $lang,
+                    source,
+                    config,
+                    $source_type,
+                ))
             }
         }
     };
@@ -324,4 +341,35 @@ mod tests {
             "TS parser must reject JSX syntax when --language typescript is forced"
         );
     }
+
+    #[test]
+    fn max_nodes_caps_the_token_count() {
+        let file = SourceFile::new(
+            "a.ts".into(),
+            Language::TypeScript,
+            "function f(x: number) {\n  return x ? 1 : 0;\n}\n".to_string(),
+        );
+        let capped = AnalysisConfig {
+            max_nodes: Some(5),
+            ..AnalysisConfig::default()
+        };
+        let a = TypeScriptAnalyzer::new().analyze(&file, &capped).unwrap();
+        assert!(a.root.spaces.is_empty());
+        assert_eq!(a.diagnostics[0].code, mehen_metrics::NODE_CAP_DIAGNOSTIC);
+        assert_eq!(
+            a.root
+                .metrics
+                .get(&MetricKey::new(keys::LOC_SLOC))
+                .map(|v| v.as_f64()),
+            Some(3.0)
+        );
+
+        let generous = AnalysisConfig {
+            max_nodes: Some(1_000),
+            ..AnalysisConfig::default()
+        };
+        let a = TypeScriptAnalyzer::new().analyze(&file, &generous).unwrap();
+        assert_eq!(a.root.spaces.len(), 1);
+        assert!(a.diagnostics.is_empty());
+    }
 }
@@ -13,8 +13,10 @@
 //! plus the AST traversal.
 //!
 //! Halstead operator/operand classification reads the lexer token
-//! stream — see [`emit_halstead_from_tokens`]. Every other metric is
-//! driven by [`Visitor::enter_node`] over `AstKind`.
+//! stream — see [`emit_halstead_from_tokens`]. Comments, including JSX
+//! `{/* … */}` comments, count toward CLOC from `program.comments` — see
+//! [`Visitor::emit_comments`]. Every other metric is driven by
+//! [`Visitor::enter_node`] over `AstKind`.
 //!
 //! Reference for the operator / operand / decision-point sets:
 //! `crates/mehen-engine/src/legacy/getter.rs` (TS `get_op_type`),
@@ -25,13 +27,16 @@
 
 use mehen_core::{LineIndex, MetricSpace, SourceSpan, SpaceKind};
 use mehen_metrics::{
-    ContainerKind, HalsteadOperand, HalsteadOperator, MetricTreeBuilder, SpaceRangeTracker, State,
-    apply_state_to, close_space, finalize_state,
+    ContainerKind, HalsteadOperand, HalsteadOperator, MetricTreeBuilder, NargsStats,
+    SpaceRangeTracker, State, apply_state_to, close_space, finalize_state, publish_function_loc,
 };
+use std::collections::HashSet;
+
 use oxc_allocator::Vec as ArenaVec;
 use oxc_ast::AstKind;
 use oxc_ast::ast::{
-    AssignmentTarget, Class, Function, FunctionType, Program, PropertyKey, TSAccessibility,
+    AssignmentTarget, CallExpression, Class, Comment, Expression, FormalParameters, Function,
+    FunctionType, JSXExpression, LogicalExpression, Program, PropertyKey, TSAccessibility,
 };
 use oxc_ast_visit::{Visit, walk};
 use oxc_parser::Kind;
@@ -48,6 +53,7 @@ pub(crate) fn walk_program<'a>(
     tokens: &ArenaVec<'a, Token>,
     source: &str,
     line_index: &LineIndex,
+    halstead_tokens: bool,
 ) -> MetricSpace {
     let unit_span = program_span(program, line_index);
     let mut visitor = Visitor::new(source, line_index, unit_span);
@@ -60,8 +66,9 @@ pub(crate) fn walk_program<'a>(
     // the recorded scope spans to assign each token. See
     // `emit_halstead_from_tokens` for the assignment algorithm.
     visitor.emit_halstead_from_tokens(tokens, source);
+    visitor.emit_comments(&program.comments);
 
-    visitor.finish()
+    visitor.finish(halstead_tokens)
 }
 
 fn program_span(program: &Program<'_>, line_index: &LineIndex) -> SourceSpan {
@@ -108,6 +115,10 @@ struct Visitor<'a> {
     /// the same `stack[0]`-only behaviour and now shares the routing
     /// helper.
     halstead_routing: SpaceRangeTracker,
+    /// Every row the AST walk recorded as a PLOC line, in any space.
+    /// The comment pass reads it to tell trailing comments from
+    /// comment-only lines.
+    code_rows: HashSet<u32>,
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -134,14 +145,20 @@ impl<'a> Visitor<'a> {
             cognitive: CognitiveContext::default(),
             type_only_ranges: Vec::new(),
             halstead_routing: SpaceRangeTracker::new(),
+            code_rows: HashSet::new(),
         }
     }
 
+    fn observe_code_line(&mut self, row: u32) {
+        self.code_rows.insert(row);
+        self.current().loc.observe_code_line(row);
+    }
+
     fn current(&mut self) -> &mut State {
         self.stack.last_mut().expect("walker stack empty")
     }
 
-    fn finish(mut self) -> MetricSpace {
+    fn finish(mut self, halstead_tokens: bool) -> MetricSpace {
         // Close the unit.
         let mut unit_state = self.stack.pop().expect("walker stack underflow");
         finalize_state(&mut unit_state);
@@ -160,7 +177,12 @@ impl<'a> Visitor<'a> {
             .finalize_into_tree(&mut tree, &mut unit_halstead, &mut unit_loc);
         unit_state.halstead = unit_halstead;
         unit_state.loc = unit_loc;
+        if halstead_tokens {
+            tree.halstead_tokens = Some(unit_state.halstead.token_frequencies());
+        }
+        let blank_rows = unit_state.loc.blank_rows();
         apply_state_to(unit_state, &mut tree.metrics);
+        publish_function_loc(&mut tree, &blank_rows);
         tree
     }
 
@@ -258,6 +280,30 @@ impl<'a> Visitor<'a> {
         }
     }
 
+    /// Route every comment to the deepest enclosing space's CLOC. The
+    /// AST walk recorded code lines straight into the per-space state,
+    /// which the routed comment events cannot see, so a comment that
+    /// shares a row with code re-observes that row as code first; the
+    /// row is counted once either way.
+    fn emit_comments(&mut self, comments: &[Comment]) {
+        for comment in comments {
+            let span = comment.span;
+            let start_row = self.line_index.line_at(span.start).saturating_sub(1);
+            let end_row = self.line_index.line_at(span.end).saturating_sub(1);
+            let unit_loc = &mut self.stack[0].loc;
+            if self.code_rows.contains(&start_row) {
+                self.halstead_routing
+                    .observe_code_line(span.start, span.end, unit_loc, start_row);
+            }
+            self.halstead_routing
+                .observe_comment(span.start, span.end, unit_loc, start_row, end_row);
+            if end_row != start_row && self.code_rows.contains(&end_row) {
+                self.halstead_routing
+                    .observe_code_line(span.start, span.end, unit_loc, end_row);
+            }
+        }
+    }
+
     fn is_inside_type_only(&self, span: Span) -> bool {
         // Linear scan — `type_only_ranges` is small in practice (a
         // handful of nodes per TS file). The ranges are already sorted
@@ -453,6 +499,10 @@ impl<'a> Visit<'a> for Visitor<'a> {
             SpaceKind::Closure => self.current().nargs.record_closure_args(argc),
             _ => {}
         }
+        self.current()
+            .nargs
+            .record_defaulted_args(defaulted_params(&it.params));
+        record_param_names(&mut self.current().nargs, &it.params);
 
         // Cognitive — function entry resets nesting/lambda and bumps
         // depth when nested inside another function (legacy
@@ -503,6 +553,10 @@ impl<'a> Visit<'a> for Visitor<'a> {
         self.current()
             .nargs
             .record_closure_args(it.params.items.len() as u32);
+        self.current()
+            .nargs
+            .record_defaulted_args(defaulted_params(&it.params));
+        record_param_names(&mut self.current().nargs, &it.params);
 
         let mut ctx = self.cognitive;
         ctx.lambda = ctx.lambda.saturating_add(1);
@@ -540,6 +594,10 @@ impl<'a> Visit<'a> for Visitor<'a> {
 
         let argc = it.value.params.items.len() as u32;
         self.current().nargs.record_function_args(argc);
+        self.current()
+            .nargs
+            .record_defaulted_args(defaulted_params(&it.value.params));
+        record_param_names(&mut self.current().nargs, &it.value.params);
 
         let mut ctx = self.cognitive;
         let nested = self
@@ -587,6 +645,9 @@ impl<'a> Visit<'a> for Visitor<'a> {
     // ---------- Per-node classification ----------
 
     fn enter_node(&mut self, kind: AstKind<'a>) {
+        if is_depth_expr(kind) {
+            self.current().expr_depth.enter();
+        }
         // Cyclomatic decision points — `IfStatement, ForStatement,
         // ForInStatement, ForOfStatement, WhileStatement, DoStatement,
         // SwitchCase, CatchClause, ConditionalExpression`, plus `&&` /
@@ -606,12 +667,18 @@ impl<'a> Visit<'a> for Visitor<'a> {
         ) {
             self.current().cyclomatic.record_decision();
         }
+        // `??` short-circuits exactly like `&&` / `||`, and every `?.`
+        // link in an optional chain is an implicit null check — both
+        // add a path through the function.
         if let AstKind::LogicalExpression(le) = kind {
             use oxc_syntax::operator::LogicalOperator::*;
-            if matches!(le.operator, And | Or) {
+            if matches!(le.operator, And | Or | Coalesce) {
                 self.current().cyclomatic.record_decision();
             }
         }
+        if is_optional_chain_link(kind) {
+            self.current().cyclomatic.record_decision();
+        }
 
         // NExit — `ReturnStatement`, `ThrowStatement`. Legacy:
         // `crates/mehen-engine/src/legacy/metrics/exit.rs:132-152`.
@@ -631,7 +698,11 @@ impl<'a> Visit<'a> for Visitor<'a> {
             AstKind::VariableDeclarator(decl) if decl.init.is_some() => {
                 self.current().abc.record_assignment();
             }
-            AstKind::CallExpression(_) | AstKind::NewExpression(_) => {
+            AstKind::CallExpression(call) => {
+                self.current().abc.record_branch();
+                self.current().call_chain.observe(call_chain_len(call));
+            }
+            AstKind::NewExpression(_) => {
                 self.current().abc.record_branch();
             }
             AstKind::IfStatement(_)
@@ -668,6 +739,7 @@ impl<'a> Visit<'a> for Visitor<'a> {
                 use oxc_syntax::operator::LogicalOperator::*;
                 if matches!(le.operator, And | Or) {
                     self.current().abc.record_condition();
+                    self.current().condition_terms.observe(condition_terms(le));
                 }
             }
             _ => {}
@@ -704,13 +776,19 @@ impl<'a> Visit<'a> for Visitor<'a> {
         // Legacy: `crates/mehen-engine/src/legacy/metrics/loc.rs:622-645`.
         // Containers (`Program`, `String`, `DQUOTE`) skip; statement
         // shapes bump LLOC; comments are handled separately via
-        // `program.comments` in a pre-pass.
+        // `program.comments` after the walk. A JSX comment sits in an
+        // otherwise empty `{}` container, which is not code, and the
+        // whitespace text around it starts on its line.
         let span = ast_kind_span(kind);
         let start_row = self.line_index.line_at(span.start).saturating_sub(1);
         match kind {
             AstKind::Program(_) | AstKind::StringLiteral(_) => {
                 // Containers — skip per legacy.
             }
+            AstKind::JSXExpressionContainer(container)
+                if matches!(container.expression, JSXExpression::EmptyExpression(_)) => {}
+            AstKind::JSXEmptyExpression(_) => {}
+            AstKind::JSXText(text) if text.value.trim().is_empty() => {}
             AstKind::ExpressionStatement(_)
             | AstKind::ImportDeclaration(_)
             | AstKind::ExportNamedDeclaration(_)
@@ -733,10 +811,10 @@ impl<'a> Visit<'a> for Visitor<'a> {
             | AstKind::ThrowStatement(_)
             | AstKind::EmptyStatement(_) => {
                 self.current().loc.observe_lloc();
-                self.current().loc.observe_code_line(start_row);
+                self.observe_code_line(start_row);
             }
             _ => {
-                self.current().loc.observe_code_line(start_row);
+                self.observe_code_line(start_row);
             }
         }
 
@@ -759,9 +837,11 @@ impl<'a> Visit<'a> for Visitor<'a> {
                 // IfStatement as a fresh nesting bump; any visible
                 // drift falls into snapshot review.
                 let _ = if_stmt;
-                let effective =
-                    self.cognitive.nesting + self.cognitive.depth + self.cognitive.lambda;
-                self.current().cognitive.increase_nesting(effective);
+                let nesting = self.cognitive.nesting;
+                let effective = nesting + self.cognitive.depth + self.cognitive.lambda;
+                self.current()
+                    .cognitive
+                    .increase_nesting(effective, nesting);
                 self.cognitive.nesting += 1;
             }
             AstKind::ForStatement(_)
@@ -773,9 +853,11 @@ impl<'a> Visit<'a> for Visitor<'a> {
             | AstKind::TryStatement(_)
             | AstKind::CatchClause(_)
             | AstKind::ConditionalExpression(_) => {
-                let effective =
-                    self.cognitive.nesting + self.cognitive.depth + self.cognitive.lambda;
-                self.current().cognitive.increase_nesting(effective);
+                let nesting = self.cognitive.nesting;
+                let effective = nesting + self.cognitive.depth + self.cognitive.lambda;
+                self.current()
+                    .cognitive
+                    .increase_nesting(effective, nesting);
                 self.cognitive.nesting += 1;
             }
             AstKind::ExpressionStatement(_) => {
@@ -789,10 +871,14 @@ impl<'a> Visit<'a> for Visitor<'a> {
             }
             AstKind::LogicalExpression(le) => {
                 use oxc_syntax::operator::LogicalOperator::*;
-                if matches!(le.operator, And) {
-                    self.current().cognitive.observe_boolean("&&");
-                } else if matches!(le.operator, Or) {
-                    self.current().cognitive.observe_boolean("||");
+                // `??` joins boolean sequences like `&&` / `||`: a run
+                // of the same operator costs +1, each switch +1 more.
+                // Optional chains (`?.`) are shorthand, not flow breaks,
+                // so cognitive leaves them alone.
+                match le.operator {
+                    And => self.current().cognitive.observe_boolean("&&"),
+                    Or => self.current().cognitive.observe_boolean("||"),
+                    Coalesce => self.current().cognitive.observe_boolean("??"),
                 }
             }
             _ => {}
@@ -953,6 +1039,9 @@ impl<'a> Visit<'a> for Visitor<'a> {
     }
 
     fn leave_node(&mut self, kind: AstKind<'a>) {
+        if is_depth_expr(kind) {
+            self.current().expr_depth.leave();
+        }
         match kind {
             AstKind::IfStatement(_)
             | AstKind::ForStatement(_)
@@ -980,6 +1069,26 @@ impl<'a> Visitor<'a> {
     }
 }
 
+/// Parameters a caller may omit: `x = 1` and `x?: T`. A rest parameter
+/// is not in `items`.
+fn defaulted_params(params: &FormalParameters<'_>) -> u32 {
+    params
+        .items
+        .iter()
+        .filter(|p| p.optional || p.initializer.is_some())
+        .count() as u32
+}
+
+/// Every parameter bound to a plain identifier, the rest parameter
+/// included. Destructured parameters have no single name.
+fn record_param_names(nargs: &mut NargsStats, params: &FormalParameters<'_>) {
+    let items = params.items.iter().map(|p| &p.pattern);
+    let rest = params.rest.iter().map(|r| &r.rest.argument);
+    for name in items.chain(rest).filter_map(|p| p.get_identifier_name()) {
+        nargs.record_param_name(name.as_str());
+    }
+}
+
 fn function_space_kind(f: &Function<'_>) -> SpaceKind {
     match f.r#type {
         FunctionType::FunctionDeclaration | FunctionType::FunctionExpression => {
@@ -1005,6 +1114,66 @@ fn function_space_kind(f: &Function<'_>) -> SpaceKind {
     }
 }
 
+/// Calls in the `.`-connected chain ending at `call`: `a.b().c()` is
+/// 2. Member access, parentheses and TS-only wrappers (`!`, `as`)
+/// between calls do not break the chain.
+fn call_chain_len(call: &CallExpression<'_>) -> u32 {
+    let mut len = 1;
+    let mut expr = call.callee.get_inner_expression();
+    loop {
+        if let Some(member) = expr.as_member_expression() {
+            expr = member.object().get_inner_expression();
+        } else if let Expression::CallExpression(inner) = expr {
+            len += 1;
+            expr = inner.callee.get_inner_expression();
+        } else {
+            return len;
+        }
+    }
+}
+
+/// Does `kind` count towards `max_expr_depth`? Binary and logical
+/// operators, calls, `new` and computed member access (`a[i]`).
+fn is_depth_expr(kind: AstKind<'_>) -> bool {
+    matches!(
+        kind,
+        AstKind::BinaryExpression(_)
+            | AstKind::LogicalExpression(_)
+            | AstKind::CallExpression(_)
+            | AstKind::NewExpression(_)
+            | AstKind::ComputedMemberExpression(_)
+    )
+}
+
+/// Leaf operands of the `&&` / `||` tree rooted at `expr`:
+/// `a && (b || c)` is 3. Parentheses are looked through; `??` and any
+/// other expression, including `!(…)`, is a single term.
+fn condition_terms(expr: &LogicalExpression<'_>) -> u32 {
+    fn side(expr: &Expression<'_>) -> u32 {
+        match expr.get_inner_expression() {
+            Expression::LogicalExpression(inner) => condition_terms(inner),
+            _ => 1,
+        }
+    }
+    use oxc_syntax::operator::LogicalOperator::*;
+    if matches!(expr.operator, And | Or) {
+        side(&expr.left) + side(&expr.right)
+    } else {
+        1
+    }
+}
+
+/// `a?.b`, `a?.[k]`, `a?.#p`, `f?.()` — one optional link in a chain.
+fn is_optional_chain_link(kind: AstKind<'_>) -> bool {
+    match kind {
+        AstKind::StaticMemberExpression(e) => e.optional,
+        AstKind::ComputedMemberExpression(e) => e.optional,
+        AstKind::PrivateFieldExpression(e) => e.optional,
+        AstKind::CallExpression(e) => e.optional,
+        _ => false,
+    }
+}
+
 fn method_name(key: &PropertyKey<'_>) -> Option<String> {
     match key {
         PropertyKey::StaticIdentifier(id) => Some(id.name.as_str().to_string()),
@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Longest `.`-connected method-call chain (`max_call_chain`).
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, MetricKey, SourceFile, keys};
+use mehen_typescript::TypeScriptAnalyzer;
+
+fn analyze_ts(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let file = SourceFile::new("foo.ts".into(), Language::TypeScript, text);
+    TypeScriptAnalyzer::new()
+        .analyze(&file, &AnalysisConfig::default())
+        .unwrap()
+}
+
+#[test]
+fn typescript_five_call_chain() {
+    let a = analyze_ts(
+        "function f(xs: number[]) {
+             return xs.slice().filter(x => x > 0).map(x => x * 2).reverse().join(',');
+         }",
+    );
+    let value = a.root.spaces[0]
+        .metrics
+        .get(&MetricKey::new(keys::MAX_CALL_CHAIN))
+        .map(|v| v.as_f64());
+    assert_eq!(value, Some(5.0));
+}
+
+#[test]
+fn typescript_non_null_and_parens_keep_the_chain() {
+    let a = analyze_ts(
+        "function f(el: HTMLElement | null) {
+             return (el!.querySelector('a')!).getAttribute('href')!.trim();
+         }",
+    );
+    let value = a.root.spaces[0]
+        .metrics
+        .get(&MetricKey::new(keys::MAX_CALL_CHAIN))
+        .map(|v| v.as_f64());
+    assert_eq!(value, Some(3.0));
+}
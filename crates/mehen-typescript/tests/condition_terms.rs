@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Most `&&` / `||` operands in a single condition (`max_condition_terms`).
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, MetricKey, SourceFile, keys};
+use mehen_typescript::TypeScriptAnalyzer;
+
+fn analyze_ts(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let file = SourceFile::new("foo.ts".into(), Language::TypeScript, text);
+    TypeScriptAnalyzer::new()
+        .analyze(&file, &AnalysisConfig::default())
+        .unwrap()
+}
+
+#[test]
+fn typescript_eight_term_condition() {
+    let a = analyze_ts(
+        "function f(a: boolean, b: boolean, c: boolean, d: boolean, x?: number) {
+             if (a && (b || c) && d && !(a || b) && (x ?? 0) > 1 && a && b) {
+                 return 1;
+             }
+             return 0;
+         }",
+    );
+    let value = a.root.spaces[0]
+        .metrics
+        .get(&MetricKey::new(keys::MAX_CONDITION_TERMS))
+        .map(|v| v.as_f64());
+    assert_eq!(value, Some(8.0));
+}
@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Optional and default-valued parameters (`defaulted_args`) and the
+//! longest parameter name (`max_param_name_length`).
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, MetricKey, SourceFile, keys};
+use mehen_typescript::TypeScriptAnalyzer;
+
+fn analyze(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let file = SourceFile::new("foo.ts".into(), Language::TypeScript, text);
+    TypeScriptAnalyzer::new()
+        .analyze(&file, &AnalysisConfig::default())
+        .unwrap()
+}
+
+fn defaulted_args(space: &mehen_core::MetricSpace) -> Option<f64> {
+    space
+        .metrics
+        .get(&MetricKey::new(keys::DEFAULTED_ARGS))
+        .map(|v| v.as_f64())
+}
+
+#[test]
+fn typescript_counts_optional_and_default_params() {
+    let a = analyze(
+        "function f(a: number, b?: string, c = 1, ...rest: number[]) {
+             return rest.map((x, y = 2) => x + y);
+         }
+         class C {
+             m(a?: number) { return a; }
+         }
+         function g(a: number) { return a; }",
+    );
+    let f = &a.root.spaces[0];
+    assert_eq!(defaulted_args(f), Some(3.0));
+    assert_eq!(defaulted_args(&f.spaces[0]), Some(1.0));
+    assert_eq!(defaulted_args(&a.root.spaces[1].spaces[0]), Some(1.0));
+    assert_eq!(defaulted_args(&a.root.spaces[2]), None);
+    assert_eq!(defaulted_args(&a.root), Some(4.0));
+}
+
+fn max_param_name_length(space: &mehen_core::MetricSpace) -> Option<f64> {
+    space
+        .metrics
+        .get(&MetricKey::new(keys::MAX_PARAM_NAME_LENGTH))
+        .map(|v| v.as_f64())
+}
+
+#[test]
+fn typescript_reports_the_longest_parameter_name() {
+    let a = analyze(
+        "function f(a: number, { nestedName }: Opts, ...remaining: number[]) {
+             return remaining.map((veryLongName) => veryLongName + a);
+         }
+         function g() { return 1; }",
+    );
+    let f = &a.root.spaces[0];
+    assert_eq!(max_param_name_length(&f.spaces[0]), Some(12.0));
+    assert_eq!(max_param_name_length(f), Some(12.0));
+    assert_eq!(max_param_name_length(&a.root.spaces[1]), None);
+    assert_eq!(max_param_name_length(&a.root), Some(12.0));
+}
+
+#[test]
+fn typescript_counts_the_rest_parameter_name() {
+    let a = analyze("function f(a: number, ...remaining: number[]) { return a; }");
+    assert_eq!(max_param_name_length(&a.root.spaces[0]), Some(9.0));
+}
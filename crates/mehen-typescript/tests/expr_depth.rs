@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Deepest nesting of operators, calls and indexing (`max_expr_depth`).
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, MetricKey, SourceFile, keys};
+use mehen_typescript::TypeScriptAnalyzer;
+
+fn analyze_ts(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let file = SourceFile::new("foo.ts".into(), Language::TypeScript, text);
+    TypeScriptAnalyzer::new()
+        .analyze(&file, &AnalysisConfig::default())
+        .unwrap()
+}
+
+fn max_expr_depth(space: &mehen_core::MetricSpace) -> Option<f64> {
+    space
+        .metrics
+        .get(&MetricKey::new(keys::MAX_EXPR_DEPTH))
+        .map(|v| v.as_f64())
+}
+
+#[test]
+fn typescript_nested_arithmetic() {
+    let a = analyze_ts(
+        "function f(a: number, b: number[], i: number) {
+             const x = a + 1;
+             return Math.max(((a * (b[i - 1] + 2)) % 7) || 0, x);
+         }",
+    );
+    // call, ||, %, *, +, index, - — parentheses do not count.
+    assert_eq!(max_expr_depth(&a.root.spaces[0]), Some(7.0));
+    assert_eq!(max_expr_depth(&a.root), Some(7.0));
+}
+
+#[test]
+fn typescript_arrow_starts_from_zero() {
+    let a = analyze_ts(
+        "function f(xs: number[]) {
+             return xs.map((x) => x * 2);
+         }",
+    );
+    let f = &a.root.spaces[0];
+    assert_eq!(max_expr_depth(f), Some(1.0));
+    assert_eq!(max_expr_depth(&f.spaces[0]), Some(1.0));
+}
@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! Nullish coalescing (`??`) and optional chaining (`?.`) in the
+//! cyclomatic and cognitive metrics. Neither operator existed in the
+//! legacy tree-sitter grammar's counted set, so these are not parity
+//! snapshots.
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, SourceFile};
+use mehen_typescript::{TsxAnalyzer, TypeScriptAnalyzer};
+
+fn analyze_ts(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let file = SourceFile::new("foo.ts".into(), Language::TypeScript, text);
+    TypeScriptAnalyzer::new()
+        .analyze(&file, &AnalysisConfig::default())
+        .unwrap()
+}
+
+fn analyze_tsx(source: &str) -> mehen_core::LanguageAnalysis {
+    let mut text = source.trim_end().trim_matches('\n').to_string();
+    text.push('\n');
+    let file = SourceFile::new("foo.tsx".into(), Language::Tsx, text);
+    TsxAnalyzer::new()
+        .analyze(&file, &AnalysisConfig::default())
+        .unwrap()
+}
+
+#[test]
+fn typescript_nullish_coalescing() {
+    let a = analyze_ts(
+        "function f(a, b, c) {
+             return a ?? b ?? c; // +2 cyclomatic, +1 cognitive (one sequence)
+         }",
+    );
+    let cy = mehen_report::metrics_json::cyclomatic(&a.root.metrics);
+    insta::assert_json_snapshot!(cy, @r###"
+    {
+      "sum": 4.0,
+      "average": 2.0,
+      "min": 1.0,
+      "max": 3.0
+    }"###);
+    let cog = mehen_report::metrics_json::cognitive(&a.root.metrics);
+    insta::assert_json_snapshot!(cog, @r###"
+    {
+      "sum": 1.0,
+      "average": 1.0,
+      "min": 0.0,
+      "max": 1.0
+    }"###);
+}
+
+#[test]
+fn typescript_nullish_mixed_with_or() {
+    let a = analyze_ts(
+        "function f(a, b, c) {
+             return a || b ?? c; // +2 cyclomatic, +2 cognitive (operator switch)
+         }",
+    );
+    let cog = mehen_report::metrics_json::cognitive(&a.root.metrics);
+    insta::assert_json_snapshot!(cog, @r###"
+    {
+      "sum": 2.0,
+      "average": 2.0,
+      "min": 0.0,
+      "max": 2.0
+    }"###);
+}
+
+#[test]
+fn typescript_optional_chaining() {
+    let a = analyze_ts(
+        "function f(o) {
+             return o?.a.b?.[0]?.(); // +3 cyclomatic, no cognitive
+         }",
+    );
+    let cy = mehen_report::metrics_json::cyclomatic(&a.root.metrics);
+    insta::assert_json_snapshot!(cy, @r###"
+    {
+      "sum": 5.0,
+      "average": 2.5,
+      "min": 1.0,
+      "max": 4.0
+    }"###);
+    let cog = mehen_report::metrics_json::cognitive(&a.root.metrics);
+    insta::assert_json_snapshot!(cog, @r###"
+    {
+      "sum": 0.0,
+      "average": 0.0,
+      "min": 0.0,
+      "max": 0.0
+    }"###);
+}
+
+#[test]
+fn tsx_nullish_and_optional_chaining() {
+    let a = analyze_tsx(
+        "function Label(props) {
+             return <span>{props.user?.name ?? 'anonymous'}</span>; // +2 cyclomatic
+         }",
+    );
+    let cy = mehen_report::metrics_json::cyclomatic(&a.root.metrics);
+    insta::assert_json_snapshot!(cy, @r###"
+    {
+      "sum": 4.0,
+      "average": 2.0,
+      "min": 1.0,
+      "max": 3.0
+    }"###);
+}
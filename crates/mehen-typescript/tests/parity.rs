@@ -211,7 +211,8 @@ fn typescript_operators_and_operands() {
       "level": 0.10476190476190476,
       "effort": 1886.699983875422,
       "time": 104.81666577085679,
-      "bugs": 0.05089564733125986
+      "bugs": 0.05089564733125986,
+      "operator_operand_ratio": 1.1428571428571428
     }"###);
 }
 
@@ -242,7 +243,40 @@ fn tsx_operators_and_operands() {
       "level": 0.10476190476190476,
       "effort": 1886.699983875422,
       "time": 104.81666577085679,
-      "bugs": 0.05089564733125986
+      "bugs": 0.05089564733125986,
+      "operator_operand_ratio": 1.1428571428571428
+    }"###);
+}
+
+#[test]
+fn tsx_jsx_comment_counts_as_comment_not_code() {
+    let a = analyze_tsx(
+        "function App() {
+           // greeting banner
+           return (
+             <div>
+               {/* title goes here */}
+               <h1>hi</h1>
+             </div>
+           );
+         }",
+        "foo.tsx",
+    );
+    let loc = mehen_report::metrics_json::loc(&a.root.spaces[0].metrics);
+    let split = serde_json::json!({
+        "blank": loc.blank,
+        "cloc": loc.cloc,
+        "ploc": loc.ploc,
+        "sloc": loc.sloc,
+    });
+    // Lines 2 and 5 are comments; `);` and `}` start no node, so they
+    // read as blank, as for any other TypeScript function.
+    insta::assert_json_snapshot!(split, @r###"
+    {
+      "blank": 2.0,
+      "cloc": 2.0,
+      "ploc": 5.0,
+      "sloc": 9.0
     }"###);
 }
 
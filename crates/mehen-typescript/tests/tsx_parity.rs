@@ -0,0 +1,129 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2026 Konstantin Vyatkin <tino@vtkn.io>
+
+//! TypeScript / TSX cross-check.
+//!
+//! Both flavors run the same walker over an Oxc AST; only the parser's
+//! `SourceType` differs. Code without JSX must therefore produce the
+//! same space tree and the same cyclomatic, cognitive and Halstead
+//! values under either analyzer. Each fixture avoids the two constructs
+//! whose meaning does change in TSX: `<T>expr` type assertions and
+//! `<T>(x) => …` generic arrows without a constraint.
+
+use mehen_core::{AnalysisConfig, Language, LanguageAnalyzer, MetricKey, MetricSpace, SourceFile};
+use mehen_typescript::{TsxAnalyzer, TypeScriptAnalyzer};
+
+const KEYS: &[&str] = &[
+    "cyclomatic",
+    "cyclomatic.sum",
+    "cognitive",
+    "cognitive.sum",
+    "halstead.n1",
+    "halstead.N1",
+    "halstead.n2",
+    "halstead.N2",
+    "halstead.volume",
+];
+
+fn analyze(language: Language, source: &str) -> mehen_core::LanguageAnalysis {
+    let (path, analyzer): (&str, Box<dyn LanguageAnalyzer>) = match language {
+        Language::Tsx => ("foo.tsx", Box::new(TsxAnalyzer::new())),
+        _ => ("foo.ts", Box::new(TypeScriptAnalyzer::new())),
+    };
+    let file = SourceFile::new(path.into(), language, source.to_string());
+    analyzer.analyze(&file, &AnalysisConfig::default()).unwrap()
+}
+
+/// `(kind, name, values of KEYS)` for every space, in document order.
+fn fingerprint(root: &MetricSpace) -> Vec<String> {
+    let mut rows = Vec::new();
+    root.visit(&mut |space, _| {
+        let values: Vec<String> = KEYS
+            .iter()
+            .map(|key| format!("{key}={:?}", space.metrics.get(&MetricKey::new(*key))))
+            .collect();
+        rows.push(format!(
+            "{:?} {:?} {}",
+            space.kind,
+            space.name,
+            values.join(" ")
+        ));
+    });
+    rows
+}
+
+fn assert_parity(source: &str) {
+    let ts = analyze(Language::TypeScript, source);
+    let tsx = analyze(Language::Tsx, source);
+    assert!(ts.diagnostics.is_empty(), "{:?}", ts.diagnostics);
+    assert!(tsx.diagnostics.is_empty(), "{:?}", tsx.diagnostics);
+    assert_eq!(fingerprint(&ts.root), fingerprint(&tsx.root));
+}
+
+#[test]
+fn control_flow_matches() {
+    assert_parity(
+        "function classify(n: number, flags: string[]): string {
+             if (n < 0 && flags.length > 0) {
+                 return 'negative';
+             } else if (n === 0 || flags.includes('zero')) {
+                 return 'zero';
+             }
+             for (const flag of flags) {
+                 switch (flag) {
+                     case 'a':
+                         continue;
+                     case 'b':
+                         break;
+                     default:
+                         n += flag.length > 2 ? 1 : 0;
+                 }
+             }
+             while (n > 100) { n /= 2; }
+             try {
+                 return String(n ?? 0);
+             } catch (e) {
+                 return 'error';
+             }
+         }
+",
+    );
+}
+
+#[test]
+fn classes_and_generics_match() {
+    assert_parity(
+        "interface Shape { area(): number }
+         enum Kind { Circle, Square }
+         class Circle<T extends object> implements Shape {
+             private readonly r: number;
+             constructor(r: number, public meta?: T) { this.r = r; }
+             area(): number { return Math.PI * this.r ** 2; }
+             get kind(): Kind { return Kind.Circle; }
+             static of<U extends object>(r: number): Circle<U> { return new Circle<U>(r); }
+         }
+         export function largest<S extends Shape>(shapes: S[]): S | undefined {
+             return shapes.reduce<S | undefined>(
+                 (best, s) => (best === undefined || s.area() > best.area() ? s : best),
+                 undefined,
+             );
+         }
+",
+    );
+}
+
+#[test]
+fn closures_and_operators_match() {
+    assert_parity(
+        "const total = async (items: { price?: number }[]): Promise<number> => {
+             const prices = items.map((i) => i.price ?? 0).filter((p) => p > 0);
+             let sum = 0;
+             prices.forEach(function (p) { sum += p; });
+             const label = `total: ${sum}`;
+             return sum satisfies number ? (sum as number) : Number(label);
+         };
+         const { a, ...rest } = { a: 1, b: 2 };
+         export default total;
+",
+    );
+}